@@ -0,0 +1,90 @@
+//! Opening SonarQube pages in the user's browser
+//!
+//! The platform opener (`open`, `xdg-open`, `rundll32`) is spawned directly.
+//! Without a graphical session (CI, SSH, containers) the URL is printed
+//! instead so it can be copied.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Whether a browser can be launched, judged from which environment variables are set
+pub fn gui_available(is_set: impl Fn(&str) -> bool) -> bool {
+    if is_set("CI") {
+        return false;
+    }
+    if cfg!(any(target_os = "macos", windows)) {
+        return true;
+    }
+    is_set("DISPLAY") || is_set("WAYLAND_DISPLAY")
+}
+
+/// The platform command that opens `url` in the default browser
+fn opener(url: &str) -> Command {
+    let mut cmd = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut c = Command::new("rundll32");
+        c.arg("url.dll,FileProtocolHandler");
+        c
+    } else {
+        Command::new("xdg-open")
+    };
+    cmd.arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    cmd
+}
+
+/// Open `url` in the default browser when `gui` is set, otherwise write it to `out`.
+///
+/// A failed launch also falls back to writing the URL. Returns whether a
+/// browser was launched.
+pub fn open_or_print<W: Write>(url: &str, gui: bool, out: &mut W) -> std::io::Result<bool> {
+    if gui && opener(url).spawn().is_ok() {
+        return Ok(true);
+    }
+    writeln!(out, "{url}")?;
+    Ok(false)
+}
+
+/// Open `url` in the browser, or print it to stderr when there is no GUI session
+pub fn open_url(url: &str) {
+    let gui = gui_available(|name| std::env::var_os(name).is_some_and(|v| !v.is_empty()));
+    if gui {
+        eprintln!("Opening {url}");
+    }
+    if let Err(e) = open_or_print(url, gui, &mut std::io::stderr()) {
+        eprintln!("Failed to print URL: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headless_prints_url_instead_of_launching() {
+        let headless = gui_available(|name| name == "CI");
+        assert!(!headless);
+
+        let mut out = Vec::new();
+        let launched = open_or_print("https://sonar.example.com/project/issues?id=p", headless, &mut out)
+            .unwrap();
+        assert!(!launched);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "https://sonar.example.com/project/issues?id=p\n"
+        );
+    }
+
+    #[test]
+    fn test_gui_available_needs_display_on_linux() {
+        if cfg!(any(target_os = "macos", windows)) {
+            return;
+        }
+        assert!(!gui_available(|_| false));
+        assert!(gui_available(|name| name == "DISPLAY"));
+        assert!(gui_available(|name| name == "WAYLAND_DISPLAY"));
+    }
+}
@@ -0,0 +1,163 @@
+//! On-disk cache of GET response bodies (`--cache`)
+//!
+//! Each successful GET body is stored in its own file under
+//! `<config_dir>/sonar-cli/cache/`, named by a hash of the request URL and
+//! the credentials, so switching tokens never serves another user's data.
+//! An entry is fresh while its file is younger than the TTL.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Default `--cache-ttl` in seconds
+pub const DEFAULT_TTL_SECS: u64 = 300;
+
+const ENTRY_EXTENSION: &str = "body";
+
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Duration,
+    /// Skip lookups but still store responses (`--no-cache`)
+    refresh: bool,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl,
+            refresh: false,
+        }
+    }
+
+    pub fn with_refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Cached body for `url`, unless missing, expired or refreshing
+    pub fn get(&self, url: &str, credentials: Option<&str>) -> Option<String> {
+        if self.refresh {
+            return None;
+        }
+        let path = self.entry_path(url, credentials);
+        let age = std::fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())?;
+        if age >= self.ttl {
+            return None;
+        }
+        std::fs::read_to_string(&path).ok()
+    }
+
+    /// Store `body` for `url`. Failures only cost a future cache miss, so
+    /// they are logged rather than reported.
+    pub fn put(&self, url: &str, credentials: Option<&str>, body: &str) {
+        let path = self.entry_path(url, credentials);
+        let result = std::fs::create_dir_all(&self.dir).and_then(|()| {
+            // Write then rename so a concurrent reader never sees half a body
+            let tmp = path.with_extension("tmp");
+            std::fs::write(&tmp, body)?;
+            std::fs::rename(&tmp, &path)
+        });
+        if let Err(e) = result {
+            tracing::debug!(error = %e, path = %path.display(), "failed to write cache entry");
+        }
+    }
+
+    fn entry_path(&self, url: &str, credentials: Option<&str>) -> PathBuf {
+        let key = fnv1a(&[url.as_bytes(), b"\0", credentials.unwrap_or_default().as_bytes()]);
+        self.dir.join(format!("{key:016x}.{ENTRY_EXTENSION}"))
+    }
+}
+
+/// Cache directory: `<config_dir>/sonar-cli/cache`
+pub fn default_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("sonar-cli").join("cache"))
+}
+
+/// Delete every cache entry in `dir` and return how many were removed;
+/// a missing directory counts as empty
+pub fn clear(dir: &Path) -> std::io::Result<usize> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    let mut removed = 0;
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == ENTRY_EXTENSION || ext == "tmp") {
+            std::fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// 64-bit FNV-1a: stable across builds, unlike `DefaultHasher`
+fn fnv1a(parts: &[&[u8]]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in parts.iter().flat_map(|p| p.iter()) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sonar-cli-cache-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_put_then_get_within_ttl() {
+        let dir = temp_dir("hit");
+        let cache = ResponseCache::new(&dir, Duration::from_secs(60));
+        assert_eq!(cache.get("http://s/api/a", None), None);
+
+        cache.put("http://s/api/a", None, "{\"a\":1}");
+        assert_eq!(cache.get("http://s/api/a", None).as_deref(), Some("{\"a\":1}"));
+        // Other URLs and other credentials miss
+        assert_eq!(cache.get("http://s/api/b", None), None);
+        assert_eq!(cache.get("http://s/api/a", Some("token")), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_expired_and_refresh_miss() {
+        let dir = temp_dir("miss");
+        let expired = ResponseCache::new(&dir, Duration::ZERO);
+        expired.put("http://s/api/a", None, "old");
+        assert_eq!(expired.get("http://s/api/a", None), None);
+
+        let refresh = ResponseCache::new(&dir, Duration::from_secs(60)).with_refresh(true);
+        assert_eq!(refresh.get("http://s/api/a", None), None);
+        refresh.put("http://s/api/a", None, "new");
+        let cache = ResponseCache::new(&dir, Duration::from_secs(60));
+        assert_eq!(cache.get("http://s/api/a", None).as_deref(), Some("new"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_clear() {
+        let dir = temp_dir("clear");
+        assert_eq!(clear(&dir).unwrap(), 0);
+
+        let cache = ResponseCache::new(&dir, Duration::from_secs(60));
+        cache.put("http://s/api/a", None, "a");
+        cache.put("http://s/api/b", None, "b");
+        assert_eq!(clear(&dir).unwrap(), 2);
+        assert_eq!(cache.get("http://s/api/a", None), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,234 @@
+//! Cobertura → SonarQube generic coverage conversion
+//!
+//! Cobertura reports store class filenames relative to a `<source>` directory,
+//! which in monorepos is usually a sub-package rather than the scanned project
+//! root. Converting to the generic coverage format lets us rewrite every path
+//! relative to a chosen base directory.
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Coverage of a single line, merged across classes that share a file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct LineCoverage {
+    covered: bool,
+    branches_to_cover: u32,
+    covered_branches: u32,
+}
+
+/// Lexically normalise a path, resolving `.` and `..` without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// Resolve `path` against `dir` when relative, then normalise
+fn absolutize(path: &Path, dir: &Path) -> PathBuf {
+    if path.is_absolute() {
+        normalize(path)
+    } else {
+        normalize(&dir.join(path))
+    }
+}
+
+/// Parse Cobertura's `condition-coverage="50% (1/2)"` into (covered, total)
+fn parse_condition_coverage(value: &str) -> Option<(u32, u32)> {
+    let inner = value.split_once('(')?.1.strip_suffix(')')?;
+    let (covered, total) = inner.split_once('/')?;
+    Some((covered.trim().parse().ok()?, total.trim().parse().ok()?))
+}
+
+fn escape_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Convert a Cobertura XML report to SonarQube's generic coverage format.
+///
+/// Class filenames are resolved against the report's first `<source>` (itself
+/// resolved against `work_dir` when relative) and then made relative to `base`.
+/// Files outside `base` keep their absolute path.
+pub fn convert_cobertura_to_sonarqube(
+    report: &str,
+    work_dir: &Path,
+    base: &Path,
+) -> Result<String, String> {
+    let doc = roxmltree::Document::parse(report)
+        .map_err(|e| format!("Invalid Cobertura report: {e}"))?;
+    let root = doc.root_element();
+    if !root.has_tag_name("coverage") {
+        return Err(format!(
+            "Invalid Cobertura report: expected <coverage> root, found <{}>",
+            root.tag_name().name()
+        ));
+    }
+
+    let base = absolutize(base, work_dir);
+    let source_dir = root
+        .descendants()
+        .find(|n| n.has_tag_name("source"))
+        .and_then(|n| n.text())
+        .map(|s| absolutize(Path::new(s.trim()), work_dir))
+        .unwrap_or_else(|| work_dir.to_path_buf());
+
+    let mut files: BTreeMap<String, BTreeMap<u32, LineCoverage>> = BTreeMap::new();
+    for class in root.descendants().filter(|n| n.has_tag_name("class")) {
+        let Some(filename) = class.attribute("filename") else {
+            continue;
+        };
+        let resolved = absolutize(Path::new(filename), &source_dir);
+        let path = resolved
+            .strip_prefix(&base)
+            .map(Path::to_path_buf)
+            .unwrap_or(resolved);
+        let file_lines = files.entry(path.to_string_lossy().into_owned()).or_default();
+
+        // Only the class-level <lines>; per-method <lines> repeat the same entries
+        let lines = class
+            .children()
+            .filter(|n| n.has_tag_name("lines"))
+            .flat_map(|n| n.children())
+            .filter(|n| n.has_tag_name("line"));
+        for line in lines {
+            let Some(number) = line.attribute("number").and_then(|v| v.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            let hits: u64 = line.attribute("hits").and_then(|v| v.parse().ok()).unwrap_or(0);
+            let (covered_branches, branches_to_cover) = match line.attribute("branch") {
+                Some("true") => line
+                    .attribute("condition-coverage")
+                    .and_then(parse_condition_coverage)
+                    .unwrap_or((0, 0)),
+                _ => (0, 0),
+            };
+
+            let entry = file_lines.entry(number).or_default();
+            entry.covered |= hits > 0;
+            entry.branches_to_cover = entry.branches_to_cover.max(branches_to_cover);
+            entry.covered_branches = entry.covered_branches.max(covered_branches);
+        }
+    }
+
+    let mut out = String::from("<coverage version=\"1\">\n");
+    for (path, lines) in &files {
+        out.push_str(&format!("  <file path=\"{}\">\n", escape_attr(path)));
+        for (number, cov) in lines {
+            out.push_str(&format!(
+                "    <lineToCover lineNumber=\"{number}\" covered=\"{}\"",
+                cov.covered
+            ));
+            if cov.branches_to_cover > 0 {
+                out.push_str(&format!(
+                    " branchesToCover=\"{}\" coveredBranches=\"{}\"",
+                    cov.branches_to_cover, cov.covered_branches
+                ));
+            }
+            out.push_str("/>\n");
+        }
+        out.push_str("  </file>\n");
+    }
+    out.push_str("</coverage>\n");
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(source: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?>
+<coverage line-rate="0.5" branch-rate="0.5" version="1.9">
+  <sources><source>{source}</source></sources>
+  <packages><package name="api"><classes>
+    <class name="lib" filename="src/lib.rs" line-rate="0.5">
+      <methods><method name="f"><lines><line number="1" hits="9"/></lines></method></methods>
+      <lines>
+        <line number="1" hits="3"/>
+        <line number="2" hits="0"/>
+        <line number="3" hits="1" branch="true" condition-coverage="50% (1/2)"/>
+      </lines>
+    </class>
+  </classes></package></packages>
+</coverage>"#
+        )
+    }
+
+    #[test]
+    fn test_convert_source_differs_from_base() {
+        // Report generated inside packages/api, scan runs from the repo root
+        let xml = report("/repo/packages/api");
+        let out =
+            convert_cobertura_to_sonarqube(&xml, Path::new("/repo"), Path::new("/repo")).unwrap();
+        assert!(out.contains(r#"<file path="packages/api/src/lib.rs">"#));
+        assert!(out.contains(r#"<lineToCover lineNumber="1" covered="true"/>"#));
+        assert!(out.contains(r#"<lineToCover lineNumber="2" covered="false"/>"#));
+        assert!(out.contains(
+            r#"<lineToCover lineNumber="3" covered="true" branchesToCover="2" coveredBranches="1"/>"#
+        ));
+        // Method-level lines are not duplicated
+        assert_eq!(out.matches("lineNumber=\"1\"").count(), 1);
+    }
+
+    #[test]
+    fn test_convert_relative_source_and_base() {
+        // Both <source> and base are relative to the working directory
+        let xml = report("./packages/api/");
+        let out = convert_cobertura_to_sonarqube(&xml, Path::new("/repo"), Path::new("packages"))
+            .unwrap();
+        assert!(out.contains(r#"<file path="api/src/lib.rs">"#));
+    }
+
+    #[test]
+    fn test_convert_base_independent_of_work_dir() {
+        let xml = report("/repo/packages/api");
+        let out = convert_cobertura_to_sonarqube(
+            &xml,
+            Path::new("/somewhere/else"),
+            Path::new("/repo/packages/api"),
+        )
+        .unwrap();
+        assert!(out.contains(r#"<file path="src/lib.rs">"#));
+    }
+
+    #[test]
+    fn test_convert_path_outside_base_stays_absolute() {
+        let xml = report("/other/pkg");
+        let out =
+            convert_cobertura_to_sonarqube(&xml, Path::new("/repo"), Path::new("/repo")).unwrap();
+        assert!(out.contains(r#"<file path="/other/pkg/src/lib.rs">"#));
+    }
+
+    #[test]
+    fn test_convert_rejects_invalid_report() {
+        let err = convert_cobertura_to_sonarqube("<nope", Path::new("/"), Path::new("/"));
+        assert!(err.unwrap_err().contains("Invalid Cobertura report"));
+        let err = convert_cobertura_to_sonarqube("<report/>", Path::new("/"), Path::new("/"));
+        assert!(err.unwrap_err().contains("expected <coverage> root"));
+    }
+
+    #[test]
+    fn test_parse_condition_coverage() {
+        assert_eq!(parse_condition_coverage("50% (1/2)"), Some((1, 2)));
+        assert_eq!(parse_condition_coverage("100% (4/4)"), Some((4, 4)));
+        assert_eq!(parse_condition_coverage("garbage"), None);
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(Path::new("/a/./b/../c")), PathBuf::from("/a/c"));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::error::CliError;
+use crate::output;
+use crate::types::task_status;
+
+/// Largest page `/api/ce/activity` returns
+pub const MAX_LIMIT: usize = 1000;
+
+/// Normalise a comma-separated `--status` list to upper-case task statuses
+fn parse_statuses(value: &str) -> Result<String, String> {
+    let mut statuses: Vec<&'static str> = Vec::new();
+    for part in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let upper = part.to_uppercase();
+        match task_status::ALL.iter().find(|s| **s == upper) {
+            Some(status) if !statuses.contains(status) => statuses.push(status),
+            Some(_) => {}
+            None => {
+                return Err(format!(
+                    "Unknown task status '{part}'. Valid values: {}",
+                    task_status::ALL.join(", ")
+                ));
+            }
+        }
+    }
+    if statuses.is_empty() {
+        return Err("--status requires at least one status".to_string());
+    }
+    Ok(statuses.join(","))
+}
+
+/// List the project's most recent compute engine tasks
+pub async fn run(
+    config: SonarQubeConfig,
+    project: &str,
+    status: Option<&str>,
+    limit: usize,
+    json: bool,
+) -> Result<(), CliError> {
+    let statuses = status.map(parse_statuses).transpose().map_err(CliError::Validation)?;
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(CliError::Validation(format!("--limit must be between 1 and {MAX_LIMIT}")));
+    }
+
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+    let tasks = client
+        .get_activity(project, None, statuses.as_deref(), limit)
+        .await
+        .map_err(|e| CliError::api("Failed to get analysis activity", e))?;
+    output::print_activity(&tasks, project, json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn try_mock_server() -> Option<MockServer> {
+        let listener = match std::net::TcpListener::bind("127.0.0.1:0") {
+            Ok(l) => l,
+            Err(_) => return None,
+        };
+        Some(MockServer::builder().listener(listener).start().await)
+    }
+
+    #[test]
+    fn test_parse_statuses() {
+        assert_eq!(parse_statuses("failed, success,FAILED").unwrap(), "FAILED,SUCCESS");
+        assert_eq!(parse_statuses("in_progress").unwrap(), "IN_PROGRESS");
+        let err = parse_statuses("DONE").unwrap_err();
+        assert!(err.contains("Valid values: PENDING, IN_PROGRESS"), "{err}");
+        assert!(parse_statuses(" , ").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_lists_mixed_status_tasks() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/ce/activity"))
+            .and(query_param("component", "my-proj"))
+            .and(query_param("ps", "5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "tasks": [
+                    {
+                        "id": "AX-failed",
+                        "type": "REPORT",
+                        "status": "FAILED",
+                        "submittedAt": "2026-03-02T09:30:00+0000",
+                        "executedAt": "2026-03-02T09:30:40+0000",
+                        "errorMessage": "Unsupported language"
+                    },
+                    {
+                        "id": "AX-ok",
+                        "type": "REPORT",
+                        "status": "SUCCESS",
+                        "submittedAt": "2026-03-01T10:00:00+0000",
+                        "executedAt": "2026-03-01T10:01:23+0000",
+                        "analysisId": "AU-1"
+                    }
+                ]
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let client = SonarQubeClient::new(config.clone()).unwrap();
+        let tasks = client.get_activity("my-proj", None, None, 5).await.unwrap();
+        let statuses: Vec<&str> = tasks.iter().map(|t| t.status.as_str()).collect();
+        assert_eq!(statuses, ["FAILED", "SUCCESS"]);
+        assert_eq!(tasks[0].error_message.as_deref(), Some("Unsupported language"));
+
+        assert!(run(config, "my-proj", None, 5, true).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_forwards_status_filter() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/ce/activity"))
+            .and(query_param("status", "PENDING,IN_PROGRESS"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"tasks": []})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(run(config, "my-proj", Some("pending,in_progress"), 20, false).await.is_ok());
+    }
+}
@@ -1,4 +1,8 @@
+use std::path::Path;
+
+use crate::client::normalize_url;
 use crate::config;
+use crate::error::CliError;
 
 /// Mask a token for display: show first 4 + last 4 chars, or `****` if ≤8 chars.
 fn mask_token(token: &str) -> String {
@@ -32,116 +36,148 @@ fn print_credentials(url: &Option<String>, token: &Option<String>) {
     }
 }
 
-/// Serialize a JSON value with pretty-print and write to stdout.
+/// Serialize a JSON value and write to stdout (honours `--compact-json`).
 fn print_json_value(value: &serde_json::Value) {
-    println!("{}", serde_json::to_string_pretty(value).unwrap());
+    crate::output::print_json(value);
 }
 
 /// Print the result of a successful login in human-readable or JSON format.
-fn print_login_result(stored: &config::StoredConfig, json: bool) {
+fn print_login_result(profile: &str, server: &config::ServerProfile, json: bool) {
     if json {
         let obj = serde_json::json!({
             "status": "saved",
-            "url": stored.url,
-            "token": stored.token.as_deref().map(mask_token),
+            "profile": profile,
+            "url": server.url,
+            "token": server.token.as_deref().map(mask_token),
         });
         print_json_value(&obj);
     } else {
-        println!("Credentials saved.");
-        print_credentials(&stored.url, &stored.token);
+        println!("Credentials saved (profile '{profile}').");
+        print_credentials(&server.url, &server.token);
     }
 }
 
-/// Merge url/token into stored config and validate token.
+/// Merge url/token into a server profile, normalizing the URL and validating both.
 /// Returns an error message if validation fails.
 fn apply_credentials(
-    stored: &mut config::StoredConfig,
+    server: &mut config::ServerProfile,
     url: Option<String>,
     token: Option<String>,
-) -> Result<(), &'static str> {
+) -> Result<(), String> {
     if let Some(u) = url {
-        stored.url = Some(u);
+        let normalized = normalize_url(&u)?;
+        if normalized.assumed_scheme {
+            eprintln!("Warning: URL '{u}' has no scheme; saving {}", normalized.url);
+        }
+        server.url = Some(normalized.url);
     }
     if let Some(t) = token {
         if t.is_empty() {
-            return Err("Token must not be empty.");
+            return Err("Token must not be empty.".to_string());
         }
-        stored.token = Some(t);
+        server.token = Some(t);
     }
     Ok(())
 }
 
-pub async fn login(url: Option<String>, token: Option<String>, json: bool) -> i32 {
+pub async fn login(
+    config_path: &Path,
+    url: Option<String>,
+    token: Option<String>,
+    profile: Option<&str>,
+    json: bool,
+) -> Result<(), CliError> {
     // Prompt via stdin if flags are omitted
     let url = url.or_else(|| prompt_stdin("SonarQube URL (leave empty to keep current): "));
     let token = token.or_else(|| prompt_stdin("SonarQube token: "));
 
     if url.is_none() && token.is_none() {
-        eprintln!("Nothing to save — both URL and token are empty.");
-        return 1;
+        return Err(CliError::Validation(
+            "Nothing to save — both URL and token are empty.".to_string(),
+        ));
     }
 
     // Merge with existing config to preserve fields not being set
-    let mut stored = config::load();
-    if let Err(msg) = apply_credentials(&mut stored, url, token) {
-        eprintln!("{msg}");
-        return 1;
-    }
+    let mut stored = config::load_from(config_path);
+    let name = stored.profile_name(profile).to_string();
+    let server = stored.profiles.entry(name.clone()).or_default();
+    apply_credentials(server, url, token).map_err(CliError::Validation)?;
+    let server = server.clone();
 
-    if let Err(e) = config::save(&stored) {
-        eprintln!("Failed to save config: {e}");
-        return 1;
-    }
+    config::save_to(&stored, config_path)
+        .map_err(|e| CliError::Failed(format!("Failed to save config: {e}")))?;
 
-    print_login_result(&stored, json);
-    0
+    print_login_result(&name, &server, json);
+    Ok(())
 }
 
-pub async fn status(json: bool) -> i32 {
-    let stored = config::load();
+pub async fn status(config_path: &Path, profile: Option<&str>, json: bool) -> Result<(), CliError> {
+    let stored = config::load_from(config_path);
+    let name = stored.profile_name(profile);
 
-    if stored.url.is_none() && stored.token.is_none() {
+    let configured = stored.server(profile).is_ok_and(|server| !server.is_empty());
+    if !configured {
         if json {
-            let obj = serde_json::json!({"status": "not_configured"});
+            let obj = serde_json::json!({"status": "not_configured", "profile": name});
             print_json_value(&obj);
         } else {
-            println!("No credentials configured. Run `sonar-cli auth login` to set up.");
+            println!("No credentials configured for profile '{name}'. Run `sonar-cli auth login` to set up.");
         }
-        return 0;
+        return Ok(());
     }
 
     if json {
-        let obj = serde_json::json!({"status": "configured"});
+        let obj = serde_json::json!({"status": "configured", "profile": name});
         print_json_value(&obj);
     } else {
-        println!("Credentials configured.");
+        println!("Credentials configured (profile '{name}').");
     }
 
-    0
+    Ok(())
 }
 
-pub async fn logout(json: bool) -> i32 {
-    match config::remove() {
-        Ok(()) => {
-            if json {
-                let obj = serde_json::json!({"status": "removed"});
-                print_json_value(&obj);
-            } else {
-                println!("Credentials removed.");
+/// Remove stored credentials: only the named profile when `--profile` is
+/// given, otherwise the whole config file.
+pub async fn logout(config_path: &Path, profile: Option<&str>, json: bool) -> Result<(), CliError> {
+    let result = match profile {
+        Some(name) => {
+            let mut stored = config::load_from(config_path);
+            if stored.profiles.remove(name).is_none() {
+                return Err(CliError::NotFound(format!("Unknown profile '{name}'.")));
             }
-            0
-        }
-        Err(e) => {
-            eprintln!("Failed to remove credentials: {e}");
-            1
+            if stored.default_profile.as_deref() == Some(name) {
+                stored.default_profile = None;
+            }
+            config::save_to(&stored, config_path)
         }
+        None => config::remove_at(config_path),
+    };
+    result.map_err(|e| CliError::Failed(format!("Failed to remove credentials: {e}")))?;
+    if json {
+        let obj = serde_json::json!({"status": "removed"});
+        print_json_value(&obj);
+    } else {
+        println!("Credentials removed.");
     }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serial_test::serial;
+    use std::path::PathBuf;
+
+    fn stored_with_credentials() -> config::StoredConfig {
+        let mut stored = config::StoredConfig::default();
+        stored.profiles.insert(
+            config::DEFAULT_PROFILE.to_string(),
+            config::ServerProfile {
+                url: Some("https://sonar.example.com".to_string()),
+                token: Some("squ_abcdefgh1234".to_string()),
+            },
+        );
+        stored
+    }
 
     #[test]
     fn test_mask_token_short() {
@@ -164,7 +200,7 @@ mod tests {
 
     #[test]
     fn test_apply_credentials_url_only() {
-        let mut stored = config::StoredConfig::default();
+        let mut stored = config::ServerProfile::default();
         let result = apply_credentials(&mut stored, Some("https://sonar.example.com".to_string()), None);
         assert!(result.is_ok());
         assert_eq!(stored.url.as_deref(), Some("https://sonar.example.com"));
@@ -173,7 +209,7 @@ mod tests {
 
     #[test]
     fn test_apply_credentials_token_only() {
-        let mut stored = config::StoredConfig::default();
+        let mut stored = config::ServerProfile::default();
         let result = apply_credentials(&mut stored, None, Some("squ_abc123xyz".to_string()));
         assert!(result.is_ok());
         assert!(stored.url.is_none());
@@ -182,7 +218,7 @@ mod tests {
 
     #[test]
     fn test_apply_credentials_both() {
-        let mut stored = config::StoredConfig::default();
+        let mut stored = config::ServerProfile::default();
         let result = apply_credentials(
             &mut stored,
             Some("https://sonar.example.com".to_string()),
@@ -195,7 +231,7 @@ mod tests {
 
     #[test]
     fn test_apply_credentials_neither_preserves_existing() {
-        let mut stored = config::StoredConfig {
+        let mut stored = config::ServerProfile {
             url: Some("existing_url".to_string()),
             token: Some("existing_token".to_string()),
         };
@@ -207,7 +243,7 @@ mod tests {
 
     #[test]
     fn test_apply_credentials_empty_token_returns_error() {
-        let mut stored = config::StoredConfig::default();
+        let mut stored = config::ServerProfile::default();
         let result = apply_credentials(&mut stored, None, Some(String::new()));
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), "Token must not be empty.");
@@ -215,16 +251,28 @@ mod tests {
 
     #[test]
     fn test_apply_credentials_overwrites_url() {
-        let mut stored = config::StoredConfig {
-            url: Some("old_url".to_string()),
+        let mut stored = config::ServerProfile {
+            url: Some("https://old.example.com".to_string()),
             token: Some("old_token".to_string()),
         };
-        let result = apply_credentials(&mut stored, Some("new_url".to_string()), None);
+        let result = apply_credentials(&mut stored, Some("https://new.example.com".to_string()), None);
         assert!(result.is_ok());
-        assert_eq!(stored.url.as_deref(), Some("new_url"));
+        assert_eq!(stored.url.as_deref(), Some("https://new.example.com"));
         assert_eq!(stored.token.as_deref(), Some("old_token"));
     }
 
+    #[test]
+    fn test_apply_credentials_normalizes_url() {
+        let mut stored = config::ServerProfile::default();
+        apply_credentials(&mut stored, Some("sonar.example.com/".to_string()), None).unwrap();
+        assert_eq!(stored.url.as_deref(), Some("https://sonar.example.com"));
+
+        let mut stored = config::ServerProfile::default();
+        let err = apply_credentials(&mut stored, Some("ftp://sonar.example.com".to_string()), None).unwrap_err();
+        assert!(err.contains("scheme must be http or https"), "{err}");
+        assert!(stored.url.is_none());
+    }
+
     // ── print_credentials ───────────────────────────────────────────────────
 
     #[test]
@@ -262,237 +310,226 @@ mod tests {
 
     #[test]
     fn test_print_login_result_human_with_token() {
-        let stored = config::StoredConfig {
+        let server = config::ServerProfile {
             url: Some("https://sonar.example.com".to_string()),
             token: Some("squ_abcdefgh1234".to_string()),
         };
-        print_login_result(&stored, false);
+        print_login_result("default", &server, false);
     }
 
     #[test]
     fn test_print_login_result_json_with_token() {
-        let stored = config::StoredConfig {
+        let server = config::ServerProfile {
             url: Some("https://sonar.example.com".to_string()),
             token: Some("squ_abcdefgh1234".to_string()),
         };
-        print_login_result(&stored, true);
+        print_login_result("default", &server, true);
     }
 
     #[test]
     fn test_print_login_result_human_no_token() {
-        let stored = config::StoredConfig {
+        let server = config::ServerProfile {
             url: Some("https://sonar.example.com".to_string()),
             token: None,
         };
-        print_login_result(&stored, false);
+        print_login_result("default", &server, false);
     }
 
     #[test]
     fn test_print_login_result_json_no_token() {
-        let stored = config::StoredConfig {
+        let server = config::ServerProfile {
             url: Some("https://sonar.example.com".to_string()),
             token: None,
         };
-        print_login_result(&stored, true);
+        print_login_result("default", &server, true);
     }
 
     #[test]
     fn test_print_login_result_json_no_url_no_token() {
-        let stored = config::StoredConfig { url: None, token: None };
-        print_login_result(&stored, true);
+        print_login_result("default", &config::ServerProfile::default(), true);
+    }
+
+    /// A config file path of the test's own, so tests never touch the user's config
+    fn config_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("sonar-cli-test-auth-{name}-{}.toml", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
     }
 
     // ── login ───────────────────────────────────────────────────────────────
 
     #[tokio::test]
     async fn test_login_empty_token_returns_error() {
-        // url provided, empty token → apply_credentials returns Err → returns 1
-        // Does NOT write to config (early return before config::save)
+        // url provided, empty token → apply_credentials returns Err
+        // Does NOT write to config (early return before config::save_to)
+        let path = config_file("login-empty-token");
         let result = login(
+            &path,
             Some("https://sonar.example.com".to_string()),
             Some(String::new()),
+            None,
             false,
         )
         .await;
-        assert_eq!(result, 1);
+        assert!(result.is_err());
+        assert!(!path.exists());
     }
 
     #[tokio::test]
     async fn test_login_empty_token_json_returns_error() {
+        let path = config_file("login-empty-token-json");
         let result = login(
+            &path,
             Some("https://sonar.example.com".to_string()),
             Some(String::new()),
+            None,
             true,
         )
         .await;
-        assert_eq!(result, 1);
+        assert!(result.is_err());
+        assert!(!path.exists());
     }
 
-    /// Saves test credentials, asserts login succeeds, then restores the previous
-    /// config state so the test is idempotent on developer machines.
     #[tokio::test]
-    #[serial]
     async fn test_login_success_human() {
-        let backup = config::load();
+        let path = config_file("login-human");
         let result = login(
+            &path,
             Some("https://test.sonar.example.com".to_string()),
             Some("squ_test_token_abcdefgh1234".to_string()),
+            None,
             false,
         )
         .await;
-        // Restore prior state
-        if backup.url.is_none() && backup.token.is_none() {
-            let _ = config::remove();
-        } else {
-            let _ = config::save(&backup);
-        }
-        assert_eq!(result, 0);
+        assert!(result.is_ok());
+        let server = config::load_from(&path).server(None).unwrap();
+        assert_eq!(server.url.as_deref(), Some("https://test.sonar.example.com"));
+        assert_eq!(server.token.as_deref(), Some("squ_test_token_abcdefgh1234"));
+        let _ = std::fs::remove_file(&path);
     }
 
     #[tokio::test]
-    #[serial]
     async fn test_login_success_json() {
-        let backup = config::load();
+        let path = config_file("login-json");
         let result = login(
+            &path,
             Some("https://test.sonar.example.com".to_string()),
             Some("squ_test_token_abcdefgh1234".to_string()),
+            Some("staging"),
             true,
         )
         .await;
-        if backup.url.is_none() && backup.token.is_none() {
-            let _ = config::remove();
-        } else {
-            let _ = config::save(&backup);
-        }
-        assert_eq!(result, 0);
+        assert!(result.is_ok());
+        let stored = config::load_from(&path);
+        assert!(stored.profiles.contains_key("staging"));
+        assert!(!stored.profiles.contains_key(config::DEFAULT_PROFILE));
+        let _ = std::fs::remove_file(&path);
     }
 
     // ── status ──────────────────────────────────────────────────────────────
 
     #[tokio::test]
     async fn test_status_human_returns_success() {
-        // config::load() is read-only — safe in all environments
-        let result = status(false).await;
-        assert_eq!(result, 0);
+        let path = config_file("status-default-human");
+        let result = status(&path, None, false).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
     async fn test_status_json_returns_success() {
-        let result = status(true).await;
-        assert_eq!(result, 0);
+        let path = config_file("status-default-json");
+        let result = status(&path, None, true).await;
+        assert!(result.is_ok());
     }
 
     /// Exercise the "configured" branch of status by saving credentials first.
     #[tokio::test]
-    #[serial]
     async fn test_status_human_with_credentials() {
-        let backup = config::load();
-        let _ = config::save(&config::StoredConfig {
-            url: Some("https://sonar.example.com".to_string()),
-            token: Some("squ_abcdefgh1234".to_string()),
-        });
-        let result = status(false).await;
-        // Restore
-        if backup.url.is_none() && backup.token.is_none() {
-            let _ = config::remove();
-        } else {
-            let _ = config::save(&backup);
-        }
-        assert_eq!(result, 0);
+        let path = config_file("status-human");
+        config::save_to(&stored_with_credentials(), &path).unwrap();
+        let result = status(&path, None, false).await;
+        let _ = std::fs::remove_file(&path);
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
-    #[serial]
     async fn test_status_json_with_credentials() {
-        let backup = config::load();
-        let _ = config::save(&config::StoredConfig {
-            url: Some("https://sonar.example.com".to_string()),
-            token: Some("squ_abcdefgh1234".to_string()),
-        });
-        let result = status(true).await;
-        if backup.url.is_none() && backup.token.is_none() {
-            let _ = config::remove();
-        } else {
-            let _ = config::save(&backup);
-        }
-        assert_eq!(result, 0);
+        let path = config_file("status-json");
+        config::save_to(&stored_with_credentials(), &path).unwrap();
+        let result = status(&path, None, true).await;
+        let _ = std::fs::remove_file(&path);
+        assert!(result.is_ok());
     }
 
     /// Exercise status when no credentials are configured.
     #[tokio::test]
-    #[serial]
     async fn test_status_human_no_credentials() {
-        let backup = config::load();
-        let _ = config::remove();
-        let result = status(false).await;
-        // Restore
-        if backup.url.is_some() || backup.token.is_some() {
-            let _ = config::save(&backup);
-        }
-        assert_eq!(result, 0);
+        let path = config_file("status-none-human");
+        assert!(status(&path, None, false).await.is_ok());
     }
 
     #[tokio::test]
-    #[serial]
     async fn test_status_json_no_credentials() {
-        let backup = config::load();
-        let _ = config::remove();
-        let result = status(true).await;
-        if backup.url.is_some() || backup.token.is_some() {
-            let _ = config::save(&backup);
-        }
-        assert_eq!(result, 0);
+        let path = config_file("status-none-json");
+        assert!(status(&path, None, true).await.is_ok());
     }
 
     // ── logout ──────────────────────────────────────────────────────────────
 
     /// Saves credentials, then logs out — exercises the file-removal path.
-    /// Uses #[serial] to avoid races with other config-touching tests.
     #[tokio::test]
-    #[serial]
     async fn test_logout_human_removes_credentials() {
-        let backup = config::load();
-        let _ = config::save(&config::StoredConfig {
-            url: Some("https://sonar.example.com".to_string()),
-            token: Some("squ_abcdefgh1234".to_string()),
-        });
-        let result = logout(false).await;
-        // Restore if there were real credentials before the test
-        if backup.url.is_some() || backup.token.is_some() {
-            let _ = config::save(&backup);
-        }
-        assert_eq!(result, 0);
+        let path = config_file("logout-human");
+        config::save_to(&stored_with_credentials(), &path).unwrap();
+        let result = logout(&path, None, false).await;
+        assert!(result.is_ok());
+        assert!(!path.exists());
     }
 
     #[tokio::test]
-    #[serial]
     async fn test_logout_json_removes_credentials() {
-        let backup = config::load();
-        let _ = config::save(&config::StoredConfig {
-            url: Some("https://sonar.example.com".to_string()),
-            token: Some("squ_abcdefgh1234".to_string()),
-        });
-        let result = logout(true).await;
-        if backup.url.is_some() || backup.token.is_some() {
-            let _ = config::save(&backup);
-        }
-        assert_eq!(result, 0);
+        let path = config_file("logout-json");
+        config::save_to(&stored_with_credentials(), &path).unwrap();
+        let result = logout(&path, None, true).await;
+        assert!(result.is_ok());
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_logout_profile_keeps_other_profiles() {
+        let path = config_file("logout-profile");
+        let mut stored = stored_with_credentials();
+        stored.profiles.insert("prod".to_string(), config::ServerProfile::default());
+        config::save_to(&stored, &path).unwrap();
+
+        assert!(logout(&path, Some("prod"), false).await.is_ok());
+        let stored = config::load_from(&path);
+        assert!(!stored.profiles.contains_key("prod"));
+        assert!(stored.profiles.contains_key(config::DEFAULT_PROFILE));
+
+        let err = logout(&path, Some("prod"), false).await.unwrap_err();
+        assert!(matches!(err, CliError::NotFound(_)));
+        let _ = std::fs::remove_file(&path);
     }
 
     // ── login: both url and token None path ─────────────────────────────────
 
-    /// When both url and token are None, login exits early with code 1.
+    /// When both url and token are None, login fails early.
     /// In the test environment stdin is closed (EOF), so prompt_stdin returns None
-    /// for both calls, exercising the "nothing to save" early return (lines 79-82).
+    /// for both calls, exercising the "nothing to save" early return.
     #[tokio::test]
     async fn test_login_both_none_returns_error_human() {
-        let result = login(None, None, false).await;
-        assert_eq!(result, 1);
+        let path = config_file("login-none-human");
+        let result = login(&path, None, None, None, false).await;
+        assert!(result.is_err());
+        assert!(!path.exists());
     }
 
     #[tokio::test]
     async fn test_login_both_none_returns_error_json() {
-        let result = login(None, None, true).await;
-        assert_eq!(result, 1);
+        let path = config_file("login-none-json");
+        let result = login(&path, None, None, None, true).await;
+        assert!(result.is_err());
+        assert!(!path.exists());
     }
 }
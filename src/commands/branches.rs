@@ -0,0 +1,86 @@
+use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::error::CliError;
+use crate::output;
+
+pub async fn run(config: SonarQubeConfig, project: &str, json: bool) -> Result<(), CliError> {
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+    let branches = client
+        .list_branches(project)
+        .await
+        .map_err(|e| CliError::api("Failed to list branches", e))?;
+    output::print_branches(&branches, project, json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn try_mock_server() -> Option<MockServer> {
+        let listener = match std::net::TcpListener::bind("127.0.0.1:0") {
+            Ok(l) => l,
+            Err(_) => return None,
+        };
+        Some(MockServer::builder().listener(listener).start().await)
+    }
+
+    #[tokio::test]
+    async fn test_list_branches_flags_main() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/project_branches/list"))
+            .and(query_param("project", "my-proj"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "branches": [
+                    {
+                        "name": "main",
+                        "isMain": true,
+                        "type": "LONG",
+                        "status": {"qualityGateStatus": "OK"},
+                        "analysisDate": "2026-03-01T10:00:00+0000"
+                    },
+                    {
+                        "name": "feature/login",
+                        "isMain": false,
+                        "type": "SHORT",
+                        "status": {"qualityGateStatus": "ERROR"},
+                        "analysisDate": "2026-03-02T09:30:00+0000"
+                    }
+                ]
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let client = SonarQubeClient::new(config.clone()).unwrap();
+        let branches = client.list_branches("my-proj").await.unwrap();
+        assert_eq!(branches.len(), 2);
+        let main: Vec<&str> = branches.iter().filter(|b| b.is_main).map(|b| b.name.as_str()).collect();
+        assert_eq!(main, ["main"]);
+        assert_eq!(branches[1].branch_type.as_deref(), Some("SHORT"));
+
+        assert!(run(config, "my-proj", true).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_unknown_project_fails() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/project_branches/list"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(run(config, "missing", false).await.is_err());
+    }
+}
@@ -0,0 +1,21 @@
+use crate::cache;
+use crate::error::CliError;
+use crate::output;
+
+/// Delete all cached responses (`cache clear`)
+pub fn clear(json: bool) -> Result<(), CliError> {
+    let dir = cache::default_dir()
+        .ok_or_else(|| CliError::Failed("Could not determine config directory".to_string()))?;
+    let removed = cache::clear(&dir)
+        .map_err(|e| CliError::Failed(format!("Failed to clear cache {}: {e}", dir.display())))?;
+
+    if json {
+        output::print_json(&serde_json::json!({
+            "removed": removed,
+            "path": dir.display().to_string(),
+        }));
+    } else {
+        output::print_line(&format!("Removed {removed} cached response(s) from {}", dir.display()));
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use std::io::Write;
+
+use clap_complete::Shell;
+
+use crate::error::CliError;
+
+pub fn parse_shell(s: &str) -> Result<Shell, String> {
+    match s.to_lowercase().as_str() {
+        "bash" => Ok(Shell::Bash),
+        "zsh" => Ok(Shell::Zsh),
+        "fish" => Ok(Shell::Fish),
+        "powershell" => Ok(Shell::PowerShell),
+        other => Err(format!(
+            "Unknown shell '{other}'. Valid values: bash, zsh, fish, powershell"
+        )),
+    }
+}
+
+/// Write the completion script for `cmd` to `out`
+fn write_completions<W: Write>(shell: Shell, cmd: &mut clap::Command, out: &mut W) {
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, cmd, name, out);
+}
+
+/// Print a shell completion script for the CLI to stdout (or `--output`)
+pub fn run(shell: &str, mut cmd: clap::Command) -> Result<(), CliError> {
+    let shell = parse_shell(shell).map_err(CliError::Validation)?;
+
+    let mut out = crate::output::sink();
+    write_completions(shell, &mut cmd, &mut out);
+    out.flush().map_err(CliError::output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn completions(shell: Shell) -> String {
+        let mut out = Vec::new();
+        write_completions(shell, &mut crate::Cli::command(), &mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_parse_shell() {
+        assert_eq!(parse_shell("bash").unwrap(), Shell::Bash);
+        assert_eq!(parse_shell("PowerShell").unwrap(), Shell::PowerShell);
+        assert!(parse_shell("tcsh").unwrap_err().contains("Unknown shell 'tcsh'"));
+    }
+
+    #[test]
+    fn test_bash_completions_mention_subcommands() {
+        let script = completions(Shell::Bash);
+        assert!(script.contains("sonar-cli"));
+        assert!(script.contains("issues"));
+        assert!(script.contains("quality-gate"));
+        // Subcommand flags are reflected too
+        assert!(script.contains("--fail-on-error"));
+    }
+
+    #[test]
+    fn test_all_shells_generate() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            assert!(!completions(shell).is_empty());
+        }
+    }
+}
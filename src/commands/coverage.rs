@@ -1,31 +1,30 @@
 use crate::client::{SonarQubeClient, SonarQubeConfig};
-use crate::output;
-use crate::helpers::{self, FileCoverage};
+use crate::error::CliError;
+use crate::output::{self, OutputFormat};
+use crate::helpers::{self, FileCoverage, Window};
+use crate::ignore_file::PathFilter;
+use crate::types::TreeComponent;
 
-pub async fn run(
-    config: SonarQubeConfig,
+/// Sort files by `coverage` (ascending, the default), `uncovered` (descending) or `file`
+fn sort_coverage(coverage: &mut [FileCoverage], sort: Option<&str>) {
+    match sort.unwrap_or("coverage") {
+        "uncovered" => coverage.sort_by_key(|f| std::cmp::Reverse(f.uncovered_lines)),
+        "file" => coverage.sort_by(|a, b| a.file.cmp(&b.file)),
+        _ => coverage.sort_by(|a, b| {
+            a.coverage_percent
+                .partial_cmp(&b.coverage_percent)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        }),
+    }
+}
+
+/// Per-file coverage from component tree entries, dropping files at or above `min_coverage`
+pub fn file_coverage(
+    files: Vec<TreeComponent>,
     project: &str,
     min_coverage: Option<f64>,
-    sort: Option<&str>,
-    json: bool,
-) -> i32 {
-    let client = match SonarQubeClient::new(config) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to create client: {e}");
-            return 1;
-        }
-    };
-
-    let files = match client.get_files_coverage(project).await {
-        Ok(f) => f,
-        Err(e) => {
-            eprintln!("Failed to get coverage: {e}");
-            return 1;
-        }
-    };
-
-    let mut coverage: Vec<FileCoverage> = files
+) -> Vec<FileCoverage> {
+    files
         .into_iter()
         .filter_map(|f| {
             let path = helpers::extract_path(&f.key, project);
@@ -52,20 +51,50 @@ pub async fn run(
                 lines_to_cover,
             })
         })
-        .collect();
+        .collect()
+}
 
-    match sort.unwrap_or("coverage") {
-        "uncovered" => coverage.sort_by(|a, b| b.uncovered_lines.cmp(&a.uncovered_lines)),
-        "file" => coverage.sort_by(|a, b| a.file.cmp(&b.file)),
-        _ => coverage.sort_by(|a, b| {
-            a.coverage_percent
-                .partial_cmp(&b.coverage_percent)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        }),
+pub async fn run(
+    config: SonarQubeConfig,
+    project: &str,
+    min_coverage: Option<f64>,
+    sort: Option<&str>,
+    window: Window,
+    ignore: &PathFilter,
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    if format == OutputFormat::Ndjson {
+        return Err(CliError::Validation(output::unsupported_format(
+            format,
+            "coverage",
+            "text, json, csv or tsv",
+        )));
     }
+    let json = format == OutputFormat::Json;
 
-    output::print_coverage(&coverage, project, json);
-    0
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let files = client.get_files_coverage(project).await;
+    if helpers::empty_or_missing(&files, Vec::is_empty) && helpers::never_analyzed(&client, project).await {
+        output::print_not_analyzed(project, json);
+        return Ok(());
+    }
+    let files = files.map_err(|e| CliError::api("Failed to get coverage", e))?;
+
+    let mut coverage = file_coverage(files, project, min_coverage);
+
+    ignore.retain(&mut coverage, |f| f.file.as_str());
+    sort_coverage(&mut coverage, sort);
+    let coverage = window.apply(coverage);
+
+    match format.delimiter() {
+        Some(delimiter) => output::write_coverage_delimited(&mut output::sink(), &coverage, delimiter)
+            .map_err(CliError::output),
+        None => {
+            output::print_coverage(&coverage, project, json);
+            Ok(())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -82,6 +111,32 @@ mod tests {
         Some(MockServer::builder().listener(listener).start().await)
     }
 
+    fn file_coverage(file: &str, coverage_percent: f64) -> FileCoverage {
+        FileCoverage {
+            file: file.to_string(),
+            coverage_percent,
+            uncovered_lines: 0,
+            lines_to_cover: 10,
+        }
+    }
+
+    #[test]
+    fn test_sort_then_window() {
+        let mut files: Vec<FileCoverage> = [70.0, 10.0, 50.0, 90.0, 30.0, 60.0]
+            .iter()
+            .enumerate()
+            .map(|(i, c)| file_coverage(&format!("f{i}.rs"), *c))
+            .collect();
+        sort_coverage(&mut files, None);
+        let window = Window { skip: 2, top: Some(3) };
+        let shown: Vec<f64> = window
+            .apply(files)
+            .iter()
+            .map(|f| f.coverage_percent)
+            .collect();
+        assert_eq!(shown, vec![50.0, 60.0, 70.0]);
+    }
+
     fn coverage_tree_body(coverage_val: &str) -> serde_json::Value {
         serde_json::json!({
             "paging": {"total": 1},
@@ -112,8 +167,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", None, None, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", None, None, Window::default(), &PathFilter::none(), OutputFormat::Text).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -129,8 +184,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", None, None, true).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", None, None, Window::default(), &PathFilter::none(), OutputFormat::Json).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -147,8 +202,8 @@ mod tests {
 
         // min_coverage=80 should filter out files with coverage >= 80 (95.0 gets filtered)
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", Some(80.0), None, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", Some(80.0), None, Window::default(), &PathFilter::none(), OutputFormat::Text).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -164,8 +219,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", None, Some("uncovered"), false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", None, Some("uncovered"), Window::default(), &PathFilter::none(), OutputFormat::Text).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -181,8 +236,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", None, Some("file"), false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", None, Some("file"), Window::default(), &PathFilter::none(), OutputFormat::Text).await;
+        assert!(result.is_ok());
     }
 
     fn coverage_tree_multi_body() -> serde_json::Value {
@@ -234,8 +289,8 @@ mod tests {
 
         let config = SonarQubeConfig::new(mock_server.uri());
         // Default sort (None → "coverage") with 3 files exercises the sort comparator
-        let exit = run(config, "my-proj", None, None, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", None, None, Window::default(), &PathFilter::none(), OutputFormat::Text).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -251,7 +306,7 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", None, None, false).await;
-        assert_eq!(exit, 1);
+        let result = run(config, "my-proj", None, None, Window::default(), &PathFilter::none(), OutputFormat::Text).await;
+        assert!(result.is_err());
     }
 }
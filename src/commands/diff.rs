@@ -0,0 +1,137 @@
+use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::commands::{history, measures};
+use crate::error::CliError;
+use crate::helpers::{self, diff_history, is_plain_date};
+use crate::output;
+
+/// Turn a `--from`/`--to` value into a history cutoff: plain dates are used
+/// as-is, anything else is looked up as an analysis key
+async fn resolve_point(client: &SonarQubeClient, project: &str, point: &str) -> Result<String, CliError> {
+    if is_plain_date(point) {
+        return Ok(point.to_string());
+    }
+    match client.get_analysis_date(project, point).await {
+        Ok(Some(date)) => Ok(date),
+        Ok(None) => Err(CliError::Validation(format!(
+            "'{point}' is neither a date (YYYY-MM-DD) nor an analysis of {project}"
+        ))),
+        Err(e) => Err(CliError::api(format!("Failed to look up analysis {point}"), e)),
+    }
+}
+
+/// Compare metric values between two dates or analyses
+pub async fn run(
+    config: SonarQubeConfig,
+    project: &str,
+    metrics: Option<&str>,
+    from: &str,
+    to: Option<&str>,
+    json: bool,
+) -> Result<(), CliError> {
+    let metrics = match metrics {
+        Some(m) => history::normalize_metrics(m).map_err(CliError::Validation)?,
+        None => measures::DEFAULT_METRICS.join(","),
+    };
+
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let from_cutoff = resolve_point(&client, project, from).await?;
+    let to_cutoff = match to {
+        Some(t) => Some(resolve_point(&client, project, t).await?),
+        None => None,
+    };
+
+    // No date bounds: the value as of `from` may come from any earlier analysis
+    let history = history::fetch_history(&client, project, &metrics, None, None).await;
+    if helpers::empty_or_missing(&history, |h| history::no_history(h))
+        && helpers::never_analyzed(&client, project).await
+    {
+        output::print_not_analyzed(project, json);
+        return Ok(());
+    }
+    let history = history.map_err(|e| CliError::api("Failed to fetch measures history", e))?;
+
+    let rows = diff_history(&history, &from_cutoff, to_cutoff.as_deref());
+    output::print_metric_diff(&rows, project, from, to.unwrap_or("latest"), json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn try_mock_server() -> Option<MockServer> {
+        let listener = match std::net::TcpListener::bind("127.0.0.1:0") {
+            Ok(l) => l,
+            Err(_) => return None,
+        };
+        Some(MockServer::builder().listener(listener).start().await)
+    }
+
+    async fn mount_history(server: &MockServer) {
+        Mock::given(method("GET"))
+            .and(path("/api/measures/search_history"))
+            .and(query_param("metrics", "coverage"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "paging": {"pageIndex": 1, "pageSize": 100, "total": 1},
+                "measures": [{
+                    "metric": "coverage",
+                    "history": [
+                        {"date": "2026-01-01T10:00:00+0000", "value": "80.0"},
+                        {"date": "2026-02-01T10:00:00+0000", "value": "76.5"}
+                    ]
+                }]
+            })))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_run_diff_by_analysis_key() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        mount_history(&mock_server).await;
+        Mock::given(method("GET"))
+            .and(path("/api/project_analyses/search"))
+            .and(query_param("project", "my-proj"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "paging": {"pageIndex": 1, "pageSize": 100, "total": 2},
+                "analyses": [
+                    {"key": "AU-new", "date": "2026-02-01T10:00:00+0000"},
+                    {"key": "AU-old", "date": "2026-01-01T10:00:00+0000"}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", Some("coverage"), "AU-old", Some("2026-02-01"), true).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_unknown_analysis_key_fails() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/project_analyses/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "paging": {"pageIndex": 1, "pageSize": 100, "total": 0},
+                "analyses": []
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", Some("coverage"), "AU-missing", None, false).await;
+        assert!(result.is_err());
+    }
+}
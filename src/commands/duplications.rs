@@ -1,31 +1,32 @@
 use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::error::CliError;
 use crate::output;
-use crate::helpers;
+use crate::helpers::{self, Window};
+use crate::ignore_file::PathFilter;
 
 pub async fn run(
     config: SonarQubeConfig,
     project: &str,
     details: bool,
+    window: Window,
+    ignore: &PathFilter,
     json: bool,
-) -> i32 {
-    let client = match SonarQubeClient::new(config) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to create client: {e}");
-            return 1;
-        }
-    };
+) -> Result<(), CliError> {
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
 
-    match helpers::fetch_extended_data(&client, project).await {
-        Ok(data) => {
-            output::print_duplications(&data.duplications, project, json, details);
-            0
-        }
-        Err(e) => {
-            eprintln!("Failed to get duplications: {e}");
-            1
-        }
+    let data = helpers::fetch_extended_data(&client, project).await;
+    if helpers::empty_or_missing(&data, |d| d.duplications.is_empty())
+        && helpers::never_analyzed(&client, project).await
+    {
+        output::print_not_analyzed(project, json);
+        return Ok(());
     }
+    let data = data.map_err(|e| CliError::api("Failed to get duplications", e))?;
+    let mut duplications = data.duplications;
+    ignore.retain(&mut duplications, |f| f.file.as_str());
+    let duplications = window.apply(duplications);
+    output::print_duplications(&duplications, project, json, details);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -92,8 +93,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", false, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", false, Window::default(), &PathFilter::none(), false).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -109,8 +110,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", true, true).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", true, Window::default(), &PathFilter::none(), true).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -126,9 +127,9 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", false, false).await;
+        let result = run(config, "my-proj", false, Window::default(), &PathFilter::none(), false).await;
         // fetch_extended_data swallows the error with unwrap_or_default, so still 0
-        assert_eq!(exit, 0);
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -144,8 +145,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", true, true).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", true, Window::default(), &PathFilter::none(), true).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -192,8 +193,8 @@ mod tests {
 
         let config = SonarQubeConfig::new(mock_server.uri());
         // Test with details=true, json=false (text output)
-        let exit = run(config, "my-proj", true, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", true, Window::default(), &PathFilter::none(), false).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -240,8 +241,8 @@ mod tests {
 
         let config = SonarQubeConfig::new(mock_server.uri());
         // Test with details=false, json=true
-        let exit = run(config, "my-proj", false, true).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", false, Window::default(), &PathFilter::none(), true).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -258,7 +259,7 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", false, true).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", false, Window::default(), &PathFilter::none(), true).await;
+        assert!(result.is_ok());
     }
 }
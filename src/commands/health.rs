@@ -1,34 +1,65 @@
-use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::client::{SonarQubeClient, SonarQubeConfig, SonarQubeError};
+use crate::error::{exit_code, CliError};
 use crate::output;
+use crate::types::SystemInfo;
 
-pub async fn run(config: SonarQubeConfig, json: bool) -> i32 {
-    let client = match SonarQubeClient::new(config.clone()) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to create client: {e}");
-            return 1;
+/// Health check: node health with `full` when a token is configured,
+/// otherwise the coarse UP/STARTING/DOWN status
+pub async fn run(config: SonarQubeConfig, full: bool, strict: bool, json: bool) -> Result<(), CliError> {
+    let client = SonarQubeClient::new(config.clone()).map_err(CliError::client)?;
+
+    if full {
+        if config.token.is_some() {
+            return run_full(&client, &config.url, strict, json).await;
         }
-    };
+        eprintln!("Warning: --full needs a token; showing /api/system/status instead");
+    }
 
-    let status_result = client.get_status().await;
-    match status_result {
-        Ok(status) => {
-            let url = config.url.clone();
-            output::print_health(&status, &url, json);
-            if status == "UP" { 0 } else { 1 }
+    match status_with_info(&client).await {
+        Ok((status, info)) => {
+            output::print_health(&status, &config.url, info.as_ref(), json);
+            if status == "UP" { Ok(()) } else { Err(CliError::Exit(exit_code::FAILURE)) }
         }
         Err(e) => {
+            let context = format!("Failed to reach SonarQube at {}", config.url);
             if json {
-                let url = config.url.clone();
-                output::print_health("UNREACHABLE", &url, json);
-            } else {
-                eprintln!("Failed to reach SonarQube at {}: {e}", config.url);
+                // The JSON report says UNREACHABLE; keep the cause off stdout
+                output::print_health("UNREACHABLE", &config.url, None, json);
             }
-            1
+            Err(CliError::api(context, e))
         }
     }
 }
 
+/// The coarse server status, with server details when it is UP
+async fn status_with_info(
+    client: &SonarQubeClient,
+) -> Result<(String, Option<SystemInfo>), SonarQubeError> {
+    let status = client.get_status().await?;
+    // Best effort: a server that is not UP may not answer these either
+    let info = if status == "UP" {
+        client.get_system_info().await.ok()
+    } else {
+        None
+    };
+    Ok((status, info))
+}
+
+/// Report `/api/system/health`; RED fails, and YELLOW too when `strict`
+async fn run_full(client: &SonarQubeClient, url: &str, strict: bool, json: bool) -> Result<(), CliError> {
+    let health = client
+        .get_system_health()
+        .await
+        .map_err(|e| CliError::api(format!("Failed to fetch health from {url}"), e))?;
+    let healthy = match health.health.as_str() {
+        "RED" => false,
+        "YELLOW" => !strict,
+        _ => true,
+    };
+    output::print_system_health(&health, url, healthy, json);
+    if healthy { Ok(()) } else { Err(CliError::Exit(exit_code::FAILURE)) }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,8 +90,45 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, false, false, false).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_health_json_with_version() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/system/status"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"status": "UP"})),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/server/version"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("10.4.1.88267"))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/system/info"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let client = SonarQubeClient::new(config.clone()).unwrap();
+        let (status, info) = status_with_info(&client).await.unwrap();
+        let doc = output::health_json(&status, &config.url, info.as_ref());
+        assert_eq!(doc["status"], "UP");
+        assert_eq!(doc["version"], "10.4.1.88267");
+
+        let result = run(config, false, false, true).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -79,8 +147,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, false).await;
-        assert_eq!(exit, 1);
+        let result = run(config, false, false, false).await;
+        assert!(result.is_err());
     }
 
     #[tokio::test]
@@ -96,8 +164,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, true).await;
-        assert_eq!(exit, 1);
+        let result = run(config, false, false, true).await;
+        assert!(result.is_err());
     }
 
     #[tokio::test]
@@ -113,8 +181,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, false).await;
-        assert_eq!(exit, 1);
+        let result = run(config, false, false, false).await;
+        assert!(result.is_err());
     }
 
     #[tokio::test]
@@ -133,7 +201,88 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, true).await;
-        assert_eq!(exit, 0);
+        let result = run(config, false, false, true).await;
+        assert!(result.is_ok());
+    }
+
+    async fn mount_health(server: &MockServer, body: serde_json::Value) {
+        Mock::given(method("GET"))
+            .and(path("/api/system/health"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .expect(1..)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_run_health_full_green() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        mount_health(&mock_server, serde_json::json!({"health": "GREEN", "causes": []})).await;
+
+        let config = SonarQubeConfig::new(mock_server.uri()).with_token("admin");
+        assert!(run(config.clone(), true, true, false).await.is_ok());
+        assert!(run(config, true, true, true).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_health_full_red_with_causes_fails() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        mount_health(
+            &mock_server,
+            serde_json::json!({
+                "health": "RED",
+                "causes": [{"message": "Elasticsearch status is RED"}, {"message": "Compute Engine is down"}]
+            }),
+        )
+        .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri()).with_token("admin");
+        let err = run(config, true, false, false).await.unwrap_err();
+        assert_eq!(err.exit_code(), exit_code::FAILURE);
+    }
+
+    #[tokio::test]
+    async fn test_run_health_full_yellow_fails_only_when_strict() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        mount_health(
+            &mock_server,
+            serde_json::json!({"health": "YELLOW", "causes": [{"message": "Elasticsearch status is YELLOW"}]}),
+        )
+        .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri()).with_token("admin");
+        assert!(run(config.clone(), true, false, true).await.is_ok());
+        assert!(run(config, true, true, true).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_health_full_without_token_uses_status() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/system/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"status": "UP"})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/system/health"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"health": "RED"})))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(run(config, true, false, false).await.is_ok());
     }
 }
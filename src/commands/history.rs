@@ -1,4 +1,6 @@
-use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::client::{SonarQubeClient, SonarQubeConfig, SonarQubeError};
+use crate::error::CliError;
+use crate::helpers::{self, gate_timeline, pivot_history, ALERT_STATUS_METRIC};
 use crate::output;
 use crate::types::{MeasureHistory, MeasuresHistoryResponse};
 
@@ -16,42 +18,61 @@ fn merge_page_measures(all: &mut Vec<MeasureHistory>, page: Vec<MeasureHistory>)
     }
 }
 
+/// Normalise a comma-separated metric list: trim each key and drop blanks.
+///
+/// Errors when no usable key remains, so an empty `--metrics` fails locally
+/// instead of producing an opaque server error.
+pub fn normalize_metrics(metrics: &str) -> Result<String, String> {
+    let keys: Vec<&str> = metrics
+        .split(',')
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+        .collect();
+    if keys.is_empty() {
+        return Err(
+            "--metrics requires at least one metric key (e.g. --metrics coverage,bugs)".to_string(),
+        );
+    }
+    Ok(keys.join(","))
+}
+
+/// How the fetched history is rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryView {
+    /// One section per metric
+    PerMetric,
+    /// Dates as rows, metrics as columns
+    Table,
+    /// Quality gate status changes only (`alert_status`)
+    GateTimeline,
+}
+
 /// Returns true when all pages have been fetched.
 fn pagination_done(response_total: usize, page: usize, page_size: usize) -> bool {
     page * page_size >= response_total || page >= 100
 }
 
-pub async fn run(
-    config: SonarQubeConfig,
+/// Whether fetched history has no data points at all, as for a never-analyzed project
+pub fn no_history(measures: &[MeasureHistory]) -> bool {
+    measures.iter().all(|m| m.history.is_empty())
+}
+
+/// Fetch every page of history for `metrics`, merged into one entry per metric
+pub async fn fetch_history(
+    client: &SonarQubeClient,
     project: &str,
     metrics: &str,
     from: Option<&str>,
     to: Option<&str>,
-    json: bool,
-) -> i32 {
-    let client = match SonarQubeClient::new(config) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to create client: {e}");
-            return 1;
-        }
-    };
-
+) -> Result<Vec<MeasureHistory>, SonarQubeError> {
     let mut all_measures: Vec<MeasureHistory> = Vec::new();
     let mut page = 1;
     let page_size = 100;
 
     loop {
-        let response: MeasuresHistoryResponse = match client
+        let response: MeasuresHistoryResponse = client
             .get_measures_history(project, metrics, from, to, page, page_size)
-            .await
-        {
-            Ok(r) => r,
-            Err(e) => {
-                eprintln!("Failed to fetch measures history: {e}");
-                return 1;
-            }
-        };
+            .await?;
 
         let total = response.paging.total;
         merge_page_measures(&mut all_measures, response.measures);
@@ -61,9 +82,46 @@ pub async fn run(
         }
         page += 1;
     }
+    Ok(all_measures)
+}
+
+pub async fn run(
+    config: SonarQubeConfig,
+    project: &str,
+    metrics: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+    view: HistoryView,
+    json: bool,
+) -> Result<(), CliError> {
+    let metrics = if view == HistoryView::GateTimeline {
+        ALERT_STATUS_METRIC.to_string()
+    } else {
+        normalize_metrics(metrics).map_err(CliError::Validation)?
+    };
+
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let all_measures = fetch_history(&client, project, &metrics, from, to).await;
+    if helpers::empty_or_missing(&all_measures, |m| no_history(m))
+        && helpers::never_analyzed(&client, project).await
+    {
+        output::print_not_analyzed(project, json);
+        return Ok(());
+    }
+    let all_measures =
+        all_measures.map_err(|e| CliError::api("Failed to fetch measures history", e))?;
 
-    output::print_history(&all_measures, project, json);
-    0
+    match view {
+        HistoryView::PerMetric => output::print_history(&all_measures, project, json),
+        HistoryView::Table => {
+            output::print_history_table(&pivot_history(&all_measures), project, json)
+        }
+        HistoryView::GateTimeline => {
+            output::print_gate_history(&gate_timeline(&all_measures), project, json)
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -107,8 +165,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", "coverage", None, None, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", "coverage", None, None, HistoryView::PerMetric, false).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -124,16 +182,17 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(
+        let result = run(
             config,
             "my-proj",
             "coverage,bugs",
             Some("2026-01-01"),
             Some("2026-02-01"),
+            HistoryView::PerMetric,
             true,
         )
         .await;
-        assert_eq!(exit, 0);
+        assert!(result.is_ok());
     }
 
     #[test]
@@ -172,6 +231,109 @@ mod tests {
         assert_eq!(all[0].history.len(), 2);
     }
 
+    #[test]
+    fn test_normalize_metrics_empty() {
+        assert!(normalize_metrics("").is_err());
+    }
+
+    #[test]
+    fn test_normalize_metrics_whitespace_only() {
+        assert!(normalize_metrics("   ").is_err());
+        assert!(normalize_metrics(" , ,").is_err());
+    }
+
+    #[test]
+    fn test_normalize_metrics_trims_keys() {
+        assert_eq!(
+            normalize_metrics(" coverage , bugs,,ncloc ").unwrap(),
+            "coverage,bugs,ncloc"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_history_sends_trimmed_metrics() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/measures/search_history"))
+            .and(wiremock::matchers::query_param("metrics", "coverage,bugs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(history_body()))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", "coverage , bugs", None, None, HistoryView::PerMetric, false).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_history_table() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/measures/search_history"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "paging": {"total": 2},
+                "measures": [
+                    {"metric": "coverage", "history": [
+                        {"date": "2026-01-01T00:00:00+0000", "value": "75.0"}
+                    ]},
+                    {"metric": "bugs", "history": [
+                        {"date": "2026-01-01T00:00:00+0000", "value": "3"},
+                        {"date": "2026-02-01T00:00:00+0000", "value": "1"}
+                    ]}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", "coverage,bugs", None, None, HistoryView::Table, false).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_gate_history() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/measures/search_history"))
+            .and(wiremock::matchers::query_param("metrics", "alert_status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "paging": {"total": 1},
+                "measures": [
+                    {"metric": "alert_status", "history": [
+                        {"date": "2026-01-01T00:00:00+0000", "value": "OK"},
+                        {"date": "2026-01-02T00:00:00+0000", "value": "ERROR"},
+                        {"date": "2026-01-03T00:00:00+0000", "value": "OK"}
+                    ]}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // --metrics is ignored in favour of alert_status
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", "", None, None, HistoryView::GateTimeline, false).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_history_empty_metrics() {
+        // Rejected locally: no server is contacted
+        let config = SonarQubeConfig::new("http://localhost:1");
+        let result = run(config, "my-proj", " ", None, None, HistoryView::PerMetric, false).await;
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_pagination_done() {
         assert!(pagination_done(50, 1, 100));
@@ -190,7 +352,7 @@ mod tests {
 
         // Page 1: return 100 history items with total=101 to trigger page 2
         use wiremock::matchers::query_param;
-        let page1_history = vec![crate::types::HistoryValue {
+        let page1_history = [crate::types::HistoryValue {
             date: "2026-01-01".to_string(),
             value: Some("80.0".to_string()),
         }];
@@ -225,8 +387,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", "coverage", None, None, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", "coverage", None, None, HistoryView::PerMetric, false).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -242,7 +404,7 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", "coverage", None, None, false).await;
-        assert_eq!(exit, 1);
+        let result = run(config, "my-proj", "coverage", None, None, HistoryView::PerMetric, false).await;
+        assert!(result.is_err());
     }
 }
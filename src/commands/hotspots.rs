@@ -1,4 +1,6 @@
-use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::client::{SonarQubeClient, SonarQubeConfig, SonarQubeError};
+use crate::error::CliError;
+use crate::helpers;
 use crate::output;
 
 pub async fn run(
@@ -6,32 +8,124 @@ pub async fn run(
     project: &str,
     status: Option<&str>,
     new_code: bool,
+    assigned_to: Option<&str>,
     json: bool,
-) -> i32 {
-    let client = match SonarQubeClient::new(config) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to create client: {e}");
-            return 1;
-        }
-    };
-
-    match client.get_security_hotspots(project, status, new_code).await {
-        Ok(hotspots) => {
-            output::print_hotspots(&hotspots, project, json);
-            0
-        }
-        Err(e) => {
-            eprintln!("Failed to get hotspots: {e}");
-            1
-        }
+) -> Result<(), CliError> {
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    // "me" maps to the server-side filter; other logins are matched client-side,
+    // since the hotspots search has no assignee parameter
+    let only_mine = assigned_to == Some("me");
+    let hotspots = client
+        .get_security_hotspots(project, status, new_code, only_mine)
+        .await;
+    if helpers::empty_or_missing(&hotspots, Vec::is_empty) && helpers::never_analyzed(&client, project).await {
+        output::print_not_analyzed(project, json);
+        return Ok(());
+    }
+    let mut hotspots = hotspots.map_err(|e| CliError::api("Failed to get hotspots", e))?;
+    if let Some(login) = assigned_to.filter(|_| !only_mine) {
+        hotspots.retain(|h| h.assignee.as_deref() == Some(login));
+    }
+    output::print_hotspots(&hotspots, project, json);
+    Ok(())
+}
+
+pub async fn assign(config: SonarQubeConfig, hotspot: &str, assignee: &str, json: bool) -> Result<(), CliError> {
+    if config.token.is_none() {
+        return Err(CliError::Auth(
+            "Assigning a hotspot requires a token. Use --token or run `sonar-cli auth login`.".to_string(),
+        ));
+    }
+
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    client
+        .assign_hotspot(hotspot, assignee)
+        .await
+        .map_err(|e| CliError::api("Failed to assign hotspot", e))?;
+    output::print_hotspot_assignment(hotspot, assignee, json);
+    Ok(())
+}
+
+/// Review statuses accepted by `hotspots resolve --status`
+pub const STATUSES: &[&str] = &["TO_REVIEW", "REVIEWED"];
+
+/// Resolutions accepted by `hotspots resolve --resolution` (with `REVIEWED` only)
+pub const RESOLUTIONS: &[&str] = &["SAFE", "FIXED", "ACKNOWLEDGED"];
+
+/// Upper-case and check a status/resolution pair: `REVIEWED` needs a
+/// resolution, `TO_REVIEW` takes none
+pub fn validate_status_change(status: &str, resolution: Option<&str>) -> Result<(String, Option<String>), String> {
+    let status = status.to_uppercase();
+    if !STATUSES.contains(&status.as_str()) {
+        return Err(format!("Unknown hotspot status '{status}'. Valid values: {}", STATUSES.join(", ")));
     }
+    let resolution = resolution.map(str::to_uppercase);
+    match (status.as_str(), resolution.as_deref()) {
+        ("REVIEWED", None) => Err(format!(
+            "--status REVIEWED requires --resolution ({})",
+            RESOLUTIONS.join(", ")
+        )),
+        ("REVIEWED", Some(r)) if !RESOLUTIONS.contains(&r) => Err(format!(
+            "Unknown hotspot resolution '{r}'. Valid values: {}",
+            RESOLUTIONS.join(", ")
+        )),
+        ("TO_REVIEW", Some(_)) => Err("--resolution cannot be used with --status TO_REVIEW".to_string()),
+        _ => Ok((status, resolution)),
+    }
+}
+
+/// Show one hotspot: where it is, its rule, and the rule's risk, vulnerability
+/// and fix guidance
+pub async fn show(config: SonarQubeConfig, hotspot: &str, json: bool) -> Result<(), CliError> {
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let details = client
+        .get_hotspot(hotspot)
+        .await
+        .map_err(|e| CliError::api(format!("Failed to fetch hotspot {hotspot}"), e))?;
+    output::print_hotspot_details(&details, json);
+    Ok(())
+}
+
+/// Change a hotspot's review status (requires a token with the "Administer
+/// Security Hotspots" permission)
+pub async fn resolve(
+    config: SonarQubeConfig,
+    hotspot: &str,
+    status: &str,
+    resolution: Option<&str>,
+    json: bool,
+) -> Result<(), CliError> {
+    if config.token.is_none() {
+        return Err(CliError::Auth(
+            "Reviewing a hotspot requires a token. Use --token or run `sonar-cli auth login`.".to_string(),
+        ));
+    }
+    let (status, resolution) = validate_status_change(status, resolution).map_err(CliError::Validation)?;
+
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let context = format!("Failed to change the status of hotspot {hotspot}");
+    client
+        .change_hotspot_status(hotspot, &status, resolution.as_deref())
+        .await
+        .map_err(|e| match e {
+            SonarQubeError::Forbidden => CliError::Auth(format!(
+                "{context}: the token lacks the 'Administer Security Hotspots' permission on the project"
+            )),
+            e => CliError::api(&context, e),
+        })?;
+    output::print_hotspot_status_change(hotspot, &status, resolution.as_deref(), json);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wiremock::matchers::{method, path};
+    use crate::error::exit_code;
+    use wiremock::matchers::{body_string, method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     async fn try_mock_server() -> Option<MockServer> {
@@ -81,8 +175,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", None, false, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", None, false, None, false).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -98,8 +192,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", Some("TO_REVIEW"), false, true).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", Some("TO_REVIEW"), false, None, true).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -115,8 +209,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", None, false, false).await;
-        assert_eq!(exit, 1);
+        let result = run(config, "my-proj", None, false, None, false).await;
+        assert!(result.is_err());
     }
 
     #[tokio::test]
@@ -132,8 +226,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", None, true, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", None, true, None, false).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -149,8 +243,150 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", Some("TO_REVIEW"), false, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", Some("TO_REVIEW"), false, None, false).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_hotspots_assigned_to_me() {
+        use wiremock::matchers::query_param;
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/hotspots/search"))
+            .and(query_param("onlyMine", "true"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(hotspots_body()))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", None, false, Some("me"), false).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_assign_requires_token() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("POST"))
+            .and(path("/api/hotspots/assign"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(assign(config, "h1", "alice", false).await.is_err());
     }
 
+    #[tokio::test]
+    async fn test_assign_success() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("POST"))
+            .and(path("/api/hotspots/assign"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri()).with_token("t");
+        assert!(assign(config, "h1", "alice", true).await.is_ok());
+    }
+
+    #[test]
+    fn test_validate_status_change() {
+        assert_eq!(
+            validate_status_change("reviewed", Some("safe")).unwrap(),
+            ("REVIEWED".to_string(), Some("SAFE".to_string()))
+        );
+        assert_eq!(validate_status_change("TO_REVIEW", None).unwrap(), ("TO_REVIEW".to_string(), None));
+        assert!(validate_status_change("REVIEWED", None).unwrap_err().contains("requires --resolution"));
+        assert!(validate_status_change("REVIEWED", Some("IGNORED")).unwrap_err().contains("Valid values: SAFE, FIXED, ACKNOWLEDGED"));
+        assert!(validate_status_change("TO_REVIEW", Some("SAFE")).is_err());
+        assert!(validate_status_change("CLOSED", None).unwrap_err().contains("Valid values: TO_REVIEW, REVIEWED"));
+    }
+
+    #[tokio::test]
+    async fn test_show_hotspot() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/hotspots/show"))
+            .and(query_param("hotspot", "AX1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "key": "AX1",
+                "component": {"key": "my-proj:src/db.rs", "path": "src/db.rs"},
+                "rule": {
+                    "key": "rust:S2077",
+                    "name": "Formatting SQL queries is security-sensitive",
+                    "securityCategory": "sql-injection",
+                    "vulnerabilityProbability": "HIGH",
+                    "riskDescription": "<p>Formatted SQL queries can be hard to maintain.</p>",
+                    "fixRecommendations": "<p>Use parameterized queries.</p>"
+                },
+                "status": "TO_REVIEW",
+                "line": 42,
+                "message": "Make sure using a dynamically formatted SQL query is safe here."
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(show(config.clone(), "AX1", false).await.is_ok());
+        assert!(show(config, "AX1", true).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_hotspot_posts_status_and_resolution() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("POST"))
+            .and(path("/api/hotspots/change_status"))
+            .and(body_string("hotspot=AX1&status=REVIEWED&resolution=FIXED"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri()).with_token("t");
+        assert!(resolve(config, "AX1", "reviewed", Some("fixed"), false).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_hotspot_forbidden_is_auth_error() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("POST"))
+            .and(path("/api/hotspots/change_status"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri()).with_token("t");
+        let err = resolve(config, "AX1", "REVIEWED", Some("SAFE"), false).await.unwrap_err();
+        assert_eq!(err.exit_code(), exit_code::AUTH);
+        assert!(err.to_string().contains("Administer Security Hotspots"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_hotspot_requires_token() {
+        let config = SonarQubeConfig::new("http://localhost:1");
+        let err = resolve(config, "AX1", "REVIEWED", Some("SAFE"), false).await.unwrap_err();
+        assert_eq!(err.exit_code(), exit_code::AUTH);
+    }
 }
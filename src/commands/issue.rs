@@ -0,0 +1,139 @@
+use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::error::CliError;
+use crate::output;
+
+/// Workflow transitions accepted by `issue transition --do`
+pub const TRANSITIONS: &[&str] = &["confirm", "resolve", "falsepositive", "wontfix", "reopen"];
+
+/// A change applied to a single issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueAction<'a> {
+    Assign { to: &'a str },
+    Comment { text: &'a str },
+    Transition { transition: &'a str },
+}
+
+impl IssueAction<'_> {
+    /// Action name and its argument, as reported in JSON output
+    fn name_and_value(&self) -> (&'static str, &str) {
+        match *self {
+            IssueAction::Assign { to } => ("assign", to),
+            IssueAction::Comment { text } => ("comment", text),
+            IssueAction::Transition { transition } => ("transition", transition),
+        }
+    }
+
+    /// One-line confirmation for text output
+    fn summary(&self, issue: &str) -> String {
+        match *self {
+            IssueAction::Assign { to } => format!("Assigned issue {issue} to {to}"),
+            IssueAction::Comment { .. } => format!("Commented on issue {issue}"),
+            IssueAction::Transition { transition } => {
+                format!("Applied transition '{transition}' to issue {issue}")
+            }
+        }
+    }
+}
+
+/// Check `transition` against the supported workflow transitions
+fn validate_transition(transition: &str) -> Result<(), String> {
+    if TRANSITIONS.contains(&transition) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown transition '{transition}'. Valid values: {}",
+            TRANSITIONS.join(", ")
+        ))
+    }
+}
+
+pub async fn run(config: SonarQubeConfig, issue: &str, action: IssueAction<'_>, json: bool) -> Result<(), CliError> {
+    if config.token.is_none() {
+        return Err(CliError::Auth(
+            "Changing an issue requires a token. Use --token or run `sonar-cli auth login`.".to_string(),
+        ));
+    }
+    if let IssueAction::Transition { transition } = action {
+        validate_transition(transition).map_err(CliError::Validation)?;
+    }
+
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let result = match action {
+        IssueAction::Assign { to } => client.assign_issue(issue, to).await,
+        IssueAction::Comment { text } => client.add_issue_comment(issue, text).await,
+        IssueAction::Transition { transition } => client.transition_issue(issue, transition).await,
+    };
+    let (name, value) = action.name_and_value();
+    result.map_err(|e| CliError::api(format!("Failed to {name} issue {issue}"), e))?;
+    output::print_issue_action(issue, name, value, &action.summary(issue), json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn try_mock_server() -> Option<MockServer> {
+        let listener = match std::net::TcpListener::bind("127.0.0.1:0") {
+            Ok(l) => l,
+            Err(_) => return None,
+        };
+        Some(MockServer::builder().listener(listener).start().await)
+    }
+
+    #[test]
+    fn test_validate_transition() {
+        for t in TRANSITIONS {
+            assert!(validate_transition(t).is_ok());
+        }
+        let err = validate_transition("close").unwrap_err();
+        assert!(err.contains("Valid values: confirm, resolve"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_run_requires_token() {
+        let config = SonarQubeConfig::new("http://localhost:1");
+        let result = run(config, "AX1", IssueAction::Assign { to: "alice" }, false).await;
+        assert!(matches!(result, Err(CliError::Auth(_))));
+    }
+
+    #[tokio::test]
+    async fn test_run_transition() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("POST"))
+            .and(path("/api/issues/do_transition"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri()).with_token("t");
+        let action = IssueAction::Transition { transition: "falsepositive" };
+        assert!(run(config, "AX1", action, true).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_forbidden_fails() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("POST"))
+            .and(path("/api/issues/add_comment"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri()).with_token("t");
+        let result = run(config, "AX1", IssueAction::Comment { text: "hi" }, false).await;
+        let err = result.unwrap_err();
+        assert!(matches!(err, CliError::Auth(_)), "{err:?}");
+        assert_eq!(err.exit_code(), crate::error::exit_code::AUTH);
+    }
+}
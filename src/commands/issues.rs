@@ -1,6 +1,17 @@
-use crate::client::{IssueSearchParams, SonarQubeClient, SonarQubeConfig};
-use crate::output;
-use crate::types::severity;
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+
+use crate::client::{
+    IssueSearchParams, RuleSearchParams, SonarQubeClient, SonarQubeConfig, SonarQubeError,
+};
+use crate::browser;
+use crate::commands::history;
+use crate::error::CliError;
+use crate::helpers::{self, SeverityChange, Window, ALERT_STATUS_METRIC};
+use crate::ignore_file::PathFilter;
+use crate::output::{self, OutputFormat};
+use crate::paging::{self, PageRequest};
+use crate::types::{issue_status, severity, RuleInfo, SonarIssue, SourceLine};
 
 /// Build a comma-separated severity filter from a minimum severity level.
 ///
@@ -17,62 +28,955 @@ pub fn build_severity_filter(min_severity: Option<&str>) -> Option<String> {
     })
 }
 
+/// Build the status filter, appending `CLOSED` to the active set when `include_closed`.
+///
+/// The active set is `statuses` if given, otherwise the default open statuses.
+/// Returns `None` (use the default) when nothing needs to change.
+pub fn build_status_filter(statuses: Option<&str>, include_closed: bool) -> Option<String> {
+    if !include_closed {
+        return statuses.map(str::to_string);
+    }
+    let base = statuses.unwrap_or(issue_status::DEFAULT);
+    if base
+        .split(',')
+        .any(|s| s.trim().eq_ignore_ascii_case(issue_status::CLOSED))
+    {
+        return Some(base.to_string());
+    }
+    Some(format!("{base},{}", issue_status::CLOSED))
+}
+
+/// What `issues --sort` orders the listed issues by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IssueSort {
+    /// Most severe first
+    #[default]
+    Severity,
+    File,
+    Line,
+    Rule,
+}
+
+/// Parse `--sort`
+pub fn parse_sort(value: &str) -> Result<IssueSort, String> {
+    match value.to_lowercase().as_str() {
+        "severity" => Ok(IssueSort::Severity),
+        "file" => Ok(IssueSort::File),
+        "line" => Ok(IssueSort::Line),
+        "rule" => Ok(IssueSort::Rule),
+        other => Err(format!("Unknown sort key '{other}'. Valid values: severity, file, line, rule")),
+    }
+}
+
+/// Sort issues the way the SonarQube UI lists them: most severe first, then by
+/// file and line. The sort is stable, so equal issues keep their fetch order.
+pub fn sort_issues(issues: &mut [SonarIssue]) {
+    sort_issues_by(issues, IssueSort::Severity, false);
+}
+
+/// Sort issues by `key`, breaking ties by file then line (`--sort file` falls
+/// back to line, then most severe first). `reverse` flips the whole order.
+pub fn sort_issues_by(issues: &mut [SonarIssue], key: IssueSort, reverse: bool) {
+    let by_severity = |a: &SonarIssue, b: &SonarIssue| {
+        severity::ordinal(&b.severity).cmp(&severity::ordinal(&a.severity))
+    };
+    let by_file = |a: &SonarIssue, b: &SonarIssue| a.component.cmp(&b.component);
+    let by_line = |a: &SonarIssue, b: &SonarIssue| a.line.cmp(&b.line);
+    issues.sort_by(|a, b| {
+        let order = match key {
+            IssueSort::Severity => by_severity(a, b).then_with(|| by_file(a, b)).then_with(|| by_line(a, b)),
+            IssueSort::File => by_file(a, b).then_with(|| by_line(a, b)).then_with(|| by_severity(a, b)),
+            IssueSort::Line => by_line(a, b).then_with(|| by_file(a, b)),
+            IssueSort::Rule => a.rule.cmp(&b.rule).then_with(|| by_file(a, b)).then_with(|| by_line(a, b)),
+        };
+        if reverse {
+            order.reverse()
+        } else {
+            order
+        }
+    });
+}
+
+/// Issues that appeared in or dropped out of a result set between two polls
+#[derive(Debug, Default)]
+pub struct IssueDiff<'a> {
+    pub added: Vec<&'a SonarIssue>,
+    pub removed: Vec<&'a SonarIssue>,
+}
+
+impl IssueDiff<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Compare two result sets by issue key. Added issues keep the order of
+/// `current`, removed ones the order of `previous`.
+pub fn diff_issues<'a>(previous: &'a [SonarIssue], current: &'a [SonarIssue]) -> IssueDiff<'a> {
+    let before: BTreeSet<&str> = previous.iter().map(|i| i.key.as_str()).collect();
+    let after: BTreeSet<&str> = current.iter().map(|i| i.key.as_str()).collect();
+    IssueDiff {
+        added: current.iter().filter(|i| !before.contains(i.key.as_str())).collect(),
+        removed: previous.iter().filter(|i| !after.contains(i.key.as_str())).collect(),
+    }
+}
+
+/// How `issues --watch` shows each poll
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchMode {
+    /// Print only the changes, below the previous output
+    Append,
+    /// Clear the screen and show the full list, then the changes
+    Redraw,
+}
+
+/// Parse `--watch-mode`
+pub fn parse_watch_mode(value: &str) -> Result<WatchMode, String> {
+    match value.to_lowercase().as_str() {
+        "append" => Ok(WatchMode::Append),
+        "redraw" => Ok(WatchMode::Redraw),
+        other => Err(format!("Unknown watch mode '{other}'. Valid values: redraw, append")),
+    }
+}
+
+/// What `issues --group-by` counts issues by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueGroup {
+    Rule,
+    File,
+    Severity,
+    Type,
+}
+
+impl IssueGroup {
+    /// Name used in the summary header ("42 issues in 7 rules")
+    pub fn plural(self) -> &'static str {
+        match self {
+            Self::Rule => "rules",
+            Self::File => "files",
+            Self::Severity => "severities",
+            Self::Type => "types",
+        }
+    }
+}
+
+/// Parse `--group-by`
+pub fn parse_group_by(value: &str) -> Result<IssueGroup, String> {
+    match value.to_lowercase().as_str() {
+        "rule" => Ok(IssueGroup::Rule),
+        "file" => Ok(IssueGroup::File),
+        "severity" => Ok(IssueGroup::Severity),
+        "type" => Ok(IssueGroup::Type),
+        other => Err(format!("Unknown group '{other}'. Valid values: rule, file, severity, type")),
+    }
+}
+
+/// Count `issues` per group, largest group first (ties by group name).
+/// Files are project-relative paths unless `full_component`.
+pub fn group_issues(
+    issues: &[SonarIssue],
+    project: &str,
+    group: IssueGroup,
+    full_component: bool,
+) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for issue in issues {
+        let key = match group {
+            IssueGroup::Rule => issue.rule.clone(),
+            IssueGroup::File if full_component => issue.component.clone(),
+            IssueGroup::File => helpers::extract_path(&issue.component, project),
+            IssueGroup::Severity => issue.severity.clone(),
+            IssueGroup::Type => issue.issue_type.clone(),
+        };
+        *counts.entry(key).or_default() += 1;
+    }
+    let mut groups: Vec<(String, usize)> = counts.into_iter().collect();
+    groups.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    groups
+}
+
+/// Polling settings for `issues --watch`
+#[derive(Debug, Clone, Copy)]
+pub struct WatchOptions {
+    pub interval: std::time::Duration,
+    pub mode: WatchMode,
+}
+
+/// CI limits on the listed issues (`--fail-on-issues`, `--max-issues`, `--fail-on-severity`)
+#[derive(Debug, Clone, Default)]
+pub struct IssueThreshold {
+    /// Fail when any issue matches
+    pub fail_on_any: bool,
+    /// Fail when more than this many issues match
+    pub max_issues: Option<usize>,
+    /// Fail when an issue at or above this severity matches
+    pub min_severity: Option<String>,
+}
+
+impl IssueThreshold {
+    /// Number of `issues` at or above `min_severity` (0 when it is not set)
+    pub fn severe_count(&self, issues: &[SonarIssue]) -> usize {
+        let Some(ref level) = self.min_severity else {
+            return 0;
+        };
+        let min = severity::ordinal(level);
+        issues.iter().filter(|i| severity::ordinal(&i.severity) >= min).count()
+    }
+
+    /// Why `listed` issues, `severe` of them at or above `min_severity`, breach
+    /// the threshold, or `None` when they pass
+    pub fn breach(&self, listed: usize, severe: usize) -> Option<String> {
+        if self.fail_on_any && listed > 0 {
+            return Some(format!("{listed} issues found (--fail-on-issues)"));
+        }
+        if let Some(max) = self.max_issues.filter(|max| listed > *max) {
+            return Some(format!("{listed} issues exceed --max-issues {max}"));
+        }
+        match self.min_severity {
+            Some(ref level) if severe > 0 => {
+                Some(format!("{severe} issues at {level} or above (--fail-on-severity)"))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Validate a `--fail-on-severity` level, returning it upper-cased
+pub fn parse_fail_severity(level: &str) -> Result<String, String> {
+    let upper = level.to_uppercase();
+    if severity::ALL.contains(&upper.as_str()) {
+        Ok(upper)
+    } else {
+        Err(format!(
+            "Unknown severity '{level}'. Valid values: {}",
+            severity::ALL.join(", ")
+        ))
+    }
+}
+
+/// `--path`/`--exclude-path`: keep issues whose project-relative path starts
+/// with one of `include` (any path when empty) and with none of `exclude`
+#[derive(Debug, Clone, Default)]
+pub struct PathPrefixes {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl PathPrefixes {
+    /// Parse the comma-separated flag values; a leading `./` is ignored
+    pub fn from_flags(include: Option<&str>, exclude: Option<&str>) -> Self {
+        let split = |value: Option<&str>| -> Vec<String> {
+            value
+                .unwrap_or_default()
+                .split(',')
+                .map(|p| p.trim().trim_start_matches("./"))
+                .filter(|p| !p.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        Self {
+            include: split(include),
+            exclude: split(exclude),
+        }
+    }
+
+    pub fn keeps(&self, path: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| path.starts_with(p.as_str())))
+            && !self.exclude.iter().any(|p| path.starts_with(p.as_str()))
+    }
+}
+
+/// Client-side shaping of the fetched issues before they are printed
+#[derive(Debug, Clone, Default)]
+pub struct IssueView {
+    pub window: Window,
+    /// Annotate each issue with its rule's default severity
+    pub show_rule_severity: bool,
+    /// Annotate each issue with its rule's name
+    pub with_rule_names: bool,
+    /// Keep the server's order instead of sorting by severity, file and line
+    pub no_sort: bool,
+    /// Sort key for the listed issues (ignored with `no_sort`)
+    pub sort: IssueSort,
+    /// Reverse the sort order
+    pub reverse: bool,
+    /// Drop issues on files matched by the ignore file
+    pub ignore: PathFilter,
+    /// Only issues under these path prefixes
+    pub paths: PathPrefixes,
+    /// Open the result in the SonarQube UI afterwards
+    pub open_in_browser: bool,
+    /// Show the full component key instead of the project-relative path
+    pub show_full_component: bool,
+    /// Only issues created after the most recent analysis that passed the gate
+    pub since_last_green: bool,
+    /// Print issue counts per group instead of the issues; the window then
+    /// applies to the groups
+    pub group_by: Option<IssueGroup>,
+    /// Follow the issues with their total remediation effort, per severity
+    pub show_effort: bool,
+    /// Fetch only this page instead of every page
+    pub page: Option<PageRequest>,
+    /// Browse the issues in the terminal UI instead of printing them
+    pub interactive: bool,
+    /// Lines of source to fetch and show around each issue (0 = none)
+    pub context: usize,
+}
+
+impl IssueView {
+    /// Whether `issue` passes the ignore file and the path prefixes
+    fn keeps(&self, issue: &SonarIssue, project: &str) -> bool {
+        let path = helpers::extract_path(&issue.component, project);
+        !self.ignore.is_ignored(&path) && self.paths.keeps(&path)
+    }
+}
+
+/// SonarQube UI URL for one issue, or for the issue search matching `params`
+pub fn issues_web_url(
+    base: &str,
+    project: &str,
+    branch: Option<&str>,
+    params: &IssueSearchParams<'_>,
+    issue: Option<&str>,
+) -> String {
+    let mut url = format!("{}/project/issues?id={}", base.trim_end_matches('/'), project);
+    if let Some(b) = branch {
+        url.push_str(&format!("&branch={}", helpers::encode_query_value(b)));
+    }
+    if let Some(key) = issue {
+        url.push_str(&format!("&issues={key}&open={key}"));
+        return url;
+    }
+    let filters = [
+        ("severities", params.severities),
+        ("types", params.types),
+        ("statuses", params.statuses),
+        ("resolutions", params.resolutions),
+        ("tags", params.tags),
+        ("rules", params.rules),
+        ("createdAfter", params.created_after),
+        ("createdBefore", params.created_before),
+        ("author", params.author),
+        ("assignees", params.assignees),
+        ("languages", params.languages),
+        ("scopes", params.scopes),
+    ];
+    for (name, value) in filters {
+        if let Some(v) = value {
+            url.push_str(&format!("&{name}={}", helpers::encode_query_value(v)));
+        }
+    }
+    if params.statuses.is_none() && !params.all_statuses {
+        url.push_str("&resolved=false");
+    }
+    if params.in_new_code_period == Some(true) {
+        url.push_str("&inNewCodePeriod=true");
+    }
+    url
+}
+
+/// Fetch metadata for every distinct rule referenced by `issues`, keyed by rule key.
+///
+/// All rules are looked up in one batched rules search.
+async fn fetch_issue_rules(
+    client: &SonarQubeClient,
+    issues: &[SonarIssue],
+) -> Result<HashMap<String, RuleInfo>, SonarQubeError> {
+    let rule_keys: BTreeSet<&str> = issues.iter().map(|i| i.rule.as_str()).collect();
+    if rule_keys.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let rule_keys = rule_keys.into_iter().collect::<Vec<_>>().join(",");
+    let params = RuleSearchParams {
+        rule_keys: Some(&rule_keys),
+        ..Default::default()
+    };
+    Ok(client
+        .get_all_rules(&params)
+        .await?
+        .into_iter()
+        .map(|r| (r.key.clone(), r))
+        .collect())
+}
+
+/// Fill in each issue's `rule_severity` and/or `rule_name` from its rule's metadata.
+///
+/// The rules are fetched once and shared by both annotations.
+async fn annotate_rules(
+    client: &SonarQubeClient,
+    issues: &mut [SonarIssue],
+    severity: bool,
+    name: bool,
+) -> Result<(), SonarQubeError> {
+    if !severity && !name {
+        return Ok(());
+    }
+    let rules = fetch_issue_rules(client, issues).await?;
+    for issue in issues {
+        let rule = rules.get(&issue.rule);
+        if severity {
+            issue.rule_severity = rule.and_then(|r| r.severity.clone());
+        }
+        if name {
+            issue.rule_name = rule.map(|r| r.name.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Fill in each issue's `snippet` with `context` lines of source around its line.
+///
+/// One request per distinct file and line, at most `--concurrency` in flight.
+/// Issues without a line are skipped; returns how many snippets could not be fetched.
+async fn attach_context(client: &SonarQubeClient, issues: &mut [SonarIssue], context: usize) -> usize {
+    let mut ranges: Vec<(&str, usize)> = issues
+        .iter()
+        .filter_map(|i| Some((i.component.as_str(), i.line? as usize)))
+        .collect();
+    ranges.sort_unstable();
+    ranges.dedup();
+    let fetches = ranges
+        .iter()
+        .map(|&(component, line)| helpers::fetch_snippet(client, component, line, context));
+    let results = paging::join_bounded(client.concurrency(), fetches).await;
+
+    let mut snippets: HashMap<(&str, usize), Vec<SourceLine>> = HashMap::new();
+    let mut failed = 0;
+    for (&(component, line), result) in ranges.iter().zip(results) {
+        match result {
+            Ok(lines) => {
+                snippets.insert((component, line), lines);
+            }
+            Err(e) => {
+                tracing::warn!("Could not fetch source for {component}:{line}: {e}");
+                failed += 1;
+            }
+        }
+    }
+    // Look the snippets up while `issues` is still borrowed for the keys, then attach them
+    let attached: Vec<Option<Vec<SourceLine>>> = issues
+        .iter()
+        .map(|i| snippets.get(&(i.component.as_str(), i.line? as usize)).cloned())
+        .collect();
+    for (issue, snippet) in issues.iter_mut().zip(attached) {
+        if let Some(snippet) = snippet {
+            issue.snippet = snippet;
+        }
+    }
+    failed
+}
+
+/// Fetch issues page by page, stopping once `limit` issues have been seen
+pub async fn fetch_issues(
+    client: &SonarQubeClient,
+    project: &str,
+    search_params: &IssueSearchParams<'_>,
+    limit: Option<usize>,
+) -> Result<Vec<SonarIssue>, SonarQubeError> {
+    client.search_all_issues(project, search_params, limit).await
+}
+
+/// Fetch every page of issues, or only `page` when given; at most `limit` either way
+async fn fetch_issues_or_page(
+    client: &SonarQubeClient,
+    project: &str,
+    search_params: &IssueSearchParams<'_>,
+    limit: Option<usize>,
+    page: Option<PageRequest>,
+) -> Result<Vec<SonarIssue>, SonarQubeError> {
+    let Some(page) = page else {
+        return fetch_issues(client, project, search_params, limit).await;
+    };
+    let mut issues = client
+        .search_issues_with_params(project, page.page, page.size, search_params)
+        .await?
+        .issues;
+    issues.truncate(limit.unwrap_or(usize::MAX));
+    Ok(issues)
+}
+
+/// Issue changes over the last `analyses` analyses, with the baseline analysis date.
+///
+/// Opened counts issues raised after the baseline analysis, whatever their status
+/// now. Resolved counts issues raised up to the baseline that are no longer open;
+/// the API has no resolution-date filter, so this also includes issues resolved
+/// before the baseline for as long as the server retains them.
+pub async fn fetch_issue_changes(
+    client: &SonarQubeClient,
+    project: &str,
+    search_params: &IssueSearchParams<'_>,
+    analyses: usize,
+) -> Result<(Vec<SeverityChange>, String), CliError> {
+    let tasks = client
+        .get_analysis_activity(project, analyses + 1)
+        .await
+        .map_err(|e| CliError::api("Failed to fetch analysis activity", e))?;
+    let baseline = tasks
+        .get(analyses)
+        .and_then(|t| t.executed_at.clone().or_else(|| Some(t.submitted_at.clone())))
+        .ok_or_else(|| {
+            CliError::Failed(format!(
+                "Project {project} has {} analyses; need at least {} to compare the last {analyses}",
+                tasks.len(),
+                analyses + 1
+            ))
+        })?;
+    let since = helpers::encode_query_date(&baseline);
+
+    let opened_params = IssueSearchParams {
+        statuses: Some(issue_status::ALL),
+        created_after: Some(&since),
+        created_before: None,
+        facets: Some("severities"),
+        all_statuses: false,
+        ..*search_params
+    };
+    let resolved_params = IssueSearchParams {
+        statuses: Some(issue_status::INACTIVE),
+        created_after: None,
+        created_before: None,
+        facets: None,
+        sort_desc: Some("UPDATE_DATE"),
+        all_statuses: false,
+        ..*search_params
+    };
+    let (opened, resolved) = tokio::join!(
+        client.search_issues_with_params(project, 1, 1, &opened_params),
+        resolved_since(client, project, &resolved_params, &baseline),
+    );
+    let opened = opened.map_err(|e| CliError::api("Failed to count opened issues", e))?;
+    let resolved = resolved.map_err(|e| CliError::api("Failed to count resolved issues", e))?;
+    Ok((helpers::severity_changes(&opened.facets, &resolved), baseline))
+}
+
+/// Page size for [`resolved_since`]; the most the issue search allows
+const RESOLVED_PAGE_SIZE: usize = 500;
+
+/// Inactive issues closed (or last updated) at or after `baseline`.
+///
+/// The search has no "resolved after" filter, so `params` must sort by update
+/// date, newest first; paging stops at the first issue older than the baseline.
+async fn resolved_since(
+    client: &SonarQubeClient,
+    project: &str,
+    params: &IssueSearchParams<'_>,
+    baseline: &str,
+) -> Result<Vec<SonarIssue>, SonarQubeError> {
+    let baseline = helpers::parse_sonar_timestamp(baseline);
+    let since_baseline = |issue: &SonarIssue| {
+        let date = issue.close_date.as_deref().or(issue.update_date.as_deref());
+        match (date.and_then(helpers::parse_sonar_timestamp), baseline) {
+            (Some(date), Some(baseline)) => date >= baseline,
+            _ => false,
+        }
+    };
+
+    let mut resolved = Vec::new();
+    for page in 1..=paging::MAX_RESULT_WINDOW / RESOLVED_PAGE_SIZE {
+        let response = client
+            .search_issues_with_params(project, page, RESOLVED_PAGE_SIZE, params)
+            .await?;
+        let fetched = response.issues.len();
+        let before = resolved.len();
+        resolved.extend(response.issues.into_iter().filter(|i| since_baseline(i)));
+        // Sorted newest first: an older issue means the rest are older too
+        if resolved.len() - before < fetched || page * RESOLVED_PAGE_SIZE >= response.total {
+            break;
+        }
+    }
+    Ok(resolved)
+}
+
+/// Date of the most recent analysis whose quality gate passed, from the gate history
+pub async fn last_green_cutoff(client: &SonarQubeClient, project: &str) -> Result<String, CliError> {
+    let history = history::fetch_history(client, project, ALERT_STATUS_METRIC, None, None)
+        .await
+        .map_err(|e| CliError::api("Failed to fetch quality gate history", e))?;
+    helpers::last_green_analysis(&history).ok_or_else(|| {
+        CliError::NotFound(format!("No analysis of {project} has passed the quality gate"))
+    })
+}
+
+/// Report issues opened and resolved per severity over the last `analyses` analyses
+pub async fn run_changes(
+    config: SonarQubeConfig,
+    project: &str,
+    search_params: &IssueSearchParams<'_>,
+    analyses: usize,
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    if !matches!(format, OutputFormat::Text | OutputFormat::Json) {
+        return Err(CliError::Validation(output::unsupported_format(
+            format,
+            "issues --changed-since-analysis",
+            "text or json",
+        )));
+    }
+    let json = format == OutputFormat::Json;
+    if analyses == 0 {
+        return Err(CliError::Validation(
+            "--changed-since-analysis must be at least 1".to_string(),
+        ));
+    }
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let result = fetch_issue_changes(&client, project, search_params, analyses).await;
+    // Too few analyses, or a missing project: say why plainly when it was never analyzed
+    if matches!(result, Err(CliError::Failed(_) | CliError::NotFound(_)))
+        && helpers::never_analyzed(&client, project).await
+    {
+        output::print_not_analyzed(project, json);
+        return Ok(());
+    }
+    let (changes, since) = result?;
+    output::print_issue_changes(&changes, project, analyses, &since, json);
+    Ok(())
+}
+
+/// `createdAfter` value for `--since-last-green`, or `None` when the flag is off
+async fn green_cutoff(
+    client: &SonarQubeClient,
+    project: &str,
+    view: &IssueView,
+    json: bool,
+) -> Result<Option<String>, CliError> {
+    if !view.since_last_green {
+        return Ok(None);
+    }
+    let date = last_green_cutoff(client, project).await?;
+    if !json {
+        output::progress(&format!("Issues created since the last green analysis ({date})"));
+    }
+    Ok(Some(helpers::encode_query_date(&date)))
+}
+
+/// Issues that passed the search and the client-side filters, before
+/// `--top`/`--skip` cut the list; what the threshold is checked against
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Matched {
+    count: usize,
+    /// At or above the threshold's severity
+    severe: usize,
+}
+
+/// Whether an empty or missing issue search comes from a never-analyzed project
+async fn no_issues(
+    client: &SonarQubeClient,
+    project: &str,
+    collected: &Result<(Vec<SonarIssue>, Matched), SonarQubeError>,
+) -> bool {
+    helpers::empty_or_missing(collected, |(_, matched)| matched.count == 0)
+        && helpers::never_analyzed(client, project).await
+}
+
+/// Fetch issues and apply the client-side part of `view`: ignore file, path
+/// prefixes, sort, window and rule annotations
+async fn collect_issues(
+    client: &SonarQubeClient,
+    project: &str,
+    search_params: &IssueSearchParams<'_>,
+    limit: Option<usize>,
+    view: &IssueView,
+    threshold: &IssueThreshold,
+) -> Result<(Vec<SonarIssue>, Matched), SonarQubeError> {
+    let mut all_issues = fetch_issues_or_page(client, project, search_params, limit, view.page).await?;
+    all_issues.retain(|i| view.keeps(i, project));
+    let matched = Matched {
+        count: all_issues.len(),
+        severe: threshold.severe_count(&all_issues),
+    };
+    if !view.no_sort {
+        sort_issues_by(&mut all_issues, view.sort, view.reverse);
+    }
+    let mut issues = view.window.apply(all_issues);
+    if let Err(e) =
+        annotate_rules(client, &mut issues, view.show_rule_severity, view.with_rule_names).await
+    {
+        eprintln!("Warning: could not fetch rule details: {e}");
+    }
+    if view.context > 0 {
+        let failed = attach_context(client, &mut issues, view.context).await;
+        if failed > 0 {
+            eprintln!("Warning: could not fetch source for {failed} issue locations");
+        }
+    }
+    Ok((issues, matched))
+}
+
+/// Stream issues as NDJSON in server order, writing the part of each page
+/// inside `view.window` (after the ignore file and path prefixes) as soon as it arrives.
+///
+/// Returns the issues that matched, counted before the window.
+async fn stream_issues<W: Write>(
+    client: &SonarQubeClient,
+    project: &str,
+    search_params: &IssueSearchParams<'_>,
+    limit: Option<usize>,
+    view: &IssueView,
+    threshold: &IssueThreshold,
+    out: &mut W,
+) -> Result<Matched, CliError> {
+    let mut write_err = None;
+    let mut matched = Matched::default();
+    let on_page = |mut page: Vec<SonarIssue>| {
+        page.retain(|i| view.keeps(i, project));
+        let range = view.window.page_range(matched.count, page.len());
+        matched.count += page.len();
+        matched.severe += threshold.severe_count(&page);
+        if write_err.is_none() && !range.is_empty() {
+            write_err = output::write_ndjson(out, &page[range]).err();
+        }
+    };
+    match view.page {
+        Some(page) => fetch_issues_or_page(client, project, search_params, limit, Some(page))
+            .await
+            .map(on_page),
+        None => client
+            .for_each_issues_page(project, search_params, limit, on_page)
+            .await
+            .map(|_| ()),
+    }
+    .map_err(|e| CliError::api("Failed to fetch issues", e))?;
+    match write_err {
+        Some(e) => Err(CliError::output(e)),
+        None => Ok(matched),
+    }
+}
+
+/// Fail with [`CliError::Threshold`] when the matched issues breach `threshold`
+fn check_threshold(threshold: &IssueThreshold, matched: Matched) -> Result<(), CliError> {
+    match threshold.breach(matched.count, matched.severe) {
+        Some(reason) => Err(CliError::Threshold(reason)),
+        None => Ok(()),
+    }
+}
+
+/// `--interactive` needs the `tui` feature, text output and a terminal on stdout
+fn check_interactive(format: OutputFormat) -> Result<(), CliError> {
+    use std::io::IsTerminal;
+    if !cfg!(feature = "tui") {
+        return Err(CliError::Validation(
+            "issues --interactive needs sonar-cli built with the `tui` feature (cargo install --path . --features tui)"
+                .to_string(),
+        ));
+    }
+    if format != OutputFormat::Text {
+        return Err(CliError::Validation(output::unsupported_format(format, "issues --interactive", "text")));
+    }
+    if !std::io::stdout().is_terminal() {
+        return Err(CliError::Validation(
+            "issues --interactive needs a terminal; stdout is not a TTY".to_string(),
+        ));
+    }
+    Ok(())
+}
+
 pub async fn run(
     config: SonarQubeConfig,
     project: &str,
     search_params: &IssueSearchParams<'_>,
     limit: Option<usize>,
-    json: bool,
-) -> i32 {
-    let client = match SonarQubeClient::new(config) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to create client: {e}");
-            return 1;
+    view: &IssueView,
+    threshold: &IssueThreshold,
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    if format == OutputFormat::Ndjson && (view.show_rule_severity || view.with_rule_names) {
+        return Err(CliError::Validation(
+            "--show-rule-severity and --with-rule-names are not supported with --format ndjson"
+                .to_string(),
+        ));
+    }
+    if format == OutputFormat::Ndjson && (view.sort != IssueSort::Severity || view.reverse) {
+        return Err(CliError::Validation(
+            "--sort and --reverse are not supported with --format ndjson, which streams issues in server order"
+                .to_string(),
+        ));
+    }
+    if view.context > 0 && (format == OutputFormat::Ndjson || format.delimiter().is_some()) {
+        return Err(CliError::Validation(output::unsupported_format(format, "issues --context", "text or json")));
+    }
+    for (enabled, flag) in [(view.group_by.is_some(), "--group-by"), (view.show_effort, "--show-effort")] {
+        if enabled && !matches!(format, OutputFormat::Text | OutputFormat::Json) {
+            return Err(CliError::Validation(output::unsupported_format(
+                format,
+                &format!("issues {flag}"),
+                "text or json",
+            )));
+        }
+    }
+    if view.interactive {
+        check_interactive(format)?;
+    }
+    let json = format == OutputFormat::Json;
+
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let cutoff = green_cutoff(&client, project, view, json).await?;
+    let green_params;
+    let search_params = match cutoff.as_deref() {
+        Some(since) => {
+            green_params = IssueSearchParams {
+                created_after: Some(since),
+                ..*search_params
+            };
+            &green_params
         }
+        None => search_params,
     };
 
-    let mut all_issues = Vec::new();
-    let mut page = 1;
-    let page_size = 100;
+    if format == OutputFormat::Ndjson {
+        let mut out = output::sink();
+        let matched = stream_issues(&client, project, search_params, limit, view, threshold, &mut out).await?;
+        if matched.count == 0 && helpers::never_analyzed(&client, project).await {
+            // Keep stdout a valid (empty) stream
+            eprintln!("Project {project} has no completed analysis yet");
+            return Ok(());
+        }
+        return check_threshold(threshold, matched);
+    }
+
+    if let Some(group) = view.group_by {
+        let all = IssueView {
+            window: Window::default(),
+            ..view.clone()
+        };
+        let collected = collect_issues(&client, project, search_params, limit, &all, threshold).await;
+        if no_issues(&client, project, &collected).await {
+            output::print_not_analyzed(project, json);
+            return Ok(());
+        }
+        let (issues, matched) = collected.map_err(|e| CliError::api("Failed to fetch issues", e))?;
+        let groups = view.window.apply(group_issues(&issues, project, group, view.show_full_component));
+        output::print_issues_summary(&groups, issues.len(), group.plural(), json);
+        return check_threshold(threshold, matched);
+    }
+
+    let collected = collect_issues(&client, project, search_params, limit, view, threshold).await;
+    if no_issues(&client, project, &collected).await {
+        output::print_not_analyzed(project, json);
+        return Ok(());
+    }
+    let (issues, matched) = collected.map_err(|e| CliError::api("Failed to fetch issues", e))?;
+    #[cfg(feature = "tui")]
+    if view.interactive {
+        return crate::tui::browse(&client, project, &issues).await;
+    }
+    let written = match format.delimiter() {
+        Some(delimiter) => {
+            output::write_issues_delimited(&mut output::sink(), &issues, project, delimiter)
+                .map_err(CliError::output)
+        }
+        None if view.show_effort => {
+            let effort = helpers::summarize_effort(&issues);
+            output::print_issues_with_effort(&issues, project, view.show_full_component, &effort, json);
+            Ok(())
+        }
+        None => {
+            output::print_issues(&issues, project, view.show_full_component, json);
+            Ok(())
+        }
+    };
+    if view.open_in_browser {
+        let single = match issues.as_slice() {
+            [issue] => Some(issue.key.as_str()),
+            _ => None,
+        };
+        let config = client.config();
+        let url = issues_web_url(&config.url, project, config.branch.as_deref(), search_params, single);
+        browser::open_url(&url);
+    }
+    written?;
+    check_threshold(threshold, matched)
+}
 
+/// Re-run the search every `watch.interval` and print what changed, until Ctrl-C
+pub async fn run_watch(
+    config: SonarQubeConfig,
+    project: &str,
+    search_params: &IssueSearchParams<'_>,
+    limit: Option<usize>,
+    view: &IssueView,
+    watch: WatchOptions,
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    if format != OutputFormat::Text {
+        return Err(CliError::Validation(format!(
+            "--watch only supports text output; {} would be an ambiguous stream of snapshots",
+            format.label()
+        )));
+    }
+
+    // Each poll must see the server's current issues
+    let config = SonarQubeConfig { cache: None, ..config };
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let cutoff = green_cutoff(&client, project, view, false).await?;
+    let green_params;
+    let search_params = match cutoff.as_deref() {
+        Some(since) => {
+            green_params = IssueSearchParams {
+                created_after: Some(since),
+                ..*search_params
+            };
+            &green_params
+        }
+        None => search_params,
+    };
+
+    // One listener for the whole loop, so Ctrl-C during a fetch is not lost
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    let mut previous: Option<Vec<SonarIssue>> = None;
     loop {
-        let response = match client
-            .search_issues_with_params(project, page, page_size, search_params)
-            .await
-        {
-            Ok(r) => r,
-            Err(e) => {
-                eprintln!("Failed to fetch issues: {e}");
-                return 1;
+        let first = previous.is_none();
+        let poll = async {
+            if !first {
+                tokio::time::sleep(watch.interval).await;
             }
+            collect_issues(&client, project, search_params, limit, view, &IssueThreshold::default()).await
         };
+        let result = tokio::select! {
+            _ = &mut ctrl_c => return Ok(()),
+            result = poll => result,
+        };
+        let now = helpers::format_utc_timestamp(helpers::unix_now());
 
-        let count = response.issues.len();
-        let total = response.total;
-        all_issues.extend(response.issues);
+        let current = match result {
+            Ok((issues, _)) => issues,
+            Err(e) if first => return Err(CliError::api("Failed to fetch issues", e)),
+            Err(e) => {
+                eprintln!("[{now}] Failed to fetch issues, retrying: {e}");
+                continue;
+            }
+        };
 
-        if let Some(lim) = limit {
-            if all_issues.len() >= lim {
-                all_issues.truncate(lim);
-                break;
+        let diff = match previous.as_deref() {
+            Some(prev) => diff_issues(prev, &current),
+            None => IssueDiff::default(),
+        };
+        if first || watch.mode == WatchMode::Redraw {
+            if watch.mode == WatchMode::Redraw {
+                output::clear_screen();
+            }
+            if !output::quiet() {
+                output::print_line(&format!(
+                    "[{now}] Watching every {}s, Ctrl-C to stop",
+                    watch.interval.as_secs()
+                ));
             }
+            output::print_issues(&current, project, view.show_full_component, false);
         }
-
-        if all_issues.len() >= total || count < page_size || page >= 100 {
-            break;
+        if !diff.is_empty() {
+            output::print_issue_diff(&diff.added, &diff.removed, &now, view.show_full_component);
         }
-        page += 1;
+        previous = Some(current);
     }
-
-    output::print_issues(&all_issues, project, json);
-    0
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wiremock::matchers::{method, path};
+    use wiremock::matchers::{method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     async fn try_mock_server() -> Option<MockServer> {
@@ -117,8 +1021,8 @@ mod tests {
 
         let config = SonarQubeConfig::new(mock_server.uri());
         let params = IssueSearchParams::default();
-        let exit = run(config, "my-proj", &params, None, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", &params, None, &IssueView::default(), &IssueThreshold::default(), OutputFormat::Text).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -140,8 +1044,8 @@ mod tests {
             types: Some("CODE_SMELL"),
             ..IssueSearchParams::default()
         };
-        let exit = run(config, "my-proj", &params, None, true).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", &params, None, &IssueView::default(), &IssueThreshold::default(), OutputFormat::Json).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -158,8 +1062,8 @@ mod tests {
 
         let config = SonarQubeConfig::new(mock_server.uri());
         let params = IssueSearchParams::default();
-        let exit = run(config, "my-proj", &params, Some(2), false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", &params, Some(2), &IssueView::default(), &IssueThreshold::default(), OutputFormat::Text).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -176,8 +1080,8 @@ mod tests {
 
         let config = SonarQubeConfig::new(mock_server.uri());
         let params = IssueSearchParams::default();
-        let exit = run(config, "my-proj", &params, None, false).await;
-        assert_eq!(exit, 1);
+        let result = run(config, "my-proj", &params, None, &IssueView::default(), &IssueThreshold::default(), OutputFormat::Text).await;
+        assert!(result.is_err());
     }
 
     #[tokio::test]
@@ -194,8 +1098,8 @@ mod tests {
 
         let config = SonarQubeConfig::new(mock_server.uri());
         let params = IssueSearchParams::default();
-        let exit = run(config, "my-proj", &params, None, true).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", &params, None, &IssueView::default(), &IssueThreshold::default(), OutputFormat::Json).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -259,8 +1163,790 @@ mod tests {
 
         let config = SonarQubeConfig::new(mock_server.uri());
         let params = IssueSearchParams::default();
-        let exit = run(config, "my-proj", &params, None, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", &params, None, &IssueView::default(), &IssueThreshold::default(), OutputFormat::Text).await;
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_threshold_breach_under_and_over() {
+        let max = IssueThreshold { max_issues: Some(3), ..Default::default() };
+        assert_eq!(max.breach(3, 0), None);
+        assert_eq!(max.breach(4, 0).as_deref(), Some("4 issues exceed --max-issues 3"));
+
+        let any = IssueThreshold { fail_on_any: true, ..Default::default() };
+        assert_eq!(any.breach(0, 0), None);
+        assert!(any.breach(1, 0).is_some());
+
+        assert_eq!(IssueThreshold::default().breach(1000, 0), None);
+    }
+
+    #[test]
+    fn test_threshold_severity_counts_level_and_above() {
+        let issue = |sev: &str| -> SonarIssue {
+            serde_json::from_value(serde_json::json!({
+                "key": "k", "rule": "r", "severity": sev, "component": "c", "project": "p",
+                "message": "m", "type": "BUG", "status": "OPEN"
+            }))
+            .unwrap()
+        };
+        let issues = vec![issue("MAJOR"), issue("CRITICAL"), issue("BLOCKER")];
+        let threshold = IssueThreshold {
+            min_severity: Some(parse_fail_severity("critical").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(threshold.severe_count(&issues), 2);
+        assert_eq!(threshold.severe_count(&issues[..1]), 0);
+        assert_eq!(threshold.breach(1, 0), None);
+        assert!(threshold.breach(3, 2).unwrap().contains("2 issues at CRITICAL or above"));
+        assert!(parse_fail_severity("HUGE").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_exit_code_two_when_threshold_exceeded() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/issues/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(issues_body(2)))
+            .mount(&mock_server)
+            .await;
+
+        let params = IssueSearchParams::default();
+        let run_with = |threshold: IssueThreshold| {
+            let config = SonarQubeConfig::new(mock_server.uri());
+            let params = &params;
+            async move {
+                run(config, "my-proj", params, None, &IssueView::default(), &threshold, OutputFormat::Json).await
+            }
+        };
+        // issues_body issues are all CRITICAL
+        assert!(run_with(IssueThreshold { max_issues: Some(2), ..Default::default() }).await.is_ok());
+        let err = run_with(IssueThreshold { max_issues: Some(1), ..Default::default() }).await.unwrap_err();
+        assert!(matches!(err, CliError::Threshold(_)), "{err:?}");
+        assert_eq!(err.exit_code(), crate::error::exit_code::THRESHOLD);
+        let blocker = IssueThreshold { min_severity: Some("BLOCKER".to_string()), ..Default::default() };
+        assert!(run_with(blocker).await.is_ok());
+        let critical = IssueThreshold { min_severity: Some("CRITICAL".to_string()), ..Default::default() };
+        assert!(matches!(run_with(critical).await, Err(CliError::Threshold(_))));
+    }
+
+    #[tokio::test]
+    async fn test_run_threshold_counts_issues_cut_by_top() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/issues/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(issues_body(3)))
+            .mount(&mock_server)
+            .await;
+
+        let view = IssueView {
+            window: Window { skip: 0, top: Some(1) },
+            ..IssueView::default()
+        };
+        let threshold = IssueThreshold { max_issues: Some(2), ..Default::default() };
+        for format in [OutputFormat::Json, OutputFormat::Ndjson] {
+            let config = SonarQubeConfig::new(mock_server.uri());
+            let result = run(config, "my-proj", &IssueSearchParams::default(), None, &view, &threshold, format).await;
+            assert!(matches!(result, Err(CliError::Threshold(_))), "{format:?}: {result:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_issues_ndjson_one_object_per_line() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/issues/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(issues_body(5)))
+            .mount(&mock_server)
+            .await;
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let view = IssueView {
+            window: Window { skip: 1, top: None },
+            ..IssueView::default()
+        };
+        let mut out = Vec::new();
+        let matched = stream_issues(&client, "my-proj", &IssueSearchParams::default(), Some(4), &view, &IssueThreshold::default(), &mut out)
+            .await
+            .unwrap();
+        // Counted before --skip drops the first
+        assert_eq!(matched, Matched { count: 4, severe: 0 });
+
+        let text = String::from_utf8(out).unwrap();
+        let keys: Vec<String> = text
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let value: serde_json::Value = serde_json::from_str(line).unwrap();
+                assert!(value.is_object(), "{line}");
+                value["key"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(keys, ["issue-1", "issue-2", "issue-3"]);
+        assert!(!text.contains("issues found"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn test_stream_issues_single_page() {
+        use wiremock::matchers::query_param;
+
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        // A total far past one page must not trigger any further request
+        let mut body = issues_body(3);
+        body["total"] = serde_json::json!(5000);
+        Mock::given(method("GET"))
+            .and(path("/api/issues/search"))
+            .and(query_param("p", "7"))
+            .and(query_param("ps", "3"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let view = IssueView {
+            page: PageRequest::from_flags(Some(7), Some(3)),
+            ..IssueView::default()
+        };
+        let mut out = Vec::new();
+        let matched = stream_issues(&client, "my-proj", &IssueSearchParams::default(), None, &view, &IssueThreshold::default(), &mut out)
+            .await
+            .unwrap();
+        assert_eq!(matched, Matched { count: 3, severe: 0 });
+    }
+
+    #[test]
+    fn test_path_prefixes() {
+        let all = PathPrefixes::from_flags(None, None);
+        assert!(all.keeps("src/main.rs"));
+
+        let paths = PathPrefixes::from_flags(Some("./src/api/, lib/"), Some("src/api/gen/"));
+        assert_eq!(paths.include, ["src/api/", "lib/"]);
+        assert!(paths.keeps("src/api/routes.rs"));
+        assert!(paths.keeps("lib/util.rs"));
+        assert!(!paths.keeps("src/core/model.rs"));
+        assert!(!paths.keeps("src/api/gen/client.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_collect_issues_filters_by_path_prefix() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        let mut body = issues_body(3);
+        for (issue, component) in body["issues"]
+            .as_array_mut()
+            .unwrap()
+            .iter_mut()
+            .zip(["my-proj:src/api/routes.rs", "my-proj:src/core/model.rs", "my-proj:src/core/store.rs"])
+        {
+            issue["component"] = serde_json::json!(component);
+        }
+        Mock::given(method("GET"))
+            .and(path("/api/issues/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&mock_server)
+            .await;
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let view = IssueView {
+            paths: PathPrefixes::from_flags(Some("src/api/"), None),
+            ..IssueView::default()
+        };
+        let (issues, _) = collect_issues(&client, "my-proj", &IssueSearchParams::default(), None, &view, &IssueThreshold::default())
+            .await
+            .unwrap();
+        let components: Vec<&str> = issues.iter().map(|i| i.component.as_str()).collect();
+        assert_eq!(components, ["my-proj:src/api/routes.rs"]);
+    }
+
+    #[tokio::test]
+    async fn test_run_ndjson_rejects_rule_annotations() {
+        let config = SonarQubeConfig::new("http://localhost:1");
+        let view = IssueView {
+            with_rule_names: true,
+            ..IssueView::default()
+        };
+        let result = run(config, "my-proj", &IssueSearchParams::default(), None, &view, &IssueThreshold::default(), OutputFormat::Ndjson).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_build_status_filter_include_closed_with_defaults() {
+        assert_eq!(
+            build_status_filter(None, true).as_deref(),
+            Some("OPEN,CONFIRMED,REOPENED,CLOSED")
+        );
+    }
+
+    #[test]
+    fn test_build_status_filter_include_closed_with_user_statuses() {
+        assert_eq!(
+            build_status_filter(Some("RESOLVED"), true).as_deref(),
+            Some("RESOLVED,CLOSED")
+        );
+        // Not duplicated when already requested
+        assert_eq!(
+            build_status_filter(Some("OPEN,closed"), true).as_deref(),
+            Some("OPEN,closed")
+        );
+    }
+
+    #[test]
+    fn test_build_status_filter_without_include_closed() {
+        assert_eq!(build_status_filter(None, false), None);
+        assert_eq!(build_status_filter(Some("OPEN"), false).as_deref(), Some("OPEN"));
+    }
+
+    #[tokio::test]
+    async fn test_annotate_rule_severities_single_fetch() {
+        use wiremock::matchers::query_param;
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/rules/search"))
+            .and(query_param("rule_key", "java:S1,java:S2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total": 2,
+                "rules": [
+                    {"key": "java:S1", "name": "One", "severity": "MINOR"},
+                    {"key": "java:S2", "name": "Two", "severity": "BLOCKER"}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut issues: Vec<SonarIssue> = ["java:S2", "java:S1", "java:S2"]
+            .iter()
+            .enumerate()
+            .map(|(i, rule)| {
+                serde_json::from_value(serde_json::json!({
+                    "key": format!("issue-{i}"),
+                    "rule": rule,
+                    "severity": "CRITICAL",
+                    "component": "my-proj:src/Main.java",
+                    "project": "my-proj",
+                    "message": "msg",
+                    "type": "BUG",
+                    "status": "OPEN"
+                }))
+                .unwrap()
+            })
+            .collect();
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        annotate_rules(&client, &mut issues, true, false).await.unwrap();
+        let annotated: Vec<Option<&str>> =
+            issues.iter().map(|i| i.rule_severity.as_deref()).collect();
+        assert_eq!(annotated, vec![Some("BLOCKER"), Some("MINOR"), Some("BLOCKER")]);
+        // Instance severity is untouched
+        assert!(issues.iter().all(|i| i.severity == "CRITICAL"));
+        assert!(issues.iter().all(|i| i.rule_name.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_annotate_rule_names_single_lookup() {
+        use wiremock::matchers::query_param;
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/rules/search"))
+            .and(query_param("rule_key", "rust:S1192,rust:S3776"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total": 2,
+                "rules": [
+                    {"key": "rust:S3776", "name": "Cognitive Complexity of functions should not be too high", "severity": "CRITICAL"},
+                    {"key": "rust:S1192", "name": "String literals should not be duplicated", "severity": "CRITICAL"}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut issues: Vec<SonarIssue> = ["rust:S3776", "rust:S1192", "rust:S3776"]
+            .iter()
+            .enumerate()
+            .map(|(i, rule)| {
+                serde_json::from_value(serde_json::json!({
+                    "key": format!("issue-{i}"),
+                    "rule": rule,
+                    "severity": "MAJOR",
+                    "component": "my-proj:src/lib.rs",
+                    "project": "my-proj",
+                    "message": "msg",
+                    "type": "CODE_SMELL",
+                    "status": "OPEN"
+                }))
+                .unwrap()
+            })
+            .collect();
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        annotate_rules(&client, &mut issues, true, true).await.unwrap();
+        let names: Vec<Option<&str>> = issues.iter().map(|i| i.rule_name.as_deref()).collect();
+        assert_eq!(
+            names,
+            vec![
+                Some("Cognitive Complexity of functions should not be too high"),
+                Some("String literals should not be duplicated"),
+                Some("Cognitive Complexity of functions should not be too high"),
+            ]
+        );
+        // Severities come from the same lookup
+        assert!(issues.iter().all(|i| i.rule_severity.as_deref() == Some("CRITICAL")));
+    }
+
+    #[tokio::test]
+    async fn test_last_green_cutoff_skips_failing_analyses() {
+        use wiremock::matchers::query_param;
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/measures/search_history"))
+            .and(query_param("metrics", "alert_status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "paging": {"pageIndex": 1, "pageSize": 100, "total": 1},
+                "measures": [{
+                    "metric": "alert_status",
+                    "history": [
+                        {"date": "2025-03-01T10:00:00+0000", "value": "OK"},
+                        {"date": "2025-03-02T10:00:00+0000", "value": "ERROR"},
+                        {"date": "2025-03-03T10:00:00+0000", "value": "OK"},
+                        {"date": "2025-03-04T10:00:00+0000", "value": "ERROR"}
+                    ]
+                }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let cutoff = last_green_cutoff(&client, "my-proj").await.unwrap();
+        assert_eq!(cutoff, "2025-03-03T10:00:00+0000");
+    }
+
+    #[tokio::test]
+    async fn test_last_green_cutoff_errors_when_never_green() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/measures/search_history"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "paging": {"pageIndex": 1, "pageSize": 100, "total": 1},
+                "measures": [{
+                    "metric": "alert_status",
+                    "history": [{"date": "2025-03-04T10:00:00+0000", "value": "ERROR"}]
+                }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let err = last_green_cutoff(&client, "my-proj").await.unwrap_err().to_string();
+        assert!(err.contains("passed the quality gate"), "{err}");
+    }
+
+    fn severity_facet(counts: &[(&str, usize)]) -> serde_json::Value {
+        let values: Vec<serde_json::Value> = counts
+            .iter()
+            .map(|(val, count)| serde_json::json!({"val": val, "count": count}))
+            .collect();
+        serde_json::json!({
+            "total": 0,
+            "issues": [],
+            "facets": [{"property": "severities", "values": values}]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_issue_changes_count_issues_resolved_since_baseline() {
+        use wiremock::matchers::query_param;
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        let task = |id: &str, executed: &str| {
+            serde_json::json!({
+                "id": id,
+                "type": "REPORT",
+                "status": "SUCCESS",
+                "submittedAt": executed,
+                "executedAt": executed
+            })
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/ce/activity"))
+            .and(query_param("component", "my-proj"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "tasks": [
+                    task("t3", "2026-03-01T10:00:00+0000"),
+                    task("t2", "2026-02-15T10:00:00+0000"),
+                    task("t1", "2026-02-01T10:00:00+0000")
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+        // Issues raised after the baseline analysis, in any status
+        Mock::given(method("GET"))
+            .and(path("/api/issues/search"))
+            .and(query_param("createdAfter", "2026-02-01T10:00:00+0000"))
+            .and(query_param("statuses", issue_status::ALL))
+            .and(query_param("facets", "severities"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(severity_facet(&[
+                ("BLOCKER", 1),
+                ("MAJOR", 4),
+                ("MINOR", 2),
+            ])))
+            .mount(&mock_server)
+            .await;
+        // Inactive issues, most recently updated first; paging stops at the
+        // first one resolved before the baseline
+        let inactive = |key: &str, severity: &str, date_field: &str, date: &str| {
+            let mut issue = serde_json::to_value(issue(key, severity, "my-proj:src/a.rs", None)).unwrap();
+            issue["status"] = "CLOSED".into();
+            issue[date_field] = date.into();
+            issue
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/issues/search"))
+            .and(query_param("statuses", issue_status::INACTIVE))
+            .and(query_param("s", "UPDATE_DATE"))
+            .and(query_param("asc", "false"))
+            .and(query_param("ps", "500"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total": 5,
+                "issues": [
+                    inactive("r1", "CRITICAL", "closeDate", "2026-02-20T10:00:00+0000"),
+                    inactive("r2", "MAJOR", "updateDate", "2026-02-10T10:00:00+0000"),
+                    inactive("r3", "CRITICAL", "closeDate", "2026-02-01T10:00:00+0000"),
+                    inactive("r4", "MAJOR", "closeDate", "2026-01-15T10:00:00+0000"),
+                    inactive("r5", "BLOCKER", "closeDate", "2026-01-01T10:00:00+0000")
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let (changes, since) =
+            fetch_issue_changes(&client, "my-proj", &IssueSearchParams::default(), 2)
+                .await
+                .unwrap();
+        assert_eq!(since, "2026-02-01T10:00:00+0000");
+        let rows: Vec<(&str, usize, usize, i64)> = changes
+            .iter()
+            .map(|c| (c.severity.as_str(), c.opened, c.resolved, c.net))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("BLOCKER", 1, 0, 1),
+                ("CRITICAL", 0, 2, -2),
+                ("MAJOR", 4, 1, 3),
+                ("MINOR", 2, 0, 2),
+                ("INFO", 0, 0, 0),
+            ]
+        );
+
+        // Fewer analyses than requested is an error, not an empty report
+        let err = fetch_issue_changes(&client, "my-proj", &IssueSearchParams::default(), 5)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("need at least 6"), "{err}");
+    }
+
+    fn issue(key: &str, severity: &str, component: &str, line: Option<u32>) -> SonarIssue {
+        serde_json::from_value(serde_json::json!({
+            "key": key,
+            "rule": "java:S1",
+            "severity": severity,
+            "component": component,
+            "project": "my-proj",
+            "line": line,
+            "message": "msg",
+            "type": "BUG",
+            "status": "OPEN"
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_attach_context_marks_issue_line() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/sources/show"))
+            .and(query_param("key", "my-proj:src/lib.rs"))
+            .and(query_param("from", "40"))
+            .and(query_param("to", "44"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sources": [
+                    [40, "fn parse(input: &str) -> u32 {"],
+                    [41, "    let value = input.trim();"],
+                    [42, "    value.parse().<span class=\"k\">unwrap</span>()"],
+                    [43, "}"],
+                    [44, ""]
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let mut issues = vec![
+            issue("a", "MAJOR", "my-proj:src/lib.rs", Some(42)),
+            issue("b", "MINOR", "my-proj:src/lib.rs", Some(42)),
+            issue("no-line", "INFO", "my-proj:src/lib.rs", None),
+        ];
+        assert_eq!(attach_context(&client, &mut issues, 2).await, 0);
+
+        assert_eq!(
+            output::issue_snippet_lines(&issues[0]),
+            [
+                "  40 | fn parse(input: &str) -> u32 {",
+                "  41 |     let value = input.trim();",
+                "> 42 |     value.parse().unwrap()",
+                "  43 | }",
+                "  44 | ",
+            ]
+        );
+        assert_eq!(issues[1].snippet.len(), 5);
+        assert!(issues[2].snippet.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_context_rejects_csv() {
+        let config = SonarQubeConfig::new("http://localhost:1");
+        let view = IssueView {
+            context: 3,
+            ..IssueView::default()
+        };
+        let result = run(config, "my-proj", &IssueSearchParams::default(), None, &view, &IssueThreshold::default(), OutputFormat::Csv).await;
+        assert!(matches!(result, Err(CliError::Validation(_))), "{result:?}");
+    }
+
+    #[tokio::test]
+    async fn test_run_ndjson_rejects_sort_and_reverse() {
+        for view in [
+            IssueView { sort: IssueSort::File, ..IssueView::default() },
+            IssueView { reverse: true, ..IssueView::default() },
+        ] {
+            let config = SonarQubeConfig::new("http://localhost:1");
+            let result = run(config, "my-proj", &IssueSearchParams::default(), None, &view, &IssueThreshold::default(), OutputFormat::Ndjson).await;
+            assert!(matches!(result, Err(CliError::Validation(_))), "{result:?}");
+        }
+    }
+
+    #[test]
+    fn test_diff_issues_added_and_removed_keys() {
+        let previous = vec![
+            issue("kept", "MAJOR", "my-proj:a.rs", Some(1)),
+            issue("fixed", "CRITICAL", "my-proj:b.rs", Some(2)),
+        ];
+        let current = vec![
+            issue("new", "BLOCKER", "my-proj:c.rs", Some(3)),
+            issue("kept", "MAJOR", "my-proj:a.rs", Some(1)),
+        ];
+        let diff = diff_issues(&previous, &current);
+        let keys = |issues: &[&SonarIssue]| issues.iter().map(|i| i.key.clone()).collect::<Vec<_>>();
+        assert_eq!(keys(&diff.added), ["new"]);
+        assert_eq!(keys(&diff.removed), ["fixed"]);
+        assert!(diff_issues(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_group_issues_counts_per_rule() {
+        let with_rule = |key: &str, rule: &str, component: &str| SonarIssue {
+            rule: rule.to_string(),
+            ..issue(key, "MAJOR", component, None)
+        };
+        let issues = vec![
+            with_rule("1", "rust:S3776", "my-proj:src/a.rs"),
+            with_rule("2", "rust:S1135", "my-proj:src/a.rs"),
+            with_rule("3", "rust:S3776", "my-proj:src/b.rs"),
+            with_rule("4", "rust:S107", "my-proj:src/b.rs"),
+            with_rule("5", "rust:S3776", "my-proj:src/c.rs"),
+            with_rule("6", "rust:S1135", "my-proj:src/c.rs"),
+        ];
+        let counts = |groups: Vec<(String, usize)>| {
+            groups.into_iter().map(|(k, n)| format!("{k}={n}")).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            counts(group_issues(&issues, "my-proj", IssueGroup::Rule, false)),
+            ["rust:S3776=3", "rust:S1135=2", "rust:S107=1"]
+        );
+        assert_eq!(
+            counts(group_issues(&issues, "my-proj", IssueGroup::File, false)),
+            ["src/a.rs=2", "src/b.rs=2", "src/c.rs=2"]
+        );
+        assert_eq!(
+            counts(group_issues(&issues, "my-proj", IssueGroup::File, true))[0],
+            "my-proj:src/a.rs=2"
+        );
+        assert_eq!(counts(group_issues(&issues, "my-proj", IssueGroup::Severity, false)), ["MAJOR=6"]);
+        assert_eq!(counts(group_issues(&issues, "my-proj", IssueGroup::Type, false)), ["BUG=6"]);
+    }
+
+    #[test]
+    fn test_parse_group_by() {
+        assert_eq!(parse_group_by("Rule").unwrap(), IssueGroup::Rule);
+        assert_eq!(parse_group_by("type").unwrap(), IssueGroup::Type);
+        let err = parse_group_by("author").unwrap_err();
+        assert!(err.contains("Valid values: rule, file, severity, type"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_run_group_by_rejects_csv() {
+        let config = SonarQubeConfig::new("http://localhost:1");
+        let view = IssueView {
+            group_by: Some(IssueGroup::Rule),
+            ..IssueView::default()
+        };
+        let result = run(config, "my-proj", &IssueSearchParams::default(), None, &view, &IssueThreshold::default(), OutputFormat::Csv).await;
+        assert!(matches!(result, Err(CliError::Validation(_))), "{result:?}");
+    }
+
+    #[test]
+    fn test_parse_watch_mode() {
+        assert_eq!(parse_watch_mode("redraw").unwrap(), WatchMode::Redraw);
+        assert_eq!(parse_watch_mode("APPEND").unwrap(), WatchMode::Append);
+        let err = parse_watch_mode("scroll").unwrap_err();
+        assert!(err.contains("Valid values: redraw, append"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_run_watch_rejects_json() {
+        let config = SonarQubeConfig::new("http://localhost:1");
+        let watch = WatchOptions {
+            interval: std::time::Duration::from_secs(1),
+            mode: WatchMode::Append,
+        };
+        let params = IssueSearchParams::default();
+        let result = run_watch(config, "my-proj", &params, None, &IssueView::default(), watch, OutputFormat::Json)
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_sort_issues_by_severity_then_file_then_line() {
+        let mut issues = vec![
+            issue("minor", "MINOR", "my-proj:a.rs", Some(1)),
+            issue("info", "INFO", "my-proj:a.rs", Some(1)),
+            issue("blocker-b", "BLOCKER", "my-proj:b.rs", Some(3)),
+            issue("major", "MAJOR", "my-proj:a.rs", Some(1)),
+            issue("blocker-a9", "BLOCKER", "my-proj:a.rs", Some(9)),
+            issue("critical", "CRITICAL", "my-proj:a.rs", Some(1)),
+            issue("blocker-a2", "BLOCKER", "my-proj:a.rs", Some(2)),
+        ];
+        sort_issues(&mut issues);
+        let keys: Vec<&str> = issues.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec!["blocker-a2", "blocker-a9", "blocker-b", "critical", "major", "minor", "info"]
+        );
+    }
+
+    #[test]
+    fn test_sort_issues_by_each_key() {
+        let with_rule = |key: &str, rule: &str, severity: &str, component: &str, line: u32| SonarIssue {
+            rule: rule.to_string(),
+            ..issue(key, severity, component, Some(line))
+        };
+        let mixed = vec![
+            with_rule("b7-major", "java:S2", "MAJOR", "my-proj:b.rs", 7),
+            with_rule("a3-minor", "java:S1", "MINOR", "my-proj:a.rs", 3),
+            with_rule("b1-blocker", "java:S3", "BLOCKER", "my-proj:b.rs", 1),
+            with_rule("a3-critical", "java:S2", "CRITICAL", "my-proj:a.rs", 3),
+            with_rule("a1-major", "java:S1", "MAJOR", "my-proj:a.rs", 1),
+        ];
+        let sorted = |key: IssueSort, reverse: bool| {
+            let mut issues = mixed.clone();
+            sort_issues_by(&mut issues, key, reverse);
+            issues.into_iter().map(|i| i.key).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sorted(IssueSort::Severity, false),
+            ["b1-blocker", "a3-critical", "a1-major", "b7-major", "a3-minor"]
+        );
+        assert_eq!(
+            sorted(IssueSort::File, false),
+            ["a1-major", "a3-critical", "a3-minor", "b1-blocker", "b7-major"]
+        );
+        assert_eq!(
+            sorted(IssueSort::Line, false),
+            ["a1-major", "b1-blocker", "a3-minor", "a3-critical", "b7-major"]
+        );
+        assert_eq!(
+            sorted(IssueSort::Rule, false),
+            ["a1-major", "a3-minor", "a3-critical", "b7-major", "b1-blocker"]
+        );
+        assert_eq!(
+            sorted(IssueSort::Severity, true),
+            ["a3-minor", "b7-major", "a1-major", "a3-critical", "b1-blocker"]
+        );
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        assert_eq!(parse_sort("Severity").unwrap(), IssueSort::Severity);
+        assert_eq!(parse_sort("line").unwrap(), IssueSort::Line);
+        let err = parse_sort("age").unwrap_err();
+        assert!(err.contains("Valid values: severity, file, line, rule"), "{err}");
+    }
+
+    #[test]
+    fn test_issues_web_url_single_issue_and_search() {
+        let params = IssueSearchParams {
+            severities: Some("CRITICAL,BLOCKER"),
+            rules: Some("java:S2259"),
+            ..Default::default()
+        };
+        assert_eq!(
+            issues_web_url("https://sonar.example.com/", "my-proj", Some("main"), &params, Some("AX1")),
+            "https://sonar.example.com/project/issues?id=my-proj&branch=main&issues=AX1&open=AX1"
+        );
+        assert_eq!(
+            issues_web_url("https://sonar.example.com", "my-proj", None, &params, None),
+            "https://sonar.example.com/project/issues?id=my-proj&severities=CRITICAL,BLOCKER\
+             &rules=java:S2259&resolved=false"
+        );
+    }
+
+    #[test]
+    fn test_issues_web_url_encodes_filter_values() {
+        let params = IssueSearchParams {
+            author: Some("jane doe&x"),
+            created_after: Some("2026-03-01T10:00:00+0100"),
+            ..Default::default()
+        };
+        assert_eq!(
+            issues_web_url("https://sonar.example.com", "my-proj", Some("feat#1"), &params, None),
+            "https://sonar.example.com/project/issues?id=my-proj&branch=feat%231\
+             &createdAfter=2026-03-01T10:00:00%2B0100&author=jane%20doe%26x&resolved=false"
+        );
     }
 
     #[test]
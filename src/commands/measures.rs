@@ -1,7 +1,15 @@
-use crate::client::{SonarQubeClient, SonarQubeConfig};
-use crate::output;
+use std::io::Read;
 
-const DEFAULT_METRICS: &[&str] = &[
+use crate::client::{SonarQubeClient, SonarQubeConfig, SonarQubeError};
+use crate::commands::history::no_history;
+use crate::error::{exit_code, CliError};
+use crate::helpers::{self, compare_measures, measure_trends, measures_matrix, MeasuresMatrix};
+use crate::types::{MeasureHistory, MeasuresResponse};
+use crate::output::{self, OutputFormat};
+use crate::paging;
+
+/// Metrics shown when `--metrics` is not given
+pub const DEFAULT_METRICS: &[&str] = &[
     "ncloc",
     "coverage",
     "duplicated_lines_density",
@@ -14,41 +22,505 @@ const DEFAULT_METRICS: &[&str] = &[
     "sqale_rating",
 ];
 
+/// New-code metrics whose name isn't simply `new_` + the overall metric
+const NEW_CODE_ALIASES: &[(&str, &str)] = &[
+    ("ncloc", "new_lines"),
+    ("lines", "new_lines"),
+    ("sqale_index", "new_technical_debt"),
+    ("sqale_rating", "new_maintainability_rating"),
+];
+
+/// Metric that gives the size of the new code, shown next to percentages
+const NEW_LINES_METRIC: &str = "new_lines";
+
+/// Map a metric key to its new-code counterpart (`coverage` -> `new_coverage`)
+fn new_code_metric(key: &str) -> String {
+    if key.starts_with("new_") {
+        return key.to_string();
+    }
+    match NEW_CODE_ALIASES.iter().find(|(overall, _)| *overall == key) {
+        Some((_, new)) => new.to_string(),
+        None => format!("new_{key}"),
+    }
+}
+
+/// Split a comma-separated metric list: trim keys, drop blanks and repeats.
+///
+/// First-seen order is kept; some servers reject requests with repeated keys.
+fn parse_metric_keys(metrics: &str) -> Vec<&str> {
+    let mut keys: Vec<&str> = Vec::new();
+    for key in metrics.split(',').map(str::trim) {
+        if !key.is_empty() && !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// Comparison operator of a `--assert` expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssertOp {
+    Ge,
+    Le,
+    Gt,
+    Lt,
+    Eq,
+    Ne,
+}
+
+impl AssertOp {
+    /// Two-character operators first, so `>=` is not read as `>` followed by `=80`
+    const ALL: [(&'static str, AssertOp); 6] = [
+        (">=", AssertOp::Ge),
+        ("<=", AssertOp::Le),
+        ("==", AssertOp::Eq),
+        ("!=", AssertOp::Ne),
+        (">", AssertOp::Gt),
+        ("<", AssertOp::Lt),
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ge => ">=",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Lt => "<",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+        }
+    }
+
+    fn holds(self, actual: f64, expected: f64) -> bool {
+        match self {
+            Self::Ge => actual >= expected,
+            Self::Le => actual <= expected,
+            Self::Gt => actual > expected,
+            Self::Lt => actual < expected,
+            Self::Eq => actual == expected,
+            Self::Ne => actual != expected,
+        }
+    }
+}
+
+/// A `--assert <metric><op><value>` check on a measure, e.g. `coverage>=80`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeasureAssertion {
+    pub metric: String,
+    pub op: AssertOp,
+    pub value: f64,
+}
+
+impl std::fmt::Display for MeasureAssertion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}{}", self.metric, self.op.as_str(), self.value)
+    }
+}
+
+impl MeasureAssertion {
+    /// Whether the measure's `value` satisfies the assertion; a missing or
+    /// non-numeric value never does
+    pub fn holds(&self, value: Option<&str>) -> bool {
+        value
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .is_some_and(|actual| self.op.holds(actual, self.value))
+    }
+}
+
+/// Parse `--assert`: a metric key, one of `>=,<=,>,<,==,!=`, then a number
+pub fn parse_assertion(expr: &str) -> Result<MeasureAssertion, String> {
+    let invalid = |reason: &str| {
+        format!("Invalid assertion '{expr}': {reason} (expected e.g. coverage>=80 or bugs==0)")
+    };
+    let start = expr
+        .find(['<', '>', '=', '!'])
+        .ok_or_else(|| invalid("no operator (>=, <=, >, <, ==, !=)"))?;
+    let metric = expr[..start].trim();
+    if metric.is_empty() {
+        return Err(invalid("missing metric key"));
+    }
+    let rest = &expr[start..];
+    let (symbol, op) = AssertOp::ALL
+        .iter()
+        .find(|(symbol, _)| rest.starts_with(symbol))
+        .ok_or_else(|| invalid("unknown operator"))?;
+    let value = rest[symbol.len()..]
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| invalid("value is not a number"))?;
+    Ok(MeasureAssertion {
+        metric: metric.to_string(),
+        op: *op,
+        value,
+    })
+}
+
+/// Print each assertion as passed or failed (on stderr, so JSON output stays
+/// parseable) and exit with the threshold code if any failed
+fn check_assertions(response: &MeasuresResponse, assertions: &[MeasureAssertion]) -> Result<(), CliError> {
+    let mut failed = 0;
+    for assertion in assertions {
+        let value = response
+            .component
+            .measures
+            .iter()
+            .find(|m| m.metric == assertion.metric)
+            .and_then(|m| m.value.as_deref());
+        let passed = assertion.holds(value);
+        if !passed {
+            failed += 1;
+        }
+        eprintln!(
+            "{} {assertion} (actual: {})",
+            if passed { "PASS" } else { "FAIL" },
+            value.unwrap_or("no value")
+        );
+    }
+    if failed == 0 {
+        Ok(())
+    } else {
+        eprintln!("{failed} of {} measure assertions failed", assertions.len());
+        Err(CliError::Exit(exit_code::THRESHOLD))
+    }
+}
+
+/// Which values `measures` shows for the requested metrics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeasuresView<'a> {
+    /// Current values
+    Current,
+    /// Side by side with another branch, with deltas
+    CompareBranch(&'a str),
+    /// New-code variants for the new code period
+    NewCode,
+    /// Current values with the change since the previous analysis
+    Trend,
+    /// Current values of every metric defined on the server
+    All,
+}
+
+/// Print current measures in the requested format
+fn print_current(response: &MeasuresResponse, human: bool, format: OutputFormat) -> Result<(), CliError> {
+    match format.delimiter() {
+        Some(delimiter) => output::write_measures_delimited(&mut output::sink(), response, delimiter)
+            .map_err(CliError::output),
+        None => {
+            output::print_measures(response, human, format == OutputFormat::Json);
+            Ok(())
+        }
+    }
+}
+
+/// Enumerate the server's metrics, then fetch them in chunks and print one table
+async fn run_all(
+    client: &SonarQubeClient,
+    project: &str,
+    human: bool,
+    format: OutputFormat,
+    assertions: &[MeasureAssertion],
+) -> Result<(), CliError> {
+    let metrics = client
+        .get_all_metrics()
+        .await
+        .map_err(|e| CliError::api("Failed to list metrics", e))?;
+    let keys: Vec<&str> = metrics.iter().map(|m| m.key.as_str()).collect();
+    let response = client.get_measures_chunked(project, &keys).await;
+    if no_measures(client, project, &response).await {
+        return not_analyzed(project, format == OutputFormat::Json, assertions);
+    }
+    let response = response.map_err(|e| CliError::api("Failed to get measures", e))?;
+    print_current(&response, human, format)?;
+    check_assertions(&response, assertions)
+}
+
+/// Show measures for `project`; `assertions` are checked against the current
+/// values (`MeasuresView::Current` or `All`), and their metrics are fetched
+/// even when not listed in `metrics`
 pub async fn run(
     config: SonarQubeConfig,
     project: &str,
     metrics: Option<&str>,
-    json: bool,
-) -> i32 {
-    let client = match SonarQubeClient::new(config) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to create client: {e}");
-            return 1;
+    view: MeasuresView<'_>,
+    human: bool,
+    assertions: &[MeasureAssertion],
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    if format == OutputFormat::Ndjson {
+        return Err(CliError::Validation(output::unsupported_format(
+            format,
+            "measures",
+            "text, json, csv or tsv",
+        )));
+    }
+    if format.delimiter().is_some() && !matches!(view, MeasuresView::Current | MeasuresView::All) {
+        return Err(CliError::Validation(format!(
+            "{} output is only supported for current measures, not with --compare-branch, --new-code or --trend",
+            format.label()
+        )));
+    }
+    if !assertions.is_empty() && !matches!(view, MeasuresView::Current | MeasuresView::All) {
+        return Err(CliError::Validation(
+            "--assert is only supported for current measures, not with --compare-branch, --new-code or --trend"
+                .to_string(),
+        ));
+    }
+    let json = format == OutputFormat::Json;
+    let branch = config.branch.clone();
+    let mut metric_keys: Vec<&str> = match metrics {
+        Some(m) => parse_metric_keys(m),
+        None => DEFAULT_METRICS.to_vec(),
+    };
+    if metric_keys.is_empty() {
+        return Err(CliError::Validation(
+            "--metrics requires at least one metric key (e.g. --metrics coverage,bugs)".to_string(),
+        ));
+    }
+    for assertion in assertions {
+        if !metric_keys.contains(&assertion.metric.as_str()) {
+            metric_keys.push(&assertion.metric);
         }
+    }
+
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    match view {
+        MeasuresView::CompareBranch(base) => {
+            run_compare(&client, project, &metric_keys, branch.as_deref(), base, json).await
+        }
+        MeasuresView::NewCode => run_new_code(&client, project, &metric_keys, human, json).await,
+        MeasuresView::Trend => run_trend(&client, project, &metric_keys, json).await,
+        MeasuresView::All => run_all(&client, project, human, format, assertions).await,
+        MeasuresView::Current => {
+            let response = client.get_measures(project, &metric_keys).await;
+            if no_measures(&client, project, &response).await {
+                return not_analyzed(project, json, assertions);
+            }
+            let response = response.map_err(|e| CliError::api("Failed to get measures", e))?;
+            print_current(&response, human, format)?;
+            check_assertions(&response, assertions)
+        }
+    }
+}
+
+/// Project keys for `--projects`: a comma-separated list, or `-` to read
+/// newline-delimited keys from `stdin`. Blank entries and repeats are dropped.
+pub fn read_project_keys<R: Read>(arg: &str, mut stdin: R) -> Result<Vec<String>, String> {
+    let input = if arg == "-" {
+        let mut buf = String::new();
+        stdin
+            .read_to_string(&mut buf)
+            .map_err(|e| format!("Failed to read project keys from stdin: {e}"))?;
+        buf
+    } else {
+        arg.to_string()
     };
 
+    let mut keys: Vec<String> = Vec::new();
+    for key in input.split(['\n', ',']).map(str::trim) {
+        if !key.is_empty() && !keys.iter().any(|k| k == key) {
+            keys.push(key.to_string());
+        }
+    }
+    if keys.is_empty() {
+        return Err(if arg == "-" {
+            "No project keys on stdin (expected one key per line)".to_string()
+        } else {
+            "--projects requires at least one project key".to_string()
+        });
+    }
+    Ok(keys)
+}
+
+/// Fetch the same metrics for every project, at most `client.concurrency()`
+/// at a time, and lay them out as a matrix in the order of `projects`.
+///
+/// Projects whose fetch failed get an empty column and are returned alongside.
+async fn fetch_matrix(
+    client: &SonarQubeClient,
+    projects: &[String],
+    metric_keys: &[&str],
+) -> (MeasuresMatrix, Vec<(String, SonarQubeError)>) {
+    let fetches = projects.iter().map(|project| async move {
+        (project, client.get_measures(project, metric_keys).await)
+    });
+    let results = paging::join_bounded(client.concurrency(), fetches).await;
+
+    let mut failures = Vec::new();
+    let columns: Vec<(String, Option<MeasuresResponse>)> = results
+        .into_iter()
+        .map(|(project, result)| match result {
+            Ok(response) => (project.clone(), Some(response)),
+            Err(e) => {
+                failures.push((project.clone(), e));
+                (project.clone(), None)
+            }
+        })
+        .collect();
+    (measures_matrix(metric_keys, &columns), failures)
+}
+
+/// Print one column of measures per project; exits 1 if any project failed
+pub async fn run_matrix(
+    config: SonarQubeConfig,
+    projects: &[String],
+    metrics: Option<&str>,
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    if !matches!(format, OutputFormat::Text | OutputFormat::Json) {
+        return Err(CliError::Validation(output::unsupported_format(
+            format,
+            "measures --projects",
+            "text or json",
+        )));
+    }
+    let json = format == OutputFormat::Json;
     let metric_keys: Vec<&str> = match metrics {
-        Some(m) => m.split(',').map(|s| s.trim()).collect(),
+        Some(m) => parse_metric_keys(m),
         None => DEFAULT_METRICS.to_vec(),
     };
+    if metric_keys.is_empty() {
+        return Err(CliError::Validation(
+            "--metrics requires at least one metric key (e.g. --metrics coverage,bugs)".to_string(),
+        ));
+    }
 
-    match client.get_measures(project, &metric_keys).await {
-        Ok(response) => {
-            output::print_measures(&response, json);
-            0
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let (matrix, failures) = fetch_matrix(&client, projects, &metric_keys).await;
+    for (project, e) in &failures {
+        eprintln!("Failed to get measures for {project}: {e}");
+    }
+    output::print_measures_matrix(&matrix, json);
+    if failures.is_empty() { Ok(()) } else { Err(CliError::Exit(exit_code::FAILURE)) }
+}
+
+/// Whether an empty or missing measures result comes from a never-analyzed project
+async fn no_measures(
+    client: &SonarQubeClient,
+    project: &str,
+    response: &Result<MeasuresResponse, SonarQubeError>,
+) -> bool {
+    helpers::empty_or_missing(response, |r| r.component.measures.is_empty())
+        && helpers::never_analyzed(client, project).await
+}
+
+/// Report a never-analyzed project; `--assert` fails, since no measure can pass
+fn not_analyzed(project: &str, json: bool, assertions: &[MeasureAssertion]) -> Result<(), CliError> {
+    output::print_not_analyzed(project, json);
+    if !assertions.is_empty() {
+        eprintln!("Measure assertions failed: the project has no analysis");
+        return Err(CliError::Exit(exit_code::THRESHOLD));
+    }
+    Ok(())
+}
+
+/// Fetch only the two most recent history points of each metric.
+///
+/// History is returned oldest first, so a one-point probe gives the number of
+/// analyses and the last two pages of size one hold the points we want.
+async fn fetch_last_two_points(
+    client: &SonarQubeClient,
+    project: &str,
+    metrics: &str,
+) -> Result<Vec<MeasureHistory>, SonarQubeError> {
+    let probe = client.get_measures_history(project, metrics, None, None, 1, 1).await?;
+    let total = probe.paging.total;
+    if total <= 1 {
+        return Ok(probe.measures);
+    }
+
+    let (previous, latest) = tokio::join!(
+        client.get_measures_history(project, metrics, None, None, total - 1, 1),
+        client.get_measures_history(project, metrics, None, None, total, 1),
+    );
+    let previous = previous?.measures;
+    let mut latest = latest?.measures;
+    for measure in &mut latest {
+        if let Some(prev) = previous.iter().find(|p| p.metric == measure.metric) {
+            let mut points = prev.history.clone();
+            points.append(&mut measure.history);
+            measure.history = points;
         }
-        Err(e) => {
-            eprintln!("Failed to get measures: {e}");
-            1
+    }
+    Ok(latest)
+}
+
+/// Show each metric's latest value with an arrow and delta against the previous analysis
+async fn run_trend(
+    client: &SonarQubeClient,
+    project: &str,
+    metric_keys: &[&str],
+    json: bool,
+) -> Result<(), CliError> {
+    let history = fetch_last_two_points(client, project, &metric_keys.join(",")).await;
+    if helpers::empty_or_missing(&history, |h| no_history(h))
+        && helpers::never_analyzed(client, project).await
+    {
+        return not_analyzed(project, json, &[]);
+    }
+    let mut history = history.map_err(|e| CliError::api("Failed to get measures history", e))?;
+    // Keep the requested metric order rather than the server's
+    history.sort_by_key(|m| metric_keys.iter().position(|k| *k == m.metric));
+
+    output::print_measure_trends(&measure_trends(&history), project, json);
+    Ok(())
+}
+
+/// Fetch the new-code variants of the metrics, plus the new line count for context
+async fn run_new_code(
+    client: &SonarQubeClient,
+    project: &str,
+    metric_keys: &[&str],
+    human: bool,
+    json: bool,
+) -> Result<(), CliError> {
+    let mut new_keys: Vec<String> = Vec::new();
+    for key in metric_keys.iter().map(|k| new_code_metric(k)) {
+        if !new_keys.contains(&key) {
+            new_keys.push(key);
         }
     }
+    if !new_keys.iter().any(|k| k == NEW_LINES_METRIC) {
+        new_keys.push(NEW_LINES_METRIC.to_string());
+    }
+    let new_keys: Vec<&str> = new_keys.iter().map(String::as_str).collect();
+
+    let response = client.get_measures(project, &new_keys).await;
+    if no_measures(client, project, &response).await {
+        return not_analyzed(project, json, &[]);
+    }
+    let response = response.map_err(|e| CliError::api("Failed to get measures", e))?;
+    output::print_new_code_measures(&response, human, json);
+    Ok(())
+}
+
+/// Fetch measures for the current and comparison branches and print them side by side
+async fn run_compare(
+    client: &SonarQubeClient,
+    project: &str,
+    metric_keys: &[&str],
+    branch: Option<&str>,
+    base: &str,
+    json: bool,
+) -> Result<(), CliError> {
+    let current = client.get_measures(project, metric_keys).await;
+    if no_measures(client, project, &current).await {
+        return not_analyzed(project, json, &[]);
+    }
+    let current = current.map_err(|e| CliError::api("Failed to get measures", e))?;
+    let base_measures = client
+        .get_measures_on_branch(project, metric_keys, Some(base))
+        .await
+        .map_err(|e| CliError::api(format!("Failed to get measures for branch '{base}'"), e))?;
+
+    let rows = compare_measures(&current, &base_measures);
+    output::print_measures_comparison(&rows, project, branch.unwrap_or("(main)"), base, json);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wiremock::matchers::{method, path};
+    use wiremock::matchers::{method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     async fn try_mock_server() -> Option<MockServer> {
@@ -71,6 +543,41 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_new_code_metric_mapping() {
+        assert_eq!(new_code_metric("coverage"), "new_coverage");
+        assert_eq!(new_code_metric("ncloc"), "new_lines");
+        assert_eq!(new_code_metric("sqale_index"), "new_technical_debt");
+        assert_eq!(new_code_metric("new_bugs"), "new_bugs");
+    }
+
+    #[tokio::test]
+    async fn test_run_measures_new_code_requests_new_metrics() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/measures/component"))
+            .and(query_param("metricKeys", "new_coverage,new_lines"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "component": {
+                    "key": "my-proj",
+                    "measures": [
+                        {"metric": "new_coverage", "period": {"value": "64.0"}},
+                        {"metric": "new_lines", "period": {"value": "1200"}}
+                    ]
+                }
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", Some("coverage"), MeasuresView::NewCode, false, &[], OutputFormat::Text).await;
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     async fn test_run_measures_default_metrics() {
         let mock_server = match try_mock_server().await {
@@ -84,8 +591,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", None, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", None, MeasuresView::Current, false, &[], OutputFormat::Text).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -101,8 +608,91 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", Some("bugs,coverage"), true).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", Some("bugs,coverage"), MeasuresView::Current, false, &[], OutputFormat::Json).await;
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_parse_assertion() {
+        let assertion = parse_assertion("coverage>=80").unwrap();
+        assert_eq!(assertion.metric, "coverage");
+        assert_eq!(assertion.op, AssertOp::Ge);
+        assert_eq!(assertion.value, 80.0);
+        assert_eq!(assertion.to_string(), "coverage>=80");
+        assert_eq!(parse_assertion("bugs==0").unwrap().op, AssertOp::Eq);
+        assert_eq!(parse_assertion("bugs > 0").unwrap().op, AssertOp::Gt);
+        assert_eq!(parse_assertion("code_smells!=3").unwrap().op, AssertOp::Ne);
+        assert!(parse_assertion("coverage").unwrap_err().contains("no operator"));
+        assert!(parse_assertion(">=80").unwrap_err().contains("missing metric"));
+        assert!(parse_assertion("coverage=80").unwrap_err().contains("unknown operator"));
+        assert!(parse_assertion("coverage>=high").unwrap_err().contains("not a number"));
+    }
+
+    #[test]
+    fn test_assertion_holds_against_measure_value() {
+        let min_coverage = parse_assertion("coverage>=80").unwrap();
+        assert!(!min_coverage.holds(Some("75.0")));
+        assert!(min_coverage.holds(Some("80.0")));
+        assert!(!min_coverage.holds(None));
+        assert!(parse_assertion("coverage<80").unwrap().holds(Some("75.0")));
+        assert!(parse_assertion("coverage!=80").unwrap().holds(Some("75.0")));
+        assert!(!parse_assertion("coverage==80").unwrap().holds(Some("75.0")));
+    }
+
+    #[tokio::test]
+    async fn test_run_measures_failed_assertion_exits_threshold() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/measures/component"))
+            .and(query_param("metricKeys", "bugs,coverage"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(measures_body()))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let assertions = [parse_assertion("bugs==0").unwrap(), parse_assertion("coverage>=80").unwrap()];
+        let result = run(config, "my-proj", Some("bugs"), MeasuresView::Current, false, &assertions, OutputFormat::Text).await;
+        assert!(matches!(result, Err(CliError::Exit(exit_code::THRESHOLD))), "{result:?}");
+    }
+
+    #[test]
+    fn test_parse_metric_keys_dedupes_in_order() {
+        assert_eq!(
+            parse_metric_keys("coverage,,coverage ,bugs"),
+            vec!["coverage", "bugs"]
+        );
+        assert_eq!(parse_metric_keys("bugs, coverage,"), vec!["bugs", "coverage"]);
+        assert!(parse_metric_keys(" , ").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_measures_sends_deduplicated_metrics() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/measures/component"))
+            .and(query_param("metricKeys", "coverage,bugs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(measures_body()))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", Some("coverage,,coverage ,bugs"), MeasuresView::Current, false, &[], OutputFormat::Text).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_measures_empty_metrics() {
+        // Rejected locally: no server is contacted
+        let config = SonarQubeConfig::new("http://localhost:1");
+        let result = run(config, "my-proj", Some(" , "), MeasuresView::Current, false, &[], OutputFormat::Text).await;
+        assert!(result.is_err());
     }
 
     #[tokio::test]
@@ -118,7 +708,316 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", None, false).await;
-        assert_eq!(exit, 1);
+        let result = run(config, "my-proj", None, MeasuresView::Current, false, &[], OutputFormat::Text).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_measures_compare_branch() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/measures/component"))
+            .and(query_param("branch", "feature"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "component": {"key": "my-proj", "measures": [
+                    {"metric": "bugs", "value": "2"},
+                    {"metric": "coverage", "value": "81.0"}
+                ]}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/measures/component"))
+            .and(query_param("branch", "main"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(measures_body()))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri()).with_branch("feature");
+        let result = run(config, "my-proj", Some("bugs,coverage"), MeasuresView::CompareBranch("main"), false, &[], OutputFormat::Text).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_measures_compare_branch_error() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/measures/component"))
+            .and(query_param("branch", "missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/measures/component"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(measures_body()))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", None, MeasuresView::CompareBranch("missing"), false, &[], OutputFormat::Json).await;
+        assert!(result.is_err());
+    }
+
+    fn history_page(page: usize, value: &str) -> serde_json::Value {
+        serde_json::json!({
+            "paging": {"pageIndex": page, "pageSize": 1, "total": 3},
+            "measures": [{
+                "metric": "coverage",
+                "history": [{"date": format!("2026-0{page}-01T00:00:00+0000"), "value": value}]
+            }]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_run_measures_trend_fetches_last_two_points() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        for (page, value) in [("1", "70.0"), ("2", "80.0"), ("3", "82.5")] {
+            Mock::given(method("GET"))
+                .and(path("/api/measures/search_history"))
+                .and(query_param("ps", "1"))
+                .and(query_param("p", page))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(history_page(page.parse().unwrap(), value)),
+                )
+                // Once from the direct fetch, once from `run`
+                .expect(2)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let history = fetch_last_two_points(&client, "my-proj", "coverage").await.unwrap();
+        let values: Vec<_> = history[0].history.iter().map(|h| h.value.as_deref()).collect();
+        assert_eq!(values, vec![Some("80.0"), Some("82.5")]);
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", Some("coverage"), MeasuresView::Trend, false, &[], OutputFormat::Json).await;
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_read_project_keys() {
+        let keys = read_project_keys("-", std::io::Cursor::new("api\n\n web \napi\n")).unwrap();
+        assert_eq!(keys, vec!["api", "web"]);
+        let keys = read_project_keys("api,web", std::io::empty()).unwrap();
+        assert_eq!(keys, vec!["api", "web"]);
+        let err = read_project_keys("-", std::io::Cursor::new("\n  \n")).unwrap_err();
+        assert!(err.contains("No project keys on stdin"));
+    }
+
+    #[tokio::test]
+    async fn test_measures_matrix_from_stdin_keys() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        for (project, coverage) in [("api", "82.5"), ("web", "64.0")] {
+            Mock::given(method("GET"))
+                .and(path("/api/measures/component"))
+                .and(query_param("component", project))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "component": {
+                        "key": project,
+                        "measures": [{"metric": "coverage", "value": coverage}]
+                    }
+                })))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let keys = read_project_keys("-", std::io::Cursor::new("api\nweb\n")).unwrap();
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let (matrix, failures) = fetch_matrix(&client, &keys, &["coverage"]).await;
+        assert!(failures.is_empty());
+
+        let lines = output::measures_matrix_lines(&matrix);
+        assert!(lines[0].contains("api") && lines[0].contains("web"));
+        assert!(lines[2].contains("82.5") && lines[2].contains("64.0"));
+        assert!(lines[2].find("82.5") < lines[2].find("64.0"));
+    }
+
+    #[tokio::test]
+    async fn test_measures_matrix_bounded_keeps_order_and_reports_failures() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        for (project, coverage) in [("api", "82.5"), ("web", "64.0")] {
+            Mock::given(method("GET"))
+                .and(path("/api/measures/component"))
+                .and(query_param("component", project))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(serde_json::json!({
+                            "component": {
+                                "key": project,
+                                "measures": [{"metric": "coverage", "value": coverage}]
+                            }
+                        }))
+                        .set_delay(std::time::Duration::from_millis(50)),
+                )
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/api/measures/component"))
+            .and(query_param("component", "gone"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let keys: Vec<String> = ["web", "gone", "api"].map(String::from).to_vec();
+        let config = SonarQubeConfig::new(mock_server.uri()).with_concurrency(1);
+        let client = SonarQubeClient::new(config).unwrap();
+        let (matrix, failures) = fetch_matrix(&client, &keys, &["coverage"]).await;
+
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, "gone");
+        let lines = output::measures_matrix_lines(&matrix);
+        assert!(lines[0].find("web") < lines[0].find("gone"));
+        assert!(lines[0].find("gone") < lines[0].find("api"));
+        assert!(lines[2].find("64.0") < lines[2].find("82.5"));
+    }
+
+    #[tokio::test]
+    async fn test_run_measures_unanalyzed_project() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/components/show"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "component": {"key": "my-proj", "qualifier": "TRK"}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/measures/component"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "component": {"key": "my-proj", "measures": []}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", None, MeasuresView::Current, false, &[], OutputFormat::Json).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_measures_analyzed_project_skips_analysis_probe() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/components/show"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/measures/component"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(measures_body()))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", None, MeasuresView::Current, false, &[], OutputFormat::Json).await;
+        assert!(result.is_ok());
+    }
+
+    /// Answers a measures request with one measure per requested metric key
+    struct EchoMeasures;
+
+    impl wiremock::Respond for EchoMeasures {
+        fn respond(&self, request: &wiremock::Request) -> ResponseTemplate {
+            let keys = request
+                .url
+                .query_pairs()
+                .find(|(k, _)| k == "metricKeys")
+                .map(|(_, v)| v.into_owned())
+                .unwrap_or_default();
+            let measures: Vec<serde_json::Value> = keys
+                .split(',')
+                .map(|k| serde_json::json!({"metric": k, "value": "1"}))
+                .collect();
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "component": {"key": "my-proj", "measures": measures}
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_all_metrics_split_into_chunks_and_merged() {
+        use crate::client::MAX_METRIC_KEYS_PER_REQUEST;
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        let count = MAX_METRIC_KEYS_PER_REQUEST + 7;
+        let mut metrics: Vec<serde_json::Value> = (0..count)
+            .map(|i| serde_json::json!({"key": format!("metric_{i:03}"), "type": "INT"}))
+            .collect();
+        // A key listed twice is requested once
+        metrics.push(serde_json::json!({"key": "metric_000", "type": "INT"}));
+        Mock::given(method("GET"))
+            .and(path("/api/metrics/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "metrics": metrics,
+                "total": count + 1
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/measures/component"))
+            .respond_with(EchoMeasures)
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let keys: Vec<String> = client
+            .get_all_metrics()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|m| m.key)
+            .collect();
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        let response = client.get_measures_chunked("my-proj", &keys).await.unwrap();
+
+        let merged: Vec<&str> = response.component.measures.iter().map(|m| m.metric.as_str()).collect();
+        let expected: Vec<String> = (0..count).map(|i| format!("metric_{i:03}")).collect();
+        assert_eq!(merged, expected.iter().map(String::as_str).collect::<Vec<_>>());
+
+        let requests = mock_server.received_requests().await.unwrap();
+        let chunk_sizes: Vec<usize> = requests
+            .iter()
+            .filter(|r| r.url.path() == "/api/measures/component")
+            .map(|r| {
+                r.url
+                    .query_pairs()
+                    .find(|(k, _)| k == "metricKeys")
+                    .map_or(0, |(_, v)| v.split(',').count())
+            })
+            .collect();
+        assert_eq!(chunk_sizes, vec![MAX_METRIC_KEYS_PER_REQUEST, 7]);
+        mock_server.verify().await;
     }
 }
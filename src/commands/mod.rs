@@ -1,14 +1,27 @@
+pub mod activity;
 pub mod auth;
+pub mod branches;
+pub mod cache;
+pub mod completions;
 pub mod coverage;
+pub mod diff;
 pub mod duplications;
 pub mod health;
 pub mod history;
 pub mod hotspots;
+pub mod issue;
 pub mod issues;
 pub mod measures;
+pub mod new_code;
+pub mod profiles;
 pub mod projects;
 pub mod quality_gate;
+pub mod report;
+pub mod rule;
 pub mod rules;
 pub mod scan;
 pub mod source;
+pub mod tree;
+pub mod version;
 pub mod wait;
+pub mod whoami;
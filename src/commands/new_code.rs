@@ -0,0 +1,69 @@
+use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::error::CliError;
+use crate::output;
+
+pub async fn run(config: SonarQubeConfig, project: &str, json: bool) -> Result<(), CliError> {
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+    let period = client
+        .get_new_code_period(project)
+        .await
+        .map_err(|e| CliError::api("Failed to get new code definition", e))?;
+    output::print_new_code_period(&period, project, json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn try_mock_server() -> Option<MockServer> {
+        let listener = match std::net::TcpListener::bind("127.0.0.1:0") {
+            Ok(l) => l,
+            Err(_) => return None,
+        };
+        Some(MockServer::builder().listener(listener).start().await)
+    }
+
+    #[tokio::test]
+    async fn test_run_new_code_reference_branch() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/new_code_periods/show"))
+            .and(query_param("project", "my-proj"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "projectKey": "my-proj",
+                "branchKey": "feature/x",
+                "type": "REFERENCE_BRANCH",
+                "value": "main",
+                "inherited": false
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(run(config.clone(), "my-proj", false).await.is_ok());
+        assert!(run(config, "my-proj", true).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_new_code_api_error() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/new_code_periods/show"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(run(config, "my-proj", false).await.is_err());
+    }
+}
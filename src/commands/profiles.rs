@@ -0,0 +1,113 @@
+use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::error::CliError;
+use crate::output;
+
+/// List quality profiles, optionally for one language or the ones `project` uses
+pub async fn run(
+    config: SonarQubeConfig,
+    language: Option<&str>,
+    project: Option<&str>,
+    json: bool,
+) -> Result<(), CliError> {
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let mut profiles = client
+        .search_profiles(language, project)
+        .await
+        .map_err(|e| CliError::api("Failed to get quality profiles", e))?;
+    profiles.sort_by(|a, b| a.language.cmp(&b.language).then_with(|| a.name.cmp(&b.name)));
+    output::print_profiles(&profiles, json);
+    Ok(())
+}
+
+/// Show the rules activated differently by two quality profiles
+pub async fn run_compare(config: SonarQubeConfig, left: &str, right: &str, json: bool) -> Result<(), CliError> {
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let comparison = client
+        .compare_profiles(left, right)
+        .await
+        .map_err(|e| CliError::api("Failed to compare quality profiles", e))?;
+    output::print_profile_comparison(&comparison, json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn try_mock_server() -> Option<MockServer> {
+        let listener = match std::net::TcpListener::bind("127.0.0.1:0") {
+            Ok(l) => l,
+            Err(_) => return None,
+        };
+        Some(MockServer::builder().listener(listener).start().await)
+    }
+
+    #[tokio::test]
+    async fn test_run_profiles_filters() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/qualityprofiles/search"))
+            .and(query_param("language", "java"))
+            .and(query_param("project", "my-proj"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "profiles": [{
+                    "key": "AU-Tpxb", "name": "Sonar way", "language": "java",
+                    "languageName": "Java", "activeRuleCount": 512, "isDefault": true
+                }]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(run(config, Some("java"), Some("my-proj"), false).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_compare() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/qualityprofiles/compare"))
+            .and(query_param("leftKey", "A"))
+            .and(query_param("rightKey", "B"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "left": {"key": "A", "name": "Sonar way"},
+                "right": {"key": "B", "name": "Strict"},
+                "inLeft": [],
+                "inRight": [{"key": "java:S100", "name": "Method names", "severity": "MINOR"}],
+                "modified": []
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(run_compare(config, "A", "B", true).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_compare_unknown_profile() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/qualityprofiles/compare"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(run_compare(config, "A", "missing", false).await.is_err());
+    }
+}
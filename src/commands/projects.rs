@@ -1,28 +1,66 @@
+use std::io::Write;
+
 use crate::client::{SonarQubeClient, SonarQubeConfig};
-use crate::output;
+use crate::error::CliError;
+use crate::output::{self, OutputFormat};
+use crate::paging::PageRequest;
+
+/// Stream projects as NDJSON, writing each page as soon as it arrives.
+///
+/// Returns the number of projects written.
+async fn stream_projects<W: Write>(
+    client: &SonarQubeClient,
+    search: Option<&str>,
+    qualifier: Option<&str>,
+    out: &mut W,
+) -> Result<usize, CliError> {
+    let mut write_err = None;
+    let count = client
+        .for_each_projects_page(search, qualifier, |page| {
+            if write_err.is_none() {
+                write_err = output::write_ndjson(out, &page).err();
+            }
+        })
+        .await
+        .map_err(|e| CliError::api("Failed to fetch projects", e))?;
+    match write_err {
+        Some(e) => Err(CliError::output(e)),
+        None => Ok(count),
+    }
+}
 
+/// List projects: every page, or only `page` when given
 pub async fn run(
     config: SonarQubeConfig,
     search: Option<&str>,
     qualifier: Option<&str>,
-    json: bool,
-) -> i32 {
-    let client = match SonarQubeClient::new(config) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to create client: {e}");
-            return 1;
-        }
-    };
+    page: Option<PageRequest>,
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
 
-    match client.get_all_projects(search, qualifier).await {
-        Ok(projects) => {
-            output::print_projects(&projects, json);
-            0
-        }
-        Err(e) => {
-            eprintln!("Failed to fetch projects: {e}");
-            1
+    if format == OutputFormat::Ndjson && page.is_none() {
+        let mut out = output::sink();
+        return stream_projects(&client, search, qualifier, &mut out).await.map(|_| ());
+    }
+
+    let projects = match page {
+        Some(page) => client
+            .search_projects(search, qualifier, page.page, page.size)
+            .await
+            .map(|response| response.components),
+        None => client.get_all_projects(search, qualifier).await,
+    }
+    .map_err(|e| CliError::api("Failed to fetch projects", e))?;
+    if format == OutputFormat::Ndjson {
+        return output::write_ndjson(&mut output::sink(), &projects).map_err(CliError::output);
+    }
+    match format.delimiter() {
+        Some(delimiter) => output::write_projects_delimited(&mut output::sink(), &projects, delimiter)
+            .map_err(CliError::output),
+        None => {
+            output::print_projects(&projects, format == OutputFormat::Json);
+            Ok(())
         }
     }
 }
@@ -30,7 +68,7 @@ pub async fn run(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wiremock::matchers::{method, path};
+    use wiremock::matchers::{method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     async fn try_mock_server() -> Option<MockServer> {
@@ -74,8 +112,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, None, None, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, None, None, None, OutputFormat::Text).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -91,8 +129,33 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, Some("sonar"), Some("TRK"), true).await;
-        assert_eq!(exit, 0);
+        let result = run(config, Some("sonar"), Some("TRK"), None, OutputFormat::Json).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_projects_single_page() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        // A large total would make the fetch-all loop request more pages
+        Mock::given(method("GET"))
+            .and(path("/api/components/search"))
+            .and(query_param("p", "2"))
+            .and(query_param("ps", "50"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "paging": {"total": 1000},
+                "components": [{"key": "sonar-cli", "name": "sonar-cli", "qualifier": "TRK"}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let page = PageRequest::from_flags(Some(2), Some(50));
+        let result = run(config, None, None, page, OutputFormat::Ndjson).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -108,7 +171,29 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, None, None, false).await;
-        assert_eq!(exit, 1);
+        let result = run(config, None, None, None, OutputFormat::Text).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stream_projects_ndjson() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/components/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(projects_body()))
+            .mount(&mock_server)
+            .await;
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let mut buf = Vec::new();
+        let count = stream_projects(&client, None, None, &mut buf).await.unwrap();
+        assert_eq!(count, 1);
+
+        let text = String::from_utf8(buf).unwrap();
+        let v: serde_json::Value = serde_json::from_str(text.trim_end()).unwrap();
+        assert_eq!(v["key"], "sonar-cli");
     }
 }
@@ -1,30 +1,63 @@
 use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::error::{exit_code, CliError};
+use crate::helpers;
 use crate::output;
+use crate::types::QualityGateCondition;
 
-pub async fn run(config: SonarQubeConfig, project: &str, fail_on_error: bool, json: bool) -> i32 {
-    let client = match SonarQubeClient::new(config) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to create client: {e}");
-            return 1;
-        }
-    };
-
-    match client.get_quality_gate(project).await {
-        Ok(response) => {
-            output::print_quality_gate(&response, project, json);
-            let status = &response.project_status.status;
-            if fail_on_error && status != "OK" {
-                1
-            } else {
-                0
-            }
-        }
-        Err(e) => {
-            eprintln!("Failed to get quality gate: {e}");
-            1
-        }
+/// Which quality gate conditions to display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionFilter {
+    Passed,
+    Failed,
+}
+
+pub fn parse_condition_filter(s: &str) -> Result<ConditionFilter, String> {
+    match s.to_lowercase().as_str() {
+        "passed" => Ok(ConditionFilter::Passed),
+        "failed" => Ok(ConditionFilter::Failed),
+        other => Err(format!(
+            "Unknown condition filter '{other}'. Valid values: passed, failed"
+        )),
+    }
+}
+
+/// Keep only conditions matching the filter; ERROR and WARN both count as failed
+fn filter_conditions(conditions: &mut Vec<QualityGateCondition>, filter: ConditionFilter) {
+    conditions.retain(|c| (c.status == "OK") == (filter == ConditionFilter::Passed));
+}
+
+pub async fn run(
+    config: SonarQubeConfig,
+    project: &str,
+    fail_on_error: bool,
+    only: Option<&str>,
+    json: bool,
+) -> Result<(), CliError> {
+    let only = only.map(parse_condition_filter).transpose().map_err(CliError::Validation)?;
+
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let response = client.get_quality_gate(project).await;
+    // A never-analyzed project has no gate status ("NONE") to report
+    if helpers::empty_or_missing(&response, |r| r.project_status.status == "NONE")
+        && helpers::never_analyzed(&client, project).await
+    {
+        output::print_not_analyzed(project, json);
+        return if fail_on_error {
+            Err(CliError::Exit(exit_code::FAILURE))
+        } else {
+            Ok(())
+        };
     }
+    let mut response = response.map_err(|e| CliError::api("Failed to get quality gate", e))?;
+    if let Some(filter) = only {
+        filter_conditions(&mut response.project_status.conditions, filter);
+    }
+    output::print_quality_gate(&response, project, json);
+    if fail_on_error && response.project_status.status != "OK" {
+        return Err(CliError::Exit(exit_code::FAILURE));
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -75,6 +108,72 @@ mod tests {
         })
     }
 
+    fn condition(metric: &str, status: &str) -> QualityGateCondition {
+        QualityGateCondition {
+            status: status.to_string(),
+            metric_key: metric.to_string(),
+            comparator: Some("GT".to_string()),
+            error_threshold: Some("0".to_string()),
+            actual_value: Some("1".to_string()),
+        }
+    }
+
+    fn mixed_conditions() -> Vec<QualityGateCondition> {
+        vec![
+            condition("new_bugs", "ERROR"),
+            condition("coverage", "OK"),
+            condition("new_code_smells", "WARN"),
+        ]
+    }
+
+    #[test]
+    fn test_parse_condition_filter() {
+        assert_eq!(parse_condition_filter("failed").unwrap(), ConditionFilter::Failed);
+        assert_eq!(parse_condition_filter("PASSED").unwrap(), ConditionFilter::Passed);
+        assert!(parse_condition_filter("error").is_err());
+    }
+
+    #[test]
+    fn test_filter_conditions_failed() {
+        let mut conditions = mixed_conditions();
+        filter_conditions(&mut conditions, ConditionFilter::Failed);
+        let statuses: Vec<&str> = conditions.iter().map(|c| c.status.as_str()).collect();
+        assert_eq!(statuses, vec!["ERROR", "WARN"]);
+    }
+
+    #[test]
+    fn test_filter_conditions_passed() {
+        let mut conditions = mixed_conditions();
+        filter_conditions(&mut conditions, ConditionFilter::Passed);
+        assert_eq!(conditions.len(), 1);
+        assert_eq!(conditions[0].metric_key, "coverage");
+    }
+
+    #[tokio::test]
+    async fn test_run_quality_gate_only_failed_keeps_exit_code() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/qualitygates/project_status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(quality_gate_error_body()))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        // Filtering out every failing condition must not hide the gate failure
+        let result = run(config, "my-proj", true, Some("passed"), false).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_quality_gate_invalid_only() {
+        let config = SonarQubeConfig::new("http://localhost:1");
+        let result = run(config, "my-proj", false, Some("maybe"), false).await;
+        assert!(result.is_err());
+    }
+
     #[tokio::test]
     async fn test_run_quality_gate_ok() {
         let mock_server = match try_mock_server().await {
@@ -88,8 +187,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", false, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", false, None, false).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -105,8 +204,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", false, true).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", false, None, true).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -123,8 +222,8 @@ mod tests {
 
         let config = SonarQubeConfig::new(mock_server.uri());
         // fail_on_error=true should return exit code 1
-        let exit = run(config, "my-proj", true, false).await;
-        assert_eq!(exit, 1);
+        let result = run(config, "my-proj", true, None, false).await;
+        assert!(result.is_err());
     }
 
     #[tokio::test]
@@ -141,8 +240,8 @@ mod tests {
 
         let config = SonarQubeConfig::new(mock_server.uri());
         // fail_on_error=false should still return 0
-        let exit = run(config, "my-proj", false, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj", false, None, false).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -158,7 +257,72 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj", false, false).await;
-        assert_eq!(exit, 1);
+        let result = run(config, "my-proj", false, None, false).await;
+        assert!(result.is_err());
+    }
+
+    async fn mount_unanalyzed(mock_server: &MockServer) {
+        Mock::given(method("GET"))
+            .and(path("/api/qualitygates/project_status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "projectStatus": {"status": "NONE", "conditions": []}
+            })))
+            .mount(mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/components/show"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "component": {"key": "my-proj", "qualifier": "TRK"}
+            })))
+            .expect(1)
+            .mount(mock_server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_run_quality_gate_unanalyzed_fail_on_error_exits_1() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        mount_unanalyzed(&mock_server).await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let err = run(config, "my-proj", true, None, false).await.unwrap_err();
+        assert_eq!(err.exit_code(), exit_code::FAILURE);
+    }
+
+    #[tokio::test]
+    async fn test_run_quality_gate_unanalyzed_without_fail_on_error() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        mount_unanalyzed(&mock_server).await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(run(config, "my-proj", false, None, false).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_quality_gate_analyzed_skips_analysis_probe() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/qualitygates/project_status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(quality_gate_ok_body()))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/components/show"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(run(config, "my-proj", false, None, false).await.is_ok());
     }
 }
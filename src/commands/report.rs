@@ -0,0 +1,472 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::{IssueSearchParams, SonarQubeClient, SonarQubeConfig};
+use crate::commands::{coverage, issues, measures};
+use crate::error::CliError;
+use crate::helpers::{self, ReportSummary};
+use crate::ignore_file::PathFilter;
+use crate::output;
+use crate::paging::MAX_PAGES;
+use crate::types::SonarIssue;
+
+/// Index file written alongside the individual reports
+const SUMMARY_FILE: &str = "summary.json";
+
+/// Progress of an interrupted issues export, kept until the export completes
+const STATE_FILE: &str = "report-state.json";
+
+/// Directory holding one file per fetched issues page while the export runs
+const PAGES_DIR: &str = "issues-pages";
+
+/// Issues requested per page; fixed so a resumed export lines up with the saved pages
+const ISSUES_PAGE_SIZE: usize = 100;
+
+/// Checkpoint for the paginated issues export
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct ExportState {
+    project: String,
+    branch: Option<String>,
+    /// Pages fetched and saved so far (1-based pages 1..=pages_done)
+    pages_done: usize,
+}
+
+impl ExportState {
+    fn new(project: &str, branch: Option<&str>) -> Self {
+        Self {
+            project: project.to_string(),
+            branch: branch.map(str::to_string),
+            pages_done: 0,
+        }
+    }
+}
+
+fn page_file(page: usize) -> String {
+    format!("page-{page:04}.json")
+}
+
+/// Saved progress for `project`/`branch`, or a fresh state when there is none
+/// or it belongs to a different export
+fn load_state(dir: &Path, project: &str, branch: Option<&str>) -> ExportState {
+    let fresh = ExportState::new(project, branch);
+    std::fs::read_to_string(dir.join(STATE_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str::<ExportState>(&s).ok())
+        .filter(|s| s.project == fresh.project && s.branch == fresh.branch)
+        .unwrap_or(fresh)
+}
+
+/// Fetch all issues, saving each page and the progress as it goes.
+///
+/// With `resume`, pages saved by an earlier interrupted run are read back from
+/// disk and fetching continues after them. The checkpoint is removed once every
+/// page has been fetched.
+async fn fetch_issues_checkpointed(
+    client: &SonarQubeClient,
+    project: &str,
+    dir: &Path,
+    resume: bool,
+) -> Result<Vec<SonarIssue>, CliError> {
+    let branch = client.config().branch.as_deref();
+    let pages_dir = dir.join(PAGES_DIR);
+    let mut state = if resume {
+        load_state(dir, project, branch)
+    } else {
+        let _ = std::fs::remove_dir_all(&pages_dir);
+        ExportState::new(project, branch)
+    };
+    std::fs::create_dir_all(&pages_dir)
+        .map_err(|e| CliError::Failed(format!("Failed to create {}: {e}", pages_dir.display())))?;
+
+    let mut all_issues = Vec::new();
+    for page in 1..=state.pages_done {
+        let path = pages_dir.join(page_file(page));
+        let saved = std::fs::read_to_string(&path)
+            .map_err(|e| CliError::Failed(format!("Failed to read {}: {e}", path.display())))?;
+        let issues: Vec<SonarIssue> = serde_json::from_str(&saved)
+            .map_err(|e| CliError::Failed(format!("Failed to parse {}: {e}", path.display())))?;
+        all_issues.extend(issues);
+    }
+
+    let search_params = IssueSearchParams::default();
+    // A resumed export that already holds MAX_PAGES pages is complete: the
+    // next page would be past the server's result window
+    while state.pages_done < MAX_PAGES {
+        let page = state.pages_done + 1;
+        let response = client
+            .search_issues_with_params(project, page, ISSUES_PAGE_SIZE, &search_params)
+            .await
+            .map_err(|e| CliError::api("Failed to fetch issues", e))?;
+        let count = response.issues.len();
+        write_json_file(&pages_dir, &page_file(page), &response.issues).map_err(CliError::Failed)?;
+        state.pages_done = page;
+        write_json_file(dir, STATE_FILE, &state).map_err(CliError::Failed)?;
+        all_issues.extend(response.issues);
+
+        if all_issues.len() >= response.total || count < ISSUES_PAGE_SIZE {
+            break;
+        }
+    }
+
+    let _ = std::fs::remove_dir_all(&pages_dir);
+    let _ = std::fs::remove_file(dir.join(STATE_FILE));
+    Ok(all_issues)
+}
+
+/// Write `value` as pretty-printed JSON to `dir/name`
+fn write_json_file<T: serde::Serialize + ?Sized>(
+    dir: &Path,
+    name: &str,
+    value: &T,
+) -> Result<(), String> {
+    let json = output::format_json(value, false).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(name), json + "\n")
+        .map_err(|e| format!("Failed to write {}: {e}", dir.join(name).display()))
+}
+
+/// Write one report to `dir/<name>.json`, recording the outcome in `summary`.
+/// Returns the exit code a failure maps to.
+fn record<T: serde::Serialize>(
+    summary: &mut ReportSummary,
+    dir: &Path,
+    name: &str,
+    result: Result<T, CliError>,
+) -> Option<i32> {
+    let file = format!("{name}.json");
+    let written = result.and_then(|value| write_json_file(dir, &file, &value).map_err(CliError::Failed));
+    match written {
+        Ok(()) => {
+            summary.files.push(file);
+            None
+        }
+        Err(e) => {
+            summary.errors.insert(name.to_string(), e.to_string());
+            Some(e.exit_code())
+        }
+    }
+}
+
+pub async fn run(
+    config: SonarQubeConfig,
+    project: &str,
+    output_dir: &Path,
+    ignore: &PathFilter,
+    resume: bool,
+    json: bool,
+) -> Result<(), CliError> {
+    let branch = config.branch.clone();
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    // The gate comes first: a never-analyzed project has none, and nothing is worth writing
+    let gate = client.get_quality_gate(project).await;
+    if helpers::empty_or_missing(&gate, |g| g.project_status.status == "NONE")
+        && helpers::never_analyzed(&client, project).await
+    {
+        output::print_not_analyzed(project, json);
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| CliError::Failed(format!("Failed to create {}: {e}", output_dir.display())))?;
+
+    let (issues, measures, files) = tokio::join!(
+        fetch_issues_checkpointed(&client, project, output_dir, resume),
+        client.get_measures(project, measures::DEFAULT_METRICS),
+        client.get_files_coverage(project),
+    );
+
+    let measures = measures.map_err(|e| CliError::api("Failed to fetch measures", e));
+    let gate = gate.map_err(|e| CliError::api("Failed to fetch quality gate", e));
+    let files = files.map_err(|e| CliError::api("Failed to fetch coverage", e));
+    let issues = issues.map(|mut all| {
+        all.retain(|i| !ignore.is_ignored(&helpers::extract_path(&i.component, project)));
+        issues::sort_issues(&mut all);
+        all
+    });
+    let coverage = files.map(|files| {
+        let mut coverage = coverage::file_coverage(files, project, None);
+        ignore.retain(&mut coverage, |f| f.file.as_str());
+        coverage
+    });
+
+    let mut summary = ReportSummary {
+        project: project.to_string(),
+        branch,
+        quality_gate: gate.as_ref().ok().map(|g| g.project_status.status.clone()),
+        ..Default::default()
+    };
+    // The first failure decides the exit code, so a bad token still exits 4
+    let failure = [
+        record(&mut summary, output_dir, "issues", issues),
+        record(&mut summary, output_dir, "measures", measures),
+        record(&mut summary, output_dir, "quality-gate", gate),
+        record(&mut summary, output_dir, "coverage", coverage),
+    ]
+    .into_iter()
+    .flatten()
+    .next();
+    summary.files.push(SUMMARY_FILE.to_string());
+
+    write_json_file(output_dir, SUMMARY_FILE, &summary).map_err(CliError::Failed)?;
+
+    output::print_report_summary(&summary, output_dir, json);
+    match failure {
+        None => Ok(()),
+        Some(code) => Err(CliError::Exit(code)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::exit_code;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn try_mock_server() -> Option<MockServer> {
+        let listener = match std::net::TcpListener::bind("127.0.0.1:0") {
+            Ok(l) => l,
+            Err(_) => return None,
+        };
+        Some(MockServer::builder().listener(listener).start().await)
+    }
+
+    async fn mount_report_mocks(server: &MockServer, gate_status: u16) {
+        Mock::given(method("GET"))
+            .and(path("/api/issues/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total": 1,
+                "p": 1,
+                "ps": 100,
+                "issues": [{
+                    "key": "i1",
+                    "rule": "rust:S1234",
+                    "severity": "MAJOR",
+                    "component": "my-proj:src/main.rs",
+                    "project": "my-proj",
+                    "line": 10,
+                    "message": "Fix this",
+                    "type": "CODE_SMELL",
+                    "status": "OPEN",
+                    "tags": []
+                }]
+            })))
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/measures/component"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "component": {
+                    "key": "my-proj",
+                    "measures": [{"metric": "coverage", "value": "80.0"}]
+                }
+            })))
+            .mount(server)
+            .await;
+        let gate = ResponseTemplate::new(gate_status).set_body_json(serde_json::json!({
+            "projectStatus": {"status": "OK", "conditions": []}
+        }));
+        Mock::given(method("GET"))
+            .and(path("/api/qualitygates/project_status"))
+            .respond_with(gate)
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/measures/component_tree"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "paging": {"total": 1},
+                "components": [{
+                    "key": "my-proj:src/main.rs",
+                    "path": "src/main.rs",
+                    "measures": [
+                        {"metric": "coverage", "value": "75.0"},
+                        {"metric": "uncovered_lines", "value": "5"},
+                        {"metric": "lines_to_cover", "value": "20"}
+                    ]
+                }]
+            })))
+            .mount(server)
+            .await;
+    }
+
+    fn read_json(dir: &Path, name: &str) -> serde_json::Value {
+        let contents = std::fs::read_to_string(dir.join(name)).unwrap();
+        serde_json::from_str(&contents).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_run_report_writes_all_files() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        mount_report_mocks(&mock_server, 200).await;
+
+        let dir = std::env::temp_dir().join("sonar-cli-test-report-ok");
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", &dir, &PathFilter::none(), false, false).await;
+        assert!(result.is_ok());
+
+        assert_eq!(read_json(&dir, "issues.json")[0]["key"], "i1");
+        assert_eq!(read_json(&dir, "measures.json")["component"]["key"], "my-proj");
+        assert_eq!(read_json(&dir, "quality-gate.json")["projectStatus"]["status"], "OK");
+        assert_eq!(read_json(&dir, "coverage.json")[0]["file"], "src/main.rs");
+
+        let summary = read_json(&dir, SUMMARY_FILE);
+        assert_eq!(summary["project"], "my-proj");
+        assert_eq!(summary["quality_gate"], "OK");
+        assert_eq!(
+            summary["files"],
+            serde_json::json!([
+                "issues.json",
+                "measures.json",
+                "quality-gate.json",
+                "coverage.json",
+                "summary.json"
+            ])
+        );
+        assert_eq!(summary["errors"], serde_json::json!({}));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn issue_page(range: std::ops::Range<usize>, total: usize) -> serde_json::Value {
+        let issues: Vec<serde_json::Value> = range
+            .map(|i| {
+                serde_json::json!({
+                    "key": format!("i{i}"),
+                    "rule": "rust:S1234",
+                    "severity": "MAJOR",
+                    "component": "my-proj:src/main.rs",
+                    "project": "my-proj",
+                    "line": i + 1,
+                    "message": "Fix this",
+                    "type": "CODE_SMELL",
+                    "status": "OPEN"
+                })
+            })
+            .collect();
+        serde_json::json!({"total": total, "issues": issues})
+    }
+
+    async fn mount_issue_page(server: &MockServer, page: &str, response: ResponseTemplate, calls: u64) {
+        use wiremock::matchers::query_param;
+        Mock::given(method("GET"))
+            .and(path("/api/issues/search"))
+            .and(query_param("p", page))
+            .respond_with(response)
+            .with_priority(1)
+            .expect(calls)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_run_report_resumes_issue_export_after_interruption() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        let dir = std::env::temp_dir().join("sonar-cli-test-report-resume");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // First run: page 1 arrives, then the server fails on page 2
+        mount_report_mocks(&mock_server, 200).await;
+        let page1 = ResponseTemplate::new(200).set_body_json(issue_page(0..100, 150));
+        mount_issue_page(&mock_server, "1", page1, 1).await;
+        mount_issue_page(&mock_server, "2", ResponseTemplate::new(500), 1).await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", &dir, &PathFilter::none(), false, true).await;
+        assert!(result.is_err());
+        assert!(!dir.join("issues.json").exists());
+        assert!(dir.join(PAGES_DIR).join(page_file(1)).exists());
+        assert_eq!(read_json(&dir, STATE_FILE)["pages_done"], 1);
+        mock_server.verify().await;
+
+        // Resumed run: page 1 comes from disk, only page 2 is requested
+        mock_server.reset().await;
+        mount_report_mocks(&mock_server, 200).await;
+        let page1 = ResponseTemplate::new(200).set_body_json(issue_page(0..100, 150));
+        mount_issue_page(&mock_server, "1", page1, 0).await;
+        let page2 = ResponseTemplate::new(200).set_body_json(issue_page(100..150, 150));
+        mount_issue_page(&mock_server, "2", page2, 1).await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", &dir, &PathFilter::none(), true, true).await;
+        assert!(result.is_ok());
+        mock_server.verify().await;
+
+        let issues = read_json(&dir, "issues.json");
+        let keys: std::collections::BTreeSet<&str> = issues
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|i| i["key"].as_str().unwrap())
+            .collect();
+        assert_eq!(keys.len(), 150);
+        // The checkpoint is cleared once the export completes
+        assert!(!dir.join(STATE_FILE).exists());
+        assert!(!dir.join(PAGES_DIR).exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_resume_at_page_cap_requests_no_more_pages() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        let dir = std::env::temp_dir().join("sonar-cli-test-report-page-cap");
+        let _ = std::fs::remove_dir_all(&dir);
+        let pages_dir = dir.join(PAGES_DIR);
+        std::fs::create_dir_all(&pages_dir).unwrap();
+        for page in 1..=MAX_PAGES {
+            std::fs::write(pages_dir.join(page_file(page)), "[]").unwrap();
+        }
+        let state = ExportState { pages_done: MAX_PAGES, ..ExportState::new("my-proj", None) };
+        write_json_file(&dir, STATE_FILE, &state).unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/api/issues/search"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let client = SonarQubeClient::new(config).unwrap();
+        let issues = fetch_issues_checkpointed(&client, "my-proj", &dir, true).await.unwrap();
+        assert!(issues.is_empty());
+        assert!(!dir.join(STATE_FILE).exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_run_report_records_failures() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        mount_report_mocks(&mock_server, 404).await;
+
+        let dir = std::env::temp_dir().join("sonar-cli-test-report-partial");
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj", &dir, &PathFilter::none(), false, true).await;
+        assert!(matches!(result, Err(CliError::Exit(exit_code::NOT_FOUND))), "{result:?}");
+
+        // The other reports are still written
+        assert!(dir.join("issues.json").exists());
+        assert!(!dir.join("quality-gate.json").exists());
+        let summary = read_json(&dir, SUMMARY_FILE);
+        assert!(summary.get("quality_gate").is_none());
+        assert!(summary["errors"]["quality-gate"].is_string());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::error::CliError;
+use crate::output;
+
+pub async fn run(config: SonarQubeConfig, key: &str, json: bool) -> Result<(), CliError> {
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let rule = client
+        .get_rule(key)
+        .await
+        .map_err(|e| CliError::api(format!("Failed to fetch rule {key}"), e))?;
+    output::print_rule_details(&rule, json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::exit_code;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn try_mock_server() -> Option<MockServer> {
+        let listener = match std::net::TcpListener::bind("127.0.0.1:0") {
+            Ok(l) => l,
+            Err(_) => return None,
+        };
+        Some(MockServer::builder().listener(listener).start().await)
+    }
+
+    #[tokio::test]
+    async fn test_run_rule() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/rules/show"))
+            .and(query_param("key", "rust:S3776"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "rule": {
+                    "key": "rust:S3776",
+                    "name": "Cognitive Complexity of functions should not be too high",
+                    "severity": "CRITICAL",
+                    "type": "CODE_SMELL",
+                    "htmlDesc": "<p>Cognitive Complexity is a measure of how hard code is to understand.</p>",
+                    "remFnBaseEffort": "5min"
+                },
+                "actives": []
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(run(config.clone(), "rust:S3776", false).await.is_ok());
+        assert!(run(config, "rust:S3776", true).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_rule_not_found() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/rules/show"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+                "errors": [{"msg": "Rule not found: rust:S0"}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let err = run(config, "rust:S0", false).await.unwrap_err();
+        assert_eq!(err.exit_code(), exit_code::NOT_FOUND);
+    }
+}
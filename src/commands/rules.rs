@@ -1,47 +1,129 @@
-use crate::client::{RuleSearchParams, SonarQubeClient, SonarQubeConfig};
-use crate::output;
+use std::io::Write;
 
+use crate::client::{RuleSearchParams, SonarQubeClient, SonarQubeConfig, SonarQubeError};
+use crate::error::CliError;
+use crate::helpers::Window;
+use crate::output::{self, OutputFormat};
+use crate::paging::PageRequest;
+use crate::types::RuleInfo;
+
+/// Print already-fetched rules as text, JSON or NDJSON
+fn print_rules(rules: Vec<RuleInfo>, format: OutputFormat) -> Result<(), CliError> {
+    if format == OutputFormat::Ndjson {
+        return output::write_ndjson(&mut output::sink(), &rules).map_err(CliError::output);
+    }
+    output::print_rules(&rules, format == OutputFormat::Json);
+    Ok(())
+}
+
+/// Stream rules as NDJSON, writing the part of each page inside `window` as
+/// soon as it arrives.
+///
+/// Returns the number of rules written.
+async fn stream_rules<W: Write>(
+    client: &SonarQubeClient,
+    params: &RuleSearchParams<'_>,
+    window: Window,
+    out: &mut W,
+) -> Result<usize, CliError> {
+    let mut write_err = None;
+    let mut seen = 0;
+    let mut written = 0;
+    client
+        .for_each_rules_page(params, |page| {
+            let range = window.page_range(seen, page.len());
+            seen += page.len();
+            if write_err.is_none() && !range.is_empty() {
+                written += range.len();
+                write_err = output::write_ndjson(out, &page[range]).err();
+            }
+        })
+        .await
+        .map_err(|e| CliError::api("Failed to fetch rules", e))?;
+    match write_err {
+        Some(e) => Err(CliError::output(e)),
+        None => Ok(written),
+    }
+}
+
+/// Rules active in any of the project's quality profiles, without duplicates
+async fn project_rules(
+    client: &SonarQubeClient,
+    project: &str,
+    params: &RuleSearchParams<'_>,
+) -> Result<Vec<RuleInfo>, SonarQubeError> {
+    let mut rules: Vec<RuleInfo> = Vec::new();
+    for profile in client.get_project_profiles(project).await? {
+        let params = RuleSearchParams {
+            profile: Some(&profile.key),
+            ..*params
+        };
+        for rule in client.get_all_rules(&params).await? {
+            if !rules.iter().any(|r| r.key == rule.key) {
+                rules.push(rule);
+            }
+        }
+    }
+    Ok(rules)
+}
+
+/// List rules; with `project`, only those active in the project's quality profiles.
+/// `page` fetches only that page instead of every page.
 pub async fn run(
     config: SonarQubeConfig,
-    search: Option<&str>,
-    language: Option<&str>,
-    severity: Option<&str>,
-    rule_type: Option<&str>,
-    status: Option<&str>,
-    json: bool,
-) -> i32 {
-    let client = match SonarQubeClient::new(config) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to create client: {e}");
-            return 1;
-        }
-    };
-
-    let params = RuleSearchParams {
-        search,
-        language,
-        severity,
-        rule_type,
-        status,
-    };
-
-    match client.get_all_rules(&params).await {
-        Ok(rules) => {
-            output::print_rules(&rules, json);
-            0
-        }
-        Err(e) => {
-            eprintln!("Failed to fetch rules: {e}");
-            1
+    params: &RuleSearchParams<'_>,
+    project: Option<&str>,
+    page: Option<PageRequest>,
+    window: Window,
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    if format.delimiter().is_some() {
+        return Err(CliError::Validation(output::unsupported_format(
+            format,
+            "rules",
+            "text, json or ndjson",
+        )));
+    }
+
+    if let Some(page) = page {
+        if project.is_some() {
+            return Err(CliError::Validation(
+                "--page and --page-size cannot be used with --deprecated --project".to_string(),
+            ));
         }
+        let rules = client
+            .search_rules(params, page.page, page.size)
+            .await
+            .map_err(|e| CliError::api("Failed to fetch rules", e))?
+            .rules;
+        return print_rules(window.apply(rules), format);
+    }
+
+    if let Some(project) = project {
+        let rules = project_rules(&client, project, params)
+            .await
+            .map_err(|e| CliError::api(format!("Failed to fetch rules for project {project}"), e))?;
+        return print_rules(window.apply(rules), format);
     }
+
+    if format == OutputFormat::Ndjson {
+        let mut out = output::sink();
+        return stream_rules(&client, params, window, &mut out).await.map(|_| ());
+    }
+
+    let rules = client
+        .get_all_rules(params)
+        .await
+        .map_err(|e| CliError::api("Failed to fetch rules", e))?;
+    print_rules(window.apply(rules), format)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wiremock::matchers::{method, path};
+    use wiremock::matchers::{method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     async fn try_mock_server() -> Option<MockServer> {
@@ -82,8 +164,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, None, None, None, None, None, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, &RuleSearchParams::default(), None, None, Window::default(), OutputFormat::Text).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -99,17 +181,17 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(
-            config,
-            Some("cognitive"),
-            Some("rust"),
-            Some("CRITICAL"),
-            Some("CODE_SMELL"),
-            Some("READY"),
-            true,
-        )
-        .await;
-        assert_eq!(exit, 0);
+        let params = RuleSearchParams {
+            search: Some("cognitive"),
+            language: Some("rust"),
+            severity: Some("CRITICAL"),
+            rule_type: Some("CODE_SMELL"),
+            status: Some("READY"),
+            rule_keys: None,
+            profile: None,
+        };
+        let result = run(config, &params, None, None, Window::default(), OutputFormat::Json).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -125,7 +207,188 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, None, None, None, None, None, false).await;
-        assert_eq!(exit, 1);
+        let result = run(config, &RuleSearchParams::default(), None, None, Window::default(), OutputFormat::Text).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_project_rules_deprecated_with_replacement() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/qualityprofiles/search"))
+            .and(query_param("project", "my-proj"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "profiles": [
+                    {"key": "qp-java", "name": "Sonar way", "language": "java"},
+                    {"key": "qp-js", "name": "Sonar way", "language": "js"}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+        for profile in ["qp-java", "qp-js"] {
+            Mock::given(method("GET"))
+                .and(path("/api/rules/search"))
+                .and(query_param("statuses", "DEPRECATED"))
+                .and(query_param("activation", "true"))
+                .and(query_param("qprofile", profile))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "total": 1,
+                    "rules": [{
+                        "key": "java:S1135",
+                        "name": "Track uses of TODO tags",
+                        "status": "DEPRECATED",
+                        "replacedBy": "java:S7000",
+                        "deprecatedKeys": {"deprecatedKey": ["squid:S1135"]}
+                    }]
+                })))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let params = RuleSearchParams {
+            status: Some("DEPRECATED"),
+            ..Default::default()
+        };
+        let rules = project_rules(&client, "my-proj", &params).await.unwrap();
+        // The same rule active in two profiles is listed once
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].replaced_by.as_deref(), Some("java:S7000"));
+        assert_eq!(rules[0].deprecated_keys.as_ref().unwrap().keys, ["squid:S1135"]);
+    }
+
+    #[tokio::test]
+    async fn test_run_rules_for_project_profiles_error() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/qualityprofiles/search"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, &RuleSearchParams::default(), Some("missing"), None, Window::default(), OutputFormat::Text).await;
+        assert!(result.is_err());
+    }
+
+    fn rule_json(key: &str) -> serde_json::Value {
+        serde_json::json!({"key": key, "name": "Rule", "severity": "MAJOR"})
+    }
+
+    #[tokio::test]
+    async fn test_stream_rules_ndjson_covers_all_pages() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        use wiremock::matchers::query_param;
+        let page1: Vec<serde_json::Value> =
+            (0..100).map(|i| rule_json(&format!("rust:S{i}"))).collect();
+        Mock::given(method("GET"))
+            .and(path("/api/rules/search"))
+            .and(query_param("p", "1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"total": 101, "rules": page1})),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/rules/search"))
+            .and(query_param("p", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                serde_json::json!({"total": 101, "rules": [rule_json("rust:S100")]}),
+            ))
+            .mount(&mock_server)
+            .await;
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let mut buf = Vec::new();
+        let count = stream_rules(
+            &client,
+            &RuleSearchParams::default(),
+            Window::default(),
+            &mut buf,
+        )
+        .await
+        .unwrap();
+        assert_eq!(count, 101);
+
+        let text = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 101);
+        for line in &lines {
+            let v: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert!(v.is_object());
+        }
+        assert!(lines[100].contains("rust:S100"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_rules_ndjson_window_spans_pages() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        use wiremock::matchers::query_param;
+        let page1: Vec<serde_json::Value> =
+            (0..100).map(|i| rule_json(&format!("rust:S{i}"))).collect();
+        Mock::given(method("GET"))
+            .and(path("/api/rules/search"))
+            .and(query_param("p", "1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"total": 102, "rules": page1})),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/rules/search"))
+            .and(query_param("p", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total": 102,
+                "rules": [rule_json("rust:S100"), rule_json("rust:S101")]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let mut buf = Vec::new();
+        let window = Window { skip: 98, top: Some(3) };
+        let count = stream_rules(&client, &RuleSearchParams::default(), window, &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(count, 3);
+
+        let text = String::from_utf8(buf).unwrap();
+        let keys: Vec<String> = text
+            .lines()
+            .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap()["key"].to_string())
+            .collect();
+        assert_eq!(keys, vec!["\"rust:S98\"", "\"rust:S99\"", "\"rust:S100\""]);
+    }
+
+    #[tokio::test]
+    async fn test_run_rules_ndjson() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/rules/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(rules_body()))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, &RuleSearchParams::default(), None, None, Window::default(), OutputFormat::Ndjson).await;
+        assert!(result.is_ok());
     }
 }
@@ -1,7 +1,11 @@
 use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
-use crate::client::SonarQubeConfig;
+use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::error::{exit_code, CliError};
+use crate::output;
+use crate::types::task_status;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScannerKind {
@@ -50,11 +54,31 @@ fn extract_task_id(line: &str) -> Option<String> {
     }
 }
 
+/// Map a scanner exit code to ours. With `sonar.qualitygate.wait` the scanner
+/// exits non-zero when the gate fails, so any failure counts as a gate failure
+/// ([`exit_code::GATE_FAILED`]).
+fn scanner_exit_code(code: i32, gate_wait: bool) -> i32 {
+    if gate_wait && code != 0 {
+        exit_code::GATE_FAILED
+    } else {
+        code
+    }
+}
+
 pub struct ScanParams {
     pub scanner: ScannerKind,
     pub clippy_report: Option<String>,
     pub coverage_report: Option<String>,
+    /// Base directory for coverage paths; when set, the Cobertura report is
+    /// converted to SonarQube's generic coverage format before scanning
+    pub coverage_base: Option<String>,
+    /// Look for a well-known coverage report when none is given
+    pub auto_coverage: bool,
     pub wait: bool,
+    /// After waiting, print only the quality gate verdict and exit with its status
+    pub summary_only: bool,
+    /// Have the scanner itself block on the quality gate (`sonar.qualitygate.wait`)
+    pub gate_wait: bool,
     pub timeout: u64,
     pub poll_interval: u64,
     pub no_scm: bool,
@@ -95,7 +119,15 @@ fn build_command(config: &SonarQubeConfig, project: &str, params: &ScanParams) -
         cmd.arg(format!("-Dsonar.rust.clippy.reportPaths={path}"));
     }
     if let Some(ref path) = params.coverage_report {
-        cmd.arg(format!("-Dsonar.rust.cobertura.reportPaths={path}"));
+        if params.coverage_base.is_some() {
+            // Already converted by `prepare_coverage_report`
+            cmd.arg(format!("-Dsonar.coverageReportPaths={path}"));
+        } else {
+            cmd.arg(format!("-Dsonar.rust.cobertura.reportPaths={path}"));
+        }
+    }
+    if let Some(ref path) = params.lcov_report {
+        cmd.arg(format!("-Dsonar.javascript.lcov.reportPaths={path}"));
     }
 
     // Performance flags — only emitted when explicitly set via CLI flags.
@@ -130,9 +162,9 @@ fn stream_output<R: std::io::Read>(
 fn report_task_id(task_id: &Option<String>, json: bool) {
     if let Some(ref id) = task_id {
         if json {
-            println!("{{\"taskId\":\"{id}\"}}");
+            output::print_json(&serde_json::json!({"taskId": id}));
         } else {
-            eprintln!("Analysis task ID: {id}");
+            output::progress(&format!("Analysis task ID: {id}"));
         }
     } else if !json {
         eprintln!("Warning: could not extract analysis task ID from scanner output");
@@ -157,6 +189,10 @@ fn apply_common_scan_params(cmd: &mut Command, params: &ScanParams, prefix: &str
     if let Some(ref incl) = params.inclusions {
         cmd.arg(format!("{prefix}sonar.inclusions={incl}"));
     }
+    if params.gate_wait {
+        cmd.arg(format!("{prefix}sonar.qualitygate.wait=true"));
+        cmd.arg(format!("{prefix}sonar.qualitygate.timeout={}", params.timeout));
+    }
     for arg in &params.extra {
         cmd.arg(arg);
     }
@@ -259,10 +295,7 @@ fn copy_coverage_report(results_dir: &str, target: &str) -> std::io::Result<()>
             let candidate = entry.path().join("coverage.opencover.xml");
             if candidate.exists() {
                 std::fs::copy(&candidate, target)?;
-                eprintln!(
-                    "Copied {} -> {target}",
-                    candidate.display()
-                );
+                output::progress(&format!("Copied {} -> {target}", candidate.display()));
                 return Ok(());
             }
         }
@@ -280,7 +313,7 @@ fn run_phase(
     json: bool,
 ) -> (i32, Option<String>) {
     if !json {
-        eprintln!("── {phase_name} ──");
+        output::progress(&format!("── {phase_name} ──"));
     }
     let mut child = match cmd.spawn() {
         Ok(c) => c,
@@ -292,11 +325,12 @@ fn run_phase(
 
     let mut task_id: Option<String> = None;
 
+    let echo = !json && !output::quiet();
     if let Some(stdout) = child.stdout.take() {
-        task_id = stream_output(BufReader::new(stdout), !json, task_id);
+        task_id = stream_output(BufReader::new(stdout), echo, task_id);
     }
     if let Some(stderr) = child.stderr.take() {
-        task_id = stream_output(BufReader::new(stderr), !json, task_id);
+        task_id = stream_output(BufReader::new(stderr), echo, task_id);
     }
 
     let status = match child.wait() {
@@ -314,14 +348,15 @@ fn run_phase(
     (code, task_id)
 }
 
-async fn run_dotnet_scan(config: SonarQubeConfig, project: &str, params: ScanParams) -> i32 {
-    let solution = match params.solution {
-        Some(ref s) => s.clone(),
-        None => {
-            eprintln!("--solution is required for dotnet scanner");
-            return 1;
-        }
-    };
+async fn run_dotnet_scan(
+    client: &SonarQubeClient,
+    project: &str,
+    params: ScanParams,
+) -> Result<(), CliError> {
+    let solution = params
+        .solution
+        .clone()
+        .ok_or_else(|| CliError::Validation("--solution is required for dotnet scanner".to_string()))?;
 
     let run_id = generate_run_id(&params.run_id);
     let results_dir = format!("TestResults/{run_id}");
@@ -331,21 +366,21 @@ async fn run_dotnet_scan(config: SonarQubeConfig, project: &str, params: ScanPar
         .unwrap_or_else(|| "coverage.opencover.xml".to_string());
 
     if !params.json {
-        eprintln!("Running dotnet sonarscanner for project '{project}'...");
+        output::progress(&format!("Running dotnet sonarscanner for project '{project}'..."));
     }
 
     // Phase 1: begin
-    let cmd = build_dotnet_begin_command(&config, project, &params);
+    let cmd = build_dotnet_begin_command(client.config(), project, &params);
     let (code, _) = run_phase("dotnet sonarscanner begin", cmd, params.json);
     if code != 0 {
-        return code;
+        return Err(CliError::Exit(code));
     }
 
     // Phase 2: build
     let cmd = build_dotnet_build_command(&solution);
     let (code, _) = run_phase("dotnet build", cmd, params.json);
     if code != 0 {
-        return code;
+        return Err(CliError::Exit(code));
     }
 
     // Phase 3: test (unless skipped)
@@ -353,7 +388,7 @@ async fn run_dotnet_scan(config: SonarQubeConfig, project: &str, params: ScanPar
         let cmd = build_dotnet_test_command(&solution, &results_dir);
         let (code, _) = run_phase("dotnet test", cmd, params.json);
         if code != 0 {
-            return code;
+            return Err(CliError::Exit(code));
         }
 
         // Copy coverage report
@@ -363,84 +398,310 @@ async fn run_dotnet_scan(config: SonarQubeConfig, project: &str, params: ScanPar
     }
 
     // Phase 4: end
-    let cmd = build_dotnet_end_command(&config);
+    let cmd = build_dotnet_end_command(client.config());
     let (code, task_id) = run_phase("dotnet sonarscanner end", cmd, params.json);
     if code != 0 {
-        return code;
+        // The end step is where the scanner waits on the gate
+        return Err(CliError::Exit(scanner_exit_code(code, params.gate_wait)));
     }
 
     report_task_id(&task_id, params.json);
+    wait_if_requested(client, project, task_id.as_deref(), &params).await
+}
 
-    if params.wait {
-        if let Some(ref id) = task_id {
-            return super::wait::run(config, id, params.timeout, params.poll_interval, params.json)
-                .await;
-        }
-        eprintln!("Cannot wait: no task ID was extracted from scanner output");
-        return 1;
+/// A temporary file that is deleted when dropped
+#[derive(Debug)]
+struct TempFile(std::path::PathBuf);
+
+impl TempFile {
+    fn path(&self) -> String {
+        self.0.display().to_string()
     }
+}
 
-    0
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
 }
 
-async fn run_cli_scan(config: SonarQubeConfig, project: &str, params: ScanParams) -> i32 {
-    let mut cmd = build_command(&config, project, &params);
+/// Convert a Cobertura report to generic coverage with paths relative to `base`,
+/// returning the converted report, which is deleted once dropped.
+fn prepare_coverage_report(report: &str, base: &str) -> Result<TempFile, String> {
+    let xml = std::fs::read_to_string(report)
+        .map_err(|e| format!("Failed to read coverage report {report}: {e}"))?;
+    let work_dir = std::env::current_dir()
+        .map_err(|e| format!("Failed to resolve working directory: {e}"))?;
+    let converted = crate::cobertura::convert_cobertura_to_sonarqube(
+        &xml,
+        &work_dir,
+        std::path::Path::new(base),
+    )?;
+
+    let target =
+        std::env::temp_dir().join(format!("sonar-cli-coverage-{}.xml", std::process::id()));
+    std::fs::write(&target, converted)
+        .map_err(|e| format!("Failed to write {}: {e}", target.display()))?;
+    Ok(TempFile(target))
+}
 
-    if !params.json {
-        eprintln!("Running sonar-scanner for project '{project}'...");
+/// Coverage report formats recognised by `--auto-coverage`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CoverageFormat {
+    Cobertura,
+    Generic,
+    Lcov,
+}
+
+impl CoverageFormat {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Cobertura => "Cobertura",
+            Self::Generic => "generic",
+            Self::Lcov => "LCOV",
+        }
+    }
+}
+
+/// Well-known report locations relative to the project directory, in priority order.
+/// `cobertura.xml` files anywhere under `target/` are tried after these.
+const COVERAGE_CANDIDATES: &[&str] = &[
+    "cobertura.xml",
+    "coverage.xml",
+    "lcov.info",
+    "coverage/cobertura-coverage.xml",
+    "coverage/cobertura.xml",
+    "coverage/lcov.info",
+];
+
+/// Classify a coverage report by its content; `None` if it is not a known format
+fn detect_coverage_format(path: &Path) -> Option<CoverageFormat> {
+    let content = std::fs::read_to_string(path).ok()?;
+    if content.lines().any(|l| l.starts_with("SF:")) {
+        return Some(CoverageFormat::Lcov);
+    }
+    if !content.contains("<coverage") {
+        return None;
+    }
+    if content.contains("<lineToCover") {
+        Some(CoverageFormat::Generic)
+    } else if content.contains("line-rate") || content.contains("<packages") {
+        Some(CoverageFormat::Cobertura)
+    } else {
+        None
     }
+}
 
-    let mut child = match cmd.spawn() {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to start sonar-scanner: {e}");
-            eprintln!("Is sonar-scanner installed and on your PATH?");
-            return 1;
+/// Collect files called `name` under `dir`, descending at most `depth` levels
+fn find_files_named(dir: &Path, name: &str, depth: usize, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth > 0 {
+                find_files_named(&path, name, depth - 1, out);
+            }
+        } else if entry.file_name() == name {
+            out.push(path);
         }
+    }
+}
+
+/// Find the first recognisable coverage report under `root`
+fn discover_coverage_report(root: &Path) -> Option<(PathBuf, CoverageFormat)> {
+    let mut candidates: Vec<PathBuf> = COVERAGE_CANDIDATES.iter().map(|c| root.join(c)).collect();
+    let mut under_target = Vec::new();
+    find_files_named(&root.join("target"), "cobertura.xml", 4, &mut under_target);
+    under_target.sort();
+    candidates.extend(under_target);
+
+    candidates
+        .into_iter()
+        .filter(|p| p.is_file())
+        .find_map(|p| detect_coverage_format(&p).map(|f| (p, f)))
+}
+
+/// Wire a discovered coverage report into the scan parameters, unless one was given
+fn apply_auto_coverage(params: &mut ScanParams, root: &Path) {
+    if params.coverage_report.is_some() || params.lcov_report.is_some() {
+        return;
+    }
+    let Some((path, format)) = discover_coverage_report(root) else {
+        eprintln!("No coverage report found in {} (--auto-coverage)", root.display());
+        return;
     };
+    output::progress(&format!("Using {} coverage report: {}", format.label(), path.display()));
+
+    let path = path.display().to_string();
+    match format {
+        CoverageFormat::Cobertura => params.coverage_report = Some(path),
+        CoverageFormat::Lcov => params.lcov_report = Some(path),
+        CoverageFormat::Generic => params.extra.push(format!("-Dsonar.coverageReportPaths={path}")),
+    }
+}
+
+async fn run_cli_scan(
+    client: &SonarQubeClient,
+    project: &str,
+    mut params: ScanParams,
+) -> Result<(), CliError> {
+    if params.auto_coverage {
+        match std::env::current_dir() {
+            Ok(cwd) => apply_auto_coverage(&mut params, &cwd),
+            Err(e) => eprintln!("Failed to resolve working directory for --auto-coverage: {e}"),
+        }
+    }
+    let mut converted_report = None;
+    if let (Some(report), Some(base)) = (&params.coverage_report, &params.coverage_base) {
+        let converted = prepare_coverage_report(report, base).map_err(CliError::Failed)?;
+        params.coverage_report = Some(converted.path());
+        converted_report = Some(converted);
+    }
+
+    let mut cmd = build_command(client.config(), project, &params);
+
+    if !params.json {
+        output::progress(&format!("Running sonar-scanner for project '{project}'..."));
+    }
+
+    let mut child = cmd.spawn().map_err(|e| {
+        CliError::Failed(format!(
+            "Failed to start sonar-scanner: {e}\nIs sonar-scanner installed and on your PATH?"
+        ))
+    })?;
 
     let mut task_id: Option<String> = None;
 
+    let echo = !params.json && !output::quiet();
     if let Some(stdout) = child.stdout.take() {
-        task_id = stream_output(BufReader::new(stdout), !params.json, task_id);
+        task_id = stream_output(BufReader::new(stdout), echo, task_id);
     }
 
     if let Some(stderr) = child.stderr.take() {
-        task_id = stream_output(BufReader::new(stderr), !params.json, task_id);
+        task_id = stream_output(BufReader::new(stderr), echo, task_id);
     }
 
-    let status = match child.wait() {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("Failed to wait for sonar-scanner: {e}");
-            return 1;
-        }
-    };
+    let status = child.wait();
+    // The scanner has read the converted report by now
+    drop(converted_report);
+    let status = status.map_err(|e| CliError::Failed(format!("Failed to wait for sonar-scanner: {e}")))?;
 
     if !status.success() {
         let code = status.code().unwrap_or(1);
-        eprintln!("sonar-scanner exited with code {code}");
-        return code;
+        if params.gate_wait {
+            eprintln!("Quality gate failed (sonar-scanner exited with code {code})");
+        } else {
+            eprintln!("sonar-scanner exited with code {code}");
+        }
+        return Err(CliError::Exit(scanner_exit_code(code, params.gate_wait)));
     }
 
     report_task_id(&task_id, params.json);
+    wait_if_requested(client, project, task_id.as_deref(), &params).await
+}
 
-    if params.wait {
-        if let Some(ref id) = task_id {
-            return super::wait::run(config, id, params.timeout, params.poll_interval, params.json)
-                .await;
-        }
-        eprintln!("Cannot wait: no task ID was extracted from scanner output");
-        return 1;
+/// With `--wait`, wait for the analysis task the scanner submitted
+async fn wait_if_requested(
+    client: &SonarQubeClient,
+    project: &str,
+    task_id: Option<&str>,
+    params: &ScanParams,
+) -> Result<(), CliError> {
+    if !params.wait {
+        return Ok(());
+    }
+    match task_id {
+        Some(id) => wait_for_task(client, project, id, params).await,
+        None => Err(CliError::Failed(
+            "Cannot wait: no task ID was extracted from scanner output".to_string(),
+        )),
     }
+}
 
-    0
+/// Wait for the analysis task; with `--summary-only`, report just the gate verdict.
+async fn wait_for_task(
+    client: &SonarQubeClient,
+    project: &str,
+    task_id: &str,
+    params: &ScanParams,
+) -> Result<(), CliError> {
+    if !params.summary_only {
+        return super::wait::wait_with_client(
+            client,
+            task_id,
+            params.timeout,
+            params.poll_interval,
+            &[task_status::SUCCESS],
+            params.json,
+        )
+        .await;
+    }
+    wait_and_summarize(client, project, task_id, params).await
 }
 
-pub async fn run(config: SonarQubeConfig, project: &str, params: ScanParams) -> i32 {
+/// Wait for the task, then print a one-line quality gate verdict.
+///
+/// Exits 0 only when the gate passes.
+async fn wait_and_summarize(
+    client: &SonarQubeClient,
+    project: &str,
+    task_id: &str,
+    params: &ScanParams,
+) -> Result<(), CliError> {
+    let (status, summary) = summary_line(client, project, task_id, params).await?;
+    if params.json {
+        output::print_json(&serde_json::json!({
+            "taskId": task_id,
+            "status": status,
+            "summary": summary,
+        }));
+    } else {
+        output::print_line(&summary);
+    }
+    if status == "OK" { Ok(()) } else { Err(CliError::Exit(exit_code::FAILURE)) }
+}
+
+/// Wait for the task, then fetch the quality gate: its status and the
+/// one-line verdict, e.g. `Quality Gate: FAILED (coverage 72% < 80%)`
+async fn summary_line(
+    client: &SonarQubeClient,
+    project: &str,
+    task_id: &str,
+    params: &ScanParams,
+) -> Result<(String, String), CliError> {
+    client
+        .wait_for_analysis(
+            task_id,
+            std::time::Duration::from_secs(params.timeout),
+            std::time::Duration::from_secs(params.poll_interval),
+            &[task_status::SUCCESS],
+        )
+        .await
+        .map_err(|e| CliError::api("Analysis failed", e))?;
+
+    let gate = client
+        .get_quality_gate(project)
+        .await
+        .map_err(|e| CliError::api("Failed to get quality gate", e))?;
+
+    let summary = output::format_gate_summary(&gate);
+    Ok((gate.project_status.status, summary))
+}
+
+pub async fn run(mut config: SonarQubeConfig, project: &str, params: ScanParams) -> Result<(), CliError> {
+    // Resolve the branch once so the scanner and the gate query agree on it
+    if config.branch.is_none() {
+        config.branch = detect_branch();
+    }
+    // The gate must reflect this analysis, never a cached earlier one
+    config.cache = None;
+    // One client (and connection pool) serves the scan, the wait and the gate check
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
     match params.scanner {
-        ScannerKind::Cli => run_cli_scan(config, project, params).await,
-        ScannerKind::Dotnet => run_dotnet_scan(config, project, params).await,
+        ScannerKind::Cli => run_cli_scan(&client, project, params).await,
+        ScannerKind::Dotnet => run_dotnet_scan(&client, project, params).await,
     }
 }
 
@@ -481,6 +742,7 @@ mod tests {
             timeout: std::time::Duration::from_secs(30),
             project_key: None,
             branch: branch.map(|b| b.to_string()),
+            ..Default::default()
         }
     }
 
@@ -493,7 +755,11 @@ mod tests {
             scanner: ScannerKind::Cli,
             clippy_report: clippy.map(|s| s.to_string()),
             coverage_report: coverage.map(|s| s.to_string()),
+            coverage_base: None,
+            auto_coverage: false,
             wait: false,
+            summary_only: false,
+            gate_wait: false,
             timeout: 60,
             poll_interval: 5,
             no_scm: false,
@@ -576,6 +842,254 @@ mod tests {
             .any(|a| a == "-Dsonar.rust.cobertura.reportPaths=/tmp/lcov.info"));
     }
 
+    #[test]
+    fn test_build_command_with_converted_coverage_report() {
+        let config = make_config("http://localhost:9000", None, Some("main"));
+        let mut params = make_params(None, Some("/tmp/generic.xml"), vec![]);
+        params.coverage_base = Some("/repo".to_string());
+        let args = args_vec(&build_command(&config, "proj", &params));
+        assert!(args.iter().any(|a| a == "-Dsonar.coverageReportPaths=/tmp/generic.xml"));
+        assert!(!args.iter().any(|a| a.starts_with("-Dsonar.rust.cobertura.reportPaths")));
+    }
+
+    #[test]
+    fn test_prepare_coverage_report_rebases_paths() {
+        let report = std::env::temp_dir().join(format!(
+            "sonar-cli-test-cobertura-{}.xml",
+            std::process::id()
+        ));
+        std::fs::write(
+            &report,
+            r#"<coverage><sources><source>/repo/packages/api</source></sources>
+<packages><package><classes><class filename="src/lib.rs"><lines>
+<line number="4" hits="2"/></lines></class></classes></package></packages></coverage>"#,
+        )
+        .unwrap();
+
+        let converted = prepare_coverage_report(report.to_str().unwrap(), "/repo").unwrap();
+        let converted_path = converted.path();
+        let out = std::fs::read_to_string(&converted_path).unwrap();
+        let _ = std::fs::remove_file(&report);
+        drop(converted);
+        assert!(!std::path::Path::new(&converted_path).exists());
+        assert!(out.contains(r#"<file path="packages/api/src/lib.rs">"#));
+        assert!(out.contains(r#"<lineToCover lineNumber="4" covered="true"/>"#));
+    }
+
+    async fn try_mock_server() -> Option<wiremock::MockServer> {
+        let listener = match std::net::TcpListener::bind("127.0.0.1:0") {
+            Ok(l) => l,
+            Err(_) => return None,
+        };
+        Some(wiremock::MockServer::builder().listener(listener).start().await)
+    }
+
+    async fn mount_task_and_gate(server: &wiremock::MockServer, gate_status: &str) {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, ResponseTemplate};
+        Mock::given(method("GET"))
+            .and(path("/api/ce/task"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "task": {
+                    "id": "task-1",
+                    "type": "REPORT",
+                    "status": "SUCCESS",
+                    "submittedAt": "2026-01-01T00:00:00+0000"
+                }
+            })))
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/qualitygates/project_status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "projectStatus": {
+                    "status": gate_status,
+                    "conditions": [{
+                        "status": gate_status,
+                        "metricKey": "coverage",
+                        "comparator": "LT",
+                        "errorThreshold": "80",
+                        "actualValue": "72"
+                    }]
+                }
+            })))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_summary_only_failing_gate_exits_non_zero() {
+        let server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        mount_task_and_gate(&server, "ERROR").await;
+
+        let config = make_config(&server.uri(), None, Some("main"));
+        let mut params = make_params(None, None, vec![]);
+        params.wait = true;
+        params.summary_only = true;
+        params.poll_interval = 0;
+        let client = SonarQubeClient::new(config).unwrap();
+        let (status, line) = summary_line(&client, "proj", "task-1", &params).await.unwrap();
+        assert_eq!(status, "ERROR");
+        assert_eq!(line, "Quality Gate: FAILED (coverage 72% < 80%)");
+        assert!(wait_for_task(&client, "proj", "task-1", &params).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_summary_only_passing_gate_exits_zero() {
+        let server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        mount_task_and_gate(&server, "OK").await;
+
+        let config = make_config(&server.uri(), None, Some("main"));
+        let mut params = make_params(None, None, vec![]);
+        params.summary_only = true;
+        params.json = true;
+        params.poll_interval = 0;
+        let client = SonarQubeClient::new(config).unwrap();
+        assert!(wait_for_task(&client, "proj", "task-1", &params).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_wait_and_gate_share_one_client() {
+        use crate::client::{RetryPolicy, SonarQubeError};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/ce/task"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "task": {
+                    "id": "task-1",
+                    "type": "REPORT",
+                    "status": "SUCCESS",
+                    "submittedAt": "2026-01-01T00:00:00+0000"
+                }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/qualitygates/project_status"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // A single failure opens the breaker, and breaker state lives on the client
+        let config = make_config(&server.uri(), None, Some("main")).with_retry(RetryPolicy {
+            max_retries: 0,
+            failure_threshold: 1,
+            cooldown: std::time::Duration::from_secs(60),
+            ..Default::default()
+        });
+        let client = SonarQubeClient::new(config).unwrap();
+        let mut params = make_params(None, None, vec![]);
+        params.summary_only = true;
+        params.json = true;
+        params.poll_interval = 0;
+        assert!(wait_for_task(&client, "proj", "task-1", &params).await.is_err());
+
+        // The gate check ran on this instance: its circuit is now open
+        let err = client.get_quality_gate("proj").await.unwrap_err();
+        assert!(matches!(err, SonarQubeError::CircuitOpen));
+        server.verify().await;
+    }
+
+    /// Fresh, empty temp directory for coverage discovery tests
+    fn discovery_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "sonar-cli-test-discover-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_discover_cobertura_under_target() {
+        let dir = discovery_dir("cobertura");
+        let nested = dir.join("target").join("llvm-cov");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(
+            nested.join("cobertura.xml"),
+            r#"<coverage line-rate="0.8"><packages></packages></coverage>"#,
+        )
+        .unwrap();
+
+        let found = discover_coverage_report(&dir);
+        let mut params = make_params(None, None, vec![]);
+        apply_auto_coverage(&mut params, &dir);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let (path, format) = found.unwrap();
+        assert!(path.ends_with("target/llvm-cov/cobertura.xml"));
+        assert_eq!(format, CoverageFormat::Cobertura);
+        assert_eq!(params.coverage_report, Some(path.display().to_string()));
+    }
+
+    #[test]
+    fn test_discover_lcov_and_generic() {
+        let dir = discovery_dir("lcov");
+        std::fs::write(dir.join("lcov.info"), "TN:\nSF:src/lib.rs\nDA:1,1\nend_of_record\n")
+            .unwrap();
+        let mut params = make_params(None, None, vec![]);
+        apply_auto_coverage(&mut params, &dir);
+        assert_eq!(
+            discover_coverage_report(&dir).map(|(_, f)| f),
+            Some(CoverageFormat::Lcov)
+        );
+        assert!(params.lcov_report.unwrap().ends_with("lcov.info"));
+
+        // coverage.xml outranks lcov.info
+        std::fs::write(
+            dir.join("coverage.xml"),
+            r#"<coverage version="1"><file path="a.rs"><lineToCover lineNumber="1" covered="true"/></file></coverage>"#,
+        )
+        .unwrap();
+        let mut params = make_params(None, None, vec![]);
+        apply_auto_coverage(&mut params, &dir);
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(params.extra[0].starts_with("-Dsonar.coverageReportPaths="));
+        assert!(params.extra[0].ends_with("coverage.xml"));
+    }
+
+    #[test]
+    fn test_discover_skips_unrecognised_and_keeps_explicit_report() {
+        let dir = discovery_dir("none");
+        std::fs::write(dir.join("coverage.xml"), "<report/>").unwrap();
+        assert!(discover_coverage_report(&dir).is_none());
+
+        let mut params = make_params(None, Some("explicit.xml"), vec![]);
+        std::fs::write(dir.join("cobertura.xml"), r#"<coverage line-rate="1"/>"#).unwrap();
+        apply_auto_coverage(&mut params, &dir);
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(params.coverage_report.as_deref(), Some("explicit.xml"));
+    }
+
+    #[test]
+    fn test_build_command_with_lcov_report() {
+        let config = make_config("http://localhost:9000", None, None);
+        let mut params = make_params(None, None, vec![]);
+        params.lcov_report = Some("coverage/lcov.info".to_string());
+        let args = args_vec(&build_command(&config, "proj", &params));
+        assert!(args.contains(&"-Dsonar.javascript.lcov.reportPaths=coverage/lcov.info".to_string()));
+    }
+
+    #[test]
+    fn test_prepare_coverage_report_missing_file() {
+        let err = prepare_coverage_report("/nonexistent/coverage.xml", "/repo").unwrap_err();
+        assert!(err.contains("Failed to read coverage report"));
+    }
+
     #[test]
     fn test_build_command_with_extra_args() {
         let config = make_config("http://localhost:9000", None, Some("main"));
@@ -621,7 +1135,11 @@ mod tests {
             scanner: ScannerKind::Cli,
             clippy_report: None,
             coverage_report: None,
+            coverage_base: None,
+            auto_coverage: false,
             wait: false,
+            summary_only: false,
+            gate_wait: false,
             timeout: 60,
             poll_interval: 5,
             no_scm: true,
@@ -655,6 +1173,30 @@ mod tests {
         assert!(!args.iter().any(|a| a.starts_with("-Dsonar.scanner.skipUnchangedFiles")));
         assert!(!args.iter().any(|a| a.starts_with("-Dsonar.exclusions")));
         assert!(!args.iter().any(|a| a.starts_with("-Dsonar.sources")));
+        assert!(!args.iter().any(|a| a.starts_with("-Dsonar.qualitygate")));
+    }
+
+    #[test]
+    fn test_build_command_with_gate_wait() {
+        let config = make_config("http://localhost:9000", None, Some("main"));
+        let mut params = make_params(None, None, vec![]);
+        params.gate_wait = true;
+        params.timeout = 600;
+        let args = args_vec(&build_command(&config, "proj", &params));
+        assert!(args.iter().any(|a| a == "-Dsonar.qualitygate.wait=true"));
+        assert!(args.iter().any(|a| a == "-Dsonar.qualitygate.timeout=600"));
+
+        let args = args_vec(&build_dotnet_begin_command(&config, "proj", &params));
+        assert!(args.iter().any(|a| a == "/d:sonar.qualitygate.wait=true"));
+    }
+
+    #[test]
+    fn test_scanner_exit_code_maps_gate_failure() {
+        assert_eq!(scanner_exit_code(0, true), 0);
+        assert_eq!(scanner_exit_code(1, true), exit_code::GATE_FAILED);
+        assert_eq!(scanner_exit_code(2, true), exit_code::GATE_FAILED);
+        // Without --gate-wait the scanner's code passes through
+        assert_eq!(scanner_exit_code(2, false), 2);
     }
 
     #[test]
@@ -817,7 +1359,11 @@ mod tests {
             scanner: ScannerKind::Dotnet,
             clippy_report: None,
             coverage_report: None,
+            coverage_base: None,
+            auto_coverage: false,
             wait: false,
+            summary_only: false,
+            gate_wait: false,
             timeout: 60,
             poll_interval: 5,
             no_scm: false,
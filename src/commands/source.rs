@@ -1,4 +1,6 @@
 use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::error::CliError;
+use crate::helpers::strip_html_tags;
 use crate::output;
 use crate::types::SourceLine;
 
@@ -7,46 +9,51 @@ pub async fn run(
     component: &str,
     from: Option<usize>,
     to: Option<usize>,
+    blame: bool,
     json: bool,
-) -> i32 {
-    let client = match SonarQubeClient::new(config) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to create client: {e}");
-            return 1;
-        }
-    };
+) -> Result<(), CliError> {
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    if blame {
+        return run_blame(&client, component, from, to, json).await;
+    }
 
     // Use /api/sources/show when line range is specified, otherwise use /api/sources/raw
     let lines = if from.is_some() || to.is_some() {
         client.get_source_show(component, from, to).await
     } else {
-        match client.get_source_raw(component).await {
-            Ok(raw) => {
-                let lines: Vec<SourceLine> = raw
-                    .lines()
-                    .enumerate()
-                    .map(|(i, line)| SourceLine {
-                        line: i + 1,
-                        code: line.to_string(),
-                    })
-                    .collect();
-                Ok(lines)
-            }
-            Err(e) => Err(e),
-        }
+        client.get_source_raw(component).await.map(|raw| {
+            raw.lines()
+                .enumerate()
+                .map(|(i, line)| SourceLine {
+                    line: i + 1,
+                    code: line.to_string(),
+                })
+                .collect()
+        })
     };
+    let lines = lines.map_err(|e| CliError::api("Failed to fetch source", e))?;
+    output::print_source(&lines, json);
+    Ok(())
+}
 
-    match lines {
-        Ok(lines) => {
-            output::print_source(&lines, json);
-            0
-        }
-        Err(e) => {
-            eprintln!("Failed to fetch source: {e}");
-            1
-        }
+/// Fetch source lines with SCM annotations via /api/sources/lines
+async fn run_blame(
+    client: &SonarQubeClient,
+    component: &str,
+    from: Option<usize>,
+    to: Option<usize>,
+    json: bool,
+) -> Result<(), CliError> {
+    let mut lines = client
+        .get_source_lines(component, from, to)
+        .await
+        .map_err(|e| CliError::api("Failed to fetch source", e))?;
+    for line in &mut lines {
+        line.code = strip_html_tags(&line.code);
     }
+    output::print_blame(&lines, json);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -76,8 +83,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj:src/main.rs", None, None, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj:src/main.rs", None, None, false, false).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -93,8 +100,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj:src/main.rs", None, None, true).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj:src/main.rs", None, None, false, true).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -114,8 +121,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj:src/main.rs", Some(1), Some(3), false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "my-proj:src/main.rs", Some(1), Some(3), false, false).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -131,7 +138,72 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "my-proj:src/main.rs", None, None, false).await;
-        assert_eq!(exit, 1);
+        let result = run(config, "my-proj:src/main.rs", None, None, false, false).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_source_blame() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/sources/lines"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sources": [
+                    {
+                        "line": 1,
+                        "code": "<span class=\"k\">fn</span> main() {}",
+                        "scmAuthor": "alice@example.com",
+                        "scmDate": "2026-03-01T10:00:00+0000",
+                        "scmRevision": "abc123"
+                    },
+                    {"line": 2, "code": ""}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj:src/main.rs", None, None, true, false).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_source_blame_without_scm() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/sources/lines"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sources": [{"line": 1, "code": "fn main() {}"}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj:src/main.rs", Some(1), Some(1), true, true).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_source_blame_api_error() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/sources/lines"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "my-proj:src/main.rs", None, None, true, false).await;
+        assert!(result.is_err());
     }
 }
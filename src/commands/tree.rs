@@ -0,0 +1,110 @@
+use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::error::CliError;
+use crate::output;
+
+/// Component qualifiers accepted by `tree --qualifier`
+pub const QUALIFIERS: &[&str] = &["FIL", "DIR"];
+
+/// Shortest `--search` the server accepts
+const MIN_SEARCH_LEN: usize = 3;
+
+/// Upper-case and check a comma-separated `--qualifier` list
+pub fn parse_qualifiers(value: &str) -> Result<String, String> {
+    let mut qualifiers: Vec<String> = Vec::new();
+    for q in value.split(',').map(|q| q.trim().to_uppercase()).filter(|q| !q.is_empty()) {
+        if !QUALIFIERS.contains(&q.as_str()) {
+            return Err(format!("Unknown qualifier '{q}'. Valid values: {}", QUALIFIERS.join(", ")));
+        }
+        if !qualifiers.contains(&q) {
+            qualifiers.push(q);
+        }
+    }
+    if qualifiers.is_empty() {
+        return Err(format!("--qualifier needs at least one of {}", QUALIFIERS.join(", ")));
+    }
+    Ok(qualifiers.join(","))
+}
+
+pub async fn run(
+    config: SonarQubeConfig,
+    project: &str,
+    qualifiers: &str,
+    search: Option<&str>,
+    json: bool,
+) -> Result<(), CliError> {
+    let qualifiers = parse_qualifiers(qualifiers).map_err(CliError::Validation)?;
+    if let Some(q) = search.filter(|q| q.chars().count() < MIN_SEARCH_LEN) {
+        return Err(CliError::Validation(format!(
+            "--search needs at least {MIN_SEARCH_LEN} characters, got '{q}'"
+        )));
+    }
+
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
+
+    let mut components = client
+        .get_components_tree(project, &qualifiers, search)
+        .await
+        .map_err(|e| CliError::api("Failed to list components", e))?;
+    components.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.key.cmp(&b.key)));
+    output::print_component_tree(&components, project, json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn try_mock_server() -> Option<MockServer> {
+        let listener = match std::net::TcpListener::bind("127.0.0.1:0") {
+            Ok(l) => l,
+            Err(_) => return None,
+        };
+        Some(MockServer::builder().listener(listener).start().await)
+    }
+
+    #[test]
+    fn test_parse_qualifiers() {
+        assert_eq!(parse_qualifiers("fil").unwrap(), "FIL");
+        assert_eq!(parse_qualifiers("DIR, fil,DIR").unwrap(), "DIR,FIL");
+        assert!(parse_qualifiers("TRK").unwrap_err().contains("Valid values: FIL, DIR"));
+        assert!(parse_qualifiers(" , ").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_tree_lists_dirs_and_files() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/components/tree"))
+            .and(query_param("component", "my-proj"))
+            .and(query_param("qualifiers", "FIL,DIR"))
+            .and(query_param("q", "main"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "paging": {"pageIndex": 1, "pageSize": 500, "total": 3},
+                "baseComponent": {"key": "my-proj", "qualifier": "TRK"},
+                "components": [
+                    {"key": "my-proj:src", "name": "src", "path": "src", "qualifier": "DIR"},
+                    {"key": "my-proj:src/main.rs", "name": "main.rs", "path": "src/main.rs", "qualifier": "FIL", "language": "rust"},
+                    {"key": "my-proj:src/bin/main.rs", "name": "main.rs", "path": "src/bin/main.rs", "qualifier": "FIL"}
+                ]
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(run(config.clone(), "my-proj", "FIL,DIR", Some("main"), false).await.is_ok());
+        assert!(run(config, "my-proj", "FIL,DIR", Some("main"), true).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_tree_rejects_short_search() {
+        let config = SonarQubeConfig::new("http://localhost:1");
+        let result = run(config, "my-proj", "FIL", Some("rs"), false).await;
+        assert!(matches!(result, Err(CliError::Validation(_))), "{result:?}");
+    }
+}
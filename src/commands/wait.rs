@@ -1,44 +1,82 @@
 use std::time::Duration;
 
 use crate::client::{SonarQubeClient, SonarQubeConfig};
+use crate::error::CliError;
 use crate::output;
+use crate::types::task_status;
+
+/// Parse a comma-separated list of task statuses to accept as a successful wait.
+/// Defaults to SUCCESS only; every value must be a terminal task status.
+pub fn parse_accept_status(value: Option<&str>) -> Result<Vec<&'static str>, String> {
+    let Some(value) = value else {
+        return Ok(vec![task_status::SUCCESS]);
+    };
+
+    let mut accepted = Vec::new();
+    for part in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let upper = part.to_uppercase();
+        match task_status::TERMINAL.iter().find(|s| **s == upper) {
+            Some(status) => {
+                if !accepted.contains(status) {
+                    accepted.push(*status);
+                }
+            }
+            None => {
+                return Err(format!(
+                    "Unknown task status '{part}'. Valid values: {}",
+                    task_status::TERMINAL.join(", ")
+                ));
+            }
+        }
+    }
+
+    if accepted.is_empty() {
+        return Err("--accept-status requires at least one status".to_string());
+    }
+    Ok(accepted)
+}
 
 pub async fn run(
     config: SonarQubeConfig,
     task_id: &str,
     timeout_secs: u64,
     poll_interval_secs: u64,
+    accept_status: Option<&str>,
     json: bool,
-) -> i32 {
-    let client = match SonarQubeClient::new(config) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to create client: {e}");
-            return 1;
-        }
-    };
+) -> Result<(), CliError> {
+    let accepted = parse_accept_status(accept_status).map_err(CliError::Validation)?;
+
+    // The outcome of a fresh analysis is never in the cache
+    let config = SonarQubeConfig { cache: None, ..config };
+    let client = SonarQubeClient::new(config).map_err(CliError::client)?;
 
+    wait_with_client(&client, task_id, timeout_secs, poll_interval_secs, &accepted, json).await
+}
+
+/// Wait for a task on an existing client and print the result
+pub async fn wait_with_client(
+    client: &SonarQubeClient,
+    task_id: &str,
+    timeout_secs: u64,
+    poll_interval_secs: u64,
+    accepted: &[&str],
+    json: bool,
+) -> Result<(), CliError> {
     if !json {
-        eprintln!("Waiting for analysis task {task_id}...");
+        output::progress(&format!("Waiting for analysis task {task_id}..."));
     }
 
-    match client
+    let task = client
         .wait_for_analysis(
             task_id,
             Duration::from_secs(timeout_secs),
             Duration::from_secs(poll_interval_secs),
+            accepted,
         )
         .await
-    {
-        Ok(task) => {
-            output::print_wait_result(&task, json);
-            0
-        }
-        Err(e) => {
-            eprintln!("Analysis failed: {e}");
-            1
-        }
-    }
+        .map_err(|e| CliError::api("Analysis failed", e))?;
+    output::print_wait_result(&task, json);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -93,8 +131,8 @@ mod tests {
 
         let config = SonarQubeConfig::new(mock_server.uri());
         // Use short timeout and poll interval for tests
-        let exit = run(config, "task-123", 10, 1, false).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "task-123", 10, 1, None, false).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -110,8 +148,8 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "task-456", 10, 1, true).await;
-        assert_eq!(exit, 0);
+        let result = run(config, "task-456", 10, 1, None, true).await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
@@ -127,7 +165,48 @@ mod tests {
             .await;
 
         let config = SonarQubeConfig::new(mock_server.uri());
-        let exit = run(config, "task-789", 10, 1, false).await;
-        assert_eq!(exit, 1);
+        let result = run(config, "task-789", 10, 1, None, false).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_wait_failure_accepted() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/ce/task"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(task_failed_body("task-789")))
+            .mount(&mock_server)
+            .await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        let result = run(config, "task-789", 10, 1, Some("SUCCESS,FAILED"), true).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_wait_invalid_accept_status() {
+        let config = SonarQubeConfig::new("http://localhost:1");
+        let result = run(config, "task-1", 10, 1, Some("PENDING"), false).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_accept_status() {
+        assert_eq!(parse_accept_status(None).unwrap(), vec!["SUCCESS"]);
+        assert_eq!(
+            parse_accept_status(Some("success, failed")).unwrap(),
+            vec!["SUCCESS", "FAILED"]
+        );
+        assert_eq!(
+            parse_accept_status(Some("CANCELED,CANCELED")).unwrap(),
+            vec!["CANCELED"]
+        );
+        assert!(parse_accept_status(Some("IN_PROGRESS"))
+            .unwrap_err()
+            .contains("Unknown task status 'IN_PROGRESS'"));
+        assert!(parse_accept_status(Some(" , ")).is_err());
     }
 }
@@ -0,0 +1,101 @@
+use crate::client::{SonarQubeClient, SonarQubeConfig, SonarQubeError};
+use crate::error::{exit_code, CliError};
+use crate::output;
+use crate::types::{CurrentUser, TokenValidation};
+
+pub async fn run(config: SonarQubeConfig, json: bool) -> Result<(), CliError> {
+    let has_token = config.token.is_some();
+    let client = SonarQubeClient::new(config.clone()).map_err(CliError::client)?;
+
+    // A server that forces authentication answers 401 instead of `valid: false`
+    let validation = match client.validate_token().await {
+        Err(SonarQubeError::Unauthorized) => TokenValidation { valid: false },
+        other => other.map_err(|e| CliError::api("Failed to validate token", e))?,
+    };
+    let user = match client.current_user().await {
+        Err(SonarQubeError::Unauthorized) => CurrentUser::default(),
+        other => other.map_err(|e| CliError::api("Failed to fetch current user", e))?,
+    };
+    // Anonymous access may be "valid" on open servers, but there is no token to vouch for
+    let valid = has_token && validation.valid && user.is_logged_in;
+    // Best effort, like `health`
+    let info = client.get_system_info().await.ok();
+
+    output::print_whoami(&user, valid, has_token, &config.url, info.as_ref(), json);
+    if has_token && !valid {
+        return Err(CliError::Exit(exit_code::AUTH));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn try_mock_server() -> Option<MockServer> {
+        let listener = match std::net::TcpListener::bind("127.0.0.1:0") {
+            Ok(l) => l,
+            Err(_) => return None,
+        };
+        Some(MockServer::builder().listener(listener).start().await)
+    }
+
+    async fn mount(server: &MockServer, endpoint: &str, response: ResponseTemplate) {
+        Mock::given(method("GET"))
+            .and(path(endpoint))
+            .respond_with(response)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_run_whoami_valid_token() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        let ok = |body| ResponseTemplate::new(200).set_body_json(body);
+        mount(&mock_server, "/api/authentication/validate", ok(serde_json::json!({"valid": true}))).await;
+        mount(
+            &mock_server,
+            "/api/users/current",
+            ok(serde_json::json!({"isLoggedIn": true, "login": "jdoe", "name": "Jane Doe"})),
+        )
+        .await;
+        mount(&mock_server, "/api/server/version", ResponseTemplate::new(200).set_body_string("10.4.1")).await;
+
+        let config = SonarQubeConfig::new(mock_server.uri()).with_token("good");
+        assert!(run(config.clone(), false).await.is_ok());
+        assert!(run(config, true).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_whoami_invalid_token_exits_with_auth_code() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        mount(&mock_server, "/api/authentication/validate", ResponseTemplate::new(401)).await;
+        mount(&mock_server, "/api/users/current", ResponseTemplate::new(401)).await;
+
+        let config = SonarQubeConfig::new(mock_server.uri()).with_token("bad");
+        let err = run(config, true).await.unwrap_err();
+        assert_eq!(err.exit_code(), exit_code::AUTH);
+    }
+
+    #[tokio::test]
+    async fn test_run_whoami_anonymous_succeeds() {
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+        let ok = |body| ResponseTemplate::new(200).set_body_json(body);
+        mount(&mock_server, "/api/authentication/validate", ok(serde_json::json!({"valid": true}))).await;
+        mount(&mock_server, "/api/users/current", ok(serde_json::json!({"isLoggedIn": false}))).await;
+
+        let config = SonarQubeConfig::new(mock_server.uri());
+        assert!(run(config, false).await.is_ok());
+    }
+}
@@ -1,15 +1,149 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Default, Serialize, Deserialize)]
-pub struct StoredConfig {
+use crate::defaults::CommandDefaults;
+
+/// How many recently used projects are remembered
+pub const MAX_RECENT_PROJECTS: usize = 10;
+
+/// Profile used when neither `--profile` nor `default_profile` names one
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Credentials for one SonarQube server, e.g. `[profiles.prod]`
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub token: Option<String>,
 }
 
-/// Returns the path to the config file: `<config_dir>/sonar-cli/config.toml`.
+impl ServerProfile {
+    pub fn is_empty(&self) -> bool {
+        self.url.is_none() && self.token.is_none()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(from = "RawConfig")]
+pub struct StoredConfig {
+    /// Profile used when `--profile` is not given [default: `default`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    /// Project keys used by project-scoped commands, most recent first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_projects: Vec<String>,
+    /// Named servers, e.g. `[profiles.prod] url = "..."`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ServerProfile>,
+    /// Per-command flag defaults, e.g. `[defaults.issues] severity = "CRITICAL"`
+    #[serde(default, skip_serializing_if = "CommandDefaults::is_empty")]
+    pub defaults: CommandDefaults,
+}
+
+/// Config file as written, including the flat `url`/`token` of the
+/// single-server format that predates profiles
+#[derive(Deserialize)]
+struct RawConfig {
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    token: Option<String>,
+    #[serde(default)]
+    default_profile: Option<String>,
+    #[serde(default)]
+    recent_projects: Vec<String>,
+    #[serde(default)]
+    profiles: BTreeMap<String, ServerProfile>,
+    #[serde(default)]
+    defaults: CommandDefaults,
+}
+
+impl From<RawConfig> for StoredConfig {
+    /// Move flat `url`/`token` into the `default` profile, unless one is already defined
+    fn from(raw: RawConfig) -> Self {
+        let mut profiles = raw.profiles;
+        let legacy = ServerProfile {
+            url: raw.url,
+            token: raw.token,
+        };
+        if !legacy.is_empty() {
+            profiles.entry(DEFAULT_PROFILE.to_string()).or_insert(legacy);
+        }
+        StoredConfig {
+            default_profile: raw.default_profile,
+            recent_projects: raw.recent_projects,
+            profiles,
+            defaults: raw.defaults,
+        }
+    }
+}
+
+impl StoredConfig {
+    /// Name of the profile to use: `requested`, else `default_profile`, else `default`
+    pub fn profile_name<'a>(&'a self, requested: Option<&'a str>) -> &'a str {
+        requested
+            .or(self.default_profile.as_deref())
+            .unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Credentials of the selected profile (see [`Self::profile_name`]).
+    ///
+    /// A profile named with `--profile` or `default_profile` must exist; the
+    /// implicit `default` profile may be missing, which yields no credentials.
+    pub fn server(&self, requested: Option<&str>) -> Result<ServerProfile, String> {
+        let name = self.profile_name(requested);
+        match self.profiles.get(name) {
+            Some(profile) => Ok(profile.clone()),
+            None if requested.is_none() && self.default_profile.is_none() => Ok(ServerProfile::default()),
+            None => {
+                let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                let known = if known.is_empty() { "none".to_string() } else { known.join(", ") };
+                Err(format!(
+                    "Unknown profile '{name}' (configured: {known}). Run `sonar-cli auth login --profile {name}` to add it."
+                ))
+            }
+        }
+    }
+
+    /// Move `project` to the front of the recent list, dropping the oldest past the cap
+    pub fn record_recent_project(&mut self, project: &str) {
+        self.recent_projects.retain(|p| p != project);
+        self.recent_projects.insert(0, project.to_string());
+        self.recent_projects.truncate(MAX_RECENT_PROJECTS);
+    }
+
+    /// Resolve `@N` to the N-th most recently used project; other values are returned as-is
+    pub fn resolve_project_ref(&self, value: &str) -> Result<String, String> {
+        let Some(index) = value.strip_prefix('@') else {
+            return Ok(value.to_string());
+        };
+        index
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| self.recent_projects.get(i))
+            .cloned()
+            .ok_or_else(|| {
+                format!(
+                    "No recent project {value} ({} remembered). Run `sonar-cli projects --recent` to list them.",
+                    self.recent_projects.len()
+                )
+            })
+    }
+}
+
+/// Environment variable that overrides the config file location
+pub const CONFIG_ENV: &str = "SONAR_CLI_CONFIG";
+
+/// Returns the path to the config file: `$SONAR_CLI_CONFIG` when set, else
+/// `<config_dir>/sonar-cli/config.toml`.
 pub fn config_path() -> Option<PathBuf> {
-    dirs::config_dir().map(|d| d.join("sonar-cli").join("config.toml"))
+    match std::env::var_os(CONFIG_ENV) {
+        Some(path) if !path.is_empty() => Some(PathBuf::from(path)),
+        _ => dirs::config_dir().map(|d| d.join("sonar-cli").join("config.toml")),
+    }
 }
 
 /// Load config from the default path. Returns default if missing or malformed.
@@ -23,23 +157,20 @@ pub fn load() -> StoredConfig {
     }
 }
 
-/// Save config to the default path. Creates parent directories as needed.
-pub fn save(config: &StoredConfig) -> Result<(), String> {
-    match config_path() {
-        Some(p) => save_to(config, &p),
-        None => Err("Could not determine config directory".to_string()),
-    }
-}
-
-/// Remove the config file. No-op if it does not exist.
-pub fn remove() -> Result<(), String> {
-    match config_path() {
-        Some(p) => remove_at(&p),
-        None => Err("Could not determine config directory".to_string()),
+/// Record `project` as the most recently used one in the config file.
+///
+/// Best effort: failures are logged and never stop the command.
+pub fn remember_project(project: &str) {
+    let Some(path) = config_path() else {
+        return;
+    };
+    if let Err(e) = remember_project_at(project, &path) {
+        tracing::debug!("Could not record recent project: {e}");
     }
 }
 
-fn load_from(path: &PathBuf) -> StoredConfig {
+/// Load config from `path`. Returns default if missing or malformed.
+pub fn load_from(path: &Path) -> StoredConfig {
     match std::fs::read_to_string(path) {
         Ok(contents) => match toml::from_str(&contents) {
             Ok(cfg) => cfg,
@@ -52,7 +183,8 @@ fn load_from(path: &PathBuf) -> StoredConfig {
     }
 }
 
-fn save_to(config: &StoredConfig, path: &PathBuf) -> Result<(), String> {
+/// Save config to `path`. Creates parent directories as needed.
+pub fn save_to(config: &StoredConfig, path: &Path) -> Result<(), String> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)
             .map_err(|e| format!("Failed to create config directory: {e}"))?;
@@ -62,7 +194,21 @@ fn save_to(config: &StoredConfig, path: &PathBuf) -> Result<(), String> {
     std::fs::write(path, contents).map_err(|e| format!("Failed to write config file: {e}"))
 }
 
-fn remove_at(path: &PathBuf) -> Result<(), String> {
+fn remember_project_at(project: &str, path: &Path) -> Result<(), String> {
+    let mut config: StoredConfig = match std::fs::read_to_string(path) {
+        // Never overwrite a config file we could not parse
+        Ok(contents) => toml::from_str(&contents).map_err(|e| format!("Malformed config: {e}"))?,
+        Err(_) => StoredConfig::default(),
+    };
+    if config.recent_projects.first().map(String::as_str) == Some(project) {
+        return Ok(());
+    }
+    config.record_recent_project(project);
+    save_to(&config, path)
+}
+
+/// Remove the config file at `path`. No-op if it does not exist.
+pub fn remove_at(path: &Path) -> Result<(), String> {
     match std::fs::remove_file(path) {
         Ok(()) => Ok(()),
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
@@ -81,8 +227,15 @@ mod tests {
         let _ = std::fs::remove_file(&path);
 
         let cfg = load_from(&path);
-        assert!(cfg.url.is_none());
-        assert!(cfg.token.is_none());
+        assert!(cfg.profiles.is_empty());
+        assert!(cfg.server(None).unwrap().is_empty());
+    }
+
+    fn server(url: &str, token: &str) -> ServerProfile {
+        ServerProfile {
+            url: Some(url.to_string()),
+            token: Some(token.to_string()),
+        }
     }
 
     #[test]
@@ -90,13 +243,13 @@ mod tests {
         let dir = std::env::temp_dir().join("sonar-cli-test-roundtrip");
         let path = dir.join("config.toml");
 
-        let config = StoredConfig {
-            url: Some("https://sonar.example.com".to_string()),
-            token: Some("squ_abc123".to_string()),
-        };
+        let mut config = StoredConfig::default();
+        config
+            .profiles
+            .insert(DEFAULT_PROFILE.to_string(), server("https://sonar.example.com", "squ_abc123"));
         save_to(&config, &path).unwrap();
 
-        let loaded = load_from(&path);
+        let loaded = load_from(&path).server(None).unwrap();
         assert_eq!(loaded.url.as_deref(), Some("https://sonar.example.com"));
         assert_eq!(loaded.token.as_deref(), Some("squ_abc123"));
 
@@ -105,6 +258,32 @@ mod tests {
         let _ = std::fs::remove_dir(&dir);
     }
 
+    #[test]
+    fn test_defaults_roundtrip() {
+        let dir = std::env::temp_dir().join("sonar-cli-test-defaults");
+        let path = dir.join("config.toml");
+        let _ = std::fs::create_dir_all(&dir);
+        std::fs::write(
+            &path,
+            "url = \"https://sonar.example.com\"\n\n[defaults.issues]\nseverity = \"CRITICAL\"\n",
+        )
+        .unwrap();
+
+        let loaded = load_from(&path);
+        assert_eq!(
+            loaded.defaults["issues"]["severity"].as_str(),
+            Some("CRITICAL")
+        );
+
+        // Saving (e.g. after `auth login`) keeps the defaults
+        save_to(&loaded, &path).unwrap();
+        assert!(load_from(&path).defaults.contains_key("issues"));
+
+        // cleanup
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_dir(&dir);
+    }
+
     #[test]
     fn test_load_malformed_returns_default() {
         let dir = std::env::temp_dir().join("sonar-cli-test-malformed");
@@ -113,8 +292,100 @@ mod tests {
         std::fs::write(&path, "this is not valid toml {{{{").unwrap();
 
         let cfg = load_from(&path);
-        assert!(cfg.url.is_none());
-        assert!(cfg.token.is_none());
+        assert!(cfg.profiles.is_empty());
+
+        // cleanup
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_dir(&dir);
+    }
+
+    #[test]
+    fn test_multi_profile_roundtrip() {
+        let dir = std::env::temp_dir().join("sonar-cli-test-profiles");
+        let path = dir.join("config.toml");
+
+        let mut config = StoredConfig {
+            default_profile: Some("staging".to_string()),
+            ..Default::default()
+        };
+        config.profiles.insert("local".to_string(), server("http://localhost:9000", "squ_local"));
+        config.profiles.insert("staging".to_string(), server("https://staging.example.com", "squ_stg"));
+        config.profiles.insert("prod".to_string(), server("https://sonar.example.com", "squ_prod"));
+        save_to(&config, &path).unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains("[profiles.prod]"), "{text}");
+
+        let loaded = load_from(&path);
+        assert_eq!(loaded.profiles, config.profiles);
+        assert_eq!(loaded.profile_name(None), "staging");
+        assert_eq!(loaded.server(None).unwrap().token.as_deref(), Some("squ_stg"));
+        assert_eq!(loaded.server(Some("prod")).unwrap().url.as_deref(), Some("https://sonar.example.com"));
+        let err = loaded.server(Some("qa")).unwrap_err();
+        assert!(err.contains("configured: local, prod, staging"), "{err}");
+
+        // cleanup
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_dir(&dir);
+    }
+
+    #[test]
+    fn test_legacy_flat_config_migrates_to_default_profile() {
+        let legacy: StoredConfig = toml::from_str(
+            "url = \"https://sonar.example.com\"\ntoken = \"squ_abc123\"\nrecent_projects = [\"api\"]\n",
+        )
+        .unwrap();
+        assert_eq!(legacy.profiles[DEFAULT_PROFILE], server("https://sonar.example.com", "squ_abc123"));
+        assert_eq!(legacy.server(None).unwrap(), server("https://sonar.example.com", "squ_abc123"));
+        assert_eq!(legacy.recent_projects, ["api"]);
+
+        // Saved back in the profile format, without the flat keys
+        let text = toml::to_string_pretty(&legacy).unwrap();
+        assert!(text.contains("[profiles.default]"), "{text}");
+        assert!(!text.starts_with("url"), "{text}");
+
+        // An explicit default profile wins over stale flat keys
+        let mixed: StoredConfig = toml::from_str(
+            "url = \"https://old.example.com\"\n\n[profiles.default]\nurl = \"https://new.example.com\"\n",
+        )
+        .unwrap();
+        assert_eq!(mixed.server(None).unwrap().url.as_deref(), Some("https://new.example.com"));
+    }
+
+    #[test]
+    fn test_server_without_profiles() {
+        let empty = StoredConfig::default();
+        assert!(empty.server(None).unwrap().is_empty());
+        assert!(empty.server(Some("prod")).is_err());
+
+        let dangling = StoredConfig {
+            default_profile: Some("prod".to_string()),
+            ..Default::default()
+        };
+        assert!(dangling.server(None).is_err());
+    }
+
+    #[test]
+    fn test_recent_projects_most_recent_first_and_capped() {
+        let dir = std::env::temp_dir().join("sonar-cli-test-recent");
+        let path = dir.join("config.toml");
+        let _ = std::fs::remove_file(&path);
+
+        for i in 0..MAX_RECENT_PROJECTS + 2 {
+            remember_project_at(&format!("proj-{i}"), &path).unwrap();
+        }
+        remember_project_at("proj-5", &path).unwrap();
+
+        let loaded = load_from(&path);
+        assert_eq!(loaded.recent_projects.len(), MAX_RECENT_PROJECTS);
+        assert_eq!(loaded.recent_projects[0], "proj-5");
+        assert_eq!(loaded.recent_projects[1], "proj-11");
+        assert_eq!(loaded.recent_projects.iter().filter(|p| *p == "proj-5").count(), 1);
+        assert_eq!(loaded.resolve_project_ref("@1").unwrap(), "proj-5");
+        assert_eq!(loaded.resolve_project_ref("@2").unwrap(), "proj-11");
+        assert_eq!(loaded.resolve_project_ref("plain").unwrap(), "plain");
+        assert!(loaded.resolve_project_ref("@0").is_err());
+        assert!(loaded.resolve_project_ref("@11").is_err());
 
         // cleanup
         let _ = std::fs::remove_file(&path);
@@ -0,0 +1,260 @@
+//! Per-command flag defaults from the `[defaults]` section of the config file
+//!
+//! ```toml
+//! [defaults.issues]
+//! severity = "CRITICAL"
+//! new-code = true
+//! ```
+//!
+//! Defaults are applied by inserting the flags into argv before the final
+//! parse, so a flag given on the command line always wins.
+
+use std::collections::BTreeMap;
+
+use clap::parser::ValueSource;
+
+/// Flag defaults keyed by subcommand name, then by long flag name
+pub type CommandDefaults = BTreeMap<String, BTreeMap<String, toml::Value>>;
+
+/// Render a config value as a CLI argument value
+fn value_to_arg(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(s) => Some(s.clone()),
+        toml::Value::Integer(i) => Some(i.to_string()),
+        toml::Value::Float(f) => Some(f.to_string()),
+        toml::Value::Boolean(b) => Some(b.to_string()),
+        toml::Value::Datetime(d) => Some(d.to_string()),
+        toml::Value::Array(items) => {
+            let parts: Option<Vec<String>> = items.iter().map(value_to_arg).collect();
+            parts.map(|p| p.join(","))
+        }
+        toml::Value::Table(_) => None,
+    }
+}
+
+/// Position of the invoked subcommand's token in `argv`, skipping the values
+/// of global options that come before it
+fn subcommand_index(
+    cmd: &clap::Command,
+    argv: &[String],
+    sub_cmd: &clap::Command,
+) -> Option<usize> {
+    let mut i = 1;
+    while i < argv.len() {
+        let token = argv[i].as_str();
+        if token == "--" {
+            return None;
+        }
+        if token == sub_cmd.get_name() || sub_cmd.get_all_aliases().any(|a| a == token) {
+            return Some(i);
+        }
+        let option = if let Some(long) = token.strip_prefix("--") {
+            (!long.contains('='))
+                .then(|| cmd.get_arguments().find(|a| a.get_long() == Some(long)))
+                .flatten()
+        } else if let Some(short) = token.strip_prefix('-').filter(|s| s.chars().count() == 1) {
+            let short = short.chars().next();
+            cmd.get_arguments().find(|a| a.get_short() == short)
+        } else {
+            None
+        };
+        if option.is_some_and(|a| a.get_action().takes_values()) {
+            i += 1;
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Add configured defaults for the invoked subcommand to `argv` for every flag
+/// that was not given on the command line (or via its env var).
+///
+/// Defaults go right after the subcommand token, so they bind to it rather
+/// than to a nested subcommand. A default that would not parse alongside the
+/// given args (e.g. it conflicts with a flag on the command line) is skipped.
+///
+/// Returns `argv` unchanged when it does not parse, so clap reports the error
+/// on the real parse.
+pub fn apply_command_defaults(
+    cmd: &clap::Command,
+    argv: Vec<String>,
+    defaults: &CommandDefaults,
+) -> Vec<String> {
+    if defaults.is_empty() {
+        return argv;
+    }
+    let Ok(matches) = cmd.clone().try_get_matches_from(&argv) else {
+        return argv;
+    };
+    let Some((name, sub_matches)) = matches.subcommand() else {
+        return argv;
+    };
+    let (Some(command_defaults), Some(sub_cmd)) = (defaults.get(name), cmd.find_subcommand(name))
+    else {
+        return argv;
+    };
+    let Some(at) = subcommand_index(cmd, &argv, sub_cmd).map(|i| i + 1) else {
+        return argv;
+    };
+
+    let mut argv = argv;
+    for (key, value) in command_defaults {
+        let long = key.replace('_', "-");
+        let Some(arg) = sub_cmd
+            .get_arguments()
+            .find(|a| a.get_long() == Some(long.as_str()))
+        else {
+            eprintln!("Ignoring unknown default '{name}.{key}' in config");
+            continue;
+        };
+        if matches!(
+            sub_matches.value_source(arg.get_id().as_str()),
+            Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+        ) {
+            continue;
+        }
+
+        let extra = if arg.get_action().takes_values() {
+            match value_to_arg(value) {
+                Some(v) => vec![format!("--{long}"), v],
+                None => {
+                    eprintln!("Ignoring default '{name}.{key}': unsupported value");
+                    continue;
+                }
+            }
+        } else if value.as_bool() == Some(true) {
+            vec![format!("--{long}")]
+        } else {
+            continue;
+        };
+
+        let mut trial = argv.clone();
+        trial.splice(at..at, extra);
+        if cmd.clone().try_get_matches_from(&trial).is_ok() {
+            argv = trial;
+        }
+    }
+    argv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cli, Command, HotspotAction};
+    use clap::{CommandFactory, Parser};
+
+    fn defaults(toml_src: &str) -> CommandDefaults {
+        toml::from_str(toml_src).unwrap()
+    }
+
+    fn parse(args: &[&str], defaults: &CommandDefaults) -> Cli {
+        let argv = std::iter::once("sonar-cli")
+            .chain(args.iter().copied())
+            .map(String::from)
+            .collect();
+        Cli::parse_from(apply_command_defaults(&Cli::command(), argv, defaults))
+    }
+
+    #[test]
+    fn test_default_used_when_flag_absent() {
+        let d = defaults("[issues]\nseverity = \"CRITICAL\"\nnew_code = true\nlimit = 20");
+        match parse(&["--project", "p", "issues"], &d).command {
+            Command::Issues(args) => {
+                assert_eq!(args.severity.as_deref(), Some("CRITICAL"));
+                assert!(args.new_code);
+                assert_eq!(args.limit, Some(20));
+            }
+            _ => panic!("expected issues command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_flag_overrides_default() {
+        let d = defaults("[issues]\nseverity = \"CRITICAL\"");
+        match parse(&["issues", "--severity", "MAJOR"], &d).command {
+            Command::Issues(args) => assert_eq!(args.severity.as_deref(), Some("MAJOR")),
+            _ => panic!("expected issues command"),
+        }
+    }
+
+    #[test]
+    fn test_defaults_only_apply_to_their_command() {
+        let d = defaults("[issues]\nseverity = \"CRITICAL\"\n\n[quality-gate]\nfail-on-error = true");
+        match parse(&["quality-gate"], &d).command {
+            Command::QualityGate { fail_on_error, .. } => assert!(fail_on_error),
+            _ => panic!("expected quality-gate command"),
+        }
+    }
+
+    #[test]
+    fn test_defaults_inserted_before_passthrough_args() {
+        let d = defaults("[scan]\nexclusions = [\"**/vendor/**\", \"**/*.gen.rs\"]");
+        match parse(&["scan", "--", "-Dsonar.verbose=true"], &d).command {
+            Command::Scan {
+                exclusions, extra, ..
+            } => {
+                assert_eq!(exclusions.as_deref(), Some("**/vendor/**,**/*.gen.rs"));
+                assert_eq!(extra, vec!["-Dsonar.verbose=true"]);
+            }
+            _ => panic!("expected scan command"),
+        }
+    }
+
+    #[test]
+    fn test_default_skipped_when_it_conflicts_with_given_flag() {
+        let d = defaults("[issues]\nstatus = \"OPEN\"\nseverity = \"CRITICAL\"");
+        match parse(&["issues", "--all-statuses"], &d).command {
+            Command::Issues(args) => {
+                assert!(args.all_statuses);
+                assert_eq!(args.status, None);
+                assert_eq!(args.severity.as_deref(), Some("CRITICAL"));
+            }
+            _ => panic!("expected issues command"),
+        }
+    }
+
+    #[test]
+    fn test_defaults_not_appended_to_nested_subcommand() {
+        let d = defaults("[hotspots]\nstatus = \"TO_REVIEW\"");
+        match parse(&["hotspots", "show", "AX1"], &d).command {
+            Command::Hotspots {
+                action: Some(HotspotAction::Show { hotspot }),
+                status,
+                ..
+            } => {
+                assert_eq!(hotspot, "AX1");
+                assert_eq!(status, None);
+            }
+            _ => panic!("expected hotspots show command"),
+        }
+        match parse(&["hotspots"], &d).command {
+            Command::Hotspots { status, .. } => assert_eq!(status.as_deref(), Some("TO_REVIEW")),
+            _ => panic!("expected hotspots command"),
+        }
+    }
+
+    #[test]
+    fn test_defaults_bind_to_subcommand_after_global_option_value() {
+        let d = defaults("[issues]\nseverity = \"CRITICAL\"");
+        match parse(&["--project", "issues", "issues"], &d).command {
+            Command::Issues(args) => assert_eq!(args.severity.as_deref(), Some("CRITICAL")),
+            _ => panic!("expected issues command"),
+        }
+    }
+
+    #[test]
+    fn test_unknown_default_ignored() {
+        let d = defaults("[issues]\nnot_a_flag = \"x\"\nnew_code = false");
+        match parse(&["issues"], &d).command {
+            Command::Issues(args) => assert!(!args.new_code),
+            _ => panic!("expected issues command"),
+        }
+    }
+
+    #[test]
+    fn test_value_to_arg() {
+        assert_eq!(value_to_arg(&toml::Value::Integer(5)).as_deref(), Some("5"));
+        assert_eq!(value_to_arg(&toml::Value::Boolean(true)).as_deref(), Some("true"));
+        assert!(value_to_arg(&toml::Value::Table(Default::default())).is_none());
+    }
+}
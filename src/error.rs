@@ -0,0 +1,152 @@
+//! Errors returned by commands, and the process exit code for each kind
+//!
+//! Commands return `Result<(), CliError>`; `main` prints the error and exits
+//! with [`CliError::exit_code`], so scripts can tell a bad token from an
+//! unreachable server without parsing stderr.
+
+use thiserror::Error;
+
+use crate::client::SonarQubeError;
+
+/// Process exit codes
+pub mod exit_code {
+    pub const SUCCESS: i32 = 0;
+    /// Request or analysis failed, invalid flag values, or the checked status is not OK
+    pub const FAILURE: i32 = 1;
+    /// `issues --fail-on-issues`/`--max-issues`/`--fail-on-severity` exceeded, or a
+    /// `measures --assert` failed
+    pub const THRESHOLD: i32 = 2;
+    /// Quality gate failed during `scan --gate-wait`
+    pub const GATE_FAILED: i32 = 3;
+    /// Token missing, invalid or lacking permission (HTTP 401/403)
+    pub const AUTH: i32 = 4;
+    /// Server unreachable: connection, TLS or proxy failure, or circuit open
+    pub const NETWORK: i32 = 5;
+    /// Project, component, issue or other resource not found (HTTP 404)
+    pub const NOT_FOUND: i32 = 6;
+}
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("{0}")]
+    Auth(String),
+
+    #[error("{0}")]
+    Network(String),
+
+    #[error("{0}")]
+    NotFound(String),
+
+    /// Invalid flag values or combinations, caught before any request
+    #[error("{0}")]
+    Validation(String),
+
+    #[error("Issue threshold exceeded: {0}")]
+    Threshold(String),
+
+    /// Any other failure: server errors, unexpected responses, failed analyses, I/O
+    #[error("{0}")]
+    Failed(String),
+
+    /// The outcome has already been printed (a failed gate, a scanner's own
+    /// exit code); exit with this code without further output
+    #[error("exit code {0}")]
+    Exit(i32),
+}
+
+impl CliError {
+    /// Classify a client error, prefixing its message with `context`
+    /// (e.g. "Failed to fetch issues")
+    pub fn api(context: impl std::fmt::Display, err: SonarQubeError) -> Self {
+        let message = format!("{context}: {err}");
+        match err {
+            SonarQubeError::Unauthorized | SonarQubeError::Forbidden => Self::Auth(message),
+            SonarQubeError::Api { status: 404, .. } => Self::NotFound(message),
+            SonarQubeError::Http(_) | SonarQubeError::Truncated(_) | SonarQubeError::CircuitOpen => {
+                Self::Network(message)
+            }
+            // The request was printed instead of sent; that is the whole job
+            SonarQubeError::DryRun => Self::Exit(exit_code::SUCCESS),
+            _ => Self::Failed(message),
+        }
+    }
+
+    /// A client could not be built from the configuration (bad proxy URL, CA file, ...)
+    pub fn client(err: SonarQubeError) -> Self {
+        Self::Validation(format!("Failed to create client: {err}"))
+    }
+
+    /// Writing formatted output failed
+    pub fn output(err: std::io::Error) -> Self {
+        Self::Failed(format!("Failed to write output: {err}"))
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Auth(_) => exit_code::AUTH,
+            Self::Network(_) => exit_code::NETWORK,
+            Self::NotFound(_) => exit_code::NOT_FOUND,
+            Self::Threshold(_) => exit_code::THRESHOLD,
+            Self::Validation(_) | Self::Failed(_) => exit_code::FAILURE,
+            Self::Exit(code) => *code,
+        }
+    }
+}
+
+/// Print `result`'s error, if any, and return the exit code it maps to
+pub fn report(result: Result<(), CliError>) -> i32 {
+    match result {
+        Ok(()) => exit_code::SUCCESS,
+        Err(CliError::Exit(code)) => code,
+        Err(e) => {
+            eprintln!("{e}");
+            e.exit_code()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_error(status: u16) -> SonarQubeError {
+        SonarQubeError::Api {
+            status,
+            message: "denied".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_401_maps_to_auth_with_token_hint() {
+        let err = CliError::api("Failed to fetch issues", SonarQubeError::Unauthorized);
+        assert!(matches!(err, CliError::Auth(_)), "{err:?}");
+        assert_eq!(err.exit_code(), exit_code::AUTH);
+        assert_eq!(
+            err.to_string(),
+            "Failed to fetch issues: Authentication required: set --token or SONAR_TOKEN"
+        );
+    }
+
+    #[test]
+    fn test_error_kinds_and_exit_codes() {
+        let not_found = CliError::api("Failed", api_error(404));
+        assert!(matches!(not_found, CliError::NotFound(_)));
+        assert_eq!(not_found.exit_code(), exit_code::NOT_FOUND);
+
+        let network = CliError::api("Failed", SonarQubeError::Http("connection refused".to_string()));
+        assert_eq!(network.exit_code(), exit_code::NETWORK);
+        assert_eq!(CliError::api("Failed", SonarQubeError::CircuitOpen).exit_code(), exit_code::NETWORK);
+
+        assert_eq!(CliError::api("Failed", api_error(500)).exit_code(), exit_code::FAILURE);
+        assert_eq!(CliError::Validation("bad".to_string()).exit_code(), exit_code::FAILURE);
+        assert_eq!(CliError::Threshold("3 > 2".to_string()).exit_code(), exit_code::THRESHOLD);
+    }
+
+    #[test]
+    fn test_report() {
+        assert_eq!(report(Ok(())), exit_code::SUCCESS);
+        assert_eq!(report(Err(CliError::Exit(exit_code::GATE_FAILED))), exit_code::GATE_FAILED);
+        assert_eq!(report(Err(CliError::api("Failed", SonarQubeError::Forbidden))), exit_code::AUTH);
+        assert_eq!(report(Err(CliError::api("Failed", SonarQubeError::DryRun))), exit_code::SUCCESS);
+    }
+}
@@ -3,7 +3,11 @@
 use serde::Serialize;
 
 use crate::client::{SonarQubeClient, SonarQubeError};
-use crate::types::{DuplicationsResponse, Measure, TreeComponent};
+use crate::paging;
+use crate::types::{
+    severity, DuplicationsResponse, Facet, HistoryValue, Measure, MeasureHistory, MeasuresResponse,
+    SonarIssue, SourceLine, TreeComponent,
+};
 
 /// Extended SonarQube data for downstream use
 #[derive(Debug, Clone, Serialize)]
@@ -40,6 +44,607 @@ pub struct FileCoverage {
     pub lines_to_cover: u32,
 }
 
+/// Index of a `report` bundle, written as `summary.json` next to the reports
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReportSummary {
+    pub project: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Quality gate status, when the gate could be fetched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality_gate: Option<String>,
+    /// Report file names written to the output directory
+    pub files: Vec<String>,
+    /// Reports that could not be produced, keyed by name
+    pub errors: std::collections::BTreeMap<String, String>,
+}
+
+/// A metric value on two branches and the numeric difference between them
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MeasureComparison {
+    pub metric: String,
+    pub current: Option<String>,
+    pub base: Option<String>,
+    /// `current - base`, when both values are numeric
+    pub delta: Option<f64>,
+}
+
+/// Pair up measures from two branches by metric key.
+///
+/// Metrics keep the order of `current`; metrics only present on `base` follow.
+pub fn compare_measures(current: &MeasuresResponse, base: &MeasuresResponse) -> Vec<MeasureComparison> {
+    let value_of = |response: &MeasuresResponse, metric: &str| {
+        response
+            .component
+            .measures
+            .iter()
+            .find(|m| m.metric == metric)
+            .and_then(|m| m.value.clone())
+    };
+
+    let mut metrics: Vec<&str> = Vec::new();
+    for m in current.component.measures.iter().chain(&base.component.measures) {
+        if !metrics.contains(&m.metric.as_str()) {
+            metrics.push(&m.metric);
+        }
+    }
+
+    metrics
+        .into_iter()
+        .map(|metric| {
+            let current = value_of(current, metric);
+            let base = value_of(base, metric);
+            let delta = numeric_delta(current.as_deref(), base.as_deref());
+            MeasureComparison {
+                metric: metric.to_string(),
+                current,
+                base,
+                delta,
+            }
+        })
+        .collect()
+}
+
+/// `current - base` when both values are numeric
+fn numeric_delta(current: Option<&str>, base: Option<&str>) -> Option<f64> {
+    let current = current?.parse::<f64>().ok()?;
+    let base = base?.parse::<f64>().ok()?;
+    Some(current - base)
+}
+
+/// A metric's latest value and its change since the previous analysis
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MeasureTrend {
+    pub metric: String,
+    pub current: Option<String>,
+    pub previous: Option<String>,
+    /// `current - previous`, when both values are numeric
+    pub delta: Option<f64>,
+}
+
+/// Latest value and change for each metric, from its last two history points
+pub fn measure_trends(history: &[MeasureHistory]) -> Vec<MeasureTrend> {
+    history
+        .iter()
+        .map(|m| {
+            let mut points = m.history.iter().rev().map(|h| h.value.clone());
+            let current = points.next().flatten();
+            let previous = points.next().flatten();
+            MeasureTrend {
+                metric: m.metric.clone(),
+                delta: numeric_delta(current.as_deref(), previous.as_deref()),
+                current,
+                previous,
+            }
+        })
+        .collect()
+}
+
+/// Arrow for the direction of a change: ↑ increase, ↓ decrease, → no change
+pub fn trend_arrow(delta: f64) -> &'static str {
+    if delta > 0.0 {
+        "↑"
+    } else if delta < 0.0 {
+        "↓"
+    } else {
+        "→"
+    }
+}
+
+/// A metric's value at two points in time and the change between them
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MetricDiff {
+    pub metric: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// `to - from`, when both values are numeric
+    pub delta: Option<f64>,
+}
+
+/// True for a plain `YYYY-MM-DD` date, as opposed to an analysis key
+pub fn is_plain_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 10
+        && b.iter()
+            .enumerate()
+            .all(|(i, c)| if i == 4 || i == 7 { *c == b'-' } else { c.is_ascii_digit() })
+}
+
+/// Value of the last history point dated at or before `cutoff`.
+///
+/// `cutoff` is either a plain date, which then covers the whole day, or a
+/// full timestamp in the server's format. `None` cutoff means the latest point.
+fn value_at(points: &[HistoryValue], cutoff: Option<&str>) -> Option<String> {
+    points
+        .iter()
+        .filter(|p| match cutoff {
+            Some(c) => p.date.get(..c.len()).unwrap_or(&p.date) <= c,
+            None => true,
+        })
+        .max_by(|a, b| a.date.cmp(&b.date))
+        .and_then(|p| p.value.clone())
+}
+
+/// Each metric's value as of `from` and as of `to` (latest when `None`)
+pub fn diff_history(history: &[MeasureHistory], from: &str, to: Option<&str>) -> Vec<MetricDiff> {
+    history
+        .iter()
+        .map(|m| {
+            let old = value_at(&m.history, Some(from));
+            let new = value_at(&m.history, to);
+            MetricDiff {
+                metric: m.metric.clone(),
+                delta: numeric_delta(new.as_deref(), old.as_deref()),
+                from: old,
+                to: new,
+            }
+        })
+        .collect()
+}
+
+/// Measures history pivoted into one row per date and one column per metric
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HistoryTable {
+    pub metrics: Vec<String>,
+    pub rows: Vec<HistoryRow>,
+}
+
+/// A single date in a [`HistoryTable`]; `values` lines up with `HistoryTable::metrics`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HistoryRow {
+    pub date: String,
+    pub values: Vec<Option<String>>,
+}
+
+/// Pivot per-metric histories into a single table aligned by date.
+///
+/// Rows are sorted by date; a metric without a point on a given date gets `None`.
+pub fn pivot_history(measures: &[MeasureHistory]) -> HistoryTable {
+    let metrics: Vec<String> = measures.iter().map(|m| m.metric.clone()).collect();
+    let mut by_date: std::collections::BTreeMap<&str, Vec<Option<String>>> =
+        std::collections::BTreeMap::new();
+
+    for (col, measure) in measures.iter().enumerate() {
+        for point in &measure.history {
+            let row = by_date
+                .entry(point.date.as_str())
+                .or_insert_with(|| vec![None; metrics.len()]);
+            row[col] = point.value.clone();
+        }
+    }
+
+    let rows = by_date
+        .into_iter()
+        .map(|(date, values)| HistoryRow {
+            date: date.to_string(),
+            values,
+        })
+        .collect();
+    HistoryTable { metrics, rows }
+}
+
+/// Measures for several projects: one row per metric, one column per project
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MeasuresMatrix {
+    pub projects: Vec<String>,
+    pub rows: Vec<MatrixRow>,
+}
+
+/// A single metric in a [`MeasuresMatrix`]; `values` lines up with `MeasuresMatrix::projects`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MatrixRow {
+    pub metric: String,
+    pub values: Vec<Option<String>>,
+}
+
+/// Lay out per-project measures as a metric × project matrix.
+///
+/// Rows follow `metrics`; a project without a value for a metric (or whose
+/// fetch failed, `None`) gets `None` in that cell.
+pub fn measures_matrix(
+    metrics: &[&str],
+    projects: &[(String, Option<MeasuresResponse>)],
+) -> MeasuresMatrix {
+    let rows = metrics
+        .iter()
+        .map(|metric| MatrixRow {
+            metric: metric.to_string(),
+            values: projects
+                .iter()
+                .map(|(_, response)| {
+                    response
+                        .as_ref()?
+                        .component
+                        .measures
+                        .iter()
+                        .find(|m| m.metric == *metric)
+                        .and_then(|m| m.value.clone())
+                })
+                .collect(),
+        })
+        .collect();
+    MeasuresMatrix {
+        projects: projects.iter().map(|(key, _)| key.clone()).collect(),
+        rows,
+    }
+}
+
+/// Seconds since the Unix epoch, 0 if the clock is before it
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// An analysis date (`2026-03-01T10:00:00+0100`) ready for a query string:
+/// its `+hhmm` offset would otherwise reach the server as a space
+pub fn encode_query_date(date: &str) -> String {
+    date.replace('+', "%2B")
+}
+
+/// Percent-encode a query string value, leaving the `,` list separator and
+/// the `:` and `/` of rule keys and paths readable
+pub fn encode_query_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~,:/".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Format seconds since the Unix epoch as an RFC 3339 UTC timestamp
+/// (`2026-01-31T09:05:00Z`)
+pub fn format_utc_timestamp(epoch_secs: u64) -> String {
+    let days = (epoch_secs / 86_400) as i64;
+    let secs_of_day = epoch_secs % 86_400;
+
+    // Civil date from days since 1970-01-01 (proleptic Gregorian calendar)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60
+    )
+}
+
+/// Parse a SonarQube timestamp (`2026-01-01T10:00:00+0000`) into seconds since the epoch.
+///
+/// The offset may be `+hhmm`, `+hh:mm`, `Z` or absent (UTC).
+pub fn parse_sonar_timestamp(s: &str) -> Option<i64> {
+    let field = |range: std::ops::Range<usize>| s.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let offset = match s.get(19..)? {
+        "" | "Z" => 0,
+        tz => {
+            let sign = match tz.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let digits = tz[1..].replace(':', "");
+            let hours = digits.get(0..2)?.parse::<i64>().ok()?;
+            let minutes = digits.get(2..4)?.parse::<i64>().ok()?;
+            sign * (hours * 3_600 + minutes * 60)
+        }
+    };
+
+    // Days since 1970-01-01 from a civil date (proleptic Gregorian calendar)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    Some(days * 86_400 + hour * 3_600 + minute * 60 + second - offset)
+}
+
+/// Seconds from `submitted_at` to `executed_at`; `None` while the task is still
+/// running or when either timestamp cannot be parsed
+pub fn task_duration_secs(submitted_at: &str, executed_at: Option<&str>) -> Option<u64> {
+    let start = parse_sonar_timestamp(submitted_at)?;
+    let end = parse_sonar_timestamp(executed_at?)?;
+    u64::try_from(end - start).ok()
+}
+
+/// Human-readable duration: `45s`, `1m 23s`, `2h 5m 0s`
+pub fn format_duration(secs: u64) -> String {
+    let (hours, minutes, seconds) = (secs / 3_600, secs % 3_600 / 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes}m {seconds}s")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// Minutes in a SonarQube remediation "day" (8h)
+const EFFORT_MINUTES_PER_DAY: u64 = 8 * 60;
+
+/// Parse a SonarQube effort string (`6min`, `1h`, `2d`, `1h30min`, `1d 2h`) into
+/// minutes, with 8-hour days. `None` for empty or malformed input.
+pub fn parse_effort(effort: &str) -> Option<u64> {
+    let mut rest = effort.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    let mut total: u64 = 0;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let amount: u64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let (per_unit, unit_len) = if rest.starts_with("min") {
+            (1, 3)
+        } else if rest.starts_with('h') {
+            (60, 1)
+        } else if rest.starts_with('d') {
+            (EFFORT_MINUTES_PER_DAY, 1)
+        } else {
+            return None;
+        };
+        total = total.checked_add(amount.checked_mul(per_unit)?)?;
+        rest = rest[unit_len..].trim_start();
+    }
+    Some(total)
+}
+
+/// Format minutes of effort the way SonarQube does: `3h 20min`, `1d 2h`, `0min`
+pub fn format_effort(minutes: u64) -> String {
+    let days = minutes / EFFORT_MINUTES_PER_DAY;
+    let hours = minutes % EFFORT_MINUTES_PER_DAY / 60;
+    let mins = minutes % 60;
+    let parts: Vec<String> = [(days, "d"), (hours, "h"), (mins, "min")]
+        .into_iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, unit)| format!("{n}{unit}"))
+        .collect();
+    if parts.is_empty() {
+        "0min".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Remediation effort of a set of issues (`issues --show-effort`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EffortSummary {
+    pub total_minutes: u64,
+    /// Most severe first; severities without issues are left out
+    pub by_severity: Vec<SeverityEffort>,
+    /// Issues whose effort was missing or could not be parsed
+    pub unknown: usize,
+}
+
+/// Remediation effort of the issues at one severity
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SeverityEffort {
+    pub severity: String,
+    pub minutes: u64,
+}
+
+/// Sum each issue's `effort` (or legacy `debt`), overall and per severity
+pub fn summarize_effort(issues: &[SonarIssue]) -> EffortSummary {
+    let mut summary = EffortSummary::default();
+    let mut per_severity = [0u64; severity::ALL.len()];
+    let mut seen = [false; severity::ALL.len()];
+    for issue in issues {
+        let level = severity::ordinal(&issue.severity);
+        seen[level] = true;
+        match issue.effort.as_deref().or(issue.debt.as_deref()).and_then(parse_effort) {
+            Some(minutes) => {
+                summary.total_minutes += minutes;
+                per_severity[level] += minutes;
+            }
+            None => summary.unknown += 1,
+        }
+    }
+    summary.by_severity = severity::ALL
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(level, _)| seen[*level])
+        .map(|(level, name)| SeverityEffort {
+            severity: name.to_string(),
+            minutes: per_severity[level],
+        })
+        .collect();
+    summary
+}
+
+/// Client-side slice of a result list (`--skip` / `--top`), applied after sorting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Window {
+    pub skip: usize,
+    pub top: Option<usize>,
+}
+
+impl Window {
+    /// The part of a page of `len` items, starting at overall position `offset`,
+    /// that falls inside the window
+    pub fn page_range(&self, offset: usize, len: usize) -> std::ops::Range<usize> {
+        let end = match self.top {
+            Some(top) => self.skip.saturating_add(top),
+            None => usize::MAX,
+        };
+        let start = self.skip.saturating_sub(offset).min(len);
+        let stop = end.saturating_sub(offset).min(len);
+        start..stop.max(start)
+    }
+
+    /// Keep only the items inside the window
+    pub fn apply<T>(&self, mut items: Vec<T>) -> Vec<T> {
+        let range = self.page_range(0, items.len());
+        items.truncate(range.end);
+        items.drain(..range.start);
+        items
+    }
+}
+
+/// Format a count for display: `1234567` -> `1,234,567`, or `1.2M` when abbreviated.
+///
+/// Abbreviation keeps one decimal and drops a trailing `.0` (`12k`, `3.4k`, `1.2M`).
+pub fn humanize_count(n: i64, abbreviate: bool) -> String {
+    let magnitude = n.unsigned_abs();
+    if abbreviate && magnitude >= 1_000 {
+        let (scaled, suffix) = if magnitude >= 1_000_000_000 {
+            (n as f64 / 1e9, "G")
+        } else if magnitude >= 1_000_000 {
+            (n as f64 / 1e6, "M")
+        } else {
+            (n as f64 / 1e3, "k")
+        };
+        let text = format!("{scaled:.1}");
+        let text = text.strip_suffix(".0").unwrap_or(&text);
+        return format!("{text}{suffix}");
+    }
+
+    let digits = magnitude.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    if n < 0 {
+        grouped.push('-');
+    }
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+/// Metric key holding the quality gate status (`OK`/`WARN`/`ERROR`)
+pub const ALERT_STATUS_METRIC: &str = "alert_status";
+
+/// A point where the quality gate status changed
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GateChange {
+    pub date: String,
+    pub status: String,
+    pub previous: Option<String>,
+}
+
+/// Collapse an `alert_status` history into the dates where the gate status changed.
+///
+/// Points are taken in date order and points without a value are skipped, so
+/// the first entry is the earliest known status and every later entry is a flip.
+pub fn gate_timeline(measures: &[MeasureHistory]) -> Vec<GateChange> {
+    let Some(measure) = measures.iter().find(|m| m.metric == ALERT_STATUS_METRIC) else {
+        return Vec::new();
+    };
+    let mut points: Vec<(&str, &str)> = measure
+        .history
+        .iter()
+        .filter_map(|p| p.value.as_deref().map(|v| (p.date.as_str(), v)))
+        .collect();
+    points.sort_by_key(|(date, _)| *date);
+
+    let mut changes: Vec<GateChange> = Vec::new();
+    for (date, status) in points {
+        let previous = changes.last().map(|c| c.status.clone());
+        if previous.as_deref() != Some(status) {
+            changes.push(GateChange {
+                date: date.to_string(),
+                status: status.to_string(),
+                previous,
+            });
+        }
+    }
+    changes
+}
+
+/// Date of the most recent analysis whose quality gate passed (`OK`), if any
+pub fn last_green_analysis(measures: &[MeasureHistory]) -> Option<String> {
+    measures
+        .iter()
+        .find(|m| m.metric == ALERT_STATUS_METRIC)?
+        .history
+        .iter()
+        .filter(|p| p.value.as_deref() == Some("OK"))
+        .map(|p| p.date.as_str())
+        .max()
+        .map(str::to_string)
+}
+
+/// Issues opened and resolved at one severity over a range of analyses
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SeverityChange {
+    pub severity: String,
+    pub opened: usize,
+    pub resolved: usize,
+    /// `opened - resolved`
+    pub net: i64,
+}
+
+/// Count for `val` in the facet named `property`, 0 when absent
+fn facet_count(facets: &[Facet], property: &str, val: &str) -> usize {
+    facets
+        .iter()
+        .filter(|f| f.property == property)
+        .flat_map(|f| &f.values)
+        .find(|v| v.val == val)
+        .map_or(0, |v| v.count)
+}
+
+/// Per-severity changes, most severe first, from the `severities` facet of
+/// issues raised since the baseline and the issues resolved since it
+pub fn severity_changes(opened: &[Facet], resolved: &[SonarIssue]) -> Vec<SeverityChange> {
+    severity::ALL
+        .iter()
+        .rev()
+        .map(|sev| {
+            let opened = facet_count(opened, "severities", sev);
+            let resolved = resolved.iter().filter(|i| i.severity == *sev).count();
+            SeverityChange {
+                severity: sev.to_string(),
+                opened,
+                resolved,
+                net: opened as i64 - resolved as i64,
+            }
+        })
+        .collect()
+}
+
 /// Extract file path from component key (strips `project:` prefix)
 pub fn extract_path(component: &str, project_key: &str) -> String {
     if let Some(path) = component.strip_prefix(&format!("{}:", project_key)) {
@@ -59,6 +664,39 @@ pub fn parse_measure<T: std::str::FromStr + Default>(measures: &[Measure], metri
         .unwrap_or_default()
 }
 
+/// Whether a command's own result is what a never-analyzed project returns:
+/// empty, or a 404
+pub fn empty_or_missing<T>(
+    result: &Result<T, SonarQubeError>,
+    is_empty: impl FnOnce(&T) -> bool,
+) -> bool {
+    match result {
+        Ok(value) => is_empty(value),
+        Err(SonarQubeError::Api { status: 404, .. }) => true,
+        Err(_) => false,
+    }
+}
+
+/// Whether a project has never completed an analysis.
+///
+/// Call only after the command's own result came back empty or missing (see
+/// [`empty_or_missing`]), so analyzed projects cost no extra request. Only
+/// projects (`TRK`) qualify; portfolios and applications have no analysis of
+/// their own. If the lookup fails, returns false so the command's own result
+/// stands.
+pub async fn never_analyzed(client: &SonarQubeClient, project_key: &str) -> bool {
+    match client.get_component(project_key).await {
+        Ok(response) => {
+            let component = response.component;
+            component.analysis_date.is_none() && component.qualifier.as_deref() == Some("TRK")
+        }
+        Err(e) => {
+            tracing::debug!(error = %e, "Could not check whether {project_key} was analyzed");
+            false
+        }
+    }
+}
+
 /// Fetch extended data (duplications + coverage per file)
 pub async fn fetch_extended_data(
     client: &SonarQubeClient,
@@ -69,15 +707,19 @@ pub async fn fetch_extended_data(
         .await
         .unwrap_or_default();
 
-    let mut duplications = Vec::new();
-    for file in files_with_dups {
-        if let Some(mut dup) = convert_to_duplication(&file, project_key) {
-            if let Ok(dup_response) = client.get_duplications(&file.key).await {
-                dup.blocks = extract_duplication_blocks(&dup_response, &file.key);
+    // Block details are one request per file; a failed request leaves that file without blocks
+    let fetches = files_with_dups.iter().filter_map(|file| {
+        let mut dup = convert_to_duplication(file, project_key)?;
+        Some(async move {
+            match client.get_duplications(&file.key).await {
+                Ok(dup_response) => dup.blocks = extract_duplication_blocks(&dup_response, &file.key),
+                Err(e) => tracing::warn!("Could not fetch duplications for {}: {e}", file.key),
             }
-            duplications.push(dup);
-        }
-    }
+            dup
+        })
+    });
+    let mut duplications = paging::join_bounded(client.concurrency(), fetches).await;
+    duplications.sort_by(|a, b| a.file.cmp(&b.file));
 
     let mut coverage_gaps: Vec<FileCoverage> = client
         .get_files_coverage(project_key)
@@ -140,6 +782,63 @@ fn convert_to_coverage(file: &TreeComponent, project_key: &str) -> Option<FileCo
     })
 }
 
+/// Strip the syntax-highlighting markup SonarQube adds to source lines and
+/// decode the HTML entities it escapes
+pub fn strip_html_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Lines `line - context` to `line + context` of `component`, as plain text
+pub async fn fetch_snippet(
+    client: &SonarQubeClient,
+    component: &str,
+    line: usize,
+    context: usize,
+) -> Result<Vec<SourceLine>, SonarQubeError> {
+    let from = line.saturating_sub(context).max(1);
+    let lines = client.get_source_show(component, Some(from), Some(line + context)).await?;
+    Ok(lines
+        .into_iter()
+        .map(|l| SourceLine { code: strip_html_tags(&l.code), ..l })
+        .collect())
+}
+
+/// Render descriptive HTML (rule descriptions) as plain text: block elements
+/// end a line, list items get a bullet, and blank lines collapse to one
+pub fn html_to_text(html: &str) -> String {
+    let mut marked = html.replace("<li>", "<li>- ");
+    for tag in ["</p>", "</pre>", "</li>", "</ul>", "</ol>", "</h2>", "</h3>", "</h4>", "<br>", "<br/>"] {
+        marked = marked.replace(tag, &format!("{tag}\n"));
+    }
+    let text = strip_html_tags(&marked);
+
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim_end) {
+        if line.is_empty() && lines.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
 fn extract_duplication_blocks(
     response: &DuplicationsResponse,
     current_file_key: &str,
@@ -178,7 +877,7 @@ fn extract_duplication_blocks(
 mod tests {
     use super::*;
     use crate::types::Measure;
-    use wiremock::matchers::{method, path};
+    use wiremock::matchers::{method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
     use crate::client::{SonarQubeConfig, SonarQubeClient};
 
@@ -190,6 +889,365 @@ mod tests {
         Some(MockServer::builder().listener(listener).start().await)
     }
 
+    fn measures_response(values: &[(&str, &str)]) -> MeasuresResponse {
+        MeasuresResponse {
+            component: crate::types::MeasuresComponent {
+                key: "proj".to_string(),
+                measures: values
+                    .iter()
+                    .map(|(metric, value)| Measure {
+                        metric: metric.to_string(),
+                        value: Some(value.to_string()),
+                        period: None,
+                    })
+                    .collect(),
+            },
+            metrics: vec![],
+        }
+    }
+
+    #[test]
+    fn test_compare_measures_deltas() {
+        let current = measures_response(&[("coverage", "82.5"), ("bugs", "3"), ("alert_status", "OK")]);
+        let base = measures_response(&[("coverage", "80.0"), ("bugs", "5"), ("ncloc", "1200")]);
+        let rows = compare_measures(&current, &base);
+
+        let metrics: Vec<&str> = rows.iter().map(|r| r.metric.as_str()).collect();
+        assert_eq!(metrics, vec!["coverage", "bugs", "alert_status", "ncloc"]);
+        assert_eq!(rows[0].delta, Some(2.5));
+        assert_eq!(rows[1].delta, Some(-2.0));
+        // Non-numeric values have no delta
+        assert_eq!(rows[2].delta, None);
+        // Missing on the current branch
+        assert_eq!(rows[3].current, None);
+        assert_eq!(rows[3].base.as_deref(), Some("1200"));
+        assert_eq!(rows[3].delta, None);
+    }
+
+    fn history(metric: &str, points: &[(&str, &str)]) -> MeasureHistory {
+        MeasureHistory {
+            metric: metric.to_string(),
+            history: points
+                .iter()
+                .map(|(date, value)| crate::types::HistoryValue {
+                    date: date.to_string(),
+                    value: Some(value.to_string()),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_measure_trends_increase() {
+        let trends = measure_trends(&[history("coverage", &[("2026-01-01", "80.0"), ("2026-02-01", "82.5")])]);
+        assert_eq!(trends[0].current.as_deref(), Some("82.5"));
+        assert_eq!(trends[0].previous.as_deref(), Some("80.0"));
+        assert_eq!(trends[0].delta, Some(2.5));
+        assert_eq!(trend_arrow(2.5), "↑");
+    }
+
+    #[test]
+    fn test_measure_trends_decrease() {
+        let trends = measure_trends(&[history("bugs", &[("2026-01-01", "5"), ("2026-02-01", "3")])]);
+        assert_eq!(trends[0].delta, Some(-2.0));
+        assert_eq!(trend_arrow(-2.0), "↓");
+    }
+
+    #[test]
+    fn test_measure_trends_no_change() {
+        let trends = measure_trends(&[history("ncloc", &[("2026-01-01", "1200"), ("2026-02-01", "1200")])]);
+        assert_eq!(trends[0].delta, Some(0.0));
+        assert_eq!(trend_arrow(0.0), "→");
+    }
+
+    #[test]
+    fn test_measure_trends_single_point_or_non_numeric() {
+        let trends = measure_trends(&[
+            history("coverage", &[("2026-02-01", "82.5")]),
+            history("alert_status", &[("2026-01-01", "ERROR"), ("2026-02-01", "OK")]),
+        ]);
+        assert_eq!(trends[0].current.as_deref(), Some("82.5"));
+        assert_eq!(trends[0].previous, None);
+        assert_eq!(trends[0].delta, None);
+        assert_eq!(trends[1].delta, None);
+    }
+
+    #[test]
+    fn test_diff_history_two_points() {
+        let history = [history(
+            "coverage",
+            &[("2026-01-01T10:00:00+0000", "80.0"), ("2026-02-01T10:00:00+0000", "76.5")],
+        )];
+        let diff = diff_history(&history, "2026-01-15", Some("2026-02-01"));
+        assert_eq!(diff[0].from.as_deref(), Some("80.0"));
+        assert_eq!(diff[0].to.as_deref(), Some("76.5"));
+        assert_eq!(diff[0].delta, Some(-3.5));
+
+        // Full timestamps from resolved analysis keys select the exact point
+        let diff = diff_history(&history, "2026-01-01T10:00:00+0000", None);
+        assert_eq!(diff[0].from.as_deref(), Some("80.0"));
+        assert_eq!(diff[0].to.as_deref(), Some("76.5"));
+    }
+
+    #[test]
+    fn test_diff_history_no_point_before_from() {
+        let history = [history("bugs", &[("2026-02-01T10:00:00+0000", "4")])];
+        let diff = diff_history(&history, "2026-01-01", None);
+        assert_eq!(diff[0].from, None);
+        assert_eq!(diff[0].to.as_deref(), Some("4"));
+        assert_eq!(diff[0].delta, None);
+    }
+
+    #[test]
+    fn test_is_plain_date() {
+        assert!(is_plain_date("2026-01-15"));
+        assert!(!is_plain_date("AU-TpxcA-iU5OvuD2FL0"));
+        assert!(!is_plain_date("2026-01-15T10:00:00+0000"));
+        assert!(!is_plain_date("2026/01/15"));
+    }
+
+    #[test]
+    fn test_measures_matrix_aligns_projects() {
+        let projects = vec![
+            ("api".to_string(), Some(measures_response(&[("coverage", "82.5"), ("bugs", "3")]))),
+            ("web".to_string(), Some(measures_response(&[("coverage", "64.0")]))),
+            ("gone".to_string(), None),
+        ];
+        let matrix = measures_matrix(&["coverage", "bugs"], &projects);
+        assert_eq!(matrix.projects, vec!["api", "web", "gone"]);
+        assert_eq!(matrix.rows[0].metric, "coverage");
+        assert_eq!(
+            matrix.rows[0].values,
+            vec![Some("82.5".to_string()), Some("64.0".to_string()), None]
+        );
+        assert_eq!(matrix.rows[1].values, vec![Some("3".to_string()), None, None]);
+    }
+
+    #[test]
+    fn test_encode_query_date() {
+        assert_eq!(encode_query_date("2026-03-01T10:00:00+0100"), "2026-03-01T10:00:00%2B0100");
+        assert_eq!(encode_query_date("2026-03-01T10:00:00Z"), "2026-03-01T10:00:00Z");
+    }
+
+    #[test]
+    fn test_encode_query_value() {
+        assert_eq!(encode_query_value("CRITICAL,BLOCKER"), "CRITICAL,BLOCKER");
+        assert_eq!(encode_query_value("java:S2259"), "java:S2259");
+        assert_eq!(
+            encode_query_value("jane doe&x=1+2#é"),
+            "jane%20doe%26x%3D1%2B2%23%C3%A9"
+        );
+    }
+
+    #[test]
+    fn test_format_utc_timestamp() {
+        assert_eq!(format_utc_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_utc_timestamp(1_792_148_645), "2026-10-16T11:04:05Z");
+    }
+
+    #[test]
+    fn test_task_duration_from_timestamps() {
+        assert_eq!(parse_sonar_timestamp("2026-10-16T11:04:05Z"), Some(1_792_148_645));
+        assert_eq!(parse_sonar_timestamp("2026-10-16T13:04:05+0200"), Some(1_792_148_645));
+        assert_eq!(parse_sonar_timestamp("2026-10-16T10:34:05-00:30"), Some(1_792_148_645));
+        assert_eq!(parse_sonar_timestamp("not a date"), None);
+
+        // Offsets are applied before subtracting
+        let duration = task_duration_secs("2026-01-31T23:59:10+0000", Some("2026-02-01T01:00:33+0100"));
+        assert_eq!(duration, Some(83));
+        assert_eq!(format_duration(83), "1m 23s");
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(7_500), "2h 5m 0s");
+
+        // Still running: no executed timestamp yet
+        assert_eq!(task_duration_secs("2026-01-31T23:59:10+0000", None), None);
+    }
+
+    #[test]
+    fn test_pivot_history_partial_overlap() {
+        let measures = vec![
+            history("coverage", &[("2026-01-01", "70.0"), ("2026-02-01", "75.0")]),
+            history("bugs", &[("2026-02-01", "4"), ("2026-03-01", "2")]),
+        ];
+        let table = pivot_history(&measures);
+        assert_eq!(table.metrics, vec!["coverage", "bugs"]);
+        assert_eq!(table.rows.len(), 3);
+
+        assert_eq!(table.rows[0].date, "2026-01-01");
+        assert_eq!(table.rows[0].values, vec![Some("70.0".to_string()), None]);
+        assert_eq!(table.rows[1].date, "2026-02-01");
+        assert_eq!(
+            table.rows[1].values,
+            vec![Some("75.0".to_string()), Some("4".to_string())]
+        );
+        assert_eq!(table.rows[2].date, "2026-03-01");
+        assert_eq!(table.rows[2].values, vec![None, Some("2".to_string())]);
+    }
+
+    #[test]
+    fn test_pivot_history_alert_status_strings() {
+        let measures = vec![
+            history("coverage", &[("2026-01-01", "70.0")]),
+            history("alert_status", &[("2026-01-01", "ERROR")]),
+        ];
+        let table = pivot_history(&measures);
+        assert_eq!(
+            table.rows[0].values,
+            vec![Some("70.0".to_string()), Some("ERROR".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_window_skip_and_top() {
+        let sorted: Vec<u32> = (1..=10).collect();
+        let window = Window { skip: 2, top: Some(3) };
+        assert_eq!(window.apply(sorted.clone()), vec![3, 4, 5]);
+        assert_eq!(Window::default().apply(sorted.clone()), sorted);
+        assert_eq!(Window { skip: 8, top: Some(5) }.apply(sorted.clone()), vec![9, 10]);
+        assert!(Window { skip: 20, top: None }.apply(sorted).is_empty());
+    }
+
+    #[test]
+    fn test_window_page_range() {
+        let window = Window { skip: 2, top: Some(3) };
+        // Pages of 2 items: [0,1] [2,3] [4,5]
+        assert!(window.page_range(0, 2).is_empty());
+        assert_eq!(window.page_range(2, 2), 0..2);
+        assert_eq!(window.page_range(4, 2), 0..1);
+        assert!(window.page_range(6, 2).is_empty());
+    }
+
+    #[test]
+    fn test_humanize_count_separators() {
+        assert_eq!(humanize_count(0, false), "0");
+        assert_eq!(humanize_count(999, false), "999");
+        assert_eq!(humanize_count(1000, false), "1,000");
+        assert_eq!(humanize_count(1234567, false), "1,234,567");
+        assert_eq!(humanize_count(-12345, false), "-12,345");
+    }
+
+    #[test]
+    fn test_humanize_count_abbreviations() {
+        assert_eq!(humanize_count(999, true), "999");
+        assert_eq!(humanize_count(3400, true), "3.4k");
+        assert_eq!(humanize_count(12000, true), "12k");
+        assert_eq!(humanize_count(1234567, true), "1.2M");
+        assert_eq!(humanize_count(2_500_000_000, true), "2.5G");
+    }
+
+    #[test]
+    fn test_gate_timeline_collapses_repeats() {
+        let measures = vec![history(
+            "alert_status",
+            &[
+                ("2026-01-03", "ERROR"),
+                ("2026-01-01", "OK"),
+                ("2026-01-02", "OK"),
+                ("2026-01-04", "ERROR"),
+                ("2026-01-05", "OK"),
+            ],
+        )];
+        let changes = gate_timeline(&measures);
+        let summary: Vec<(&str, &str, Option<&str>)> = changes
+            .iter()
+            .map(|c| (c.date.as_str(), c.status.as_str(), c.previous.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("2026-01-01", "OK", None),
+                ("2026-01-03", "ERROR", Some("OK")),
+                ("2026-01-05", "OK", Some("ERROR")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gate_timeline_without_alert_status() {
+        let measures = vec![history("coverage", &[("2026-01-01", "70.0")])];
+        assert!(gate_timeline(&measures).is_empty());
+    }
+
+    #[test]
+    fn test_pivot_history_empty() {
+        let table = pivot_history(&[]);
+        assert!(table.metrics.is_empty());
+        assert!(table.rows.is_empty());
+    }
+
+    #[test]
+    fn test_strip_html_tags() {
+        assert_eq!(
+            strip_html_tags("<span class=\"k\">if</span> a &lt; b &amp;&amp; c &gt; d {"),
+            "if a < b && c > d {"
+        );
+        assert_eq!(strip_html_tags("plain"), "plain");
+        assert_eq!(strip_html_tags("&amp;lt;"), "&lt;");
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        let html = "<p>Functions should not be\ntoo complex.</p>\n\n\n<h2>How to fix</h2><ul><li>Split it</li><li>Return early</li></ul><pre>if a &amp;&amp; b {}</pre>";
+        assert_eq!(
+            html_to_text(html),
+            "Functions should not be\ntoo complex.\n\nHow to fix\n- Split it\n- Return early\n\nif a && b {}"
+        );
+        assert_eq!(html_to_text(""), "");
+    }
+
+    #[test]
+    fn test_parse_effort() {
+        assert_eq!(parse_effort("6min"), Some(6));
+        assert_eq!(parse_effort("1h"), Some(60));
+        assert_eq!(parse_effort("2d"), Some(960));
+        assert_eq!(parse_effort("1h30min"), Some(90));
+        assert_eq!(parse_effort("1d 2h 5min"), Some(605));
+        assert_eq!(parse_effort("0min"), Some(0));
+    }
+
+    #[test]
+    fn test_parse_effort_malformed() {
+        assert_eq!(parse_effort(""), None);
+        assert_eq!(parse_effort("min"), None);
+        assert_eq!(parse_effort("6"), None);
+        assert_eq!(parse_effort("6m"), None);
+        assert_eq!(parse_effort("1.5h"), None);
+        assert_eq!(parse_effort("1h30"), None);
+        assert_eq!(parse_effort("99999999999999999999d"), None);
+    }
+
+    #[test]
+    fn test_format_effort() {
+        assert_eq!(format_effort(0), "0min");
+        assert_eq!(format_effort(200), "3h 20min");
+        assert_eq!(format_effort(480), "1d");
+        assert_eq!(format_effort(605), "1d 2h 5min");
+    }
+
+    #[test]
+    fn test_summarize_effort_per_severity() {
+        let issue = |severity: &str, effort: Option<&str>, debt: Option<&str>| -> SonarIssue {
+            serde_json::from_value(serde_json::json!({
+                "key": "k", "rule": "r", "severity": severity, "component": "c", "project": "p",
+                "message": "m", "type": "BUG", "status": "OPEN", "effort": effort, "debt": debt
+            }))
+            .unwrap()
+        };
+        let issues = vec![
+            issue("MAJOR", Some("2h"), None),
+            issue("BLOCKER", Some("1h"), None),
+            issue("MAJOR", None, Some("20min")),
+            issue("MINOR", None, None),
+        ];
+        let summary = summarize_effort(&issues);
+        assert_eq!(summary.total_minutes, 200);
+        assert_eq!(summary.unknown, 1);
+        let levels: Vec<(&str, u64)> =
+            summary.by_severity.iter().map(|s| (s.severity.as_str(), s.minutes)).collect();
+        assert_eq!(levels, [("BLOCKER", 60), ("MAJOR", 140), ("MINOR", 0)]);
+    }
+
     #[test]
     fn test_extract_path() {
         assert_eq!(extract_path("my-project:src/main.rs", "my-project"), "src/main.rs");
@@ -240,6 +1298,51 @@ mod tests {
         assert_eq!(val, 0);
     }
 
+    async fn never_analyzed_for(show_status: u16, component: serde_json::Value) -> Option<bool> {
+        let mock_server = try_mock_server().await?;
+        Mock::given(method("GET"))
+            .and(path("/api/components/show"))
+            .respond_with(
+                ResponseTemplate::new(show_status)
+                    .set_body_json(serde_json::json!({"component": component})),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        Some(never_analyzed(&client, "my-proj").await)
+    }
+
+    #[tokio::test]
+    async fn test_never_analyzed() {
+        let analyzed = serde_json::json!({
+            "key": "my-proj", "qualifier": "TRK", "analysisDate": "2026-01-01T00:00:00+0000"
+        });
+        let unanalyzed = serde_json::json!({"key": "my-proj", "qualifier": "TRK"});
+        let portfolio = serde_json::json!({"key": "my-proj", "qualifier": "VW"});
+        match never_analyzed_for(200, analyzed).await {
+            Some(result) => assert!(!result),
+            None => return,
+        }
+        assert_eq!(never_analyzed_for(200, unanalyzed.clone()).await, Some(true));
+        assert_eq!(never_analyzed_for(200, portfolio).await, Some(false));
+        // A failed lookup doesn't hide the command's own result
+        assert_eq!(never_analyzed_for(404, unanalyzed).await, Some(false));
+    }
+
+    #[test]
+    fn test_empty_or_missing() {
+        let not_found: Result<Vec<u8>, _> = Err(SonarQubeError::Api {
+            status: 404,
+            message: "Component not found".to_string(),
+        });
+        assert!(empty_or_missing(&Ok(Vec::<u8>::new()), Vec::is_empty));
+        assert!(!empty_or_missing(&Ok(vec![1u8]), Vec::is_empty));
+        assert!(empty_or_missing(&not_found, Vec::is_empty));
+        assert!(!empty_or_missing(&Err::<Vec<u8>, _>(SonarQubeError::DryRun), Vec::is_empty));
+        assert!(!empty_or_missing(&Err::<Vec<u8>, _>(SonarQubeError::CircuitOpen), Vec::is_empty));
+    }
+
     #[tokio::test]
     async fn test_fetch_extended_data_no_dups_no_gaps() {
         let mock_server = match try_mock_server().await {
@@ -439,6 +1542,62 @@ mod tests {
         assert_eq!(data.duplications[0].blocks[0].duplicated_in_line, 50);
     }
 
+    #[tokio::test]
+    async fn test_fetch_extended_data_blocks_for_every_file() {
+        // Blocks are fetched per file concurrently; the result is sorted by path
+        let mock_server = match try_mock_server().await {
+            Some(s) => s,
+            None => return,
+        };
+
+        let dup_file = |name: &str| {
+            serde_json::json!({
+                "key": format!("my-proj:src/{name}"),
+                "path": format!("src/{name}"),
+                "measures": [
+                    {"metric": "duplicated_lines", "value": "10"},
+                    {"metric": "duplicated_lines_density", "value": "5.0"}
+                ]
+            })
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/measures/component_tree"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "paging": {"total": 2},
+                "components": [dup_file("b.rs"), dup_file("a.rs")]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        for (name, other, from) in [("a.rs", "x.rs", 3), ("b.rs", "y.rs", 7)] {
+            Mock::given(method("GET"))
+                .and(path("/api/duplications/show"))
+                .and(query_param("key", format!("my-proj:src/{name}")))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "duplications": [{"blocks": [
+                        {"_ref": "1", "from": from, "size": 10},
+                        {"_ref": "2", "from": 1, "size": 10}
+                    ]}],
+                    "files": {
+                        "1": {"key": format!("my-proj:src/{name}"), "name": name},
+                        "2": {"key": format!("my-proj:src/{other}"), "name": other}
+                    }
+                })))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let client = SonarQubeClient::new(SonarQubeConfig::new(mock_server.uri())).unwrap();
+        let data = fetch_extended_data(&client, "my-proj").await.unwrap();
+        let files: Vec<&str> = data.duplications.iter().map(|d| d.file.as_str()).collect();
+        assert_eq!(files, ["src/a.rs", "src/b.rs"]);
+        assert_eq!(data.duplications[0].blocks[0].duplicated_in, "x.rs");
+        assert_eq!(data.duplications[0].blocks[0].from_line, 3);
+        assert_eq!(data.duplications[1].blocks[0].duplicated_in, "y.rs");
+        assert_eq!(data.duplications[1].blocks[0].from_line, 7);
+    }
+
     #[tokio::test]
     async fn test_fetch_extended_data_api_error_uses_default() {
         // Exercises unwrap_or_default() in fetch_extended_data when component_tree returns 500
@@ -0,0 +1,137 @@
+//! Client-side path filtering from a committed `.sonarcliignore` file
+//!
+//! The file uses gitignore syntax and is matched against the project-relative
+//! paths SonarQube reports, so a team can hide generated or vendored code from
+//! `issues`, `coverage` and `duplications` output without repeating filters.
+
+use std::path::Path;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+/// Ignore file looked up in the working directory when `--ignore-file` is not given
+pub const DEFAULT_IGNORE_FILE: &str = ".sonarcliignore";
+
+/// Gitignore-style matcher for project-relative file paths; empty when no file is loaded
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    matcher: Option<Gitignore>,
+}
+
+impl PathFilter {
+    /// A filter that keeps everything
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Build a filter from gitignore-style lines
+    pub fn from_patterns(patterns: &str) -> Result<Self, String> {
+        let mut builder = GitignoreBuilder::new("");
+        for line in patterns.lines() {
+            builder
+                .add_line(None, line)
+                .map_err(|e| format!("Invalid ignore pattern '{line}': {e}"))?;
+        }
+        let matcher = builder
+            .build()
+            .map_err(|e| format!("Invalid ignore patterns: {e}"))?;
+        Ok(Self { matcher: Some(matcher) })
+    }
+
+    /// Resolve the filter from the CLI flags.
+    ///
+    /// `disabled` (`--no-ignore-file`) keeps everything. An explicit `path` must
+    /// exist; otherwise [`DEFAULT_IGNORE_FILE`] is used if present.
+    pub fn resolve(path: Option<&Path>, disabled: bool) -> Result<Self, String> {
+        if disabled {
+            return Ok(Self::none());
+        }
+        match path {
+            Some(p) if !p.exists() => Err(format!("Ignore file not found: {}", p.display())),
+            Some(p) => Self::load(p),
+            None => Self::load(Path::new(DEFAULT_IGNORE_FILE)),
+        }
+    }
+
+    /// Load the filter from `path`. A missing file yields an empty filter.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::from_patterns(&contents)
+                .map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::none()),
+            Err(e) => Err(format!("Failed to read {}: {e}", path.display())),
+        }
+    }
+
+    /// Whether a project-relative file path matches the ignore patterns
+    pub fn is_ignored(&self, path: &str) -> bool {
+        match self.matcher {
+            Some(ref m) => m.matched_path_or_any_parents(path, false).is_ignore(),
+            None => false,
+        }
+    }
+
+    /// Drop the items whose path is ignored
+    pub fn retain<T>(&self, items: &mut Vec<T>, path_of: impl Fn(&T) -> &str) {
+        if self.matcher.is_some() {
+            items.retain(|item| !self.is_ignored(path_of(item)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns_match_files_and_directories() {
+        let filter = PathFilter::from_patterns("# generated\nvendor/\n*.pb.go\n!keep.pb.go\n").unwrap();
+        assert!(filter.is_ignored("vendor/lib/a.go"));
+        assert!(filter.is_ignored("api/service.pb.go"));
+        assert!(!filter.is_ignored("api/keep.pb.go"));
+        assert!(!filter.is_ignored("src/main.go"));
+    }
+
+    #[test]
+    fn test_none_keeps_everything() {
+        let mut items = vec!["vendor/a.go", "src/b.go"];
+        PathFilter::none().retain(&mut items, |p| p);
+        assert_eq!(items, vec!["vendor/a.go", "src/b.go"]);
+    }
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        let path = std::env::temp_dir().join("sonar-cli-test-missing-ignore").join(DEFAULT_IGNORE_FILE);
+        let filter = PathFilter::load(&path).unwrap();
+        assert!(!filter.is_ignored("anything.rs"));
+    }
+
+    #[test]
+    fn test_resolve_explicit_missing_file_errors() {
+        let path = std::env::temp_dir().join("sonar-cli-test-missing-ignore").join("custom-ignore");
+        let err = PathFilter::resolve(Some(&path), false).unwrap_err();
+        assert!(err.contains("Ignore file not found"));
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let dir = std::env::temp_dir().join("sonar-cli-test-ignore-file");
+        let path = dir.join(DEFAULT_IGNORE_FILE);
+        let _ = std::fs::create_dir_all(&dir);
+        std::fs::write(&path, "generated/\n").unwrap();
+
+        let filter = PathFilter::resolve(Some(&path), false).unwrap();
+        let mut kept = vec!["generated/x.rs", "src/y.rs"];
+        filter.retain(&mut kept, |p| p);
+        assert_eq!(kept, vec!["src/y.rs"]);
+
+        // --no-ignore-file disables the file
+        let filter = PathFilter::resolve(Some(&path), true).unwrap();
+        let mut kept = vec!["generated/x.rs"];
+        filter.retain(&mut kept, |p| p);
+        assert_eq!(kept, vec!["generated/x.rs"]);
+
+        // cleanup
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_dir(&dir);
+    }
+}
@@ -1,13 +1,27 @@
+mod browser;
+mod cache;
 mod client;
+mod cobertura;
 mod commands;
 mod config;
+mod defaults;
+mod error;
 mod helpers;
+mod ignore_file;
 mod output;
+mod paging;
+mod token;
+#[cfg(feature = "tui")]
+mod tui;
 mod types;
 
-use clap::{Parser, Subcommand};
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand};
 
-use client::{IssueSearchParams, SonarQubeConfig};
+use cache::ResponseCache;
+use client::{parse_header, IssueSearchParams, RetryPolicy, RuleSearchParams, SonarQubeConfig};
+use helpers::Window;
+use paging::PageRequest;
+use ignore_file::PathFilter;
 
 #[derive(Parser)]
 #[command(
@@ -34,11 +48,25 @@ struct Cli {
     #[arg(long, global = true)]
     url: Option<String>,
 
-    /// Authentication token
+    /// Authentication token (else --token-file, --token-command, the stored profile, SONAR_TOKEN)
     #[arg(long, global = true)]
     token: Option<String>,
 
-    /// Project key
+    /// Read the token from the first line of this file
+    #[arg(long, value_name = "PATH", global = true)]
+    token_file: Option<std::path::PathBuf>,
+
+    /// Run this shell command and use its output as the token (e.g. "pass show sonar");
+    /// killed after 30s
+    #[arg(long, value_name = "CMD", global = true)]
+    token_command: Option<String>,
+
+    /// Named server profile from the config file (see `auth login --profile`)
+    /// [default: `default_profile` from the config, else "default"]
+    #[arg(long, value_name = "NAME", global = true)]
+    profile: Option<String>,
+
+    /// Project key, or @N for the N-th most recently used project (see `projects --recent`)
     #[arg(long, env = "SONAR_PROJECT_KEY", global = true)]
     project: Option<String>,
 
@@ -46,18 +74,118 @@ struct Cli {
     #[arg(long, env = "SONAR_BRANCH", global = true)]
     branch: Option<String>,
 
+    /// Pull request ID; scopes queries to the PR analysis (not combinable with --branch)
+    #[arg(long, env = "SONAR_PULL_REQUEST", global = true)]
+    pull_request: Option<String>,
+
     /// Output as JSON
     #[arg(long, global = true)]
     json: bool,
 
-    /// Request timeout in seconds
+    /// Print JSON output on a single line instead of pretty-printed
+    #[arg(long, global = true)]
+    compact_json: bool,
+
+    /// Print JSON payloads as-is, without the command/server/project metadata envelope
+    #[arg(long, global = true)]
+    bare_json: bool,
+
+    /// Write the command's output (text, JSON, CSV, ...) to this file instead of stdout,
+    /// creating parent directories; errors still go to stderr
+    #[arg(long, value_name = "PATH", global = true)]
+    output: Option<std::path::PathBuf>,
+
+    /// Request timeout in seconds, including the response body
     #[arg(long, default_value = "30", global = true)]
     timeout: u64,
 
+    /// Seconds allowed to connect to the server, so dead hosts fail fast
+    /// while large responses still get the full --timeout
+    #[arg(long, value_name = "SECS", default_value = "10", global = true)]
+    connect_timeout: u64,
+
+    /// Retry connection errors and 429/502/503/504 responses this many times,
+    /// with exponential backoff (429 honors Retry-After)
+    #[arg(long, env = "SONAR_RETRIES", default_value = "3", global = true)]
+    retries: u32,
+
+    /// Requests in flight at once for issue, rule and project pages and per-file
+    /// duplication details (1 = one at a time)
+    #[arg(long, value_name = "N", default_value = "8", value_parser = clap::value_parser!(u16).range(1..=64), global = true)]
+    concurrency: u16,
+
+    /// Only retry idempotent requests; set to false to also retry POST mutations
+    #[arg(long, value_name = "BOOL", default_value_t = true, action = clap::ArgAction::Set, global = true)]
+    retry_idempotent_only: bool,
+
+    /// Extra HTTP header sent with every request, as "Name: Value" (repeatable)
+    #[arg(long = "header", value_name = "NAME: VALUE", global = true)]
+    headers: Vec<String>,
+
+    /// Allow --header to override the Authorization header
+    #[arg(long, global = true)]
+    allow_auth_header: bool,
+
+    /// Gitignore-style file of paths to hide from issues, coverage and duplications [default: .sonarcliignore]
+    #[arg(long, value_name = "PATH", global = true)]
+    ignore_file: Option<std::path::PathBuf>,
+
+    /// Don't apply the ignore file
+    #[arg(long, global = true, conflicts_with = "ignore_file")]
+    no_ignore_file: bool,
+
+    /// Without --branch, look up the project's main branch and query it explicitly
+    #[arg(long, global = true)]
+    resolve_main_branch: bool,
+
+    /// Proxy for all requests, e.g. http://proxy:3128 (default: HTTP_PROXY/HTTPS_PROXY; NO_PROXY is honored)
+    #[arg(long, value_name = "URL", global = true)]
+    proxy: Option<String>,
+
+    /// Accept any TLS certificate (self-signed, expired, wrong host); insecure
+    #[arg(long, global = true)]
+    insecure: bool,
+
+    /// PEM file with an extra root certificate to trust (e.g. a private CA)
+    #[arg(long, value_name = "PATH", global = true)]
+    cacert: Option<std::path::PathBuf>,
+
+    /// Color text output: auto (only on a terminal, honoring NO_COLOR), always, never
+    #[arg(long, value_name = "WHEN", default_value = "auto", global = true)]
+    color: String,
+
+    /// Print each API URL (credentials redacted) to stderr before requesting it
+    #[arg(long, global = true)]
+    print_url: bool,
+
+    /// Print API requests (credentials redacted) instead of sending them, then exit 0
+    ///
+    /// Without responses a command cannot go on, so the output ends at the first
+    /// request whose response it needs; best-effort lookups before it (such as
+    /// the project check) are printed too. Not supported by `scan`.
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Cache GET responses on disk and reuse them while fresh (see --cache-ttl, `cache clear`)
+    #[arg(long, global = true)]
+    cache: bool,
+
+    /// Seconds a cached response stays fresh
+    #[arg(long, value_name = "SECS", default_value_t = cache::DEFAULT_TTL_SECS, global = true)]
+    cache_ttl: u64,
+
+    /// Ignore cached responses and fetch fresh ones (still stored with --cache)
+    #[arg(long, global = true)]
+    no_cache: bool,
+
     /// Verbose logging
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// Only print data: no count headers, scanner output or progress messages (errors still shown)
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -67,11 +195,46 @@ enum Command {
     /// Check SonarQube server health (no auth required)
     #[command(long_about = "Check SonarQube server health (no auth required).\n\n\
         Returns the server status (UP, STARTING, DOWN). Does not require\n\
-        --project or --token.\n\n\
+        --project or --token. When the server is UP, also shows its version and,\n\
+        if the token has admin rights, the server ID and edition.\n\n\
+        With --full and a token, reports /api/system/health instead: GREEN, YELLOW\n\
+        or RED plus the causes. RED exits with code 1, and so does YELLOW with\n\
+        --strict. Without a token, --full falls back to the status above.\n\n\
         Examples:\n  \
           sonar-cli health\n  \
-          sonar-cli --url https://sonar.example.com health")]
-    Health,
+          sonar-cli --url https://sonar.example.com health\n  \
+          sonar-cli health --full --strict")]
+    Health {
+        /// Report GREEN/YELLOW/RED and its causes from /api/system/health (needs a token)
+        #[arg(long)]
+        full: bool,
+
+        /// With --full, fail on YELLOW as well as RED
+        #[arg(long, requires = "full")]
+        strict: bool,
+    },
+
+    /// Show the SonarQube server version, edition and database
+    #[command(long_about = "Show the SonarQube server version, edition and database.\n\n\
+        The version comes from /api/server/version and needs no token. Edition\n\
+        and database come from /api/system/info, which requires admin rights;\n\
+        without them only the version is shown.\n\n\
+        Examples:\n  \
+          sonar-cli version\n  \
+          sonar-cli --token squ_admin version --json")]
+    Version,
+
+    /// Show the user behind the token and whether the token is valid
+    #[command(long_about = "Show the user behind the token and whether the token is valid.\n\n\
+        Prints the login and name of the token's user, whether the token is\n\
+        valid, and the server version and edition when known. Without a token\n\
+        it reports an anonymous user (valid: false). With --json, prints the\n\
+        user object from /api/users/current plus a \"valid\" field. Exits with\n\
+        code 4 when a configured token is not accepted.\n\n\
+        Examples:\n  \
+          sonar-cli whoami\n  \
+          sonar-cli --token squ_xxx whoami --json")]
+    Whoami,
 
     /// Check quality gate status (requires --project)
     #[command(name = "quality-gate", long_about = "Check quality gate status (requires --project).\n\n\
@@ -79,91 +242,165 @@ enum Command {
         condition with its actual value vs threshold.\n\n\
         Examples:\n  \
           sonar-cli --project my-proj quality-gate\n  \
-          sonar-cli --project my-proj quality-gate --fail-on-error")]
+          sonar-cli --project my-proj quality-gate --fail-on-error\n  \
+          sonar-cli --project my-proj quality-gate --only failed")]
     QualityGate {
         /// Exit with code 1 if quality gate fails (useful in CI)
         #[arg(long)]
         fail_on_error: bool,
+
+        /// Show only conditions that passed or failed: passed, failed (gate status is unaffected)
+        #[arg(long)]
+        only: Option<String>,
     },
 
     /// Search and filter project issues (requires --project)
     #[command(long_about = "Search and filter project issues (requires --project).\n\n\
         By default only shows open issues (OPEN, CONFIRMED, REOPENED).\n\
         Use --status to query other statuses like RESOLVED or CLOSED.\n\
-        Use 'rules' command to discover rule keys for --rule filter.\n\n\
+        Use 'rules' command to discover rule keys for --rule filter.\n\
+        Issues are sorted by severity (BLOCKER first), then file and line;\n\
+        use --no-sort to keep the server's order.\n\n\
+        --fail-on-issues, --max-issues and --fail-on-severity exit with code 2\n\
+        when the listed issues breach them (1 stays reserved for errors).\n\n\
         Examples:\n  \
           sonar-cli --project my-proj issues\n  \
           sonar-cli --project my-proj issues --severity CRITICAL\n  \
           sonar-cli --project my-proj issues --status RESOLVED --language java\n  \
-          sonar-cli --project my-proj issues --created-after 2025-01-01 --limit 50")]
-    Issues {
-        /// Minimum severity — shows this level and above (INFO, MINOR, MAJOR, CRITICAL, BLOCKER)
-        #[arg(long)]
-        severity: Option<String>,
-
-        /// Issue type filter (BUG, VULNERABILITY, CODE_SMELL, SECURITY_HOTSPOT)
-        #[arg(long, name = "type")]
-        issue_type: Option<String>,
-
-        /// Maximum number of issues to return
-        #[arg(long)]
-        limit: Option<usize>,
-
-        /// Status filter [default: OPEN,CONFIRMED,REOPENED] (OPEN, CONFIRMED, REOPENED, RESOLVED, CLOSED)
-        #[arg(long)]
-        status: Option<String>,
-
-        /// Resolution filter — only applies to RESOLVED/CLOSED issues (FALSE-POSITIVE, WONTFIX, FIXED, REMOVED)
-        #[arg(long)]
-        resolution: Option<String>,
-
-        /// Tags filter (comma-separated)
-        #[arg(long)]
-        tags: Option<String>,
-
-        /// Rule key filter (comma-separated, e.g. java:S1234 — use 'rules' command to discover keys)
-        #[arg(long)]
-        rule: Option<String>,
-
-        /// Only issues created after this date (YYYY-MM-DD)
+          sonar-cli --project my-proj issues --created-after 2025-01-01 --limit 50\n  \
+          sonar-cli --project my-proj issues --file src/main.rs\n  \
+          sonar-cli --project my-proj issues --path src/api/,src/core/ --exclude-path src/core/gen/\n  \
+          sonar-cli --project my-proj issues --qualifiers MAIN\n  \
+          sonar-cli --project my-proj issues --skip 20 --top 10\n  \
+          sonar-cli --project my-proj issues --include-closed --created-after 2025-06-01\n  \
+          sonar-cli --project my-proj issues --all-statuses --rule java:S1234\n  \
+          sonar-cli --project my-proj issues --show-rule-severity\n  \
+          sonar-cli --project my-proj issues --with-rule-names\n  \
+          sonar-cli --project my-proj issues --group-by rule --top 10\n  \
+          sonar-cli --project my-proj issues --show-effort\n  \
+          sonar-cli --project my-proj issues --page 3 --page-size 500 --json\n  \
+          sonar-cli --project my-proj issues --status CONFIRMED --comments\n  \
+          sonar-cli --project my-proj issues --changed-since-analysis 5\n  \
+          sonar-cli --project my-proj issues --since-last-green\n  \
+          sonar-cli --project my-proj issues --show-full-component\n  \
+          sonar-cli --project my-proj issues --rule java:S2259 --open\n  \
+          sonar-cli --project my-proj issues --watch --interval 60\n  \
+          sonar-cli --project my-proj issues --format tsv > issues.tsv\n  \
+          sonar-cli --project my-proj issues --new-code --fail-on-severity CRITICAL\n  \
+          sonar-cli --project my-proj issues --max-issues 50\n  \
+          sonar-cli --project my-proj issues --format ndjson | jq -c 'select(.severity == \"BLOCKER\")'")]
+    Issues(Box<IssuesArgs>),
+
+    /// Get project metrics (requires --project or --component)
+    #[command(long_about = "Get project metrics (requires --project or --component).\n\n\
+        Shows current metric values for the project. If --metrics is omitted,\n\
+        returns a default set of common metrics. Use --component to read the\n\
+        rolled-up measures of a portfolio or application instead.\n\n\
+        Common metric keys: ncloc, coverage, bugs, vulnerabilities, code_smells,\n\
+        duplicated_lines_density, sqale_index, reliability_rating, security_rating.\n\
+        Use 'history' command to view how these metrics change over time.\n\n\
+        Examples:\n  \
+          sonar-cli --project my-proj measures\n  \
+          sonar-cli --project my-proj measures --metrics coverage,bugs,ncloc\n  \
+          sonar-cli --project my-proj --branch feature/x measures --compare-branch main\n  \
+          sonar-cli measures --component VW_PORTFOLIO --metrics coverage,bugs\n  \
+          sonar-cli --project my-proj measures --metrics ncloc,lines --human\n  \
+          sonar-cli --project my-proj measures --metrics coverage,bugs --new-code\n  \
+          sonar-cli --project my-proj measures --metrics coverage,bugs --trend\n  \
+          sonar-cli --project my-proj measures --format csv\n  \
+          sonar-cli --project my-proj measures --all\n  \
+          sonar-cli --project my-proj measures --assert 'coverage>=80' --assert 'bugs==0'\n  \
+          sonar-cli measures --projects api,web --metrics coverage,bugs\n  \
+          sonar-cli projects --json | jq -r '.data[].key' | sonar-cli measures --projects -")]
+    Measures {
+        /// Comma-separated metric keys (common keys: ncloc, coverage, bugs, vulnerabilities, code_smells)
         #[arg(long)]
-        created_after: Option<String>,
+        metrics: Option<String>,
 
-        /// Only issues created before this date (YYYY-MM-DD)
+        /// Compare against another branch and show deltas (current branch is --branch)
         #[arg(long)]
-        created_before: Option<String>,
+        compare_branch: Option<String>,
 
-        /// Filter by issue author login
+        /// Component key to read instead of --project (e.g. a portfolio or application)
         #[arg(long)]
-        author: Option<String>,
+        component: Option<String>,
 
-        /// Assignee filter (comma-separated logins, use 'unassigned' for unassigned issues)
-        #[arg(long)]
-        assignee: Option<String>,
+        /// Show the new-code variant of each metric (coverage -> new_coverage) for the new code period
+        #[arg(long, conflicts_with = "compare_branch")]
+        new_code: bool,
 
-        /// Language filter (comma-separated, e.g. java,py,js)
+        /// Abbreviate large counts (1.2M, 3.4k) instead of using thousands separators
         #[arg(long)]
-        language: Option<String>,
-
-        /// Only show issues in the new code period
+        human: bool,
+
+        /// Show the change since the previous analysis (↑/↓/→ and delta) next to each value
+        #[arg(long, conflicts_with_all = ["compare_branch", "new_code"])]
+        trend: bool,
+
+        /// Comma-separated project keys to show side by side (one column each),
+        /// or - to read keys from stdin, one per line
+        #[arg(
+            long,
+            value_name = "KEYS",
+            conflicts_with_all = ["component", "compare_branch", "new_code", "trend", "human"]
+        )]
+        projects: Option<String>,
+
+        /// Show every metric defined on the server (fetched in batches)
+        #[arg(long, conflicts_with_all = ["metrics", "compare_branch", "new_code", "trend", "projects"])]
+        all: bool,
+
+        /// Check a measure after fetching, e.g. coverage>=80 or bugs==0 (operators >=, <=, >,
+        /// <, ==, !=); repeatable. Exits 2 if any fails; the metric is fetched even if not in --metrics
+        #[arg(
+            long = "assert",
+            value_name = "METRIC<OP>VALUE",
+            conflicts_with_all = ["compare_branch", "new_code", "trend", "projects"]
+        )]
+        assertions: Vec<String>,
+
+        /// Output format: text (default), json, csv, tsv (csv/tsv for current measures only)
         #[arg(long)]
-        new_code: bool,
+        format: Option<String>,
     },
 
-    /// Get project metrics (requires --project)
-    #[command(long_about = "Get project metrics (requires --project).\n\n\
-        Shows current metric values for the project. If --metrics is omitted,\n\
-        returns a default set of common metrics.\n\n\
-        Common metric keys: ncloc, coverage, bugs, vulnerabilities, code_smells,\n\
-        duplicated_lines_density, sqale_index, reliability_rating, security_rating.\n\
-        Use 'history' command to view how these metrics change over time.\n\n\
+    /// Show the project's new code definition (requires --project)
+    #[command(name = "new-code", long_about = "Show the project's new code definition (requires --project).\n\n\
+        Shows how SonarQube decides what counts as new code for the project\n\
+        (or for --branch): previous version, number of days, reference branch\n\
+        or a specific analysis, and whether the setting is inherited.\n\n\
         Examples:\n  \
-          sonar-cli --project my-proj measures\n  \
-          sonar-cli --project my-proj measures --metrics coverage,bugs,ncloc")]
-    Measures {
-        /// Comma-separated metric keys (common keys: ncloc, coverage, bugs, vulnerabilities, code_smells)
+          sonar-cli --project my-proj new-code\n  \
+          sonar-cli --project my-proj --branch feature/x new-code --json")]
+    NewCode,
+
+    /// List the project's branches with their quality gate status (requires --project)
+    #[command(long_about = "List the project's branches with their quality gate status (requires --project).\n\n\
+        Shows each branch's name, type, whether it is the main branch, its\n\
+        quality gate status and the date of its last analysis.\n\n\
+        Examples:\n  \
+          sonar-cli --project my-proj branches\n  \
+          sonar-cli --project my-proj branches --json")]
+    Branches,
+
+    /// List the project's files and directories with their component keys (requires --project)
+    #[command(long_about = "List the project's files and directories with their component keys (requires --project).\n\n\
+        Walks /api/components/tree to completion and prints each component's\n\
+        qualifier, path and key. The keys are what 'source' expects.\n\
+        --search keeps components whose name or key contains the text\n\
+        (at least 3 characters).\n\n\
+        Examples:\n  \
+          sonar-cli --project my-proj tree\n  \
+          sonar-cli --project my-proj tree --qualifier DIR\n  \
+          sonar-cli --project my-proj tree --search main --json")]
+    Tree {
+        /// Component qualifiers to list: FIL, DIR or both (comma-separated)
+        #[arg(long, default_value = "FIL,DIR")]
+        qualifier: String,
+
+        /// Only components whose name or key contains this text (at least 3 characters)
         #[arg(long)]
-        metrics: Option<String>,
+        search: Option<String>,
     },
 
     /// Per-file coverage breakdown (requires --project)
@@ -173,7 +410,9 @@ enum Command {
         Examples:\n  \
           sonar-cli --project my-proj coverage\n  \
           sonar-cli --project my-proj coverage --min-coverage 80\n  \
-          sonar-cli --project my-proj coverage --sort uncovered")]
+          sonar-cli --project my-proj coverage --sort uncovered\n  \
+          sonar-cli --project my-proj coverage --sort uncovered --top 10\n  \
+          sonar-cli --project my-proj coverage --format tsv")]
     Coverage {
         /// Only show files below this coverage percentage (e.g. 80)
         #[arg(long)]
@@ -182,6 +421,18 @@ enum Command {
         /// Sort by: coverage (default), uncovered, file
         #[arg(long)]
         sort: Option<String>,
+
+        /// Show at most N results (client-side, applied after sorting)
+        #[arg(long, value_name = "N")]
+        top: Option<usize>,
+
+        /// Skip the first M results (client-side, applied after sorting)
+        #[arg(long, value_name = "M", default_value = "0")]
+        skip: usize,
+
+        /// Output format: text (default), json, csv, tsv
+        #[arg(long)]
+        format: Option<String>,
     },
 
     /// Code duplication analysis (requires --project)
@@ -190,11 +441,33 @@ enum Command {
         Use --details to see the exact duplicated blocks and where they appear.\n\n\
         Examples:\n  \
           sonar-cli --project my-proj duplications\n  \
-          sonar-cli --project my-proj duplications --details")]
+          sonar-cli --project my-proj duplications --details\n  \
+          sonar-cli --project my-proj duplications --top 5")]
     Duplications {
         /// Show detailed duplication blocks (which lines, duplicated where)
         #[arg(long)]
         details: bool,
+
+        /// Show at most N results (client-side, applied after sorting)
+        #[arg(long, value_name = "N")]
+        top: Option<usize>,
+
+        /// Skip the first M results (client-side, applied after sorting)
+        #[arg(long, value_name = "M", default_value = "0")]
+        skip: usize,
+    },
+
+    /// Assign, comment on, or transition a single issue (requires a token)
+    #[command(long_about = "Assign, comment on, or transition a single issue (requires a token).\n\n\
+        The token's user needs permission to administer issues on the project;\n\
+        a 403 from the server exits with code 4 (token lacks permission).\n\n\
+        Examples:\n  \
+          sonar-cli issue assign AX1 --to alice\n  \
+          sonar-cli issue comment AX1 --text \"Fixed in #1234\"\n  \
+          sonar-cli issue transition AX1 --do falsepositive")]
+    Issue {
+        #[command(subcommand)]
+        action: IssueAction,
     },
 
     /// Security hotspots review (requires --project)
@@ -203,8 +476,16 @@ enum Command {
         only TO_REVIEW hotspots.\n\n\
         Examples:\n  \
           sonar-cli --project my-proj hotspots\n  \
-          sonar-cli --project my-proj hotspots --status REVIEWED")]
+          sonar-cli --project my-proj hotspots --status REVIEWED\n  \
+          sonar-cli --project my-proj hotspots --assigned-to me\n  \
+          sonar-cli --token squ_abc123 hotspots assign --hotspot AX1 --to alice\n  \
+          sonar-cli hotspots show AX1\n  \
+          sonar-cli --token squ_abc123 hotspots resolve AX1 --status REVIEWED --resolution SAFE",
+        args_conflicts_with_subcommands = true)]
     Hotspots {
+        #[command(subcommand)]
+        action: Option<HotspotAction>,
+
         /// Status filter [default: TO_REVIEW] (TO_REVIEW, REVIEWED)
         #[arg(long)]
         status: Option<String>,
@@ -212,6 +493,33 @@ enum Command {
         /// Only show hotspots in the new code period
         #[arg(long)]
         new_code: bool,
+
+        /// Only show hotspots assigned to this login ("me" for yourself)
+        #[arg(long, value_name = "LOGIN")]
+        assigned_to: Option<String>,
+    },
+
+    /// Export a bundle of JSON reports to a directory (requires --project)
+    #[command(long_about = "Export a bundle of JSON reports to a directory (requires --project).\n\n\
+        Fetches the quality gate, then issues, measures and per-file coverage\n\
+        concurrently, and writes issues.json, measures.json, quality-gate.json\n\
+        and coverage.json, plus a summary.json index listing the files written\n\
+        and any report that failed. Exits 1 if any report failed.\n\n\
+        Issues are saved page by page under issues-pages/ with progress in\n\
+        report-state.json while the export runs; after an interruption, rerun\n\
+        with --resume to fetch only the remaining pages.\n\n\
+        Examples:\n  \
+          sonar-cli --project my-proj report --output-dir reports\n  \
+          sonar-cli --project my-proj report --output-dir reports --resume\n  \
+          sonar-cli --project my-proj --branch main report --output-dir out --json")]
+    Report {
+        /// Directory to write the reports to (created if missing)
+        #[arg(long, value_name = "DIR")]
+        output_dir: std::path::PathBuf,
+
+        /// Continue an interrupted export, skipping issue pages already saved in DIR
+        #[arg(long)]
+        resume: bool,
     },
 
     /// List and search projects on the server (no --project required)
@@ -221,7 +529,11 @@ enum Command {
         Examples:\n  \
           sonar-cli projects\n  \
           sonar-cli projects --search my-app\n  \
-          sonar-cli projects --qualifier VW   # list portfolios")]
+          sonar-cli projects --qualifier VW   # list portfolios\n  \
+          sonar-cli projects --format ndjson\n  \
+          sonar-cli projects --qualifier VW --format csv\n  \
+          sonar-cli projects --page 2 --page-size 50\n  \
+          sonar-cli projects --recent   # then: sonar-cli --project @1 issues")]
     Projects {
         /// Search query to filter projects by name or key
         #[arg(long)]
@@ -230,6 +542,22 @@ enum Command {
         /// Component qualifier (TRK=projects, VW=portfolios, APP=applications)
         #[arg(long, default_value = "TRK")]
         qualifier: String,
+
+        /// Output format: text (default), json, ndjson (one JSON object per line, streamed per page), csv, tsv
+        #[arg(long)]
+        format: Option<String>,
+
+        /// List recently used projects (no server call); select one with --project @N
+        #[arg(long, conflicts_with_all = ["search", "format"])]
+        recent: bool,
+
+        /// Fetch only this page (1-based) instead of every page
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..), conflicts_with = "recent")]
+        page: Option<u16>,
+
+        /// Results per page with --page (SonarQube caps it at 500) [default: 100]
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..), conflicts_with = "recent")]
+        page_size: Option<u16>,
     },
 
     /// View metric trends over time (requires --project)
@@ -241,11 +569,13 @@ enum Command {
         Examples:\n  \
           sonar-cli --project my-proj history --metrics coverage\n  \
           sonar-cli --project my-proj history --metrics coverage,bugs --from 2025-01-01\n  \
-          sonar-cli --project my-proj history --metrics ncloc --from 2025-01-01 --to 2025-06-01")]
+          sonar-cli --project my-proj history --metrics coverage,bugs --table\n  \
+          sonar-cli --project my-proj history --metrics ncloc --from 2025-01-01 --to 2025-06-01\n  \
+          sonar-cli --project my-proj history --gate-history --from 2025-01-01")]
     History {
         /// Comma-separated metric keys (use 'measures' command to discover available keys)
-        #[arg(long)]
-        metrics: String,
+        #[arg(long, required_unless_present = "gate_history")]
+        metrics: Option<String>,
 
         /// Start date, inclusive (YYYY-MM-DD)
         #[arg(long)]
@@ -254,6 +584,52 @@ enum Command {
         /// End date, inclusive (YYYY-MM-DD)
         #[arg(long)]
         to: Option<String>,
+
+        /// Show all metrics in one table (dates as rows, metrics as columns)
+        #[arg(long)]
+        table: bool,
+
+        /// Show when the quality gate status (alert_status) changed instead of --metrics
+        #[arg(long, conflicts_with_all = ["metrics", "table"])]
+        gate_history: bool,
+    },
+
+    /// Compare metric values between two dates or analyses (requires --project)
+    #[command(long_about = "Compare metric values between two dates or analyses (requires --project).\n\n\
+        For each metric, takes the value from the last analysis at or before --from\n\
+        and at or before --to (the latest analysis when --to is omitted) and shows\n\
+        the change. Both accept a date (YYYY-MM-DD) or an analysis key. A metric\n\
+        with no value before --from shows '-'.\n\n\
+        Examples:\n  \
+          sonar-cli --project my-proj diff --from 2025-01-01\n  \
+          sonar-cli --project my-proj diff --from 2025-01-01 --to 2025-06-01 --metrics coverage,bugs\n  \
+          sonar-cli --project my-proj diff --from AU-TpxcA-iU5OvuD2FL0 --json")]
+    Diff {
+        /// Baseline: date (YYYY-MM-DD) or analysis key
+        #[arg(long)]
+        from: String,
+
+        /// Date (YYYY-MM-DD) or analysis key to compare against (default: latest analysis)
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Comma-separated metric keys (default: the 'measures' defaults)
+        #[arg(long)]
+        metrics: Option<String>,
+    },
+
+    /// Show a rule's description and remediation effort (no --project required)
+    #[command(long_about = "Show a rule's description and remediation effort (no --project required).\n\n\
+        Prints the rule's name, severity, type, language, tags and remediation\n\
+        effort, followed by its description as plain text. Use it to look up a\n\
+        rule key seen in 'issues' output. With --json, prints the rule object\n\
+        from /api/rules/show.\n\n\
+        Examples:\n  \
+          sonar-cli rule rust:S3776\n  \
+          sonar-cli rule java:S2259 --json")]
+    Rule {
+        /// Rule key, e.g. rust:S3776
+        key: String,
     },
 
     /// Search and browse quality rules (no --project required)
@@ -264,7 +640,12 @@ enum Command {
           sonar-cli rules\n  \
           sonar-cli rules --language java --severity CRITICAL\n  \
           sonar-cli rules --search \"null pointer\"\n  \
-          sonar-cli rules --rule-type BUG --status READY")]
+          sonar-cli rules --rule-type BUG --status READY\n  \
+          sonar-cli rules --language java --format ndjson\n  \
+          sonar-cli rules --language java --skip 50 --top 25\n  \
+          sonar-cli rules --language java --page 4 --page-size 500\n  \
+          sonar-cli --project my-proj rules --deprecated   # deprecated rules still active\n  \
+          sonar-cli rules --deprecated --quality-profile AU-Tpxb--iU5OvuD2FLy")]
     Rules {
         /// Search query to filter rules by name or description
         #[arg(long)]
@@ -285,6 +666,56 @@ enum Command {
         /// Status filter [default: all] (READY, DEPRECATED, BETA, REMOVED)
         #[arg(long)]
         status: Option<String>,
+
+        /// Deprecated rules only (same as --status DEPRECATED); with --project, only those
+        /// still active in the project's quality profiles
+        #[arg(long, conflicts_with = "status")]
+        deprecated: bool,
+
+        /// Only rules active in this quality profile (profile key)
+        #[arg(long = "quality-profile", value_name = "KEY")]
+        quality_profile: Option<String>,
+
+        /// Fetch only this page (1-based) instead of every page
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+        page: Option<u16>,
+
+        /// Results per page with --page (SonarQube caps it at 500) [default: 100]
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+        page_size: Option<u16>,
+
+        /// Output format: text (default), json, ndjson (one JSON object per line, streamed per page)
+        #[arg(long)]
+        format: Option<String>,
+
+        /// Show at most N results (client-side, applied after sorting)
+        #[arg(long, value_name = "N")]
+        top: Option<usize>,
+
+        /// Skip the first M results (client-side, applied after sorting)
+        #[arg(long, value_name = "M", default_value = "0")]
+        skip: usize,
+    },
+
+    /// List quality profiles or compare two of them (no --project required)
+    #[command(long_about = "List quality profiles or compare two of them (no --project required).\n\n\
+        Shows each profile's name, language, active rule count and whether it is\n\
+        the default for its language. With --project, only the profiles that\n\
+        project uses. --compare lists the rules active in only one of two\n\
+        profiles and the rules both activate with different severity or parameters.\n\n\
+        Examples:\n  \
+          sonar-cli profiles\n  \
+          sonar-cli profiles --language java\n  \
+          sonar-cli --project my-proj profiles   # profiles the project uses\n  \
+          sonar-cli profiles --compare AU-Tpxb--iU5OvuD2FLy AX-custom-java")]
+    Profiles {
+        /// Language filter (e.g. java, py, js, ts, go, cs)
+        #[arg(long)]
+        language: Option<String>,
+
+        /// Compare two profiles by key: rules only in each, and modified rules
+        #[arg(long, num_args = 2, value_names = ["LEFT_KEY", "RIGHT_KEY"], conflicts_with = "language")]
+        compare: Option<Vec<String>>,
     },
 
     /// Run sonar-scanner and optionally wait for analysis (requires --project)
@@ -297,7 +728,11 @@ enum Command {
         Examples:\n  \
           sonar-cli --project my-proj scan\n  \
           sonar-cli --project my-proj scan --wait\n  \
+          sonar-cli --project my-proj scan --wait --summary-only\n  \
+          sonar-cli --project my-proj scan --gate-wait   # exit 3 if the gate fails\n  \
           sonar-cli --project my-proj scan --clippy-report clippy.json --coverage-report coverage.xml\n  \
+          sonar-cli --project my-proj scan --coverage-report packages/api/coverage.xml --coverage-base .\n  \
+          sonar-cli --project my-proj scan --auto-coverage\n  \
           sonar-cli --project my-proj scan --wait --timeout 600 -- -Dsonar.sources=src\n  \
           sonar-cli --project my-proj scan --scanner dotnet --solution MyApp.sln\n  \
           sonar-cli --project my-proj scan --scanner dotnet --solution MyApp.sln --wait")]
@@ -314,11 +749,30 @@ enum Command {
         #[arg(long)]
         coverage_report: Option<String>,
 
+        /// Make coverage report paths relative to DIR instead of the report's <source>
+        /// (for monorepos; converts the report to SonarQube generic coverage)
+        #[arg(long, value_name = "DIR", requires = "coverage_report")]
+        coverage_base: Option<String>,
+
+        /// Find a coverage report (cobertura.xml, coverage.xml, lcov.info, target/**/cobertura.xml)
+        /// in the current directory and pass it to the scanner
+        #[arg(long, conflicts_with_all = ["coverage_report", "lcov_report"])]
+        auto_coverage: bool,
+
         /// Wait for analysis to complete after scan
         #[arg(long)]
         wait: bool,
 
-        /// Max wait time in seconds (used with --wait)
+        /// With --wait, print only the quality gate verdict and exit 1 unless it passes
+        #[arg(long, requires = "wait")]
+        summary_only: bool,
+
+        /// Have the scanner block on the quality gate (sonar.qualitygate.wait, timeout from
+        /// --wait-timeout); a failed gate exits with code 3
+        #[arg(long, conflicts_with = "wait")]
+        gate_wait: bool,
+
+        /// Max wait time in seconds (used with --wait and --gate-wait)
         #[arg(long, default_value = "300")]
         wait_timeout: u64,
 
@@ -354,7 +808,7 @@ enum Command {
         #[arg(long)]
         opencover_report: Option<String>,
 
-        /// Path to LCOV report for JS/TS coverage (dotnet scanner)
+        /// Path to LCOV report for JS/TS coverage
         #[arg(long)]
         lcov_report: Option<String>,
 
@@ -375,12 +829,13 @@ enum Command {
     #[command(long_about = "View source code of a file on the server (no --project required).\n\n\
         Retrieves the source code as stored in SonarQube. The component key is\n\
         typically PROJECT_KEY:path/to/file. Use 'projects' command to find\n\
-        project keys, then browse files.\n\n\
+        project keys, then 'tree' to find file keys.\n\n\
         Without --from/--to, fetches the entire file. With line range options,\n\
         fetches only the specified lines.\n\n\
         Examples:\n  \
           sonar-cli source my-project:src/main.rs\n  \
           sonar-cli source my-project:src/main.rs --from 1 --to 50\n  \
+          sonar-cli source my-project:src/main.rs --blame\n  \
           sonar-cli source my-project:src/main.rs --json")]
     Source {
         /// Component key (format: PROJECT_KEY:path/to/file, e.g. my-project:src/main.rs)
@@ -393,6 +848,29 @@ enum Command {
         /// End line number
         #[arg(long)]
         to: Option<usize>,
+
+        /// Show the last author and date of each line (from SCM data)
+        #[arg(long)]
+        blame: bool,
+    },
+
+    /// List recent background analysis tasks (requires --project)
+    #[command(long_about = "List recent background analysis tasks (requires --project).\n\n\
+        Shows the project's most recent compute engine tasks, newest first, with\n\
+        their type, status, submission and completion times and duration. Useful\n\
+        when a project shows stale data: a failed or stuck task explains why.\n\n\
+        Examples:\n  \
+          sonar-cli --project my-proj activity\n  \
+          sonar-cli --project my-proj activity --status FAILED --limit 5\n  \
+          sonar-cli --project my-proj activity --status PENDING,IN_PROGRESS")]
+    Activity {
+        /// Comma-separated task statuses: SUCCESS, FAILED, CANCELED, PENDING, IN_PROGRESS
+        #[arg(long)]
+        status: Option<String>,
+
+        /// Number of tasks to show (1-1000)
+        #[arg(long, default_value = "20")]
+        limit: usize,
     },
 
     /// Wait for a background analysis task to complete
@@ -402,7 +880,8 @@ enum Command {
         The 'scan --wait' flag does this automatically.\n\n\
         Examples:\n  \
           sonar-cli wait AXyz123abc\n  \
-          sonar-cli wait AXyz123abc --timeout 600 --poll-interval 10")]
+          sonar-cli wait AXyz123abc --timeout 600 --poll-interval 10\n  \
+          sonar-cli wait AXyz123abc --accept-status SUCCESS,FAILED")]
     Wait {
         /// Analysis task ID (printed by 'scan' command)
         task_id: String,
@@ -414,21 +893,330 @@ enum Command {
         /// Polling interval in seconds
         #[arg(long, default_value = "5")]
         poll_interval: u64,
+
+        /// Terminal statuses that count as success (comma-separated: SUCCESS, FAILED, CANCELED)
+        #[arg(long)]
+        accept_status: Option<String>,
     },
 
     /// Manage stored credentials (login, status, logout)
     #[command(long_about = "Manage stored credentials for SonarQube.\n\n\
         Credentials are saved to a global config file so you don't need to\n\
-        pass --url/--token or set env vars for every command.\n\n\
+        pass --url/--token or set env vars for every command. Several servers\n\
+        can be stored as named profiles and selected with --profile.\n\n\
         Priority: CLI flags > config file > defaults\n\n\
         Examples:\n  \
           sonar-cli auth login --url https://sonar.example.com --token squ_abc123\n  \
+          sonar-cli auth login --profile prod --url https://sonar.prod.example.com --token squ_def456\n  \
           sonar-cli auth status\n  \
+          sonar-cli --profile prod --project my-proj quality-gate\n  \
+          sonar-cli auth logout --profile prod\n  \
           sonar-cli auth logout")]
     Auth {
         #[command(subcommand)]
         action: AuthAction,
     },
+
+    /// Generate a shell completion script (no --url required)
+    #[command(long_about = "Generate a shell completion script (no --url required).\n\n\
+        Prints the script to stdout; source it from your shell's startup file.\n\n\
+        Examples:\n  \
+          sonar-cli completions bash > ~/.local/share/bash-completion/completions/sonar-cli\n  \
+          sonar-cli completions zsh > ~/.zfunc/_sonar-cli\n  \
+          sonar-cli completions fish > ~/.config/fish/completions/sonar-cli.fish\n  \
+          sonar-cli completions powershell >> $PROFILE")]
+    Completions {
+        /// Target shell: bash, zsh, fish, powershell
+        shell: String,
+    },
+
+    /// Manage the response cache used by --cache (no --url required)
+    #[command(long_about = "Manage the response cache used by --cache (no --url required).\n\n\
+        With --cache, GET responses are stored under the config directory\n\
+        (sonar-cli/cache) and reused for --cache-ttl seconds (default 300);\n\
+        --no-cache fetches fresh responses. Error responses and POSTs are never\n\
+        cached, and scan, wait and issues --watch always query the server.\n\n\
+        Examples:\n  \
+          sonar-cli --project my-proj --cache measures\n  \
+          sonar-cli --project my-proj --cache --cache-ttl 3600 coverage\n  \
+          sonar-cli cache clear")]
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+}
+
+#[derive(Args)]
+struct IssuesArgs {
+    /// Minimum severity — shows this level and above (INFO, MINOR, MAJOR, CRITICAL, BLOCKER)
+    #[arg(long)]
+    severity: Option<String>,
+
+    /// Issue type filter (BUG, VULNERABILITY, CODE_SMELL, SECURITY_HOTSPOT)
+    #[arg(long, name = "type")]
+    issue_type: Option<String>,
+
+    /// Maximum number of issues to return
+    #[arg(long)]
+    limit: Option<usize>,
+
+    /// Status filter [default: OPEN,CONFIRMED,REOPENED] (OPEN, CONFIRMED, REOPENED, RESOLVED, CLOSED)
+    #[arg(long)]
+    status: Option<String>,
+
+    /// Resolution filter — only applies to RESOLVED/CLOSED issues (FALSE-POSITIVE, WONTFIX, FIXED, REMOVED)
+    #[arg(long)]
+    resolution: Option<String>,
+
+    /// Tags filter (comma-separated)
+    #[arg(long)]
+    tags: Option<String>,
+
+    /// Rule key filter (comma-separated, e.g. java:S1234 — use 'rules' command to discover keys)
+    #[arg(long)]
+    rule: Option<String>,
+
+    /// Only issues created after this date (YYYY-MM-DD)
+    #[arg(long)]
+    created_after: Option<String>,
+
+    /// Only issues created before this date (YYYY-MM-DD)
+    #[arg(long)]
+    created_before: Option<String>,
+
+    /// Filter by issue author login
+    #[arg(long)]
+    author: Option<String>,
+
+    /// Assignee filter (comma-separated logins, use 'unassigned' for unassigned issues)
+    #[arg(long)]
+    assignee: Option<String>,
+
+    /// Language filter (comma-separated, e.g. java,py,js)
+    #[arg(long)]
+    language: Option<String>,
+
+    /// Only show issues in the new code period
+    #[arg(long)]
+    new_code: bool,
+
+    /// Only issues in these files (comma-separated paths relative to the project root)
+    #[arg(long)]
+    file: Option<String>,
+
+    /// Only issues under these path prefixes (comma-separated, e.g. src/api/),
+    /// filtered after fetching
+    #[arg(long, value_name = "PREFIXES", conflicts_with = "changed_since_analysis")]
+    path: Option<String>,
+
+    /// Drop issues under these path prefixes (comma-separated)
+    #[arg(long, value_name = "PREFIXES", conflicts_with = "changed_since_analysis")]
+    exclude_path: Option<String>,
+
+    /// Source scope filter, sent as the API `scopes` param (MAIN, TEST)
+    #[arg(long, visible_alias = "component-qualifiers")]
+    qualifiers: Option<String>,
+
+    /// Also include CLOSED issues alongside the active --status set
+    #[arg(long)]
+    include_closed: bool,
+
+    /// Send no status filter, so issues of every status are returned
+    #[arg(
+        long,
+        visible_alias = "no-default-status",
+        conflicts_with_all = ["status", "include_closed", "changed_since_analysis"]
+    )]
+    all_statuses: bool,
+
+    /// Show each issue's rule default severity next to its own severity
+    #[arg(long)]
+    show_rule_severity: bool,
+
+    /// Show each issue's rule name next to its rule key
+    #[arg(long)]
+    with_rule_names: bool,
+
+    /// Fetch and show each issue's comment thread
+    #[arg(long)]
+    comments: bool,
+
+    /// Keep the server's order instead of sorting by severity, file and line
+    #[arg(long)]
+    no_sort: bool,
+
+    /// Sort key: severity (most severe first), file, line or rule; ties fall back to file, then line.
+    /// Not supported with --format ndjson, which streams in server order
+    #[arg(long, value_name = "KEY", default_value = "severity", conflicts_with = "no_sort")]
+    sort: String,
+
+    /// Reverse the sort order
+    #[arg(long, conflicts_with = "no_sort")]
+    reverse: bool,
+
+    /// Show at most N results (client-side, applied after sorting)
+    #[arg(long, value_name = "N")]
+    top: Option<usize>,
+
+    /// Skip the first M results (client-side, applied after sorting)
+    #[arg(long, value_name = "M", default_value = "0")]
+    skip: usize,
+
+    /// Show each issue's full component key instead of its project-relative path
+    #[arg(long)]
+    show_full_component: bool,
+
+    /// Fetch only this page (1-based) instead of every page
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..), conflicts_with = "changed_since_analysis")]
+    page: Option<u16>,
+
+    /// Results per page with --page (SonarQube caps it at 500) [default: 100]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..), conflicts_with = "changed_since_analysis")]
+    page_size: Option<u16>,
+
+    /// Print issue counts per group instead of the issues, largest first: rule, file,
+    /// severity, type (--top/--skip then apply to the groups)
+    #[arg(long, value_name = "KEY", conflicts_with_all = ["watch", "changed_since_analysis", "open"])]
+    group_by: Option<String>,
+
+    /// Print the listed issues' total remediation effort, and per severity, after the list
+    /// (8h days, like SonarQube)
+    #[arg(long, conflicts_with_all = ["watch", "changed_since_analysis", "group_by"])]
+    show_effort: bool,
+
+    /// Only issues created since the most recent analysis that passed the quality gate
+    #[arg(long, conflicts_with = "created_after")]
+    since_last_green: bool,
+
+    /// Show N lines of source above and below each issue's line, with the line marked
+    /// (one request per issue location)
+    #[arg(long, value_name = "N", default_value = "0", conflicts_with_all = ["group_by", "changed_since_analysis", "interactive"])]
+    context: usize,
+
+    /// Browse the issues in a scrollable terminal UI with a details pane and source
+    /// snippets (needs a build with `--features tui` and a terminal)
+    #[arg(long, conflicts_with_all = ["watch", "changed_since_analysis", "group_by", "show_effort", "open"])]
+    interactive: bool,
+
+    /// Open the issue (when exactly one is found) or the filtered search in the
+    /// SonarQube UI; prints the URL instead when no browser is available
+    #[arg(long, visible_alias = "open-in-browser", conflicts_with = "changed_since_analysis")]
+    open: bool,
+
+    /// Count issues opened and resolved per severity over the last N analyses
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = [
+            "status", "include_closed", "created_after", "created_before", "new_code",
+            "limit", "comments", "show_rule_severity", "with_rule_names", "no_sort", "reverse", "top",
+            "since_last_green"
+        ]
+    )]
+    changed_since_analysis: Option<usize>,
+
+    /// Re-run the search periodically and print newly appeared and closed issues (text output only)
+    #[arg(long, conflicts_with_all = ["changed_since_analysis", "open"])]
+    watch: bool,
+
+    /// Seconds between polls with --watch
+    #[arg(long, value_name = "SECS", default_value = "30", requires = "watch")]
+    interval: u64,
+
+    /// How --watch shows each poll: append (only changes) or redraw (clear and show all)
+    #[arg(long, value_name = "MODE", default_value = "append", requires = "watch")]
+    watch_mode: String,
+
+    /// Exit with code 2 when any issue matches (counted before --top/--skip)
+    #[arg(long, conflicts_with_all = ["watch", "changed_since_analysis", "limit", "page"])]
+    fail_on_issues: bool,
+
+    /// Exit with code 2 when more than N issues match (counted before --top/--skip)
+    #[arg(long, value_name = "N", conflicts_with_all = ["watch", "changed_since_analysis", "limit", "page"])]
+    max_issues: Option<usize>,
+
+    /// Exit with code 2 when an issue at or above this severity matches, whether or not
+    /// --top/--skip list it (INFO, MINOR, MAJOR, CRITICAL, BLOCKER)
+    #[arg(long, value_name = "LEVEL", conflicts_with_all = ["watch", "changed_since_analysis", "limit", "page"])]
+    fail_on_severity: Option<String>,
+
+    /// Output format: text (default), json, csv, tsv, ndjson (one issue per line in server
+    /// order, streamed per page)
+    #[arg(long)]
+    format: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Delete all cached responses
+    Clear,
+}
+
+#[derive(Subcommand)]
+enum IssueAction {
+    /// Assign the issue to a user
+    Assign {
+        /// Issue key
+        issue: String,
+
+        /// Login of the new assignee
+        #[arg(long, value_name = "LOGIN")]
+        to: String,
+    },
+
+    /// Add a comment to the issue
+    Comment {
+        /// Issue key
+        issue: String,
+
+        /// Comment text (Markdown)
+        #[arg(long)]
+        text: String,
+    },
+
+    /// Apply a workflow transition to the issue
+    Transition {
+        /// Issue key
+        issue: String,
+
+        /// Transition: confirm, resolve, falsepositive, wontfix, reopen
+        #[arg(long = "do", value_name = "TRANSITION")]
+        transition: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum HotspotAction {
+    /// Assign a hotspot to a user (requires a token)
+    Assign {
+        /// Hotspot key
+        #[arg(long)]
+        hotspot: String,
+
+        /// Login of the new assignee
+        #[arg(long, value_name = "LOGIN")]
+        to: String,
+    },
+
+    /// Show a hotspot with its rule's risk, vulnerability and fix guidance
+    Show {
+        /// Hotspot key
+        hotspot: String,
+    },
+
+    /// Change a hotspot's review status (requires a token allowed to administer hotspots)
+    Resolve {
+        /// Hotspot key
+        hotspot: String,
+
+        /// New status: REVIEWED or TO_REVIEW
+        #[arg(long, default_value = "REVIEWED")]
+        status: String,
+
+        /// Review outcome with REVIEWED: SAFE, FIXED or ACKNOWLEDGED
+        #[arg(long)]
+        resolution: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -453,30 +1241,120 @@ enum AuthAction {
 
 impl Cli {
     fn build_config(&self) -> SonarQubeConfig {
-        let stored = config::load();
-
-        let url = self.url.clone()
-            .or(stored.url)
-            .unwrap_or_else(|| {
+        let server = config::load().server(self.profile.as_deref()).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            std::process::exit(1);
+        });
+
+        let (url, source) = match (&self.url, server.url) {
+            (Some(url), _) => (url.clone(), "--url".to_string()),
+            (None, Some(url)) => {
+                let stored = config::load();
+                (url, format!("profile '{}'", stored.profile_name(self.profile.as_deref())))
+            }
+            (None, None) => {
                 eprintln!("URL is required. Use --url, or run `sonar-cli auth login`.");
                 std::process::exit(1);
-            });
+            }
+        };
+        let url = match client::normalize_url(&url) {
+            Ok(normalized) => {
+                if normalized.assumed_scheme {
+                    eprintln!("Warning: URL '{url}' from {source} has no scheme; using {}", normalized.url);
+                }
+                normalized.url
+            }
+            Err(e) => {
+                eprintln!("{e} (from {source})");
+                std::process::exit(1);
+            }
+        };
 
         let mut config = SonarQubeConfig::new(&url)
-            .with_timeout(std::time::Duration::from_secs(self.timeout));
+            .with_timeout(std::time::Duration::from_secs(self.timeout))
+            .with_connect_timeout(std::time::Duration::from_secs(self.connect_timeout))
+            .with_retry(RetryPolicy {
+                idempotent_only: self.retry_idempotent_only,
+                ..Default::default()
+            })
+            .with_retries(self.retries)
+            .with_concurrency(usize::from(self.concurrency))
+            .with_print_urls(self.print_url)
+            .with_dry_run(self.dry_run);
+
+        if self.cache {
+            match cache::default_dir() {
+                Some(dir) => {
+                    let ttl = std::time::Duration::from_secs(self.cache_ttl);
+                    config = config.with_cache(ResponseCache::new(dir, ttl).with_refresh(self.no_cache));
+                }
+                None => tracing::warn!("Could not determine config directory; not caching responses"),
+            }
+        }
 
-        if let Some(ref token) = self.token.clone().or(stored.token) {
+        if let Some(ref proxy) = self.proxy {
+            config = config.with_proxy(proxy);
+        }
+        if let Some(ref cacert) = self.cacert {
+            config = config.with_ca_cert(cacert);
+        }
+        if self.insecure {
+            eprintln!("Warning: --insecure disables TLS certificate verification.");
+            config = config.with_insecure(true);
+        }
+        let token = token::resolve(
+            token::TokenSources {
+                flag: self.token.as_deref(),
+                file: self.token_file.as_deref(),
+                command: self.token_command.as_deref(),
+                stored: server.token,
+                env: std::env::var(token::TOKEN_ENV).ok(),
+            },
+            token::TOKEN_COMMAND_TIMEOUT,
+        )
+        .unwrap_or_else(|e| {
+            eprintln!("{e}");
+            std::process::exit(1);
+        });
+        if let Some(ref token) = token {
             config = config.with_token(token);
         }
         if let Some(ref project) = self.project {
             config = config.with_project(project);
         }
-        if let Some(ref branch) = self.branch {
-            config = config.with_branch(branch);
+        match (&self.branch, &self.pull_request) {
+            (Some(_), Some(_)) => {
+                eprintln!(
+                    "--branch and --pull-request cannot be used together (check SONAR_BRANCH / SONAR_PULL_REQUEST)."
+                );
+                std::process::exit(1);
+            }
+            (Some(branch), None) => config = config.with_branch(branch),
+            (None, Some(pr)) => config = config.with_pull_request(pr),
+            (None, None) => {}
+        }
+        for raw in &self.headers {
+            match parse_header(raw, self.allow_auth_header) {
+                Ok((name, value)) => config = config.with_header(name, value),
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            }
         }
         config
     }
 
+    /// Load the ignore file for path filtering, or print an error and exit.
+    fn path_filter(&self) -> PathFilter {
+        match PathFilter::resolve(self.ignore_file.as_deref(), self.no_ignore_file) {
+            Ok(f) => f,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+    }
 }
 
 /// Initialise the tracing subscriber.
@@ -495,20 +1373,61 @@ fn init_tracing(verbose: bool) {
 }
 
 /// Dispatch auth sub-commands that do not require a SonarQube client.
-async fn handle_auth(action: &AuthAction, json: bool) -> i32 {
+async fn handle_auth(action: &AuthAction, profile: Option<&str>, json: bool) -> Result<(), error::CliError> {
+    let path = config::config_path().ok_or_else(|| {
+        error::CliError::Failed(format!(
+            "Could not determine config directory; set {} to a config file path",
+            config::CONFIG_ENV
+        ))
+    })?;
     match action {
         AuthAction::Login { url, token } => {
-            commands::auth::login(url.clone(), token.clone(), json).await
+            commands::auth::login(&path, url.clone(), token.clone(), profile, json).await
+        }
+        AuthAction::Status => commands::auth::status(&path, profile, json).await,
+        AuthAction::Logout => commands::auth::logout(&path, profile, json).await,
+    }
+}
+
+/// Resolve the output format for a command or print an error and exit.
+fn format_or_exit(format: &Option<String>, json: bool) -> output::OutputFormat {
+    match output::resolve_output_format(format.as_deref(), json) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
         }
-        AuthAction::Status => commands::auth::status(json).await,
-        AuthAction::Logout => commands::auth::logout(json).await,
     }
 }
 
-/// Return the project key or print an error and exit.
-fn project_or_exit(project: &Option<String>) -> &str {
+/// Wrap JSON output in the command metadata envelope unless `--bare-json`
+fn set_json_envelope(
+    bare: bool,
+    command: &str,
+    server: &str,
+    project: Option<String>,
+    branch: Option<String>,
+) {
+    if bare {
+        return;
+    }
+    output::set_json_envelope(output::JsonEnvelope {
+        command: command.to_string(),
+        server: server.to_string(),
+        project,
+        branch,
+        generated_at: helpers::format_utc_timestamp(helpers::unix_now()),
+    });
+}
+
+/// Return the project key or print an error and exit. Sets `used` so the
+/// project can be remembered once the command succeeds.
+fn project_or_exit<'a>(project: &'a Option<String>, used: &mut bool) -> &'a str {
     match project.as_deref() {
-        Some(p) => p,
+        Some(p) => {
+            *used = true;
+            p
+        }
         None => {
             eprintln!("Project key is required. Use --project, set SONAR_PROJECT_KEY, or run `sonar-cli auth login`.");
             std::process::exit(1);
@@ -518,50 +1437,164 @@ fn project_or_exit(project: &Option<String>) -> &str {
 
 #[tokio::main]
 async fn main() {
-    let cli = Cli::parse();
+    let argv = std::env::args_os()
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect();
+    let argv = defaults::apply_command_defaults(&Cli::command(), argv, &config::load().defaults);
+    let matches = Cli::command().get_matches_from(argv);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let command_name = matches.subcommand_name().unwrap_or_default();
 
     init_tracing(cli.verbose);
+    output::set_compact_json(cli.compact_json);
+    output::set_quiet(cli.quiet);
+    if let Some(ref path) = cli.output {
+        if let Err(e) = output::set_output_file(path) {
+            eprintln!("Failed to open output file {}: {e}", path.display());
+            std::process::exit(1);
+        }
+    }
+    match output::parse_color_choice(&cli.color) {
+        Ok(choice) => {
+            use std::io::IsTerminal;
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+            let tty = cli.output.is_none() && std::io::stdout().is_terminal();
+            output::set_color(output::color_enabled(choice, tty, no_color));
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(ref project) = cli.project {
+        match config::load().resolve_project_ref(project) {
+            Ok(key) => cli.project = Some(key),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Command::Projects { recent: true, .. } = cli.command {
+        let stored = config::load();
+        let server = cli.url.clone()
+            .or(stored.server(cli.profile.as_deref()).unwrap_or_default().url)
+            .unwrap_or_default();
+        set_json_envelope(cli.bare_json, command_name, &server, cli.project.clone(), cli.branch.clone());
+        output::print_recent_projects(&stored.recent_projects, cli.json);
+        std::process::exit(0);
+    }
 
     // Auth commands don't need a SonarQube client — handle early.
     if let Command::Auth { ref action } = cli.command {
-        std::process::exit(handle_auth(action, cli.json).await);
+        let server = cli.url.clone()
+            .or(config::load().server(cli.profile.as_deref()).unwrap_or_default().url)
+            .unwrap_or_default();
+        set_json_envelope(cli.bare_json, command_name, &server, cli.project.clone(), cli.branch.clone());
+        let result = handle_auth(action, cli.profile.as_deref(), cli.json).await;
+        std::process::exit(output::final_exit_code(error::report(result)));
+    }
+    if let Command::Completions { ref shell } = cli.command {
+        std::process::exit(error::report(commands::completions::run(shell, Cli::command())));
+    }
+    if let Command::Cache { action: CacheAction::Clear } = cli.command {
+        let server = cli.url.clone()
+            .or(config::load().server(cli.profile.as_deref()).unwrap_or_default().url)
+            .unwrap_or_default();
+        set_json_envelope(cli.bare_json, command_name, &server, cli.project.clone(), cli.branch.clone());
+        std::process::exit(output::final_exit_code(error::report(commands::cache::clear(cli.json))));
     }
 
-    let config = cli.build_config();
+    let mut config = cli.build_config();
+    if cli.resolve_main_branch {
+        config = client::resolve_main_branch(config).await;
+    }
+    set_json_envelope(
+        cli.bare_json,
+        command_name,
+        &config.url,
+        config.project_key.clone(),
+        config.branch.clone(),
+    );
 
-    let exit_code = match cli.command {
-        Command::Auth { .. } => unreachable!(),
+    let mut project_used = false;
+    let result = match cli.command {
+        Command::Auth { .. } | Command::Completions { .. } | Command::Cache { .. } => unreachable!(),
 
-        Command::Health => commands::health::run(config, cli.json).await,
+        Command::Health { full, strict } => commands::health::run(config, full, strict, cli.json).await,
 
-        Command::QualityGate { fail_on_error } => {
-            let project = project_or_exit(&cli.project);
-            commands::quality_gate::run(config, project, fail_on_error, cli.json).await
-        }
+        Command::Version => commands::version::run(config, cli.json).await,
 
-        Command::Issues {
-            ref severity,
-            ref issue_type,
-            limit,
-            ref status,
-            ref resolution,
-            ref tags,
-            ref rule,
-            ref created_after,
-            ref created_before,
-            ref author,
-            ref assignee,
-            ref language,
-            new_code,
+        Command::Whoami => commands::whoami::run(config, cli.json).await,
+
+        Command::QualityGate {
+            fail_on_error,
+            ref only,
         } => {
-            let project = project_or_exit(&cli.project);
+            let project = project_or_exit(&cli.project, &mut project_used);
+            commands::quality_gate::run(config, project, fail_on_error, only.as_deref(), cli.json)
+                .await
+        }
+
+        Command::Issues(ref args) => {
+            let IssuesArgs {
+                ref severity,
+                ref issue_type,
+                limit,
+                ref status,
+                ref resolution,
+                ref tags,
+                ref rule,
+                ref created_after,
+                ref created_before,
+                ref author,
+                ref assignee,
+                ref language,
+                new_code,
+                ref file,
+                ref path,
+                ref exclude_path,
+                ref qualifiers,
+                include_closed,
+                all_statuses,
+                show_rule_severity,
+                with_rule_names,
+                comments,
+                no_sort,
+                ref sort,
+                reverse,
+                top,
+                skip,
+                show_full_component,
+                page,
+                page_size,
+                ref group_by,
+                show_effort,
+                since_last_green,
+                context,
+                interactive,
+                open,
+                changed_since_analysis,
+                watch,
+                interval,
+                ref watch_mode,
+                fail_on_issues,
+                max_issues,
+                ref fail_on_severity,
+                ref format,
+            } = **args;
+            let project = project_or_exit(&cli.project, &mut project_used);
+            let format = format_or_exit(format, cli.json);
             let severities = commands::issues::build_severity_filter(severity.as_deref());
+            let statuses = commands::issues::build_status_filter(status.as_deref(), include_closed);
             let types = issue_type.as_ref().map(|t| t.to_uppercase());
+            let scopes = qualifiers.as_ref().map(|q| q.to_uppercase());
             let in_new_code = if new_code { Some(true) } else { None };
             let search_params = IssueSearchParams {
                 severities: severities.as_deref(),
                 types: types.as_deref(),
-                statuses: status.as_deref(),
+                statuses: statuses.as_deref(),
                 resolutions: resolution.as_deref(),
                 tags: tags.as_deref(),
                 rules: rule.as_deref(),
@@ -571,38 +1604,240 @@ async fn main() {
                 assignees: assignee.as_deref(),
                 languages: language.as_deref(),
                 in_new_code_period: in_new_code,
+                files: file.as_deref(),
+                scopes: scopes.as_deref(),
+                with_comments: comments,
+                facets: None,
+                sort_desc: None,
+                all_statuses,
+            };
+            if let Some(analyses) = changed_since_analysis {
+                commands::issues::run_changes(config, project, &search_params, analyses, format)
+                    .await
+            } else {
+                let view = commands::issues::IssueView {
+                    window: Window { skip, top },
+                    show_rule_severity,
+                    with_rule_names,
+                    no_sort,
+                    sort: commands::issues::parse_sort(sort).unwrap_or_else(|e| {
+                        eprintln!("{e}");
+                        std::process::exit(1);
+                    }),
+                    reverse,
+                    ignore: cli.path_filter(),
+                    paths: commands::issues::PathPrefixes::from_flags(path.as_deref(), exclude_path.as_deref()),
+                    open_in_browser: open,
+                    show_full_component,
+                    since_last_green,
+                    group_by: group_by.as_deref().map(|value| {
+                        commands::issues::parse_group_by(value).unwrap_or_else(|e| {
+                            eprintln!("{e}");
+                            std::process::exit(1);
+                        })
+                    }),
+                    show_effort,
+                    page: PageRequest::from_flags(page.map(usize::from), page_size.map(usize::from)),
+                    interactive,
+                    context,
+                };
+                if watch {
+                    let mode = match commands::issues::parse_watch_mode(watch_mode) {
+                        Ok(m) => m,
+                        Err(e) => {
+                            eprintln!("{e}");
+                            std::process::exit(1);
+                        }
+                    };
+                    let watch = commands::issues::WatchOptions {
+                        interval: std::time::Duration::from_secs(interval.max(1)),
+                        mode,
+                    };
+                    commands::issues::run_watch(config, project, &search_params, limit, &view, watch, format)
+                        .await
+                } else {
+                    let min_severity = fail_on_severity.as_deref().map(|level| {
+                        commands::issues::parse_fail_severity(level).unwrap_or_else(|e| {
+                            eprintln!("{e}");
+                            std::process::exit(1);
+                        })
+                    });
+                    let threshold = commands::issues::IssueThreshold {
+                        fail_on_any: fail_on_issues,
+                        max_issues,
+                        min_severity,
+                    };
+                    commands::issues::run(config, project, &search_params, limit, &view, &threshold, format)
+                    .await
+                }
+            }
+        }
+
+        Command::Measures {
+            projects: Some(ref projects),
+            ref metrics,
+            ref format,
+            ..
+        } => match commands::measures::read_project_keys(projects, std::io::stdin().lock()) {
+            Ok(keys) => {
+                let format = format_or_exit(format, cli.json);
+                commands::measures::run_matrix(config, &keys, metrics.as_deref(), format).await
+            }
+            Err(e) => Err(error::CliError::Validation(e)),
+        },
+
+        Command::Measures {
+            ref metrics,
+            ref compare_branch,
+            ref component,
+            new_code,
+            human,
+            trend,
+            projects: None,
+            all,
+            ref assertions,
+            ref format,
+        } => {
+            let project = match component.as_deref() {
+                Some(c) => c,
+                None => project_or_exit(&cli.project, &mut project_used),
             };
-            commands::issues::run(config, project, &search_params, limit, cli.json).await
+            let view = match compare_branch.as_deref() {
+                Some(base) => commands::measures::MeasuresView::CompareBranch(base),
+                None if new_code => commands::measures::MeasuresView::NewCode,
+                None if trend => commands::measures::MeasuresView::Trend,
+                None if all => commands::measures::MeasuresView::All,
+                None => commands::measures::MeasuresView::Current,
+            };
+            let assertions: Vec<_> = assertions
+                .iter()
+                .map(|a| {
+                    commands::measures::parse_assertion(a).unwrap_or_else(|e| {
+                        eprintln!("{e}");
+                        std::process::exit(1);
+                    })
+                })
+                .collect();
+            let format = format_or_exit(format, cli.json);
+            commands::measures::run(config, project, metrics.as_deref(), view, human, &assertions, format)
+                .await
+        }
+
+        Command::NewCode => {
+            let project = project_or_exit(&cli.project, &mut project_used);
+            commands::new_code::run(config, project, cli.json).await
+        }
+
+        Command::Diff {
+            ref from,
+            ref to,
+            ref metrics,
+        } => {
+            let project = project_or_exit(&cli.project, &mut project_used);
+            commands::diff::run(config, project, metrics.as_deref(), from, to.as_deref(), cli.json).await
         }
 
-        Command::Measures { ref metrics } => {
-            let project = project_or_exit(&cli.project);
-            commands::measures::run(config, project, metrics.as_deref(), cli.json).await
+        Command::Branches => {
+            let project = project_or_exit(&cli.project, &mut project_used);
+            commands::branches::run(config, project, cli.json).await
+        }
+
+        Command::Tree { qualifier, search } => {
+            let project = project_or_exit(&cli.project, &mut project_used);
+            commands::tree::run(config, project, &qualifier, search.as_deref(), cli.json).await
         }
 
         Command::Coverage {
             min_coverage,
             ref sort,
+            top,
+            skip,
+            ref format,
         } => {
-            let project = project_or_exit(&cli.project);
-            commands::coverage::run(config, project, min_coverage, sort.as_deref(), cli.json).await
+            let project = project_or_exit(&cli.project, &mut project_used);
+            let window = Window { skip, top };
+            let format = format_or_exit(format, cli.json);
+            commands::coverage::run(
+                config,
+                project,
+                min_coverage,
+                sort.as_deref(),
+                window,
+                &cli.path_filter(),
+                format,
+            )
+            .await
         }
 
-        Command::Duplications { details } => {
-            let project = project_or_exit(&cli.project);
-            commands::duplications::run(config, project, details, cli.json).await
+        Command::Duplications { details, top, skip } => {
+            let project = project_or_exit(&cli.project, &mut project_used);
+            let window = Window { skip, top };
+            commands::duplications::run(config, project, details, window, &cli.path_filter(), cli.json)
+                .await
         }
 
-        Command::Hotspots { ref status, new_code } => {
-            let project = project_or_exit(&cli.project);
-            commands::hotspots::run(config, project, status.as_deref(), new_code, cli.json).await
+        Command::Issue { ref action } => {
+            use commands::issue::IssueAction as Action;
+            let (issue, action) = match action {
+                IssueAction::Assign { issue, to } => (issue, Action::Assign { to }),
+                IssueAction::Comment { issue, text } => (issue, Action::Comment { text }),
+                IssueAction::Transition { issue, transition } => {
+                    (issue, Action::Transition { transition })
+                }
+            };
+            commands::issue::run(config, issue, action, cli.json).await
+        }
+
+        Command::Hotspots {
+            action: Some(HotspotAction::Assign { ref hotspot, ref to }),
+            ..
+        } => commands::hotspots::assign(config, hotspot, to, cli.json).await,
+
+        Command::Hotspots {
+            action: Some(HotspotAction::Show { ref hotspot }),
+            ..
+        } => commands::hotspots::show(config, hotspot, cli.json).await,
+
+        Command::Hotspots {
+            action: Some(HotspotAction::Resolve { ref hotspot, ref status, ref resolution }),
+            ..
+        } => commands::hotspots::resolve(config, hotspot, status, resolution.as_deref(), cli.json).await,
+
+        Command::Hotspots {
+            action: None,
+            ref status,
+            new_code,
+            ref assigned_to,
+        } => {
+            let project = project_or_exit(&cli.project, &mut project_used);
+            commands::hotspots::run(
+                config,
+                project,
+                status.as_deref(),
+                new_code,
+                assigned_to.as_deref(),
+                cli.json,
+            )
+            .await
+        }
+
+        Command::Report { ref output_dir, resume } => {
+            let project = project_or_exit(&cli.project, &mut project_used);
+            let filter = cli.path_filter();
+            commands::report::run(config, project, output_dir, &filter, resume, cli.json).await
         }
 
         Command::Projects {
             ref search,
             ref qualifier,
+            ref format,
+            page,
+            page_size,
+            ..
         } => {
-            commands::projects::run(config, search.as_deref(), Some(qualifier.as_str()), cli.json)
+            let format = format_or_exit(format, cli.json);
+            let page = PageRequest::from_flags(page.map(usize::from), page_size.map(usize::from));
+            commands::projects::run(config, search.as_deref(), Some(qualifier.as_str()), page, format)
                 .await
         }
 
@@ -610,43 +1845,81 @@ async fn main() {
             ref metrics,
             ref from,
             ref to,
+            table,
+            gate_history,
         } => {
-            let project = project_or_exit(&cli.project);
+            let project = project_or_exit(&cli.project, &mut project_used);
+            let view = if gate_history {
+                commands::history::HistoryView::GateTimeline
+            } else if table {
+                commands::history::HistoryView::Table
+            } else {
+                commands::history::HistoryView::PerMetric
+            };
             commands::history::run(
                 config,
                 project,
-                metrics,
+                metrics.as_deref().unwrap_or_default(),
                 from.as_deref(),
                 to.as_deref(),
+                view,
                 cli.json,
             )
             .await
         }
 
+        Command::Profiles {
+            ref language,
+            ref compare,
+        } => match compare.as_deref() {
+            Some([left, right]) => commands::profiles::run_compare(config, left, right, cli.json).await,
+            _ => {
+                commands::profiles::run(config, language.as_deref(), cli.project.as_deref(), cli.json)
+                    .await
+            }
+        },
+
+        Command::Rule { ref key } => commands::rule::run(config, key, cli.json).await,
+
         Command::Rules {
             ref search,
             ref language,
             ref severity,
             ref rule_type,
             ref status,
+            deprecated,
+            ref quality_profile,
+            page,
+            page_size,
+            ref format,
+            top,
+            skip,
         } => {
-            commands::rules::run(
-                config,
-                search.as_deref(),
-                language.as_deref(),
-                severity.as_deref(),
-                rule_type.as_deref(),
-                status.as_deref(),
-                cli.json,
-            )
-            .await
+            let format = format_or_exit(format, cli.json);
+            let params = RuleSearchParams {
+                search: search.as_deref(),
+                language: language.as_deref(),
+                severity: severity.as_deref(),
+                rule_type: rule_type.as_deref(),
+                status: if deprecated { Some("DEPRECATED") } else { status.as_deref() },
+                rule_keys: None,
+                profile: quality_profile.as_deref(),
+            };
+            // The audit is scoped to the project's profiles unless one is named
+            let project = cli.project.as_deref().filter(|_| deprecated && quality_profile.is_none());
+            let page = PageRequest::from_flags(page.map(usize::from), page_size.map(usize::from));
+            commands::rules::run(config, &params, project, page, Window { skip, top }, format).await
         }
 
         Command::Scan {
             ref scanner,
             ref clippy_report,
             ref coverage_report,
+            ref coverage_base,
+            auto_coverage,
             wait,
+            summary_only,
+            gate_wait,
             wait_timeout,
             poll_interval,
             no_scm,
@@ -661,7 +1934,11 @@ async fn main() {
             skip_tests,
             ref extra,
         } => {
-            let project = project_or_exit(&cli.project);
+            let project = project_or_exit(&cli.project, &mut project_used);
+            if cli.dry_run {
+                eprintln!("--dry-run is not supported by scan, which runs sonar-scanner.");
+                std::process::exit(1);
+            }
             let scanner_kind = match commands::scan::parse_scanner_kind(scanner) {
                 Ok(k) => k,
                 Err(e) => {
@@ -673,7 +1950,11 @@ async fn main() {
                 scanner: scanner_kind,
                 clippy_report: clippy_report.clone(),
                 coverage_report: coverage_report.clone(),
+                coverage_base: coverage_base.clone(),
+                auto_coverage,
                 wait,
+                summary_only,
+                gate_wait,
                 timeout: wait_timeout,
                 poll_interval,
                 no_scm,
@@ -696,15 +1977,44 @@ async fn main() {
             ref component,
             from,
             to,
-        } => commands::source::run(config, component, from, to, cli.json).await,
+            blame,
+        } => commands::source::run(config, component, from, to, blame, cli.json).await,
+
+        Command::Activity { ref status, limit } => {
+            let project = project_or_exit(&cli.project, &mut project_used);
+            commands::activity::run(config, project, status.as_deref(), limit, cli.json).await
+        }
 
         Command::Wait {
             task_id,
             timeout,
             poll_interval,
-        } => commands::wait::run(config, &task_id, timeout, poll_interval, cli.json).await,
+            accept_status,
+        } => {
+            commands::wait::run(
+                config,
+                &task_id,
+                timeout,
+                poll_interval,
+                accept_status.as_deref(),
+                cli.json,
+            )
+            .await
+        }
 
     };
 
-    std::process::exit(exit_code);
+    // Remember only projects a command actually ran against; a failed gate or
+    // threshold still counts, a bad key or an unreachable server does not
+    let ran = match &result {
+        Ok(()) => true,
+        Err(e) => matches!(e, error::CliError::Threshold(_) | error::CliError::Exit(_)),
+    };
+    if project_used && ran && !cli.dry_run {
+        if let Some(ref project) = cli.project {
+            config::remember_project(project);
+        }
+    }
+
+    std::process::exit(output::final_exit_code(error::report(result)));
 }
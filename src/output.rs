@@ -1,32 +1,600 @@
 //! Output formatting — human-readable and JSON
 
-use crate::helpers::{FileCoverage, FileDuplication};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use crate::helpers::{
+    extract_path, format_duration, format_effort, html_to_text, EffortSummary, humanize_count, task_duration_secs, strip_html_tags, FileCoverage, FileDuplication, GateChange, HistoryTable,
+    trend_arrow, MeasureComparison, MeasureTrend, MetricDiff, MeasuresMatrix, ReportSummary, SeverityChange,
+};
 use crate::types::{
-    AnalysisTask, MeasureHistory, MeasuresResponse,
-    ProjectInfo, QualityGateResponse,
-    RuleInfo, SecurityHotspot, SonarIssue, SourceLine,
+    AnalysisTask, CurrentUser, Measure, MeasureHistory, MeasuresResponse, NewCodePeriod,
+    ProfileComparison, ProjectBranch, ProjectInfo, QualityGateResponse, QualityProfile, RuleActivation, RuleDetails,
+    BlameLine, HotspotDetails, MetricKind, RuleInfo, SecurityHotspot, SonarIssue, SourceLine, SystemHealth, SystemInfo, TreeComponent,
+    metric_kind, rating_label,
 };
 
-/// Print value as JSON to stdout
+/// Output format for list commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Ndjson,
+    Csv,
+    Tsv,
+}
+
+impl OutputFormat {
+    /// Name used in messages
+    pub fn label(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "Text",
+            OutputFormat::Json => "JSON",
+            OutputFormat::Ndjson => "NDJSON",
+            OutputFormat::Csv => "CSV",
+            OutputFormat::Tsv => "TSV",
+        }
+    }
+
+    /// Field separator for the delimited table formats (CSV, TSV)
+    pub fn delimiter(self) -> Option<char> {
+        match self {
+            OutputFormat::Csv => Some(','),
+            OutputFormat::Tsv => Some('\t'),
+            _ => None,
+        }
+    }
+}
+
+pub fn parse_output_format(s: &str) -> Result<OutputFormat, String> {
+    match s.to_lowercase().as_str() {
+        "text" => Ok(OutputFormat::Text),
+        "json" => Ok(OutputFormat::Json),
+        "ndjson" => Ok(OutputFormat::Ndjson),
+        "csv" => Ok(OutputFormat::Csv),
+        "tsv" => Ok(OutputFormat::Tsv),
+        other => Err(format!(
+            "Unknown output format '{other}'. Valid values: text, json, ndjson, csv, tsv"
+        )),
+    }
+}
+
+/// Error for a `--format` that `command` cannot produce, listing the ones it can
+pub fn unsupported_format(format: OutputFormat, command: &str, supported: &str) -> String {
+    format!("{} output is not supported for {command}. Use {supported}.", format.label())
+}
+
+/// Resolve `--format` against the global `--json` flag (an explicit `--format` wins).
+pub fn resolve_output_format(format: Option<&str>, json: bool) -> Result<OutputFormat, String> {
+    match format {
+        Some(f) => parse_output_format(f),
+        None if json => Ok(OutputFormat::Json),
+        None => Ok(OutputFormat::Text),
+    }
+}
+
+/// `--color` setting for text output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+pub fn parse_color_choice(s: &str) -> Result<ColorChoice, String> {
+    match s.to_lowercase().as_str() {
+        "auto" => Ok(ColorChoice::Auto),
+        "always" => Ok(ColorChoice::Always),
+        "never" => Ok(ColorChoice::Never),
+        other => Err(format!("Unknown color mode '{other}'. Valid values: auto, always, never")),
+    }
+}
+
+/// Whether text output is colored: `auto` colors only a terminal, and only
+/// when `NO_COLOR` is unset
+pub fn color_enabled(choice: ColorChoice, stdout_is_tty: bool, no_color: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => stdout_is_tty && !no_color,
+    }
+}
+
+/// Whether text printers emit ANSI colors (set once from `--color`)
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Turn ANSI colors in text output on or off
+pub fn set_color(enabled: bool) {
+    COLOR.store(enabled, Ordering::Relaxed);
+}
+
+/// Wrap `text` in the ANSI SGR `code` when `color` is on
+fn paint(text: &str, code: Option<&str>, color: bool) -> String {
+    match code {
+        Some(code) if color => format!("\x1b[{code}m{text}\x1b[0m"),
+        _ => text.to_string(),
+    }
+}
+
+/// Color for a severity: red for BLOCKER/CRITICAL, yellow for MAJOR, cyan for MINOR
+fn severity_color(severity: &str) -> Option<&'static str> {
+    match severity {
+        "BLOCKER" | "CRITICAL" => Some("31"),
+        "MAJOR" => Some("33"),
+        "MINOR" => Some("36"),
+        _ => None,
+    }
+}
+
+/// Color for a compute engine task status: green on success, red on failure,
+/// yellow when canceled, none while queued or running
+fn task_status_color(status: &str) -> Option<&'static str> {
+    match status {
+        "SUCCESS" => Some("32"),
+        "FAILED" => Some("31"),
+        "CANCELED" => Some("33"),
+        _ => None,
+    }
+}
+
+/// Color for a quality gate status: green when passed, yellow on warning, red otherwise
+fn gate_color(status: &str) -> Option<&'static str> {
+    match status {
+        "OK" => Some("32"),
+        "WARN" => Some("33"),
+        _ => Some("31"),
+    }
+}
+
+/// Whether count headers and progress messages are suppressed (set once from `--quiet`)
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Suppress count headers on stdout and progress messages on stderr
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Whether `--quiet` is set
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Print an informational message to stderr unless `--quiet` is set.
+/// Errors and warnings go straight to `eprintln!` instead.
+pub fn progress(message: &str) {
+    if !quiet() {
+        eprintln!("{message}");
+    }
+}
+
+/// Whether `print_json` emits single-line JSON (set once from `--compact-json`)
+static COMPACT_JSON: AtomicBool = AtomicBool::new(false);
+
+/// Switch `print_json` between pretty-printed and single-line output
+pub fn set_compact_json(compact: bool) {
+    COMPACT_JSON.store(compact, Ordering::Relaxed);
+}
+
+/// Command metadata wrapped around every payload printed by `print_json`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct JsonEnvelope {
+    pub command: String,
+    pub server: String,
+    pub project: Option<String>,
+    pub branch: Option<String>,
+    pub generated_at: String,
+}
+
+/// A JSON payload under `data`, next to the envelope fields
+#[derive(serde::Serialize)]
+pub struct Enveloped<'a, T: ?Sized> {
+    #[serde(flatten)]
+    meta: &'a JsonEnvelope,
+    data: &'a T,
+}
+
+/// Wrap a payload in the command metadata envelope
+pub fn wrap_json<'a, T: serde::Serialize + ?Sized>(
+    meta: &'a JsonEnvelope,
+    data: &'a T,
+) -> Enveloped<'a, T> {
+    Enveloped { meta, data }
+}
+
+/// Envelope applied by `print_json`; unset (bare payloads) with `--bare-json`
+static JSON_ENVELOPE: OnceLock<JsonEnvelope> = OnceLock::new();
+
+/// Wrap everything `print_json` prints in `envelope` (set once at startup)
+pub fn set_json_envelope(envelope: JsonEnvelope) {
+    let _ = JSON_ENVELOPE.set(envelope);
+}
+
+/// Serialize a value as JSON, pretty-printed unless `compact`.
+///
+/// Output is byte-stable for golden tests: structs keep field order, and maps
+/// (including `json!` objects) are `BTreeMap`-backed, so their keys come out
+/// sorted. Keyed shapes should use `BTreeMap`, never `HashMap`.
+pub fn format_json<T: serde::Serialize + ?Sized>(
+    value: &T,
+    compact: bool,
+) -> serde_json::Result<String> {
+    if compact {
+        serde_json::to_string(value)
+    } else {
+        serde_json::to_string_pretty(value)
+    }
+}
+
+/// File that replaces stdout for formatted output (`--output`); unset means stdout
+static OUTPUT_FILE: OnceLock<File> = OnceLock::new();
+
+/// Create (or truncate) `path` for writing, creating missing parent directories
+fn create_output_file(path: &Path) -> io::Result<File> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    File::create(path)
+}
+
+/// Send all formatted output to `path` instead of stdout (set once at startup).
+/// Errors and progress messages still go to stderr.
+pub fn set_output_file(path: &Path) -> io::Result<()> {
+    let file = create_output_file(path)?;
+    let _ = OUTPUT_FILE.set(file);
+    Ok(())
+}
+
+/// Where formatted output goes: the `--output` file when set, stdout otherwise.
+///
+/// Every printer in this module writes through here, as do commands that
+/// stream CSV/TSV/NDJSON.
+pub fn sink() -> Box<dyn Write> {
+    match OUTPUT_FILE.get() {
+        Some(file) => Box::new(io::BufWriter::new(file)),
+        None => Box::new(io::stdout().lock()),
+    }
+}
+
+/// Set once writing formatted output fails; the process then exits non-zero
+static OUTPUT_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Whether writing formatted output has failed during this run
+pub fn output_failed() -> bool {
+    OUTPUT_FAILED.load(Ordering::Relaxed)
+}
+
+/// Run a text printer against the output sink; on failure report it and mark
+/// the run as failed
+fn emit(write: impl FnOnce(&mut dyn Write) -> io::Result<()>) {
+    let mut out = sink();
+    if let Err(e) = write(&mut out).and_then(|()| out.flush()) {
+        eprintln!("Failed to write output: {e}");
+        OUTPUT_FAILED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Print one line of text output
+pub fn print_line(text: &str) {
+    emit(|out| writeln!(out, "{text}"));
+}
+
+/// Turn a successful exit code into a failure when output could not be written
+pub fn final_exit_code(code: i32) -> i32 {
+    if code == 0 && output_failed() {
+        1
+    } else {
+        code
+    }
+}
+
+/// Write value as JSON to `out`, inside the command envelope when one is set.
+///
+/// The whole document is serialized before anything is written, so a
+/// serialization error leaves `out` untouched.
+pub fn write_json<W: std::io::Write, T: serde::Serialize + ?Sized>(
+    out: &mut W,
+    value: &T,
+) -> std::io::Result<()> {
+    let compact = COMPACT_JSON.load(Ordering::Relaxed);
+    let json = match JSON_ENVELOPE.get() {
+        Some(meta) => format_json(&wrap_json(meta, value), compact),
+        None => format_json(value, compact),
+    }?;
+    writeln!(out, "{json}")
+}
+
+/// Print value as JSON to the output sink; on failure report it and mark the run as failed
 pub fn print_json<T: serde::Serialize + ?Sized>(value: &T) {
-    match serde_json::to_string_pretty(value) {
-        Ok(json) => println!("{json}"),
-        Err(e) => eprintln!("Failed to serialize JSON: {e}"),
+    let mut out = sink();
+    if let Err(e) = write_json(&mut out, value).and_then(|()| out.flush()) {
+        eprintln!("Failed to write JSON: {e}");
+        OUTPUT_FAILED.store(true, Ordering::Relaxed);
     }
 }
 
+/// Write each item as a single-line JSON object (newline-delimited JSON)
+pub fn write_ndjson<W: std::io::Write, T: serde::Serialize>(
+    out: &mut W,
+    items: &[T],
+) -> std::io::Result<()> {
+    for item in items {
+        serde_json::to_writer(&mut *out, item)?;
+        out.write_all(b"\n")?;
+    }
+    out.flush()
+}
+
+/// Escape one field for a delimited table.
+///
+/// CSV quotes fields containing a separator, quote or line break. TSV has no
+/// quoting, so tabs, line breaks and backslashes are written as `\t`, `\n`,
+/// `\r` and `\\` instead; commas and quotes are left as they are.
+fn delimited_field(value: &str, delimiter: char) -> String {
+    if delimiter == '\t' {
+        return value
+            .replace('\\', "\\\\")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r");
+    }
+    if value.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Write a header row and data rows separated by `delimiter`
+fn write_delimited<W: std::io::Write>(
+    out: &mut W,
+    delimiter: char,
+    header: &[&str],
+    rows: impl IntoIterator<Item = Vec<String>>,
+) -> std::io::Result<()> {
+    let sep = delimiter.to_string();
+    writeln!(out, "{}", header.join(&sep))?;
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|f| delimited_field(f, delimiter)).collect();
+        writeln!(out, "{}", fields.join(&sep))?;
+    }
+    out.flush()
+}
+
+/// Write projects as CSV or TSV with a header row; missing values are empty fields
+pub fn write_projects_delimited<W: std::io::Write>(
+    out: &mut W,
+    projects: &[ProjectInfo],
+    delimiter: char,
+) -> std::io::Result<()> {
+    let rows = projects.iter().map(|p| {
+        vec![
+            p.key.clone(),
+            p.name.clone(),
+            p.qualifier.clone().unwrap_or_default(),
+            p.visibility.clone().unwrap_or_default(),
+            p.last_analysis_date.clone().unwrap_or_default(),
+        ]
+    });
+    write_delimited(
+        out,
+        delimiter,
+        &["key", "name", "qualifier", "visibility", "last_analysis_date"],
+        rows,
+    )
+}
+
+/// Write issues as CSV or TSV, one row per issue
+pub fn write_issues_delimited<W: std::io::Write>(
+    out: &mut W,
+    issues: &[SonarIssue],
+    project: &str,
+    delimiter: char,
+) -> std::io::Result<()> {
+    let rows = issues.iter().map(|i| {
+        vec![
+            i.key.clone(),
+            i.severity.clone(),
+            i.issue_type.clone(),
+            i.status.clone(),
+            i.rule.clone(),
+            extract_path(&i.component, project),
+            i.line
+                .or(i.text_range.as_ref().map(|r| r.start_line))
+                .map(|l| l.to_string())
+                .unwrap_or_default(),
+            i.message.clone(),
+        ]
+    });
+    write_delimited(
+        out,
+        delimiter,
+        &["key", "severity", "type", "status", "rule", "file", "line", "message"],
+        rows,
+    )
+}
+
+/// Write file coverage as CSV or TSV, one row per file
+pub fn write_coverage_delimited<W: std::io::Write>(
+    out: &mut W,
+    files: &[FileCoverage],
+    delimiter: char,
+) -> std::io::Result<()> {
+    let rows = files.iter().map(|f| {
+        vec![
+            f.file.clone(),
+            format!("{:.1}", f.coverage_percent),
+            f.uncovered_lines.to_string(),
+            f.lines_to_cover.to_string(),
+        ]
+    });
+    write_delimited(
+        out,
+        delimiter,
+        &["file", "coverage", "uncovered_lines", "lines_to_cover"],
+        rows,
+    )
+}
+
+/// Write measures as CSV or TSV, one row per metric; missing values are empty fields
+pub fn write_measures_delimited<W: std::io::Write>(
+    out: &mut W,
+    response: &MeasuresResponse,
+    delimiter: char,
+) -> std::io::Result<()> {
+    let rows = response
+        .component
+        .measures
+        .iter()
+        .map(|m| vec![m.metric.clone(), m.value.clone().unwrap_or_default()]);
+    write_delimited(out, delimiter, &["metric", "value"], rows)
+}
+
 /// Format health check output
-pub fn print_health(status: &str, url: &str, json: bool) {
+pub fn print_health(status: &str, url: &str, info: Option<&SystemInfo>, json: bool) {
+    if json {
+        print_json(&health_json(status, url, info));
+        return;
+    }
+
+    emit(|out| {
+        let icon = if status == "UP" { "OK" } else { "FAIL" };
+        writeln!(out, "[{icon}] SonarQube at {url} — status: {status}")?;
+        if let Some(info) = info {
+            let fields = [
+                ("Version", &info.version),
+                ("Edition", &info.edition),
+                ("Server ID", &info.server_id),
+            ];
+            for (label, value) in fields {
+                if let Some(value) = value {
+                    writeln!(out, "  {label}: {value}")?;
+                }
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Build the `health --json` document; server details are only included when known
+pub fn health_json(status: &str, url: &str, info: Option<&SystemInfo>) -> serde_json::Value {
+    let mut doc = serde_json::json!({
+        "url": url,
+        "status": status,
+        "healthy": status == "UP",
+    });
+    if let Some(info) = info {
+        let fields = [
+            ("version", &info.version),
+            ("serverId", &info.server_id),
+            ("edition", &info.edition),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                doc[key] = serde_json::Value::String(value.clone());
+            }
+        }
+    }
+    doc
+}
+
+/// Format `health --full` output: the node health and each cause; `healthy`
+/// is the verdict the exit code follows
+pub fn print_system_health(health: &SystemHealth, url: &str, healthy: bool, json: bool) {
     if json {
         print_json(&serde_json::json!({
             "url": url,
-            "status": status,
-            "healthy": status == "UP",
+            "health": health.health,
+            "healthy": healthy,
+            "causes": health.causes,
         }));
-    } else {
-        let icon = if status == "UP" { "OK" } else { "FAIL" };
-        println!("[{icon}] SonarQube at {url} — status: {status}");
+        return;
+    }
+
+    emit(|out| {
+        let icon = match (health.health.as_str(), healthy) {
+            ("GREEN", _) => "OK",
+            (_, true) => "WARN",
+            (_, false) => "FAIL",
+        };
+        writeln!(out, "[{icon}] SonarQube at {url} — health: {}", health.health)?;
+        for cause in &health.causes {
+            writeln!(out, "  - {cause}")?;
+        }
+        Ok(())
+    });
+}
+
+/// Format `version` output; edition and database are only known to admin tokens
+pub fn print_version(info: &SystemInfo, json: bool) {
+    if json {
+        print_json(info);
+        return;
+    }
+
+    emit(|out| write_version(out, info));
+}
+
+/// Text output for [`print_version`]
+fn write_version(out: &mut dyn Write, info: &SystemInfo) -> io::Result<()> {
+    writeln!(out, "SonarQube {}", info.version.as_deref().unwrap_or("(unknown version)"))?;
+    for (label, value) in [("Edition", &info.edition), ("Database", &info.database)] {
+        if let Some(value) = value {
+            writeln!(out, "  {label}: {value}")?;
+        }
     }
+    Ok(())
+}
+
+/// Format `whoami` output: the user behind the token, whether the token is
+/// valid, and the server version/edition when known
+pub fn print_whoami(user: &CurrentUser, valid: bool, has_token: bool, url: &str, info: Option<&SystemInfo>, json: bool) {
+    if json {
+        print_json(&whoami_json(user, valid));
+        return;
+    }
+
+    emit(|out| write_whoami(out, user, valid, has_token, url, info));
+}
+
+/// Text output for [`print_whoami`]
+fn write_whoami(
+    out: &mut dyn Write,
+    user: &CurrentUser,
+    valid: bool,
+    has_token: bool,
+    url: &str,
+    info: Option<&SystemInfo>,
+) -> io::Result<()> {
+    writeln!(out, "SonarQube at {url}")?;
+    if let Some(info) = info {
+        for (label, value) in [("Version", &info.version), ("Edition", &info.edition)] {
+            if let Some(value) = value {
+                writeln!(out, "  {label}: {value}")?;
+            }
+        }
+    }
+    let anonymous = "(anonymous)".to_string();
+    writeln!(out, "  Login: {}", user.login.as_ref().unwrap_or(&anonymous))?;
+    if let Some(name) = &user.name {
+        writeln!(out, "  Name: {name}")?;
+    }
+    let token = match (has_token, valid) {
+        (false, _) => "none (anonymous)",
+        (true, true) => "valid",
+        (true, false) => "invalid",
+    };
+    writeln!(out, "  Token: {token}")
+}
+
+/// Build the `whoami --json` document: the user object as the server returned
+/// it, plus whether the token is valid
+fn whoami_json(user: &CurrentUser, valid: bool) -> serde_json::Value {
+    let mut doc = serde_json::to_value(user).unwrap_or_default();
+    doc["valid"] = serde_json::Value::Bool(valid);
+    doc
 }
 
 /// Format quality gate output
@@ -36,152 +604,694 @@ pub fn print_quality_gate(response: &QualityGateResponse, project: &str, json: b
         return;
     }
 
+    emit(|out| {
+        writeln!(
+            out,
+            "{}",
+            gate_header(&response.project_status.status, project, COLOR.load(Ordering::Relaxed))
+        )?;
+
+        if !response.project_status.conditions.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "  {:<30} {:<10} {:<10} Threshold", "Metric", "Status", "Value")?;
+            writeln!(out, "  {}", "-".repeat(70))?;
+            for cond in &response.project_status.conditions {
+                let value = cond.actual_value.as_deref().unwrap_or("-");
+                let threshold = cond.error_threshold.as_deref().unwrap_or("-");
+                let comparator = cond.comparator.as_deref().unwrap_or("");
+                writeln!(
+                    out,
+                    "  {:<30} {:<10} {:<10} {comparator} {threshold}",
+                    cond.metric_key, cond.status, value
+                )?;
+            }
+        }
+        Ok(())
+    });
+}
+
+/// First line of the quality gate report, e.g. `Quality Gate: [PASSED] OK  (project: p)`
+pub fn gate_header(status: &str, project: &str, color: bool) -> String {
+    let icon = match status {
+        "OK" => "PASSED",
+        "WARN" => "WARNING",
+        _ => "FAILED",
+    };
+    format!(
+        "Quality Gate: [{}] {status}  (project: {project})",
+        paint(icon, gate_color(status), color)
+    )
+}
+
+/// Comparator symbol for a gate condition (the condition fails when this holds)
+fn comparator_symbol(comparator: Option<&str>) -> &str {
+    match comparator {
+        Some("LT") => "<",
+        Some("GT") => ">",
+        Some("EQ") => "=",
+        Some("NE") => "!=",
+        Some(other) => other,
+        None => "?",
+    }
+}
+
+/// One-line quality gate verdict, e.g. `Quality Gate: FAILED (coverage 72.0% < 80%)`
+pub fn format_gate_summary(response: &QualityGateResponse) -> String {
     let status = &response.project_status.status;
-    let icon = match status.as_str() {
+    let verdict = match status.as_str() {
         "OK" => "PASSED",
         "WARN" => "WARNING",
         _ => "FAILED",
     };
-    println!("Quality Gate: [{icon}] {status}  (project: {project})");
 
-    if !response.project_status.conditions.is_empty() {
-        println!();
-        println!("  {:<30} {:<10} {:<10} Threshold", "Metric", "Status", "Value");
-        println!("  {}", "-".repeat(70));
-        for cond in &response.project_status.conditions {
-            let value = cond.actual_value.as_deref().unwrap_or("-");
-            let threshold = cond.error_threshold.as_deref().unwrap_or("-");
-            let comparator = cond.comparator.as_deref().unwrap_or("");
-            println!(
-                "  {:<30} {:<10} {:<10} {comparator} {threshold}",
-                cond.metric_key, cond.status, value
-            );
-        }
+    let failing: Vec<String> = response
+        .project_status
+        .conditions
+        .iter()
+        .filter(|c| c.status != "OK")
+        .map(|c| {
+            let unit = if c.metric_key.contains("coverage") || c.metric_key.ends_with("density") {
+                "%"
+            } else {
+                ""
+            };
+            format!(
+                "{} {}{unit} {} {}{unit}",
+                c.metric_key,
+                c.actual_value.as_deref().unwrap_or("-"),
+                comparator_symbol(c.comparator.as_deref()),
+                c.error_threshold.as_deref().unwrap_or("-"),
+            )
+        })
+        .collect();
+
+    if failing.is_empty() {
+        format!("Quality Gate: {verdict}")
+    } else {
+        format!("Quality Gate: {verdict} ({})", failing.join(", "))
+    }
+}
+
+/// Where an issue is shown: `path:line`, or the full component key with `full_component`.
+///
+/// Only the issue's own `project:` prefix is removed, so keys with more colons
+/// (e.g. branch-qualified ones) keep the rest of their path.
+pub fn issue_location(issue: &SonarIssue, full_component: bool) -> String {
+    let file = if full_component {
+        issue.component.clone()
+    } else {
+        extract_path(&issue.component, &issue.project)
+    };
+    let line = issue.line.or(issue.text_range.as_ref().map(|r| r.start_line));
+    match line {
+        Some(l) => format!("{file}:{l}"),
+        None => file,
     }
 }
 
+/// Severity, type and location line that starts each issue in text output
+pub fn issue_header(issue: &SonarIssue, full_component: bool, color: bool) -> String {
+    // Pad before painting so escape codes don't count towards the column width
+    let severity = format!("{:<8}", issue.severity);
+    format!(
+        "  [{}] [{:<8}] {}",
+        paint(&severity, severity_color(&issue.severity), color),
+        issue.issue_type,
+        issue_location(issue, full_component)
+    )
+}
+
+/// Clear the terminal and move the cursor home (ANSI), for `--watch-mode redraw`
+pub fn clear_screen() {
+    emit(|out| write!(out, "\x1b[2J\x1b[H"));
+}
+
+/// Format the issues that appeared and disappeared since the previous poll
+pub fn print_issue_diff(added: &[&SonarIssue], removed: &[&SonarIssue], timestamp: &str, full_component: bool) {
+    emit(|out| {
+        writeln!(out)?;
+        writeln!(out, "[{timestamp}] {} new, {} closed", added.len(), removed.len())?;
+        let color = COLOR.load(Ordering::Relaxed);
+        for issue in added {
+            writeln!(out, "+{}", issue_header(issue, full_component, color))?;
+            writeln!(out, "           {}", issue.message)?;
+        }
+        for issue in removed {
+            writeln!(out, "-{}", issue_header(issue, full_component, color))?;
+            writeln!(out, "           {}", issue.message)?;
+        }
+        Ok(())
+    });
+}
+
 /// Format issues output
-pub fn print_issues(issues: &[SonarIssue], project: &str, json: bool) {
+pub fn print_issues(issues: &[SonarIssue], project: &str, full_component: bool, json: bool) {
     if json {
         print_json(issues);
         return;
     }
 
-    println!("{} issues found (project: {project})", issues.len());
+    emit(|out| write_issues(out, issues, project, full_component, quiet()));
+}
+
+/// Text output for [`print_issues`]; `quiet` drops the "N issues found" header
+fn write_issues(
+    out: &mut dyn Write,
+    issues: &[SonarIssue],
+    project: &str,
+    full_component: bool,
+    quiet: bool,
+) -> io::Result<()> {
+    if !quiet {
+        writeln!(out, "{} issues found (project: {project})", issues.len())?;
+    }
     if issues.is_empty() {
-        return;
+        return Ok(());
     }
 
-    println!();
+    writeln!(out)?;
+    let color = COLOR.load(Ordering::Relaxed);
     for issue in issues {
-        let line_str = issue
-            .line
-            .or(issue.text_range.as_ref().map(|r| r.start_line))
-            .map(|l| format!(":{l}"))
-            .unwrap_or_default();
-
-        let file = issue
-            .component
-            .split(':')
-            .nth(1)
-            .unwrap_or(&issue.component);
-
-        println!(
-            "  [{:<8}] [{:<8}] {file}{line_str}",
-            issue.severity, issue.issue_type
-        );
-        println!("           {}", issue.message);
+        writeln!(out, "{}", issue_header(issue, full_component, color))?;
+        writeln!(out, "           {}", issue.message)?;
         if !issue.tags.is_empty() {
-            println!("           tags: {}", issue.tags.join(", "));
+            writeln!(out, "           tags: {}", issue.tags.join(", "))?;
+        }
+        if let Some(ref rule_name) = issue.rule_name {
+            writeln!(out, "           rule: {} ({rule_name})", issue.rule)?;
+        }
+        if let Some(ref rule_severity) = issue.rule_severity {
+            writeln!(out, "           rule default severity: {rule_severity}")?;
+        }
+        for line in issue_comment_lines(issue) {
+            writeln!(out, "           {line}")?;
+        }
+        for line in issue_snippet_lines(issue) {
+            writeln!(out, "           {line}")?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Format issues followed by their remediation effort (`issues --show-effort`).
+/// JSON wraps both: `{"issues": [...], "effort": {...}}`.
+pub fn print_issues_with_effort(
+    issues: &[SonarIssue],
+    project: &str,
+    full_component: bool,
+    effort: &EffortSummary,
+    json: bool,
+) {
+    if json {
+        print_json(&serde_json::json!({ "issues": issues, "effort": effort }));
+        return;
+    }
+
+    emit(|out| {
+        write_issues(out, issues, project, full_component, quiet())?;
+        write_effort(out, effort)
+    });
+}
+
+/// Effort summary that follows the issues in [`print_issues_with_effort`]
+fn write_effort(out: &mut dyn Write, effort: &EffortSummary) -> io::Result<()> {
+    writeln!(out)?;
+    writeln!(out, "Total effort: {}", format_effort(effort.total_minutes))?;
+    for level in &effort.by_severity {
+        writeln!(out, "  {:<10} {}", level.severity, format_effort(level.minutes))?;
+    }
+    if effort.unknown > 0 {
+        writeln!(out, "  ({} issues without an effort estimate)", effort.unknown)?;
+    }
+    Ok(())
+}
+
+/// Format `issues --group-by` output: issue count per group, largest first.
+/// JSON is an array of `{"group": ..., "count": ...}` in the same order.
+pub fn print_issues_summary(groups: &[(String, usize)], total: usize, plural: &str, json: bool) {
+    if json {
+        print_json(&issues_summary_json(groups));
+        return;
+    }
+
+    emit(|out| write_issues_summary(out, groups, total, plural));
+}
+
+/// JSON document for [`print_issues_summary`]; an array keeps the group order
+fn issues_summary_json(groups: &[(String, usize)]) -> serde_json::Value {
+    groups
+        .iter()
+        .map(|(group, count)| serde_json::json!({"group": group, "count": count}))
+        .collect()
+}
+
+/// Text output for [`print_issues_summary`]
+fn write_issues_summary(
+    out: &mut dyn Write,
+    groups: &[(String, usize)],
+    total: usize,
+    plural: &str,
+) -> io::Result<()> {
+    if !quiet() {
+        writeln!(out, "{total} issues in {} {plural}", groups.len())?;
+    }
+    if groups.is_empty() {
+        return Ok(());
+    }
+    if !quiet() {
+        writeln!(out)?;
+    }
+    let width = groups.iter().map(|(group, _)| group.len()).max().unwrap_or(0);
+    for (group, count) in groups {
+        writeln!(out, "  {group:<width$}  {count:>6}")?;
+    }
+    Ok(())
+}
+
+/// Render an issue's comment thread: a header per comment, then its text indented
+fn issue_comment_lines(issue: &SonarIssue) -> Vec<String> {
+    let mut lines = Vec::new();
+    for comment in &issue.comments {
+        let author = comment.login.as_deref().unwrap_or("unknown");
+        match comment.created_at.as_deref() {
+            Some(date) => lines.push(format!("comment by {author} on {date}:")),
+            None => lines.push(format!("comment by {author}:")),
         }
-        println!();
+        let text = match (&comment.markdown, &comment.html_text) {
+            (Some(markdown), _) => markdown.clone(),
+            (None, Some(html)) => strip_html_tags(html),
+            (None, None) => String::new(),
+        };
+        lines.extend(text.lines().map(|l| format!("  {l}")));
     }
+    lines
+}
+
+/// Render an issue's source snippet (`issues --context`), marking the issue's line with `>`
+pub fn issue_snippet_lines(issue: &SonarIssue) -> Vec<String> {
+    let width = issue.snippet.last().map_or(1, |l| l.line.to_string().len());
+    issue
+        .snippet
+        .iter()
+        .map(|l| {
+            let marker = if issue.line.map(|n| n as usize) == Some(l.line) { '>' } else { ' ' };
+            format!("{marker} {:>width$} | {}", l.line, l.code)
+        })
+        .collect()
 }
 
 /// Format measures output
-pub fn print_measures(response: &MeasuresResponse, json: bool) {
+pub fn print_measures(response: &MeasuresResponse, human: bool, json: bool) {
+    if json {
+        print_json(&measures_json(response));
+        return;
+    }
+
+    emit(|out| {
+        if !quiet() {
+            writeln!(out, "Measures for: {}", response.component.key)?;
+            writeln!(out)?;
+        }
+        writeln!(out, "  {:<35} Value", "Metric")?;
+        writeln!(out, "  {}", "-".repeat(50))?;
+        for measure in &response.component.measures {
+            let value = display_measure_value(response, measure, human);
+            writeln!(out, "  {:<35} {value}", measure.metric)?;
+        }
+        Ok(())
+    });
+}
+
+/// JSON form of a measures response, with each measure's `kind` (when known),
+/// its value as a number (`value_num`) and, for ratings, the letter (`rating`)
+fn measures_json(response: &MeasuresResponse) -> serde_json::Value {
+    let mut json = serde_json::to_value(response).unwrap_or_default();
+    let Some(measures) = json.pointer_mut("/component/measures").and_then(|m| m.as_array_mut()) else {
+        return json;
+    };
+    for (entry, measure) in measures.iter_mut().zip(&response.component.measures) {
+        let Some(entry) = entry.as_object_mut() else { continue };
+        let kind = metric_kind(&measure.metric).or_else(|| server_metric_kind(response, &measure.metric));
+        if let Some(kind) = kind {
+            entry.insert("kind".to_string(), serde_json::json!(kind));
+        }
+        let Some(value) = measure.value.as_deref() else { continue };
+        let number = match value.parse::<i64>() {
+            Ok(n) => Some(serde_json::Value::from(n)),
+            Err(_) => value.parse::<f64>().ok().and_then(|f| serde_json::Number::from_f64(f).map(Into::into)),
+        };
+        if let Some(number) = number {
+            entry.insert("value_num".to_string(), number);
+        }
+        if kind == Some(MetricKind::Rating) {
+            if let Some(label) = rating_label(value) {
+                entry.insert("rating".to_string(), serde_json::json!(label));
+            }
+        }
+    }
+    json
+}
+
+/// Kind of a metric missing from `METRIC_META`, from the server's metric type
+fn server_metric_kind(response: &MeasuresResponse, metric: &str) -> Option<MetricKind> {
+    let definition = response.metrics.iter().find(|m| m.key == metric)?;
+    match definition.metric_type.as_str() {
+        "PERCENT" => Some(MetricKind::Percent),
+        "RATING" => Some(MetricKind::Rating),
+        "INT" | "SIZE" => Some(MetricKind::Count),
+        "WORK_DUR" => Some(MetricKind::Duration),
+        _ => None,
+    }
+}
+
+/// Display form of a measure value: SIZE/INT counts get thousands separators
+/// (or k/M abbreviations with `human`), anything else is shown as returned
+fn display_measure_value(response: &MeasuresResponse, measure: &Measure, human: bool) -> String {
+    let Some(value) = measure.value.as_deref() else {
+        return "-".to_string();
+    };
+    let is_count = response
+        .metrics
+        .iter()
+        .any(|m| m.key == measure.metric && matches!(m.metric_type.as_str(), "SIZE" | "INT"));
+    match value.parse::<i64>() {
+        Ok(n) if is_count => humanize_count(n, human),
+        _ => value.to_string(),
+    }
+}
+
+/// Value of a new-code measure: current servers report it under `period`,
+/// older ones as the plain `value`
+fn new_code_value(measure: &Measure) -> Option<&str> {
+    measure
+        .period
+        .as_ref()
+        .map(|p| p.value.as_str())
+        .or(measure.value.as_deref())
+}
+
+/// One line per new-code measure, e.g. `new coverage: 64.0% (on 1,200 new lines)`
+pub fn new_code_measure_lines(response: &MeasuresResponse, human: bool) -> Vec<String> {
+    let new_lines = response
+        .component
+        .measures
+        .iter()
+        .find(|m| m.metric == "new_lines")
+        .and_then(new_code_value)
+        .and_then(|v| v.parse::<i64>().ok());
+
+    response
+        .component
+        .measures
+        .iter()
+        .map(|m| {
+            let label = m.metric.replace('_', " ");
+            let Some(value) = new_code_value(m) else {
+                return format!("{label}: -");
+            };
+            let is_percent = ["coverage", "density", "ratio"]
+                .iter()
+                .any(|suffix| m.metric.ends_with(suffix));
+            if is_percent {
+                match new_lines {
+                    Some(n) => format!("{label}: {value}% (on {} new lines)", humanize_count(n, human)),
+                    None => format!("{label}: {value}%"),
+                }
+            } else {
+                match value.parse::<i64>() {
+                    Ok(n) => format!("{label}: {}", humanize_count(n, human)),
+                    Err(_) => format!("{label}: {value}"),
+                }
+            }
+        })
+        .collect()
+}
+
+/// Format new-code measures output
+pub fn print_new_code_measures(response: &MeasuresResponse, human: bool, json: bool) {
     if json {
         print_json(response);
         return;
     }
 
-    println!("Measures for: {}", response.component.key);
-    println!();
-    println!("  {:<35} Value", "Metric");
-    println!("  {}", "-".repeat(50));
-    for measure in &response.component.measures {
-        let value = measure.value.as_deref().unwrap_or("-");
-        println!("  {:<35} {value}", measure.metric);
+    emit(|out| {
+        if !quiet() {
+            writeln!(out, "New code measures for: {}", response.component.key)?;
+            writeln!(out)?;
+        }
+        for line in new_code_measure_lines(response, human) {
+            writeln!(out, "  {line}")?;
+        }
+        Ok(())
+    });
+}
+
+/// Format a delta with an explicit sign, dropping decimals for whole numbers
+fn format_delta(delta: Option<f64>) -> String {
+    match delta {
+        Some(d) if d.fract() == 0.0 => format!("{:+}", d as i64),
+        Some(d) => format!("{:+.2}", d),
+        None => "-".to_string(),
     }
 }
 
-/// Format file coverage output
-pub fn print_coverage(files: &[FileCoverage], project: &str, json: bool) {
+/// Format a side-by-side measures comparison between two branches
+pub fn print_measures_comparison(
+    rows: &[MeasureComparison],
+    project: &str,
+    current: &str,
+    base: &str,
+    json: bool,
+) {
     if json {
-        print_json(files);
+        print_json(rows);
         return;
     }
 
-    println!(
-        "{} files with coverage data (project: {project})",
-        files.len()
-    );
-    if files.is_empty() {
+    emit(|out| write_measures_comparison(out, rows, project, current, base));
+}
+
+/// Text table for [`print_measures_comparison`]
+fn write_measures_comparison(
+    out: &mut dyn Write,
+    rows: &[MeasureComparison],
+    project: &str,
+    current: &str,
+    base: &str,
+) -> io::Result<()> {
+    if !quiet() {
+        writeln!(out, "Measures for: {project} ({current} vs {base})")?;
+        writeln!(out)?;
+    }
+    writeln!(out, "  {:<35} {:>15} {:>15} {:>10}", "Metric", current, base, "Delta")?;
+    writeln!(out, "  {}", "-".repeat(78))?;
+    for row in rows {
+        writeln!(
+            out,
+            "  {:<35} {:>15} {:>15} {:>10}",
+            row.metric,
+            row.current.as_deref().unwrap_or("-"),
+            row.base.as_deref().unwrap_or("-"),
+            format_delta(row.delta)
+        )?;
+    }
+    Ok(())
+}
+
+/// Format latest measures with the change since the previous analysis
+pub fn print_measure_trends(rows: &[MeasureTrend], project: &str, json: bool) {
+    if json {
+        print_json(rows);
         return;
     }
 
-    println!();
-    println!(
-        "  {:<50} {:>8} {:>10} {:>10}",
-        "File", "Coverage", "Uncovered", "Lines"
-    );
-    println!("  {}", "-".repeat(82));
-    for f in files {
-        println!(
-            "  {:<50} {:>7.1}% {:>10} {:>10}",
-            f.file, f.coverage_percent, f.uncovered_lines, f.lines_to_cover
-        );
+    emit(|out| {
+        if !quiet() {
+            writeln!(out, "Measures for: {project} (change since previous analysis)")?;
+            writeln!(out)?;
+        }
+        writeln!(out, "  {:<35} {:>15}   Trend", "Metric", "Value")?;
+        writeln!(out, "  {}", "-".repeat(66))?;
+        for row in rows {
+            let trend = match row.delta {
+                Some(d) => format!("{} {}", trend_arrow(d), format_delta(Some(d))),
+                None => "-".to_string(),
+            };
+            writeln!(
+                out,
+                "  {:<35} {:>15}   {trend}",
+                row.metric,
+                row.current.as_deref().unwrap_or("-"),
+            )?;
+        }
+        Ok(())
+    });
+}
+
+/// Format metric values at two points in time with the change between them
+pub fn print_metric_diff(rows: &[MetricDiff], project: &str, from: &str, to: &str, json: bool) {
+    if json {
+        print_json(&serde_json::json!({
+            "project": project,
+            "from": from,
+            "to": to,
+            "metrics": rows,
+        }));
+        return;
     }
+
+    emit(|out| {
+        if !quiet() {
+            writeln!(out, "Measures for: {project} ({from} -> {to})")?;
+            writeln!(out)?;
+        }
+        writeln!(out, "  {:<35} {:>15} {:>15} {:>10}", "Metric", "Old", "New", "Delta")?;
+        writeln!(out, "  {}", "-".repeat(80))?;
+        for row in rows {
+            let arrow = row.delta.map_or("", trend_arrow);
+            writeln!(
+                out,
+                "  {:<35} {:>15} {:>15} {:>10} {arrow}",
+                row.metric,
+                row.from.as_deref().unwrap_or("-"),
+                row.to.as_deref().unwrap_or("-"),
+                format_delta(row.delta)
+            )?;
+        }
+        Ok(())
+    });
 }
 
-/// Format duplications output
-pub fn print_duplications(files: &[FileDuplication], project: &str, json: bool, details: bool) {
+/// Format issues opened and resolved per severity over the last analyses
+pub fn print_issue_changes(
+    changes: &[SeverityChange],
+    project: &str,
+    analyses: usize,
+    since: &str,
+    json: bool,
+) {
+    if json {
+        print_json(&serde_json::json!({
+            "project": project,
+            "analyses": analyses,
+            "since": since,
+            "severities": changes,
+        }));
+        return;
+    }
+
+    emit(|out| {
+        if !quiet() {
+            writeln!(out, "Issue changes for: {project} (last {analyses} analyses, since {since})")?;
+            writeln!(out)?;
+        }
+        writeln!(out, "  {:<10} {:>8} {:>9} {:>6}", "Severity", "Opened", "Resolved", "Net")?;
+        writeln!(out, "  {}", "-".repeat(36))?;
+        for row in changes {
+            writeln!(
+                out,
+                "  {:<10} {:>8} {:>9} {:>6}",
+                row.severity,
+                row.opened,
+                row.resolved,
+                format!("{:+}", row.net)
+            )?;
+        }
+        let opened: usize = changes.iter().map(|c| c.opened).sum();
+        let resolved: usize = changes.iter().map(|c| c.resolved).sum();
+        writeln!(out, "  {}", "-".repeat(36))?;
+        writeln!(
+            out,
+            "  {:<10} {:>8} {:>9} {:>6}",
+            "Total",
+            opened,
+            resolved,
+            format!("{:+}", opened as i64 - resolved as i64)
+        )
+    });
+}
+
+/// Format file coverage output
+pub fn print_coverage(files: &[FileCoverage], project: &str, json: bool) {
     if json {
         print_json(files);
         return;
     }
 
-    println!(
-        "{} files with duplications (project: {project})",
-        files.len()
-    );
-    if files.is_empty() {
+    emit(|out| {
+        writeln!(
+            out,
+            "{} files with coverage data (project: {project})",
+            files.len()
+        )?;
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "  {:<50} {:>8} {:>10} {:>10}",
+            "File", "Coverage", "Uncovered", "Lines"
+        )?;
+        writeln!(out, "  {}", "-".repeat(82))?;
+        for f in files {
+            writeln!(
+                out,
+                "  {:<50} {:>7.1}% {:>10} {:>10}",
+                f.file, f.coverage_percent, f.uncovered_lines, f.lines_to_cover
+            )?;
+        }
+        Ok(())
+    });
+}
+
+/// Format duplications output
+pub fn print_duplications(files: &[FileDuplication], project: &str, json: bool, details: bool) {
+    if json {
+        print_json(files);
         return;
     }
 
-    println!();
-    println!(
-        "  {:<50} {:>8} {:>10}",
-        "File", "Lines", "Density"
-    );
-    println!("  {}", "-".repeat(72));
-    for f in files {
-        println!(
-            "  {:<50} {:>8} {:>9.1}%",
-            f.file, f.duplicated_lines, f.duplicated_density
-        );
-        if details && !f.blocks.is_empty() {
-            for block in &f.blocks {
-                println!(
-                    "    L{}-{} duplicated in {} L{}",
-                    block.from_line,
-                    block.from_line + block.size,
-                    block.duplicated_in,
-                    block.duplicated_in_line
-                );
+    emit(|out| {
+        writeln!(
+            out,
+            "{} files with duplications (project: {project})",
+            files.len()
+        )?;
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "  {:<50} {:>8} {:>10}",
+            "File", "Lines", "Density"
+        )?;
+        writeln!(out, "  {}", "-".repeat(72))?;
+        for f in files {
+            writeln!(
+                out,
+                "  {:<50} {:>8} {:>9.1}%",
+                f.file, f.duplicated_lines, f.duplicated_density
+            )?;
+            if details && !f.blocks.is_empty() {
+                for block in &f.blocks {
+                    writeln!(
+                        out,
+                        "    L{}-{} duplicated in {} L{}",
+                        block.from_line,
+                        block.from_line + block.size,
+                        block.duplicated_in,
+                        block.duplicated_in_line
+                    )?;
+                }
             }
         }
-    }
+        Ok(())
+    });
 }
 
 /// Format hotspots output
@@ -191,33 +1301,154 @@ pub fn print_hotspots(hotspots: &[SecurityHotspot], project: &str, json: bool) {
         return;
     }
 
-    println!(
-        "{} security hotspots (project: {project})",
-        hotspots.len()
-    );
-    if hotspots.is_empty() {
+    emit(|out| {
+        writeln!(
+            out,
+            "{} security hotspots (project: {project})",
+            hotspots.len()
+        )?;
+        if hotspots.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out)?;
+        for hs in hotspots {
+            let file = hs
+                .component
+                .split(':')
+                .nth(1)
+                .unwrap_or(&hs.component);
+            let line_str = hs.line.map(|l| format!(":{l}")).unwrap_or_default();
+
+            writeln!(
+                out,
+                "  [{:<6}] [{:<12}] {file}{line_str}",
+                hs.vulnerability_probability, hs.security_category
+            )?;
+            writeln!(out, "           {}", hs.message)?;
+            writeln!(out, "           rule: {}", hs.rule_key)?;
+            if let Some(ref assignee) = hs.assignee {
+                writeln!(out, "           assignee: {assignee}")?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    });
+}
+
+/// Confirm a hotspot assignment
+pub fn print_hotspot_assignment(hotspot: &str, assignee: &str, json: bool) {
+    if json {
+        print_json(&serde_json::json!({"hotspot": hotspot, "assignee": assignee}));
+    } else {
+        print_line(&format!("Assigned hotspot {hotspot} to {assignee}"));
+    }
+}
+
+/// Print one hotspot with its rule's guidance under SonarQube's review headings
+pub fn print_hotspot_details(hotspot: &HotspotDetails, json: bool) {
+    if json {
+        print_json(hotspot);
+        return;
+    }
+
+    emit(|out| {
+        let file = hotspot.component.path.as_deref().unwrap_or(&hotspot.component.key);
+        let line_str = hotspot.line.map(|l| format!(":{l}")).unwrap_or_default();
+        writeln!(out, "{}  {file}{line_str}", hotspot.key)?;
+        writeln!(out, "  {}", hotspot.message)?;
+        let status = match hotspot.resolution {
+            Some(ref resolution) => format!("{} ({resolution})", hotspot.status),
+            None => hotspot.status.clone(),
+        };
+        let rule = &hotspot.rule;
+        let fields = [
+            ("Rule", Some(format!("{}  {}", rule.key, rule.name))),
+            ("Category", Some(rule.security_category.clone())),
+            ("Priority", Some(rule.vulnerability_probability.clone())),
+            ("Status", Some(status)),
+            ("Assignee", hotspot.assignee.clone()),
+        ];
+        for (label, value) in fields {
+            if let Some(value) = value.filter(|v| !v.is_empty()) {
+                writeln!(out, "  {:<12} {value}", format!("{label}:"))?;
+            }
+        }
+        let sections = [
+            ("What's the risk?", &rule.risk_description),
+            ("Are you at risk?", &rule.vulnerability_description),
+            ("How can you fix it?", &rule.fix_recommendations),
+        ];
+        for (heading, html) in sections {
+            if let Some(html) = html {
+                writeln!(out)?;
+                writeln!(out, "## {heading}")?;
+                writeln!(out)?;
+                writeln!(out, "{}", html_to_text(html))?;
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Confirm a hotspot review status change (`hotspots resolve`)
+pub fn print_hotspot_status_change(hotspot: &str, status: &str, resolution: Option<&str>, json: bool) {
+    if json {
+        print_json(&serde_json::json!({"hotspot": hotspot, "status": status, "resolution": resolution}));
         return;
     }
+    match resolution {
+        Some(resolution) => print_line(&format!("Marked hotspot {hotspot} as {status} ({resolution})")),
+        None => print_line(&format!("Marked hotspot {hotspot} as {status}")),
+    }
+}
 
-    println!();
-    for hs in hotspots {
-        let file = hs
-            .component
-            .split(':')
-            .nth(1)
-            .unwrap_or(&hs.component);
-        let line_str = hs.line.map(|l| format!(":{l}")).unwrap_or_default();
+/// Confirm a change made to an issue (`issue assign|comment|transition`)
+pub fn print_issue_action(issue: &str, action: &str, value: &str, summary: &str, json: bool) {
+    if json {
+        print_json(&serde_json::json!({"issue": issue, "action": action, "value": value}));
+    } else {
+        print_line(summary);
+    }
+}
 
-        println!(
-            "  [{:<6}] [{:<12}] {file}{line_str}",
-            hs.vulnerability_probability, hs.security_category
-        );
-        println!("           {}", hs.message);
-        println!("           rule: {}", hs.rule_key);
-        println!();
+/// Describe a new code definition in plain words
+fn describe_new_code_period(period: &NewCodePeriod) -> String {
+    let value = period.value.as_deref().unwrap_or("?");
+    match period.period_type.as_str() {
+        "PREVIOUS_VERSION" => "Changes since the previous version".to_string(),
+        "NUMBER_OF_DAYS" => format!("Changes in the last {value} days"),
+        "REFERENCE_BRANCH" => format!("Changes compared to branch '{value}'"),
+        "SPECIFIC_ANALYSIS" => format!("Changes since analysis {value}"),
+        other => match &period.value {
+            Some(v) => format!("{other} ({v})"),
+            None => other.to_string(),
+        },
     }
 }
 
+/// Format the new code definition output
+pub fn print_new_code_period(period: &NewCodePeriod, project: &str, json: bool) {
+    if json {
+        print_json(period);
+        return;
+    }
+
+    emit(|out| {
+        writeln!(out, "New code definition for: {project}")?;
+        if let Some(branch) = &period.branch_key {
+            writeln!(out, "  Branch:     {branch}")?;
+        }
+        writeln!(out, "  Type:       {}", period.period_type)?;
+        if let Some(value) = &period.value {
+            writeln!(out, "  Value:      {value}")?;
+        }
+        let source = if period.inherited { "inherited" } else { "set on project" };
+        writeln!(out, "  Definition: {} ({source})", describe_new_code_period(period))?;
+        Ok(())
+    });
+}
+
 /// Format projects output
 pub fn print_projects(projects: &[ProjectInfo], json: bool) {
     if json {
@@ -225,22 +1456,117 @@ pub fn print_projects(projects: &[ProjectInfo], json: bool) {
         return;
     }
 
-    println!("{} projects found", projects.len());
-    if projects.is_empty() {
+    emit(|out| {
+        if !quiet() {
+            writeln!(out, "{} projects found", projects.len())?;
+        }
+        if projects.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "  {:<40} {:<40} {:<9} {:<10} Last Analysis",
+            "Key", "Name", "Qualifier", "Visibility"
+        )?;
+        writeln!(out, "  {}", "-".repeat(115))?;
+        for p in projects {
+            let qualifier = p.qualifier.as_deref().unwrap_or("-");
+            let vis = p.visibility.as_deref().unwrap_or("-");
+            let last = p.last_analysis_date.as_deref().unwrap_or("-");
+            writeln!(out, "  {:<40} {:<40} {:<9} {:<10} {}", p.key, p.name, qualifier, vis, last)?;
+        }
+        Ok(())
+    });
+}
+
+/// Format the branches of a project with their quality gate status
+pub fn print_branches(branches: &[ProjectBranch], project: &str, json: bool) {
+    if json {
+        print_json(branches);
         return;
     }
 
-    println!();
-    println!(
-        "  {:<40} {:<40} {:<10} Last Analysis",
-        "Key", "Name", "Visibility"
-    );
-    println!("  {}", "-".repeat(105));
-    for p in projects {
-        let vis = p.visibility.as_deref().unwrap_or("-");
-        let last = p.last_analysis_date.as_deref().unwrap_or("-");
-        println!("  {:<40} {:<40} {:<10} {}", p.key, p.name, vis, last);
+    emit(|out| {
+        if !quiet() {
+            writeln!(out, "{} branches found for {project}", branches.len())?;
+        }
+        if branches.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "  {:<40} {:<13} {:<5} {:<8} Last Analysis",
+            "Branch", "Type", "Main", "Gate"
+        )?;
+        writeln!(out, "  {}", "-".repeat(95))?;
+        let color = COLOR.load(Ordering::Relaxed);
+        for b in branches {
+            let branch_type = b.branch_type.as_deref().unwrap_or("-");
+            let main = if b.is_main { "yes" } else { "" };
+            let gate = match b.status.as_ref().and_then(|s| s.quality_gate_status.as_deref()) {
+                Some(status) => paint(&format!("{status:<8}"), gate_color(status), color),
+                None => format!("{:<8}", "-"),
+            };
+            let last = b.analysis_date.as_deref().unwrap_or("-");
+            writeln!(out, "  {:<40} {:<13} {:<5} {gate} {last}", b.name, branch_type, main)?;
+        }
+        Ok(())
+    });
+}
+
+/// Format a project's component tree: each component's key, path and qualifier
+pub fn print_component_tree(components: &[TreeComponent], project: &str, json: bool) {
+    if json {
+        print_json(components);
+        return;
     }
+
+    emit(|out| {
+        if !quiet() {
+            writeln!(out, "{} components found in {project}", components.len())?;
+        }
+        if components.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out)?;
+        writeln!(out, "  {:<9} {:<50} Key", "Qualifier", "Path")?;
+        writeln!(out, "  {}", "-".repeat(100))?;
+        for c in components {
+            let qualifier = c.qualifier.as_deref().unwrap_or("-");
+            let path = c.path.as_deref().unwrap_or("-");
+            writeln!(out, "  {:<9} {:<50} {}", qualifier, path, c.key)?;
+        }
+        Ok(())
+    });
+}
+
+/// Format the recently used projects, each with the `@N` shorthand that selects it
+pub fn print_recent_projects(recent: &[String], json: bool) {
+    if json {
+        let rows: Vec<serde_json::Value> = recent
+            .iter()
+            .enumerate()
+            .map(|(i, key)| serde_json::json!({"ref": format!("@{}", i + 1), "key": key}))
+            .collect();
+        print_json(&rows);
+        return;
+    }
+
+    emit(|out| {
+        if recent.is_empty() {
+            writeln!(out, "No recent projects yet. They are recorded when you run a command with --project.")?;
+            return Ok(());
+        }
+        for (i, key) in recent.iter().enumerate() {
+            writeln!(out, "  {:<4} {key}", format!("@{}", i + 1))?;
+        }
+        Ok(())
+    });
 }
 
 /// Format measures history output
@@ -250,56 +1576,358 @@ pub fn print_history(measures: &[MeasureHistory], project: &str, json: bool) {
         return;
     }
 
-    println!("Measures history for: {project}");
-    if measures.is_empty() {
-        println!("  No history data found.");
+    emit(|out| {
+        writeln!(out, "Measures history for: {project}")?;
+        if measures.is_empty() {
+            writeln!(out, "  No history data found.")?;
+            return Ok(());
+        }
+
+        for measure in measures {
+            writeln!(out)?;
+            writeln!(out, "  Metric: {}", measure.metric)?;
+            writeln!(out, "  {:<25} Value", "Date")?;
+            writeln!(out, "  {}", "-".repeat(40))?;
+            for point in &measure.history {
+                let value = point.value.as_deref().unwrap_or("-");
+                writeln!(out, "  {:<25} {}", point.date, value)?;
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Format measures history as a single table: one row per date, one column per metric
+pub fn print_history_table(table: &HistoryTable, project: &str, json: bool) {
+    if json {
+        print_json(table);
         return;
     }
 
-    for measure in measures {
-        println!();
-        println!("  Metric: {}", measure.metric);
-        println!("  {:<25} Value", "Date");
-        println!("  {}", "-".repeat(40));
-        for point in &measure.history {
-            let value = point.value.as_deref().unwrap_or("-");
-            println!("  {:<25} {}", point.date, value);
+    emit(|out| {
+        writeln!(out, "Measures history for: {project}")?;
+        if table.rows.is_empty() {
+            writeln!(out, "  No history data found.")?;
+            return Ok(());
+        }
+
+        let widths: Vec<usize> = table
+            .metrics
+            .iter()
+            .enumerate()
+            .map(|(col, metric)| {
+                table
+                    .rows
+                    .iter()
+                    .filter_map(|r| r.values[col].as_deref())
+                    .map(str::len)
+                    .chain(std::iter::once(metric.len()))
+                    .max()
+                    .unwrap_or(1)
+            })
+            .collect();
+
+        writeln!(out)?;
+        let mut header = format!("  {:<25}", "Date");
+        for (metric, width) in table.metrics.iter().zip(&widths) {
+            header.push_str(&format!(" {:>width$}", metric));
+        }
+        writeln!(out, "{header}")?;
+        writeln!(out, "  {}", "-".repeat(25 + widths.iter().map(|w| w + 1).sum::<usize>()))?;
+        for row in &table.rows {
+            let mut line = format!("  {:<25}", row.date);
+            for (value, width) in row.values.iter().zip(&widths) {
+                line.push_str(&format!(" {:>width$}", value.as_deref().unwrap_or("-")));
+            }
+            writeln!(out, "{line}")?;
+        }
+        Ok(())
+    });
+}
+
+/// Lines of a metric × project table, one column per project
+pub fn measures_matrix_lines(matrix: &MeasuresMatrix) -> Vec<String> {
+    let widths: Vec<usize> = matrix
+        .projects
+        .iter()
+        .enumerate()
+        .map(|(col, project)| {
+            matrix
+                .rows
+                .iter()
+                .filter_map(|r| r.values[col].as_deref())
+                .map(str::len)
+                .chain(std::iter::once(project.len()))
+                .max()
+                .unwrap_or(1)
+        })
+        .collect();
+
+    let mut header = format!("  {:<35}", "Metric");
+    for (project, width) in matrix.projects.iter().zip(&widths) {
+        header.push_str(&format!(" {:>width$}", project));
+    }
+    let mut lines = vec![
+        header,
+        format!("  {}", "-".repeat(35 + widths.iter().map(|w| w + 1).sum::<usize>())),
+    ];
+    for row in &matrix.rows {
+        let mut line = format!("  {:<35}", row.metric);
+        for (value, width) in row.values.iter().zip(&widths) {
+            line.push_str(&format!(" {:>width$}", value.as_deref().unwrap_or("-")));
         }
+        lines.push(line);
     }
+    lines
+}
+
+/// Format measures for several projects side by side
+pub fn print_measures_matrix(matrix: &MeasuresMatrix, json: bool) {
+    if json {
+        print_json(matrix);
+        return;
+    }
+
+    emit(|out| {
+        if !quiet() {
+            writeln!(out, "Measures for {} projects", matrix.projects.len())?;
+        }
+        writeln!(out)?;
+        for line in measures_matrix_lines(matrix) {
+            writeln!(out, "{line}")?;
+        }
+        Ok(())
+    });
+}
+
+/// Format the quality gate status timeline: one line per status change
+pub fn print_gate_history(changes: &[GateChange], project: &str, json: bool) {
+    if json {
+        print_json(changes);
+        return;
+    }
+
+    emit(|out| {
+        writeln!(out, "Quality gate history for: {project}")?;
+        if changes.is_empty() {
+            writeln!(out, "  No history data found.")?;
+            return Ok(());
+        }
+
+        writeln!(out)?;
+        writeln!(out, "  {:<25} {:<8} Previous", "Date", "Status")?;
+        writeln!(out, "  {}", "-".repeat(45))?;
+        for change in changes {
+            let previous = change.previous.as_deref().unwrap_or("-");
+            writeln!(out, "  {:<25} {:<8} {}", change.date, change.status, previous)?;
+        }
+        writeln!(out)?;
+        writeln!(out, "  Status changes: {}", changes.len() - 1)?;
+        Ok(())
+    });
 }
 
 /// Format rules output
+pub fn print_rule_details(rule: &RuleDetails, json: bool) {
+    if json {
+        print_json(rule);
+        return;
+    }
+
+    emit(|out| {
+        writeln!(out, "{}  {}", rule.key, rule.name)?;
+        let tags: Vec<&str> = rule.tags.iter().chain(&rule.sys_tags).map(String::as_str).collect();
+        let tags = (!tags.is_empty()).then(|| tags.join(", "));
+        let fields = [
+            ("Severity", rule.severity.clone()),
+            ("Type", rule.rule_type.clone()),
+            ("Language", rule.lang_name.clone()),
+            ("Tags", tags),
+            ("Remediation", rule_remediation(rule)),
+        ];
+        for (label, value) in fields {
+            if let Some(value) = value {
+                writeln!(out, "  {:<12} {value}", format!("{label}:"))?;
+            }
+        }
+        let description = rule_description_text(rule);
+        if !description.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "{description}")?;
+        }
+        Ok(())
+    });
+}
+
+/// Remediation effort as "5min", "1min per ...", or "5min + 1min per ..."
+fn rule_remediation(rule: &RuleDetails) -> Option<String> {
+    let gap = rule.rem_fn_gap_multiplier.as_ref().map(|multiplier| {
+        let unit = rule.gap_description.as_deref().unwrap_or("per unit of gap");
+        format!("{multiplier} {unit}")
+    });
+    match (&rule.rem_fn_base_effort, gap) {
+        (Some(base), Some(gap)) => Some(format!("{base} + {gap}")),
+        (Some(base), None) => Some(base.clone()),
+        (None, gap) => gap,
+    }
+}
+
+/// Plain-text rule description: the sections of newer servers under their
+/// SonarQube headings, else the single `htmlDesc`
+fn rule_description_text(rule: &RuleDetails) -> String {
+    if rule.description_sections.is_empty() {
+        return rule.html_desc.as_deref().map(html_to_text).unwrap_or_default();
+    }
+    let mut parts = Vec::new();
+    for section in &rule.description_sections {
+        let heading = match section.key.as_str() {
+            "introduction" => None,
+            "root_cause" => Some("Why is this an issue?"),
+            "assess_the_problem" => Some("What is the potential impact?"),
+            "how_to_fix" => Some("How to fix it"),
+            "resources" => Some("Resources"),
+            other => Some(other),
+        };
+        let text = html_to_text(&section.content);
+        match heading {
+            Some(heading) => parts.push(format!("## {heading}\n\n{text}")),
+            None => parts.push(text),
+        }
+    }
+    parts.join("\n\n")
+}
+
 pub fn print_rules(rules: &[RuleInfo], json: bool) {
     if json {
         print_json(rules);
         return;
     }
 
-    println!("{} rules found", rules.len());
-    if rules.is_empty() {
+    emit(|out| {
+        if !quiet() {
+            writeln!(out, "{} rules found", rules.len())?;
+        }
+        if rules.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "  {:<40} {:<35} {:<10} {:<15} Language",
+            "Key", "Name", "Severity", "Type"
+        )?;
+        writeln!(out, "  {}", "-".repeat(110))?;
+        for r in rules {
+            let sev = r.severity.as_deref().unwrap_or("-");
+            let rt = r.rule_type.as_deref().unwrap_or("-");
+            let lang = r.lang_name.as_deref().or(r.lang.as_deref()).unwrap_or("-");
+            let name_truncated = if r.name.len() > 33 {
+                format!("{}...", &r.name[..30])
+            } else {
+                r.name.clone()
+            };
+            writeln!(
+                out,
+                "  {:<40} {:<35} {:<10} {:<15} {}",
+                r.key, name_truncated, sev, rt, lang
+            )?;
+            for line in rule_deprecation_lines(r) {
+                writeln!(out, "    {line}")?;
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Where a deprecated rule went and what it used to be called
+fn rule_deprecation_lines(rule: &RuleInfo) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(ref replacement) = rule.replaced_by {
+        lines.push(format!("replaced by: {replacement}"));
+    }
+    if let Some(deprecated) = rule.deprecated_keys.as_ref().filter(|d| !d.keys.is_empty()) {
+        lines.push(format!("deprecated keys: {}", deprecated.keys.join(", ")));
+    }
+    lines
+}
+
+/// Format quality profiles output
+pub fn print_profiles(profiles: &[QualityProfile], json: bool) {
+    if json {
+        print_json(profiles);
         return;
     }
 
-    println!();
-    println!(
-        "  {:<40} {:<35} {:<10} {:<15} Language",
-        "Key", "Name", "Severity", "Type"
-    );
-    println!("  {}", "-".repeat(110));
-    for r in rules {
-        let sev = r.severity.as_deref().unwrap_or("-");
-        let rt = r.rule_type.as_deref().unwrap_or("-");
-        let lang = r.lang_name.as_deref().or(r.lang.as_deref()).unwrap_or("-");
-        let name_truncated = if r.name.len() > 33 {
-            format!("{}...", &r.name[..30])
-        } else {
-            r.name.clone()
-        };
-        println!(
-            "  {:<40} {:<35} {:<10} {:<15} {}",
-            r.key, name_truncated, sev, rt, lang
-        );
+    emit(|out| {
+        if !quiet() {
+            writeln!(out, "{} quality profiles found", profiles.len())?;
+        }
+        if profiles.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "  {:<25} {:<30} {:<12} {:>12}  Default",
+            "Key", "Name", "Language", "Active rules"
+        )?;
+        writeln!(out, "  {}", "-".repeat(92))?;
+        for p in profiles {
+            let lang = p.language_name.as_deref().unwrap_or(&p.language);
+            let default = if p.is_default { "yes" } else { "" };
+            writeln!(
+                out,
+                "  {:<25} {:<30} {:<12} {:>12}  {default}",
+                p.key, p.name, lang, p.active_rule_count
+            )?;
+        }
+        Ok(())
+    });
+}
+
+/// Severity and parameters of a rule activation, e.g. `MAJOR (max=7)`
+fn describe_activation(activation: &RuleActivation) -> String {
+    let severity = activation.severity.as_deref().unwrap_or("-");
+    if activation.params.is_empty() {
+        return severity.to_string();
+    }
+    let params: Vec<String> = activation
+        .params
+        .iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect();
+    format!("{severity} ({})", params.join(", "))
+}
+
+/// Format a quality profile comparison: rules only in each profile, then modified ones
+pub fn print_profile_comparison(cmp: &ProfileComparison, json: bool) {
+    if json {
+        print_json(cmp);
+        return;
     }
+
+    emit(|out| {
+        let (left, right) = (&cmp.left.name, &cmp.right.name);
+        writeln!(out, "Comparing {left} ({}) with {right} ({})", cmp.left.key, cmp.right.key)?;
+        for (name, rules) in [(left, &cmp.in_left), (right, &cmp.in_right)] {
+            writeln!(out)?;
+            writeln!(out, "Only in {name} ({}):", rules.len())?;
+            for r in rules {
+                let sev = r.severity.as_deref().unwrap_or("-");
+                writeln!(out, "  {:<30} {:<10} {}", r.key, sev, r.name)?;
+            }
+        }
+        writeln!(out)?;
+        writeln!(out, "Modified ({}):", cmp.modified.len())?;
+        for r in &cmp.modified {
+            writeln!(out, "  {:<30} {}", r.key, r.name)?;
+            writeln!(out, "    {left}: {}", describe_activation(&r.left))?;
+            writeln!(out, "    {right}: {}", describe_activation(&r.right))?;
+        }
+        Ok(())
+    });
 }
 
 /// Format source code output
@@ -309,34 +1937,172 @@ pub fn print_source(lines: &[SourceLine], json: bool) {
         return;
     }
 
-    for line in lines {
-        println!("{:>6} | {}", line.line, line.code);
+    emit(|out| {
+        for line in lines {
+            writeln!(out, "{:>6} | {}", line.line, line.code)?;
+        }
+        Ok(())
+    });
+}
+
+/// Format a single blame line: line number, author and date gutter, then code.
+/// Lines without SCM data get an empty gutter of the same width.
+fn format_blame_line(line: &BlameLine, author_width: usize) -> String {
+    let author = line.scm_author.as_deref().unwrap_or("");
+    // scmDate is an ISO timestamp; the day is enough for a gutter
+    let date = line
+        .scm_date
+        .as_deref()
+        .map(|d| d.get(..10).unwrap_or(d))
+        .unwrap_or("");
+    format!(
+        "{:>6} {:<author_width$.author_width$} {:<10} | {}",
+        line.line, author, date, line.code
+    )
+}
+
+/// Format source lines with SCM blame annotations
+pub fn print_blame(lines: &[BlameLine], json: bool) {
+    if json {
+        print_json(lines);
+        return;
+    }
+
+    emit(|out| {
+        if lines.iter().all(|l| l.scm_author.is_none() && l.scm_date.is_none()) {
+            eprintln!("No SCM data available for this file; showing source without blame.");
+            for line in lines {
+                writeln!(out, "{:>6} | {}", line.line, line.code)?;
+            }
+            return Ok(());
+        }
+
+        let author_width = lines
+            .iter()
+            .filter_map(|l| l.scm_author.as_deref())
+            .map(|a| a.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(30);
+        for line in lines {
+            writeln!(out, "{}", format_blame_line(line, author_width))?;
+        }
+        Ok(())
+    });
+}
+
+/// Format wait result output
+pub fn print_wait_result(task: &AnalysisTask, json: bool) {
+    let timed = TimedTask::new(task);
+    if json {
+        print_json(&timed);
+        return;
+    }
+
+    emit(|out| {
+        writeln!(out, "Analysis task: {}", task.id)?;
+        writeln!(out, "  Status:      {}", task.status)?;
+        writeln!(out, "  Submitted:   {}", task.submitted_at)?;
+        if let Some(ref executed) = task.executed_at {
+            writeln!(out, "  Completed:   {executed}")?;
+        }
+        if let Some(secs) = timed.duration_secs {
+            writeln!(out, "  Duration:    {}", format_duration(secs))?;
+        }
+        if let Some(ref analysis_id) = task.analysis_id {
+            writeln!(out, "  Analysis ID: {analysis_id}")?;
+        }
+        Ok(())
+    });
+}
+
+/// Task fields plus the time from submission to completion, for JSON output
+#[derive(serde::Serialize)]
+struct TimedTask<'a> {
+    #[serde(flatten)]
+    task: &'a AnalysisTask,
+    duration_secs: Option<u64>,
+}
+
+impl<'a> TimedTask<'a> {
+    fn new(task: &'a AnalysisTask) -> Self {
+        let duration_secs = task_duration_secs(&task.submitted_at, task.executed_at.as_deref());
+        TimedTask { task, duration_secs }
+    }
+}
+
+/// Format recent compute engine tasks for a project, most recent first
+pub fn print_activity(tasks: &[AnalysisTask], project: &str, json: bool) {
+    let timed: Vec<TimedTask> = tasks.iter().map(TimedTask::new).collect();
+    if json {
+        print_json(&timed);
+        return;
+    }
+
+    emit(|out| {
+        if !quiet() {
+            writeln!(out, "{} analysis tasks found for {project}", tasks.len())?;
+        }
+        if tasks.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out)?;
+        writeln!(
+            out,
+            "  {:<22} {:<8} {:<11} {:<26} {:<26} Duration",
+            "Task", "Type", "Status", "Submitted", "Executed"
+        )?;
+        writeln!(out, "  {}", "-".repeat(108))?;
+        let color = COLOR.load(Ordering::Relaxed);
+        for t in &timed {
+            let status = paint(&format!("{:<11}", t.task.status), task_status_color(&t.task.status), color);
+            let executed = t.task.executed_at.as_deref().unwrap_or("-");
+            let duration = t.duration_secs.map_or_else(|| "-".to_string(), format_duration);
+            writeln!(
+                out,
+                "  {:<22} {:<8} {status} {:<26} {:<26} {duration}",
+                t.task.id, t.task.task_type, t.task.submitted_at, executed
+            )?;
+        }
+        Ok(())
+    });
+}
+
+/// Explain that a project has nothing to show because it was never analyzed
+pub fn print_not_analyzed(project: &str, json: bool) {
+    let message = format!("Project {project} has no completed analysis yet");
+    if json {
+        print_json(&serde_json::json!({"project": project, "analyzed": false, "message": message}));
+    } else {
+        print_line(&message);
     }
 }
 
-/// Format wait result output
-pub fn print_wait_result(task: &AnalysisTask, json: bool) {
+/// Format the result of a `report` run
+pub fn print_report_summary(summary: &ReportSummary, dir: &std::path::Path, json: bool) {
     if json {
-        print_json(task);
+        print_json(summary);
         return;
     }
 
-    println!("Analysis task: {}", task.id);
-    println!("  Status:      {}", task.status);
-    println!("  Submitted:   {}", task.submitted_at);
-    if let Some(ref executed) = task.executed_at {
-        println!("  Completed:   {executed}");
-    }
-    if let Some(ref analysis_id) = task.analysis_id {
-        println!("  Analysis ID: {analysis_id}");
-    }
+    emit(|out| {
+        writeln!(out, "Report for {} written to {}", summary.project, dir.display())?;
+        for file in &summary.files {
+            writeln!(out, "  {file}")?;
+        }
+        for (name, error) in &summary.errors {
+            writeln!(out, "  {name}: FAILED ({error})")?;
+        }
+        Ok(())
+    });
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::helpers::{DuplicationBlockDetail, FileCoverage, FileDuplication};
-    use crate::types::{Measure, MeasuresComponent, ProjectStatus, QualityGateCondition, TextRange};
+    use crate::types::{DeprecatedKeys, IssueComment, MeasurePeriod, MeasuresComponent, MetricDefinition, ProjectStatus, QualityGateCondition, TextRange};
 
     fn sample_issue() -> SonarIssue {
         SonarIssue {
@@ -354,9 +2120,47 @@ mod tests {
             debt: Some("6min".to_string()),
             effort: Some("6min".to_string()),
             tags: vec!["brain-overload".to_string()],
+            update_date: None,
+            close_date: None,
+            rule_severity: None,
+            rule_name: None,
+            comments: vec![],
+            snippet: vec![],
         }
     }
 
+    #[test]
+    fn test_issue_comment_lines() {
+        let mut issue = sample_issue();
+        issue.comments = vec![
+            IssueComment {
+                key: "c1".to_string(),
+                login: Some("alice".to_string()),
+                markdown: Some("False positive?\nSee *JIRA-1*".to_string()),
+                html_text: None,
+                created_at: Some("2026-01-02T10:00:00+0000".to_string()),
+            },
+            IssueComment {
+                key: "c2".to_string(),
+                login: None,
+                markdown: None,
+                html_text: Some("Agreed &amp; <strong>confirmed</strong>".to_string()),
+                created_at: None,
+            },
+        ];
+        assert_eq!(
+            issue_comment_lines(&issue),
+            vec![
+                "comment by alice on 2026-01-02T10:00:00+0000:",
+                "  False positive?",
+                "  See *JIRA-1*",
+                "comment by unknown:",
+                "  Agreed & confirmed",
+            ]
+        );
+        assert!(issue_comment_lines(&sample_issue()).is_empty());
+    }
+
     fn sample_quality_gate() -> QualityGateResponse {
         QualityGateResponse {
             project_status: ProjectStatus {
@@ -381,6 +2185,7 @@ mod tests {
                     Measure { metric: "coverage".to_string(), value: None, period: None },
                 ],
             },
+            metrics: vec![],
         }
     }
 
@@ -396,6 +2201,7 @@ mod tests {
             message: "Review this".to_string(),
             rule_key: "rust:S2077".to_string(),
             text_range: None,
+            assignee: Some("alice".to_string()),
         }
     }
 
@@ -434,6 +2240,8 @@ mod tests {
             lang: Some("rust".to_string()),
             status: Some("READY".to_string()),
             lang_name: Some("Rust".to_string()),
+            replaced_by: None,
+            deprecated_keys: None,
         }
     }
 
@@ -449,26 +2257,519 @@ mod tests {
         }
     }
 
+    // --- output format ---
+
+    #[test]
+    fn test_parse_output_format() {
+        assert_eq!(parse_output_format("text"), Ok(OutputFormat::Text));
+        assert_eq!(parse_output_format("JSON"), Ok(OutputFormat::Json));
+        assert_eq!(parse_output_format("ndjson"), Ok(OutputFormat::Ndjson));
+        assert_eq!(parse_output_format("CSV"), Ok(OutputFormat::Csv));
+        assert_eq!(parse_output_format("tsv"), Ok(OutputFormat::Tsv));
+        assert!(parse_output_format("yaml").is_err());
+    }
+
+    #[test]
+    fn test_resolve_output_format() {
+        assert_eq!(resolve_output_format(None, false), Ok(OutputFormat::Text));
+        assert_eq!(resolve_output_format(None, true), Ok(OutputFormat::Json));
+        assert_eq!(resolve_output_format(Some("ndjson"), true), Ok(OutputFormat::Ndjson));
+    }
+
+    #[test]
+    fn test_write_ndjson_one_object_per_line() {
+        let mut buf = Vec::new();
+        write_ndjson(&mut buf, &[sample_rule(), sample_rule()]).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for line in lines {
+            let v: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(v["key"], "rust:S3776");
+        }
+    }
+
+    fn portfolio() -> ProjectInfo {
+        ProjectInfo {
+            key: "VW_ALL".to_string(),
+            name: "All Teams, 2026".to_string(),
+            qualifier: Some("VW".to_string()),
+            visibility: Some("private".to_string()),
+            last_analysis_date: None,
+        }
+    }
+
+    #[test]
+    fn test_project_json_includes_qualifier() {
+        let v = serde_json::to_value([portfolio()]).unwrap();
+        assert_eq!(v[0]["qualifier"], "VW");
+        assert_eq!(v[0]["visibility"], "private");
+        assert!(v[0]["lastAnalysisDate"].is_null());
+    }
+
+    #[test]
+    fn test_write_projects_csv_columns() {
+        let mut buf = Vec::new();
+        write_projects_delimited(&mut buf, &[portfolio()], ',').unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "key,name,qualifier,visibility,last_analysis_date");
+        assert_eq!(lines[1], "VW_ALL,\"All Teams, 2026\",VW,private,");
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn test_write_issues_tsv_escapes_tabs_not_commas() {
+        let mut with_comma = sample_issue();
+        with_comma.message = "Remove this, or rename it".to_string();
+        let mut with_tab = sample_issue();
+        with_tab.key = "def".to_string();
+        with_tab.message = "Expected\tfound\nnext".to_string();
+
+        let mut buf = Vec::new();
+        write_issues_delimited(&mut buf, &[with_comma, with_tab], "proj", '\t').unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "key\tseverity\ttype\tstatus\trule\tfile\tline\tmessage");
+        assert_eq!(
+            lines[1],
+            "abc\tCRITICAL\tCODE_SMELL\tOPEN\trust:S3776\tsrc/main.rs\t42\tRemove this, or rename it"
+        );
+        assert!(lines[2].ends_with("\t42\tExpected\\tfound\\nnext"), "{}", lines[2]);
+        assert_eq!(lines.len(), 3);
+        // Every row keeps the header's column count
+        assert!(lines.iter().all(|l| l.split('\t').count() == 8));
+    }
+
+    #[test]
+    fn test_format_json_compact_vs_pretty() {
+        let rules = [sample_rule(), sample_rule()];
+        let compact = format_json(&rules, true).unwrap();
+        let pretty = format_json(&rules, false).unwrap();
+        assert!(!compact.contains('\n'));
+        assert!(pretty.contains("\n  "));
+        assert!(compact.len() < pretty.len());
+        // Same document either way
+        let a: serde_json::Value = serde_json::from_str(&compact).unwrap();
+        let b: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_format_json_is_stable_with_sorted_keys() {
+        let info = SystemInfo {
+            version: Some("10.4".to_string()),
+            server_id: Some("ABC".to_string()),
+            edition: Some("developer".to_string()),
+            database: None,
+        };
+        let doc = health_json("UP", "https://sonar.example.com", Some(&info));
+        let first = format_json(&doc, false).unwrap();
+        let second = format_json(&health_json("UP", "https://sonar.example.com", Some(&info)), false).unwrap();
+        assert_eq!(first, second);
+
+        let keys: Vec<&str> = doc.as_object().unwrap().keys().map(String::as_str).collect();
+        assert_eq!(keys, vec!["edition", "healthy", "serverId", "status", "url", "version"]);
+        assert_eq!(
+            format_json(&doc, true).unwrap(),
+            r#"{"edition":"developer","healthy":true,"serverId":"ABC","status":"UP","url":"https://sonar.example.com","version":"10.4"}"#
+        );
+    }
+
+    #[test]
+    fn test_issues_json_written_to_output_file() {
+        let dir = std::env::temp_dir().join(format!("sonar-cli-test-output-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("reports").join("issues.json");
+
+        let file = create_output_file(&path).unwrap();
+        let mut out = io::BufWriter::new(&file);
+        write_json(&mut out, &[sample_issue()]).unwrap();
+        out.flush().unwrap();
+
+        let written: Vec<SonarIssue> = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written.len(), 1);
+        assert_eq!(written[0].key, sample_issue().key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_measures_comparison_written_to_output_file() {
+        let dir = std::env::temp_dir().join(format!("sonar-cli-test-comparison-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("comparison.txt");
+        let rows = [MeasureComparison {
+            metric: "coverage".to_string(),
+            current: Some("82.0".to_string()),
+            base: Some("80.0".to_string()),
+            delta: Some(2.0),
+        }];
+
+        let file = create_output_file(&path).unwrap();
+        let mut out = io::BufWriter::new(&file);
+        write_measures_comparison(&mut out, &rows, "proj", "feature/x", "main").unwrap();
+        out.flush().unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with("Measures for: proj (feature/x vs main)"), "{written}");
+        assert!(written.contains("coverage") && written.contains("+2"), "{written}");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_issues_json_round_trips_through_output_file() {
+        let dir = std::env::temp_dir().join(format!("sonar-cli-test-issues-json-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("issues.json");
+        let mut issue = sample_issue();
+        issue.rule_name = Some("Cognitive Complexity".to_string());
+        let issues = vec![issue, SonarIssue { key: "def".to_string(), line: None, ..sample_issue() }];
+
+        let file = create_output_file(&path).unwrap();
+        let mut out = io::BufWriter::new(&file);
+        write_json(&mut out, &issues).unwrap();
+        out.flush().unwrap();
+
+        let read: Vec<SonarIssue> = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].key, "abc");
+        assert_eq!(read[0].line, Some(42));
+        assert_eq!(read[0].rule_name.as_deref(), Some("Cognitive Complexity"));
+        assert_eq!(read[0].tags, vec!["brain-overload"]);
+        assert_eq!(read[1].key, "def");
+        assert_eq!(read[1].line, None);
+        assert_eq!(
+            serde_json::to_value(&read).unwrap(),
+            serde_json::to_value(&issues).unwrap()
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_json_serialization_failure_writes_nothing_and_fails_exit() {
+        // serde_json rejects maps whose keys are not strings
+        let unserializable: std::collections::BTreeMap<(u8, u8), u8> = [((1, 2), 3)].into();
+
+        let mut out = Vec::new();
+        assert!(write_json(&mut out, &unserializable).is_err());
+        assert!(out.is_empty());
+
+        print_json(&unserializable);
+        assert!(output_failed());
+        assert_eq!(final_exit_code(0), 1);
+        // An existing failure code is kept
+        assert_eq!(final_exit_code(2), 2);
+    }
+
+    #[test]
+    fn test_wrap_json_envelope() {
+        let meta = JsonEnvelope {
+            command: "rules".to_string(),
+            server: "https://sonar.example.com".to_string(),
+            project: Some("my-proj".to_string()),
+            branch: None,
+            generated_at: "2026-10-16T11:04:05Z".to_string(),
+        };
+        let rules = [sample_rule()];
+        let json = format_json(&wrap_json(&meta, &rules), true).unwrap();
+        assert!(json.starts_with(r#"{"command":"rules","server":"https://sonar.example.com","project":"my-proj","branch":null,"generated_at":"2026-10-16T11:04:05Z","data":["#));
+
+        let doc: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(doc["data"][0]["key"], "rust:S3776");
+        assert_eq!(doc["data"], serde_json::to_value(&rules).unwrap());
+    }
+
     // --- print_health ---
 
     #[test]
     fn test_print_health_up_text() {
-        print_health("UP", "http://localhost:9000", false);
+        print_health("UP", "http://localhost:9000", None, false);
     }
 
     #[test]
     fn test_print_health_down_text() {
-        print_health("DOWN", "http://localhost:9000", false);
+        print_health("DOWN", "http://localhost:9000", None, false);
     }
 
     #[test]
     fn test_print_health_up_json() {
-        print_health("UP", "http://localhost:9000", true);
+        print_health("UP", "http://localhost:9000", None, true);
     }
 
     #[test]
     fn test_print_health_unreachable_json() {
-        print_health("UNREACHABLE", "http://localhost:9000", true);
+        print_health("UNREACHABLE", "http://localhost:9000", None, true);
+    }
+
+    #[test]
+    fn test_health_json_includes_known_server_details() {
+        let info = SystemInfo {
+            version: Some("10.4.1.88267".to_string()),
+            server_id: None,
+            edition: Some("Community".to_string()),
+            database: None,
+        };
+        let doc = health_json("UP", "http://localhost:9000", Some(&info));
+        assert_eq!(doc["version"], "10.4.1.88267");
+        assert_eq!(doc["edition"], "Community");
+        assert!(doc.get("serverId").is_none());
+        assert_eq!(doc["healthy"], true);
+
+        let doc = health_json("UP", "http://localhost:9000", None);
+        assert!(doc.get("version").is_none());
+
+        print_health("UP", "http://localhost:9000", Some(&info), false);
+    }
+
+    fn rule_details(json: serde_json::Value) -> RuleDetails {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_rule_remediation() {
+        let rule = rule_details(serde_json::json!({
+            "key": "rust:S3776", "name": "Cognitive Complexity",
+            "remFnBaseEffort": "5min", "remFnGapMultiplier": "1min",
+            "gapDescription": "per complexity point over the threshold"
+        }));
+        assert_eq!(
+            rule_remediation(&rule).as_deref(),
+            Some("5min + 1min per complexity point over the threshold")
+        );
+
+        let rule = rule_details(serde_json::json!({"key": "k", "name": "n", "remFnBaseEffort": "10min"}));
+        assert_eq!(rule_remediation(&rule).as_deref(), Some("10min"));
+        let rule = rule_details(serde_json::json!({"key": "k", "name": "n"}));
+        assert_eq!(rule_remediation(&rule), None);
+    }
+
+    #[test]
+    fn test_rule_description_text_prefers_sections() {
+        let rule = rule_details(serde_json::json!({
+            "key": "k", "name": "n",
+            "htmlDesc": "<p>old</p>",
+            "descriptionSections": [
+                {"key": "introduction", "content": "<p>Intro</p>"},
+                {"key": "how_to_fix", "content": "<p>Do this</p>"}
+            ]
+        }));
+        assert_eq!(rule_description_text(&rule), "Intro\n\n## How to fix it\n\nDo this");
+
+        let rule = rule_details(serde_json::json!({"key": "k", "name": "n", "htmlDesc": "<p>old</p>"}));
+        assert_eq!(rule_description_text(&rule), "old");
+        print_rule_details(&rule, false);
+        print_rule_details(&rule, true);
+    }
+
+    #[test]
+    fn test_print_issues_with_effort() {
+        let effort = EffortSummary {
+            total_minutes: 200,
+            by_severity: vec![crate::helpers::SeverityEffort {
+                severity: "MAJOR".to_string(),
+                minutes: 200,
+            }],
+            unknown: 1,
+        };
+        let mut out = Vec::new();
+        write_issues(&mut out, &[sample_issue()], "proj", false, false).unwrap();
+        write_effort(&mut out, &effort).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("1 issues found (project: proj)"), "{text}");
+        assert!(text.contains("Complexity too high"), "{text}");
+        assert!(
+            text.ends_with("\nTotal effort: 3h 20min\n  MAJOR      3h 20min\n  (1 issues without an effort estimate)\n"),
+            "{text}"
+        );
+    }
+
+    #[test]
+    fn test_print_issues_summary() {
+        let groups = vec![("rust:S3776".to_string(), 42), ("rust:S1135".to_string(), 3)];
+        let mut out = Vec::new();
+        write_issues_summary(&mut out, &groups, 45, "rules").unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "45 issues in 2 rules\n\n  rust:S3776      42\n  rust:S1135       3\n"
+        );
+
+        let mut out = Vec::new();
+        write_issues_summary(&mut out, &[], 0, "files").unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "0 issues in 0 files\n");
+    }
+
+    #[test]
+    fn test_issues_header_hidden_when_quiet() {
+        let mut out = Vec::new();
+        write_issues(&mut out, &[sample_issue()], "proj", false, true).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(!text.contains("issues found"), "{text}");
+        assert!(text.contains("Complexity too high"), "{text}");
+
+        let mut out = Vec::new();
+        write_issues(&mut out, &[], "proj", false, true).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_issues_summary_json_keeps_group_order() {
+        // Largest first, which is not alphabetical
+        let groups = vec![
+            ("src/z.rs".to_string(), 9),
+            ("src/a.rs".to_string(), 4),
+            ("src/m.rs".to_string(), 1),
+        ];
+        let mut out = Vec::new();
+        write_json(&mut out, &issues_summary_json(&groups)).unwrap();
+        let doc: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(
+            doc,
+            serde_json::json!([
+                {"group": "src/z.rs", "count": 9},
+                {"group": "src/a.rs", "count": 4},
+                {"group": "src/m.rs", "count": 1}
+            ])
+        );
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.find("src/z.rs") < text.find("src/a.rs"), "{text}");
+        assert!(text.find("src/a.rs") < text.find("src/m.rs"), "{text}");
+    }
+
+    #[test]
+    fn test_print_version() {
+        let info = SystemInfo {
+            version: Some("10.4.1.88267".to_string()),
+            edition: Some("Developer".to_string()),
+            database: Some("PostgreSQL 15.4".to_string()),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        write_version(&mut out, &info).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "SonarQube 10.4.1.88267\n  Edition: Developer\n  Database: PostgreSQL 15.4\n"
+        );
+
+        let mut out = Vec::new();
+        write_version(&mut out, &SystemInfo::default()).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "SonarQube (unknown version)\n");
+    }
+
+    #[test]
+    fn test_whoami_json_keeps_raw_user_fields() {
+        let user: CurrentUser = serde_json::from_value(serde_json::json!({
+            "isLoggedIn": true,
+            "login": "jdoe",
+            "name": "Jane Doe",
+            "groups": ["sonar-users"]
+        }))
+        .unwrap();
+        let doc = whoami_json(&user, true);
+        assert_eq!(doc["login"], "jdoe");
+        assert_eq!(doc["groups"][0], "sonar-users");
+        assert_eq!(doc["valid"], true);
+
+        let doc = whoami_json(&CurrentUser::default(), false);
+        assert_eq!(doc["isLoggedIn"], false);
+        assert_eq!(doc["valid"], false);
+        assert!(doc.get("login").is_none());
+
+        let mut out = Vec::new();
+        write_whoami(&mut out, &user, true, true, "http://localhost:9000", None).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "SonarQube at http://localhost:9000\n  Login: jdoe\n  Name: Jane Doe\n  Token: valid\n"
+        );
+
+        let mut out = Vec::new();
+        write_whoami(&mut out, &CurrentUser::default(), false, false, "http://localhost:9000", None).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("  Login: (anonymous)\n  Token: none (anonymous)\n"), "{text}");
+    }
+
+    // --- print_new_code_period ---
+
+    fn new_code_period(period_type: &str, value: Option<&str>) -> NewCodePeriod {
+        NewCodePeriod {
+            project_key: Some("proj".to_string()),
+            branch_key: None,
+            period_type: period_type.to_string(),
+            value: value.map(str::to_string),
+            inherited: false,
+        }
+    }
+
+    #[test]
+    fn test_describe_new_code_period() {
+        assert_eq!(
+            describe_new_code_period(&new_code_period("REFERENCE_BRANCH", Some("main"))),
+            "Changes compared to branch 'main'"
+        );
+        assert_eq!(
+            describe_new_code_period(&new_code_period("NUMBER_OF_DAYS", Some("30"))),
+            "Changes in the last 30 days"
+        );
+        assert_eq!(
+            describe_new_code_period(&new_code_period("PREVIOUS_VERSION", None)),
+            "Changes since the previous version"
+        );
+        assert_eq!(
+            describe_new_code_period(&new_code_period("SOMETHING_NEW", Some("x"))),
+            "SOMETHING_NEW (x)"
+        );
+    }
+
+    #[test]
+    fn test_print_new_code_period() {
+        let mut period = new_code_period("REFERENCE_BRANCH", Some("main"));
+        period.branch_key = Some("feature/x".to_string());
+        print_new_code_period(&period, "proj", false);
+        print_new_code_period(&period, "proj", true);
+    }
+
+    // --- format_gate_summary ---
+
+    #[test]
+    fn test_format_gate_summary_failed() {
+        let mut gate = sample_quality_gate();
+        gate.project_status.status = "ERROR".to_string();
+        gate.project_status.conditions = vec![
+            QualityGateCondition {
+                status: "ERROR".to_string(),
+                metric_key: "new_coverage".to_string(),
+                comparator: Some("LT".to_string()),
+                error_threshold: Some("80".to_string()),
+                actual_value: Some("72.0".to_string()),
+            },
+            QualityGateCondition {
+                status: "OK".to_string(),
+                metric_key: "new_bugs".to_string(),
+                comparator: Some("GT".to_string()),
+                error_threshold: Some("0".to_string()),
+                actual_value: Some("0".to_string()),
+            },
+            QualityGateCondition {
+                status: "ERROR".to_string(),
+                metric_key: "new_violations".to_string(),
+                comparator: Some("GT".to_string()),
+                error_threshold: Some("0".to_string()),
+                actual_value: Some("4".to_string()),
+            },
+        ];
+        assert_eq!(
+            format_gate_summary(&gate),
+            "Quality Gate: FAILED (new_coverage 72.0% < 80%, new_violations 4 > 0)"
+        );
+    }
+
+    #[test]
+    fn test_format_gate_summary_passed() {
+        let mut gate = sample_quality_gate();
+        gate.project_status.status = "OK".to_string();
+        for c in &mut gate.project_status.conditions {
+            c.status = "OK".to_string();
+        }
+        assert_eq!(format_gate_summary(&gate), "Quality Gate: PASSED");
     }
 
     // --- print_quality_gate ---
@@ -512,17 +2813,30 @@ mod tests {
 
     #[test]
     fn test_print_issues_text() {
-        print_issues(&[sample_issue()], "proj", false);
+        print_issues(&[sample_issue()], "proj", false, false);
     }
 
     #[test]
     fn test_print_issues_json() {
-        print_issues(&[sample_issue()], "proj", true);
+        print_issues(&[sample_issue()], "proj", false, true);
+    }
+
+    #[test]
+    fn test_print_issues_with_rule_severity() {
+        let mut issue = sample_issue();
+        issue.rule_severity = Some("MAJOR".to_string());
+        print_issues(&[issue.clone()], "proj", false, false);
+        let v = serde_json::to_value(&issue).unwrap();
+        assert_eq!(v["ruleSeverity"], "MAJOR");
+        // Omitted from JSON unless requested
+        let v = serde_json::to_value(sample_issue()).unwrap();
+        assert!(v.get("ruleSeverity").is_none());
+        assert!(v.get("ruleName").is_none());
     }
 
     #[test]
     fn test_print_issues_empty() {
-        print_issues(&[], "proj", false);
+        print_issues(&[], "proj", false, false);
     }
 
     #[test]
@@ -535,26 +2849,156 @@ mod tests {
             start_offset: Some(0),
             end_offset: Some(10),
         });
-        print_issues(&[issue], "proj", false);
+        print_issues(&[issue], "proj", false, false);
+    }
+
+    #[test]
+    fn test_color_never_emits_no_ansi_sequences() {
+        let mut issue = sample_issue();
+        for severity in ["BLOCKER", "CRITICAL", "MAJOR", "MINOR", "INFO"] {
+            issue.severity = severity.to_string();
+            let line = issue_header(&issue, false, false);
+            assert!(!line.contains('\x1b'), "{line:?}");
+            assert!(line.starts_with(&format!("  [{severity:<8}] [CODE_SMELL]")));
+        }
+        for status in ["OK", "WARN", "ERROR"] {
+            assert!(!gate_header(status, "proj", false).contains('\x1b'));
+        }
+        assert_eq!(gate_header("OK", "proj", false), "Quality Gate: [PASSED] OK  (project: proj)");
+    }
+
+    #[test]
+    fn test_color_always_paints_severity_and_gate() {
+        let mut issue = sample_issue();
+        issue.severity = "BLOCKER".to_string();
+        let line = issue_header(&issue, false, true);
+        // Padding stays inside the colored span, so columns still line up
+        assert!(line.starts_with("  [\x1b[31mBLOCKER \x1b[0m] [CODE_SMELL]"), "{line:?}");
+        assert!(gate_header("OK", "proj", true).contains("\x1b[32mPASSED\x1b[0m"));
+        assert!(gate_header("ERROR", "proj", true).contains("\x1b[31mFAILED\x1b[0m"));
+    }
+
+    #[test]
+    fn test_color_enabled_auto_needs_tty_and_no_no_color() {
+        assert!(color_enabled(ColorChoice::Auto, true, false));
+        assert!(!color_enabled(ColorChoice::Auto, false, false));
+        assert!(!color_enabled(ColorChoice::Auto, true, true));
+        assert!(color_enabled(ColorChoice::Always, false, true));
+        assert!(!color_enabled(ColorChoice::Never, true, false));
+        assert_eq!(parse_color_choice("ALWAYS"), Ok(ColorChoice::Always));
+        assert!(parse_color_choice("sometimes").is_err());
+    }
+
+    #[test]
+    fn test_machine_formats_never_colored() {
+        let issues = [sample_issue()];
+        let mut out = Vec::new();
+        write_issues_delimited(&mut out, &issues, "proj", ',').unwrap();
+        write_json(&mut out, &issues).unwrap();
+        assert!(!out.contains(&0x1b));
+    }
+
+    #[test]
+    fn test_issue_location_keeps_colons_after_project_prefix() {
+        let mut issue = sample_issue();
+        issue.component = "proj:BRANCH:feature/x:src/a:b.rs".to_string();
+        assert_eq!(issue_location(&issue, false), "BRANCH:feature/x:src/a:b.rs:42");
+        assert_eq!(issue_location(&issue, true), "proj:BRANCH:feature/x:src/a:b.rs:42");
+
+        // Issues from another project keep their own prefix stripped, not ours
+        issue.project = "other".to_string();
+        issue.component = "other:src/lib.rs".to_string();
+        issue.line = None;
+        assert_eq!(issue_location(&issue, false), "src/lib.rs");
     }
 
     #[test]
     fn test_print_issues_no_tags() {
         let mut issue = sample_issue();
         issue.tags = vec![];
-        print_issues(&[issue], "proj", false);
+        print_issues(&[issue], "proj", false, false);
     }
 
     // --- print_measures ---
 
     #[test]
     fn test_print_measures_text() {
-        print_measures(&sample_measures_response(), false);
+        print_measures(&sample_measures_response(), false, false);
     }
 
     #[test]
     fn test_print_measures_json() {
-        print_measures(&sample_measures_response(), true);
+        print_measures(&sample_measures_response(), false, true);
+    }
+
+    #[test]
+    fn test_measures_json_adds_numbers_kinds_and_ratings() {
+        let mut response = sample_measures_response();
+        response.component.measures = vec![
+            Measure { metric: "reliability_rating".to_string(), value: Some("1.0".to_string()), period: None },
+            Measure { metric: "coverage".to_string(), value: Some("85.5".to_string()), period: None },
+            Measure { metric: "ncloc".to_string(), value: Some("1200".to_string()), period: None },
+            Measure { metric: "alert_status".to_string(), value: Some("OK".to_string()), period: None },
+        ];
+        let json = measures_json(&response);
+        let measures = json["component"]["measures"].as_array().unwrap();
+
+        assert_eq!(measures[0]["rating"], "A");
+        assert_eq!(measures[0]["kind"], "rating");
+        assert_eq!(measures[0]["value"], "1.0");
+        assert_eq!(measures[0]["value_num"], 1.0);
+        assert_eq!(measures[1]["value_num"], 85.5);
+        assert_eq!(measures[1]["kind"], "percent");
+        assert!(measures[1].get("rating").is_none());
+        assert_eq!(measures[2]["value_num"], 1200);
+        assert_eq!(measures[2]["kind"], "count");
+        assert!(measures[3].get("value_num").is_none());
+        assert!(measures[3].get("kind").is_none());
+    }
+
+    #[test]
+    fn test_new_code_measure_lines_use_period_value() {
+        let mut response = sample_measures_response();
+        response.component.measures = vec![
+            Measure {
+                metric: "new_coverage".to_string(),
+                value: None,
+                period: Some(MeasurePeriod { value: "64.0".to_string() }),
+            },
+            Measure {
+                metric: "new_bugs".to_string(),
+                value: Some("2".to_string()),
+                period: None,
+            },
+            Measure {
+                metric: "new_lines".to_string(),
+                value: None,
+                period: Some(MeasurePeriod { value: "1200".to_string() }),
+            },
+        ];
+        assert_eq!(
+            new_code_measure_lines(&response, false),
+            vec![
+                "new coverage: 64.0% (on 1,200 new lines)",
+                "new bugs: 2",
+                "new lines: 1,200",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_display_measure_value_formats_counts_only() {
+        let mut response = sample_measures_response();
+        response.metrics = vec![
+            MetricDefinition { key: "ncloc".to_string(), metric_type: "INT".to_string() },
+            MetricDefinition { key: "coverage".to_string(), metric_type: "PERCENT".to_string() },
+        ];
+        let ncloc = Measure { metric: "ncloc".to_string(), value: Some("1234567".to_string()), period: None };
+        let coverage = Measure { metric: "coverage".to_string(), value: Some("1234".to_string()), period: None };
+
+        assert_eq!(display_measure_value(&response, &ncloc, false), "1,234,567");
+        assert_eq!(display_measure_value(&response, &ncloc, true), "1.2M");
+        assert_eq!(display_measure_value(&response, &coverage, true), "1234");
     }
 
     // --- print_coverage ---
@@ -701,8 +3145,75 @@ mod tests {
         print_history(&[], "proj", false);
     }
 
+    #[test]
+    fn test_print_history_table() {
+        let table = crate::helpers::pivot_history(&[sample_history()]);
+        print_history_table(&table, "proj", false);
+        print_history_table(&table, "proj", true);
+        print_history_table(&crate::helpers::pivot_history(&[]), "proj", false);
+    }
+
+    #[test]
+    fn test_print_history_alert_status() {
+        let measures = vec![MeasureHistory {
+            metric: "alert_status".to_string(),
+            history: vec![
+                crate::types::HistoryValue {
+                    date: "2026-01-01".to_string(),
+                    value: Some("OK".to_string()),
+                },
+                crate::types::HistoryValue {
+                    date: "2026-01-02".to_string(),
+                    value: Some("ERROR".to_string()),
+                },
+            ],
+        }];
+        print_history(&measures, "proj", false);
+        print_history_table(&crate::helpers::pivot_history(&measures), "proj", false);
+
+        let changes = crate::helpers::gate_timeline(&measures);
+        assert_eq!(changes.len(), 2);
+        print_gate_history(&changes, "proj", false);
+        print_gate_history(&changes, "proj", true);
+        print_gate_history(&[], "proj", false);
+    }
+
+    // --- print_measures_comparison ---
+
+    #[test]
+    fn test_format_delta() {
+        assert_eq!(format_delta(Some(2.0)), "+2");
+        assert_eq!(format_delta(Some(-2.5)), "-2.50");
+        assert_eq!(format_delta(Some(0.0)), "+0");
+        assert_eq!(format_delta(None), "-");
+    }
+
+    #[test]
+    fn test_print_measures_comparison() {
+        let rows = vec![MeasureComparison {
+            metric: "coverage".to_string(),
+            current: Some("82.5".to_string()),
+            base: Some("80.0".to_string()),
+            delta: Some(2.5),
+        }];
+        print_measures_comparison(&rows, "proj", "feature", "main", false);
+        print_measures_comparison(&rows, "proj", "feature", "main", true);
+    }
+
     // --- print_rules ---
 
+    #[test]
+    fn test_describe_activation() {
+        let mut activation = RuleActivation {
+            severity: Some("MAJOR".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(describe_activation(&activation), "MAJOR");
+        activation.params.insert("max".to_string(), "7".to_string());
+        activation.params.insert("exclude".to_string(), "*.gen".to_string());
+        assert_eq!(describe_activation(&activation), "MAJOR (exclude=*.gen, max=7)");
+    }
+
     #[test]
     fn test_print_rules_text() {
         print_rules(&[sample_rule()], false);
@@ -713,6 +3224,19 @@ mod tests {
         print_rules(&[sample_rule()], true);
     }
 
+    #[test]
+    fn test_rule_deprecation_lines() {
+        assert!(rule_deprecation_lines(&sample_rule()).is_empty());
+
+        let mut rule = sample_rule();
+        rule.replaced_by = Some("rust:S7000".to_string());
+        rule.deprecated_keys = Some(DeprecatedKeys { keys: vec!["rust:S100".to_string(), "clippy:S1".to_string()] });
+        assert_eq!(
+            rule_deprecation_lines(&rule),
+            ["replaced by: rust:S7000", "deprecated keys: rust:S100, clippy:S1"]
+        );
+    }
+
     #[test]
     fn test_print_rules_empty() {
         print_rules(&[], false);
@@ -753,6 +3277,57 @@ mod tests {
         print_source(&[], false);
     }
 
+    // --- print_blame ---
+
+    fn blame_line(line: usize, author: Option<&str>, date: Option<&str>) -> BlameLine {
+        BlameLine {
+            line,
+            code: "let x = 1;".to_string(),
+            scm_author: author.map(String::from),
+            scm_date: date.map(String::from),
+            scm_revision: None,
+        }
+    }
+
+    #[test]
+    fn test_format_blame_line_with_scm() {
+        let line = blame_line(7, Some("alice"), Some("2026-03-01T10:00:00+0000"));
+        assert_eq!(
+            format_blame_line(&line, 8),
+            "     7 alice    2026-03-01 | let x = 1;"
+        );
+    }
+
+    #[test]
+    fn test_format_blame_line_without_scm() {
+        let line = blame_line(8, None, None);
+        assert_eq!(
+            format_blame_line(&line, 5),
+            "     8                  | let x = 1;"
+        );
+    }
+
+    #[test]
+    fn test_format_blame_line_truncates_long_author() {
+        let line = blame_line(1, Some("a-very-long-author-name"), None);
+        assert!(format_blame_line(&line, 6).starts_with("     1 a-very            | "));
+    }
+
+    #[test]
+    fn test_print_blame_text_and_json() {
+        let lines = vec![
+            blame_line(1, Some("alice"), Some("2026-03-01T10:00:00+0000")),
+            blame_line(2, None, None),
+        ];
+        print_blame(&lines, false);
+        print_blame(&lines, true);
+    }
+
+    #[test]
+    fn test_print_blame_no_scm_data() {
+        print_blame(&[blame_line(1, None, None)], false);
+    }
+
     // --- print_wait_result ---
 
     #[test]
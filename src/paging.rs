@@ -0,0 +1,349 @@
+//! Shared pagination for SonarQube list endpoints
+//!
+//! Every `*/search`-style endpoint takes `p`/`ps` and reports a total, but the
+//! total is optional on some (component trees without `paging`), so the stop
+//! rules live here instead of in each caller.
+
+use std::collections::BTreeMap;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::Poll;
+
+/// Page size requested from list endpoints
+pub const PAGE_SIZE: usize = 100;
+
+/// Hard cap on pages fetched, to bound runaway loops on inconsistent servers
+pub const MAX_PAGES: usize = 100;
+
+/// Search endpoints reject requests past `p * ps` = 10000 results
+pub const MAX_RESULT_WINDOW: usize = 10_000;
+
+/// Largest `ps` the search endpoints accept
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// Requests in flight at once for [`paginate_concurrent`] and [`join_bounded`] unless `--concurrency` says otherwise
+pub const DEFAULT_CONCURRENCY: usize = 8;
+
+/// A single page picked with `--page`/`--page-size`, fetched instead of
+/// every page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+    /// 1-based page number
+    pub page: usize,
+    pub size: usize,
+}
+
+impl PageRequest {
+    /// `None` (fetch every page) unless a page or a page size is given; the
+    /// other defaults to page 1 or [`PAGE_SIZE`]. A size above [`MAX_PAGE_SIZE`]
+    /// is clamped with a warning.
+    pub fn from_flags(page: Option<usize>, size: Option<usize>) -> Option<Self> {
+        if page.is_none() && size.is_none() {
+            return None;
+        }
+        let mut size = size.unwrap_or(PAGE_SIZE).max(1);
+        if size > MAX_PAGE_SIZE {
+            eprintln!("Warning: --page-size {size} exceeds the SonarQube maximum of {MAX_PAGE_SIZE}; using {MAX_PAGE_SIZE}");
+            size = MAX_PAGE_SIZE;
+        }
+        Some(Self {
+            page: page.unwrap_or(1).max(1),
+            size,
+        })
+    }
+}
+
+/// One page of results and the server-reported total, when there is one
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: Option<usize>,
+}
+
+/// Fetch pages (1-based) of `page_size` and hand each one to `on_page`.
+///
+/// Stops once the reported total has been seen, when a page comes back short
+/// (which also covers endpoints that report no total), or after [`MAX_PAGES`]
+/// (fewer when `page_size` would pass [`MAX_RESULT_WINDOW`]).
+/// Returns the number of items seen.
+pub async fn paginate<T, E, F, Fut>(
+    page_size: usize,
+    fetch_page: F,
+    on_page: impl FnMut(Vec<T>),
+) -> Result<usize, E>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Result<Page<T>, E>>,
+{
+    paginate_concurrent(page_size, 1, fetch_page, on_page).await
+}
+
+/// [`paginate`], with up to `concurrency` requests in flight once the first
+/// page has reported the total.
+///
+/// Pages still reach `on_page` in page order, whatever order they arrive in.
+/// Without a total the page count is unknown, so the rest is fetched one page
+/// at a time. The first error is returned and the pending requests dropped.
+pub async fn paginate_concurrent<T, E, F, Fut>(
+    page_size: usize,
+    concurrency: usize,
+    mut fetch_page: F,
+    mut on_page: impl FnMut(Vec<T>),
+) -> Result<usize, E>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Result<Page<T>, E>>,
+{
+    let page_size = page_size.max(1);
+    let max_pages = MAX_PAGES.min(MAX_RESULT_WINDOW / page_size).max(1);
+
+    let Page { items, total } = fetch_page(1).await?;
+    let mut seen = items.len();
+    let mut done = items.len() < page_size;
+    on_page(items);
+
+    let (last_page, concurrency) = match total {
+        Some(t) => (t.div_ceil(page_size).min(max_pages), concurrency.max(1)),
+        None => (max_pages, 1),
+    };
+    let mut next_request = 2;
+    let mut next_delivery = 2;
+    let mut in_flight: Vec<(usize, Pin<Box<Fut>>)> = Vec::new();
+    let mut arrived = BTreeMap::new();
+    while !done && next_delivery <= last_page {
+        while in_flight.len() < concurrency && next_request <= last_page {
+            in_flight.push((next_request, Box::pin(fetch_page(next_request))));
+            next_request += 1;
+        }
+        let (page, result) = next_finished(&mut in_flight).await;
+        arrived.insert(page, result?.items);
+
+        while let Some(items) = arrived.remove(&next_delivery) {
+            next_delivery += 1;
+            seen += items.len();
+            // A short page ends the results even if the total promised more
+            done = items.len() < page_size;
+            on_page(items);
+            if done {
+                break;
+            }
+        }
+    }
+    Ok(seen)
+}
+
+/// Run `tasks` with at most `concurrency` of them in flight at once.
+/// Outputs come back in the order of `tasks`.
+pub async fn join_bounded<Fut: Future>(
+    concurrency: usize,
+    tasks: impl IntoIterator<Item = Fut>,
+) -> Vec<Fut::Output> {
+    let mut tasks = tasks.into_iter().enumerate();
+    let mut in_flight = Vec::new();
+    let mut outputs = BTreeMap::new();
+    loop {
+        while in_flight.len() < concurrency.max(1) {
+            match tasks.next() {
+                Some((i, task)) => in_flight.push((i, Box::pin(task))),
+                None => break,
+            }
+        }
+        if in_flight.is_empty() {
+            break;
+        }
+        let (i, output) = next_finished(&mut in_flight).await;
+        outputs.insert(i, output);
+    }
+    outputs.into_values().collect()
+}
+
+/// Wait for the first of the (non-empty) `in_flight` futures to finish and
+/// remove it, returning its tag and output
+async fn next_finished<K: Copy, Fut: Future>(in_flight: &mut Vec<(K, Pin<Box<Fut>>)>) -> (K, Fut::Output) {
+    poll_fn(|cx| {
+        let ready = in_flight.iter_mut().enumerate().find_map(|(i, (tag, task))| {
+            match task.as_mut().poll(cx) {
+                Poll::Ready(output) => Some((i, *tag, output)),
+                Poll::Pending => None,
+            }
+        });
+        match ready {
+            Some((i, tag, output)) => {
+                in_flight.swap_remove(i);
+                Poll::Ready((tag, output))
+            }
+            None => Poll::Pending,
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_request_from_flags() {
+        assert_eq!(PageRequest::from_flags(None, None), None);
+        assert_eq!(PageRequest::from_flags(Some(2), Some(50)), Some(PageRequest { page: 2, size: 50 }));
+        assert_eq!(PageRequest::from_flags(Some(3), None), Some(PageRequest { page: 3, size: PAGE_SIZE }));
+        assert_eq!(PageRequest::from_flags(None, Some(10)), Some(PageRequest { page: 1, size: 10 }));
+        assert_eq!(PageRequest::from_flags(None, Some(1000)), Some(PageRequest { page: 1, size: MAX_PAGE_SIZE }));
+    }
+
+    /// Serve `len` numbered items in pages of `page_size`, reporting `total`
+    async fn run(len: usize, page_size: usize, total: Option<usize>) -> (Vec<usize>, Vec<usize>) {
+        let mut requested = Vec::new();
+        let mut items = Vec::new();
+        let seen = paginate::<_, (), _, _>(
+            page_size,
+            |page| {
+                requested.push(page);
+                let start = (page - 1) * page_size;
+                let end = (start + page_size).min(len);
+                let items = (start.min(end)..end).collect();
+                async move { Ok(Page { items, total }) }
+            },
+            |page| items.extend(page),
+        )
+        .await
+        .unwrap();
+        assert_eq!(seen, items.len());
+        (requested, items)
+    }
+
+    #[tokio::test]
+    async fn test_stops_when_total_reached() {
+        // Exactly two full pages: the total avoids requesting an empty third page
+        let (requested, items) = run(20, 10, Some(20)).await;
+        assert_eq!(requested, vec![1, 2]);
+        assert_eq!(items.len(), 20);
+    }
+
+    #[tokio::test]
+    async fn test_total_absent_reads_until_short_page() {
+        let (requested, items) = run(25, 10, None).await;
+        assert_eq!(requested, vec![1, 2, 3]);
+        assert_eq!(items, (0..25).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_total_absent_full_final_page_needs_empty_page() {
+        let (requested, items) = run(20, 10, None).await;
+        assert_eq!(requested, vec![1, 2, 3]);
+        assert_eq!(items.len(), 20);
+    }
+
+    #[tokio::test]
+    async fn test_short_final_page_stops_despite_larger_total() {
+        // The server over-reports the total; the short page still ends the loop
+        let (requested, items) = run(15, 10, Some(40)).await;
+        assert_eq!(requested, vec![1, 2]);
+        assert_eq!(items.len(), 15);
+    }
+
+    #[tokio::test]
+    async fn test_page_cap() {
+        let (requested, _) = run(usize::MAX / 2, 1, None).await;
+        assert_eq!(requested.len(), MAX_PAGES);
+    }
+
+    #[tokio::test]
+    async fn test_result_window_caps_large_pages() {
+        let (requested, _) = run(usize::MAX / 2, 500, Some(usize::MAX / 2)).await;
+        assert_eq!(requested.len(), MAX_RESULT_WINDOW / 500);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_pages_delivered_in_order() {
+        // Later pages answer first; delivery still follows page order
+        let mut requested = Vec::new();
+        let mut items = Vec::new();
+        let seen = paginate_concurrent::<_, (), _, _>(
+            10,
+            4,
+            |page| {
+                requested.push(page);
+                async move {
+                    tokio::time::sleep(std::time::Duration::from_millis(5 * (6 - page) as u64)).await;
+                    let start = (page - 1) * 10;
+                    Ok(Page { items: (start..(start + 10).min(45)).collect(), total: Some(45) })
+                }
+            },
+            |page| items.extend(page),
+        )
+        .await
+        .unwrap();
+        assert_eq!(seen, 45);
+        assert_eq!(items, (0..45).collect::<Vec<_>>());
+        requested.sort();
+        assert_eq!(requested, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_without_total_is_sequential() {
+        let mut requested = Vec::new();
+        paginate_concurrent::<usize, (), _, _>(
+            10,
+            8,
+            |page| {
+                requested.push(page);
+                let len = if page < 3 { 10 } else { 4 };
+                async move { Ok(Page { items: vec![0; len], total: None }) }
+            },
+            |_| {},
+        )
+        .await
+        .unwrap();
+        assert_eq!(requested, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_error_is_returned() {
+        let result = paginate_concurrent::<usize, _, _, _>(
+            10,
+            8,
+            |page| async move {
+                if page == 3 {
+                    Err("boom")
+                } else {
+                    Ok(Page { items: vec![0; 10], total: Some(50) })
+                }
+            },
+            |_| {},
+        )
+        .await;
+        assert_eq!(result, Err("boom"));
+    }
+
+    #[tokio::test]
+    async fn test_join_bounded_keeps_task_order_and_bound() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let tasks = (0..10u64).map(|i| {
+            let (running, peak) = (&running, &peak);
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(20 - 2 * i)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                i
+            }
+        });
+        let outputs = join_bounded(3, tasks).await;
+        assert_eq!(outputs, (0..10).collect::<Vec<_>>());
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_error_is_returned() {
+        let result = paginate::<u32, _, _, _>(
+            10,
+            |_| async { Err("boom") },
+            |_| unreachable!(),
+        )
+        .await;
+        assert_eq!(result, Err("boom"));
+    }
+}
@@ -0,0 +1,212 @@
+//! Where the API token comes from
+//!
+//! `--token` wins, then `--token-file`, then `--token-command` (for `pass`,
+//! `vault` and similar password managers), then the stored profile, and
+//! finally the `SONAR_TOKEN` environment variable. The file and command are
+//! only read when no higher-precedence source is set.
+
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long `--token-command` may run before it is killed
+pub const TOKEN_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Environment variable read when no other token source is set
+pub const TOKEN_ENV: &str = "SONAR_TOKEN";
+
+/// Every place a token can come from, highest precedence first
+#[derive(Debug, Default)]
+pub struct TokenSources<'a> {
+    pub flag: Option<&'a str>,
+    pub file: Option<&'a Path>,
+    pub command: Option<&'a str>,
+    pub stored: Option<String>,
+    pub env: Option<String>,
+}
+
+/// Pick the token from the highest-precedence source that is set.
+///
+/// A token file or command that is set but fails (unreadable, empty, nonzero
+/// exit, timeout) is an error rather than a fallthrough to the next source.
+pub fn resolve(sources: TokenSources<'_>, timeout: Duration) -> Result<Option<String>, String> {
+    if let Some(token) = sources.flag {
+        return Ok(Some(token.to_string()));
+    }
+    if let Some(path) = sources.file {
+        return read_token_file(path).map(Some);
+    }
+    if let Some(command) = sources.command {
+        return run_token_command(command, timeout).map(Some);
+    }
+    Ok(sources.stored.or(sources.env.filter(|t| !t.is_empty())))
+}
+
+/// First line of `path`, trimmed
+pub fn read_token_file(path: &Path) -> Result<String, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read token file {}: {e}", path.display()))?;
+    let token = content.lines().next().unwrap_or_default().trim();
+    if token.is_empty() {
+        return Err(format!("Token file {} is empty", path.display()));
+    }
+    Ok(token.to_string())
+}
+
+/// The platform shell running `command`
+fn shell(command: &str) -> Command {
+    let mut cmd = if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.arg("/C");
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.arg("-c");
+        c
+    };
+    cmd.arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    cmd
+}
+
+/// Drain a child's pipe on its own thread, so a chatty command cannot block on a full pipe
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut text = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut text);
+        }
+        text
+    })
+}
+
+/// Run `command` through the shell and return its trimmed stdout.
+///
+/// Fails when the command cannot start, exits nonzero (its stderr is
+/// included), prints nothing, or is still running after `timeout`.
+pub fn run_token_command(command: &str, timeout: Duration) -> Result<String, String> {
+    let mut child = shell(command)
+        .spawn()
+        .map_err(|e| format!("Failed to run token command '{command}': {e}"))?;
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "Token command '{command}' timed out after {}s",
+                    timeout.as_secs_f32()
+                ));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(format!("Failed to wait for token command '{command}': {e}")),
+        }
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    if !status.success() {
+        let code = status.code().map_or("a signal".to_string(), |c| format!("code {c}"));
+        let detail = stderr.trim();
+        return Err(if detail.is_empty() {
+            format!("Token command '{command}' exited with {code}")
+        } else {
+            format!("Token command '{command}' exited with {code}: {detail}")
+        });
+    }
+    let token = stdout.trim();
+    if token.is_empty() {
+        return Err(format!("Token command '{command}' printed no token"));
+    }
+    Ok(token.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_token_file(name: &str, content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("sonar-cli-test-token-{name}-{}", std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_read_token_file_trims_first_line() {
+        let path = temp_token_file("first-line", "  squ_from_file \nsecond line\n");
+        assert_eq!(read_token_file(&path).unwrap(), "squ_from_file");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_read_token_file_errors() {
+        let empty = temp_token_file("empty", "\n");
+        assert!(read_token_file(&empty).unwrap_err().contains("is empty"));
+        let _ = std::fs::remove_file(&empty);
+
+        let missing = std::env::temp_dir().join("sonar-cli-test-token-missing");
+        assert!(read_token_file(&missing).unwrap_err().starts_with("Failed to read token file"));
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        let path = temp_token_file("precedence", "squ_file\n");
+        let all = || TokenSources {
+            flag: Some("squ_flag"),
+            file: Some(&path),
+            command: Some("echo squ_command"),
+            stored: Some("squ_stored".to_string()),
+            env: Some("squ_env".to_string()),
+        };
+        let resolved = |sources| resolve(sources, TOKEN_COMMAND_TIMEOUT).unwrap();
+
+        assert_eq!(resolved(all()).as_deref(), Some("squ_flag"));
+        assert_eq!(resolved(TokenSources { flag: None, ..all() }).as_deref(), Some("squ_file"));
+        assert_eq!(
+            resolved(TokenSources { flag: None, file: None, ..all() }).as_deref(),
+            Some("squ_command")
+        );
+        assert_eq!(
+            resolved(TokenSources { flag: None, file: None, command: None, ..all() }).as_deref(),
+            Some("squ_stored")
+        );
+        assert_eq!(
+            resolved(TokenSources { env: Some("squ_env".to_string()), ..TokenSources::default() }).as_deref(),
+            Some("squ_env")
+        );
+        assert_eq!(resolved(TokenSources { env: Some(String::new()), ..TokenSources::default() }), None);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_resolve_failing_file_does_not_fall_through() {
+        let missing = std::env::temp_dir().join("sonar-cli-test-token-missing-resolve");
+        let sources = TokenSources {
+            file: Some(&missing),
+            stored: Some("squ_stored".to_string()),
+            ..TokenSources::default()
+        };
+        assert!(resolve(sources, TOKEN_COMMAND_TIMEOUT).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_token_command_nonzero_exit_and_timeout() {
+        let err = run_token_command("echo denied >&2; exit 3", TOKEN_COMMAND_TIMEOUT).unwrap_err();
+        assert_eq!(err, "Token command 'echo denied >&2; exit 3' exited with code 3: denied");
+
+        let err = run_token_command("true", TOKEN_COMMAND_TIMEOUT).unwrap_err();
+        assert!(err.contains("printed no token"), "{err}");
+
+        let err = run_token_command("sleep 5", Duration::from_millis(100)).unwrap_err();
+        assert!(err.contains("timed out"), "{err}");
+    }
+}
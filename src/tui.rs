@@ -0,0 +1,374 @@
+//! Interactive issue browser for `issues --interactive` (cargo feature `tui`)
+//!
+//! A scrollable list of the fetched issues next to a details pane. The source
+//! around the selected issue is fetched on demand through the client and kept
+//! for the rest of the session.
+//!
+//! Keys: ↑/↓ or j/k move, PgUp/PgDn page, Home/End jump, Enter loads the
+//! source, s cycles the severity filter, t the type filter, c clears both,
+//! q or Esc quits.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::client::SonarQubeClient;
+use crate::error::CliError;
+use crate::helpers;
+use crate::types::{severity, SonarIssue, SourceLine};
+
+/// Lines of source shown above and below the issue line
+const SNIPPET_CONTEXT: usize = 3;
+
+/// How long a single wait for a key blocks before yielding to the runtime
+const KEY_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Rows moved by PgUp/PgDn
+const PAGE: isize = 10;
+
+/// Severity filter cycle, most severe first
+const SEVERITIES: &[&str] = &[
+    severity::BLOCKER,
+    severity::CRITICAL,
+    severity::MAJOR,
+    severity::MINOR,
+    severity::INFO,
+];
+
+/// Type filter cycle
+const ISSUE_TYPES: &[&str] = &["BUG", "VULNERABILITY", "CODE_SMELL"];
+
+/// What the event loop does after a key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Redraw,
+    LoadSource,
+    Quit,
+}
+
+/// The next filter value after `current`: unset, then each of `values`, then unset again
+fn next_filter(current: Option<&'static str>, values: &[&'static str]) -> Option<&'static str> {
+    match current {
+        None => values.first().copied(),
+        Some(value) => values
+            .iter()
+            .position(|v| *v == value)
+            .and_then(|i| values.get(i + 1))
+            .copied(),
+    }
+}
+
+/// Same palette as the text output: red for BLOCKER/CRITICAL, yellow for MAJOR, cyan for MINOR
+fn severity_style(severity: &str) -> Style {
+    match severity {
+        "BLOCKER" => Style::new().fg(Color::Red).add_modifier(Modifier::BOLD),
+        "CRITICAL" => Style::new().fg(Color::Red),
+        "MAJOR" => Style::new().fg(Color::Yellow),
+        "MINOR" => Style::new().fg(Color::Cyan),
+        _ => Style::new(),
+    }
+}
+
+/// Browser state: filters, selection and the source snippets fetched so far
+struct Browser<'a> {
+    issues: &'a [SonarIssue],
+    project: &'a str,
+    severity: Option<&'static str>,
+    issue_type: Option<&'static str>,
+    /// Indices into `issues` that pass the filters
+    visible: Vec<usize>,
+    list: ListState,
+    /// Source snippet (or why there is none) per issue key
+    sources: HashMap<String, Result<Vec<SourceLine>, String>>,
+}
+
+impl<'a> Browser<'a> {
+    fn new(issues: &'a [SonarIssue], project: &'a str) -> Self {
+        let mut browser = Browser {
+            issues,
+            project,
+            severity: None,
+            issue_type: None,
+            visible: Vec::new(),
+            list: ListState::default(),
+            sources: HashMap::new(),
+        };
+        browser.refilter();
+        browser
+    }
+
+    /// Recompute the visible issues, keeping the selected one if it still passes
+    fn refilter(&mut self) {
+        let selected = self.selected().map(|i| i.key.clone());
+        self.visible = (0..self.issues.len())
+            .filter(|&i| {
+                let issue = &self.issues[i];
+                self.severity.is_none_or(|s| issue.severity == s)
+                    && self.issue_type.is_none_or(|t| issue.issue_type == t)
+            })
+            .collect();
+        let index = selected
+            .and_then(|key| self.visible.iter().position(|&i| self.issues[i].key == key))
+            .or((!self.visible.is_empty()).then_some(0));
+        self.list.select(index);
+    }
+
+    fn selected(&self) -> Option<&'a SonarIssue> {
+        let issues = self.issues;
+        self.list
+            .selected()
+            .and_then(|i| self.visible.get(i))
+            .map(|&i| &issues[i])
+    }
+
+    /// Move the selection by `delta` rows, stopping at either end
+    fn move_by(&mut self, delta: isize) {
+        let Some(last) = self.visible.len().checked_sub(1) else {
+            return;
+        };
+        let current = self.list.selected().unwrap_or(0);
+        self.list.select(Some(current.saturating_add_signed(delta).min(last)));
+    }
+
+    fn handle_key(&mut self, key: KeyCode) -> Action {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Enter => return Action::LoadSource,
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::PageDown => self.move_by(PAGE),
+            KeyCode::PageUp => self.move_by(-PAGE),
+            KeyCode::Home => self.move_by(isize::MIN),
+            KeyCode::End => self.move_by(isize::MAX),
+            KeyCode::Char('s') => {
+                self.severity = next_filter(self.severity, SEVERITIES);
+                self.refilter();
+            }
+            KeyCode::Char('t') => {
+                self.issue_type = next_filter(self.issue_type, ISSUE_TYPES);
+                self.refilter();
+            }
+            KeyCode::Char('c') => {
+                self.severity = None;
+                self.issue_type = None;
+                self.refilter();
+            }
+            _ => {}
+        }
+        Action::Redraw
+    }
+
+    /// Fetch the source around the selected issue, once per issue
+    async fn load_source(&mut self, client: &SonarQubeClient) {
+        let Some(issue) = self.selected() else {
+            return;
+        };
+        if self.sources.contains_key(&issue.key) {
+            return;
+        }
+        let snippet = match issue.line {
+            None => Err("This issue is not on a specific line".to_string()),
+            Some(line) => helpers::fetch_snippet(client, &issue.component, line as usize, SNIPPET_CONTEXT)
+                .await
+                .map_err(|e| format!("Failed to fetch source: {e}")),
+        };
+        self.sources.insert(issue.key.clone(), snippet);
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let [main, help] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, details_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(main);
+
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|&i| {
+                let issue = &self.issues[i];
+                let path = helpers::extract_path(&issue.component, self.project);
+                let location = issue.line.map_or(path.clone(), |l| format!("{path}:{l}"));
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:<9}", issue.severity), severity_style(&issue.severity)),
+                    Span::raw(format!("{location}  {}", issue.message)),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(self.list_title()))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.list);
+
+        let details = Paragraph::new(self.details())
+            .block(Block::default().borders(Borders::ALL).title("Details"))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(details, details_area);
+
+        frame.render_widget(
+            Paragraph::new("↑/↓ move  Enter source  s severity  t type  c clear filters  q quit")
+                .style(Style::new().add_modifier(Modifier::DIM)),
+            help,
+        );
+    }
+
+    /// "Issues 12/340 [severity: MAJOR] [type: BUG]"
+    fn list_title(&self) -> String {
+        let mut title = format!("Issues {}/{}", self.visible.len(), self.issues.len());
+        if let Some(severity) = self.severity {
+            title.push_str(&format!(" [severity: {severity}]"));
+        }
+        if let Some(issue_type) = self.issue_type {
+            title.push_str(&format!(" [type: {issue_type}]"));
+        }
+        title
+    }
+
+    fn details(&self) -> Vec<Line<'static>> {
+        let Some(issue) = self.selected() else {
+            return vec![Line::raw("No issues match the filters")];
+        };
+        let path = helpers::extract_path(&issue.component, self.project);
+        let location = issue.line.map_or(path.clone(), |l| format!("{path}:{l}"));
+        let dim = Style::new().add_modifier(Modifier::DIM);
+        let label = |name: &str, value: String| Line::from(vec![Span::styled(format!("{name:<10}"), dim), Span::raw(value)]);
+        let mut lines = vec![
+            Line::styled(issue.message.clone(), Style::new().add_modifier(Modifier::BOLD)),
+            Line::raw(""),
+            Line::from(vec![
+                Span::styled(format!("{:<10}", "Severity"), dim),
+                Span::styled(issue.severity.clone(), severity_style(&issue.severity)),
+            ]),
+            label("Type", issue.issue_type.clone()),
+            label("Rule", issue.rule.clone()),
+            label("File", location),
+            label("Status", issue.status.clone()),
+        ];
+        if let Some(effort) = issue.effort.as_ref().or(issue.debt.as_ref()) {
+            lines.push(label("Effort", effort.clone()));
+        }
+        lines.push(Line::raw(""));
+        match self.sources.get(&issue.key) {
+            None => lines.push(Line::styled("Press Enter to load the source", dim)),
+            Some(Err(e)) => lines.push(Line::styled(e.clone(), Style::new().fg(Color::Red))),
+            Some(Ok(source)) => lines.extend(source.iter().map(|l| {
+                let text = format!("{:>5} │ {}", l.line, l.code);
+                if issue.line.map(|n| n as usize) == Some(l.line) {
+                    Line::styled(text, Style::new().add_modifier(Modifier::REVERSED))
+                } else {
+                    Line::raw(text)
+                }
+            })),
+        }
+        lines
+    }
+}
+
+/// Browse `issues` until the user quits. The caller checks that stdout is a terminal.
+pub async fn browse(client: &SonarQubeClient, project: &str, issues: &[SonarIssue]) -> Result<(), CliError> {
+    let mut terminal = ratatui::try_init()
+        .map_err(|e| CliError::Failed(format!("Failed to start the terminal UI: {e}")))?;
+    let result = event_loop(&mut terminal, client, &mut Browser::new(issues, project)).await;
+    ratatui::restore();
+    result.map_err(|e| CliError::Failed(format!("Terminal UI error: {e}")))
+}
+
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    client: &SonarQubeClient,
+    browser: &mut Browser<'_>,
+) -> std::io::Result<()> {
+    loop {
+        terminal.draw(|frame| browser.render(frame))?;
+        // Wait in short polls, yielding between them, so the runtime thread is
+        // not held while no key is pressed
+        while !event::poll(KEY_POLL_INTERVAL)? {
+            tokio::task::yield_now().await;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match browser.handle_key(key.code) {
+            Action::Quit => return Ok(()),
+            Action::LoadSource => browser.load_source(client).await,
+            Action::Redraw => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(key: &str, severity: &str, issue_type: &str) -> SonarIssue {
+        serde_json::from_value(serde_json::json!({
+            "key": key,
+            "rule": "rust:S1",
+            "severity": severity,
+            "component": "my-proj:src/lib.rs",
+            "project": "my-proj",
+            "line": 3,
+            "message": "msg",
+            "type": issue_type,
+            "status": "OPEN"
+        }))
+        .unwrap()
+    }
+
+    fn visible_keys(browser: &Browser) -> Vec<String> {
+        browser.visible.iter().map(|&i| browser.issues[i].key.clone()).collect()
+    }
+
+    #[test]
+    fn test_next_filter_cycles_back_to_unset() {
+        assert_eq!(next_filter(None, ISSUE_TYPES), Some("BUG"));
+        assert_eq!(next_filter(Some("BUG"), ISSUE_TYPES), Some("VULNERABILITY"));
+        assert_eq!(next_filter(Some("CODE_SMELL"), ISSUE_TYPES), None);
+    }
+
+    #[test]
+    fn test_filters_keep_selection_when_possible() {
+        let issues = vec![
+            issue("a", "BLOCKER", "BUG"),
+            issue("b", "MAJOR", "CODE_SMELL"),
+            issue("c", "BLOCKER", "CODE_SMELL"),
+        ];
+        let mut browser = Browser::new(&issues, "my-proj");
+        browser.handle_key(KeyCode::End);
+        assert_eq!(browser.selected().unwrap().key, "c");
+
+        browser.handle_key(KeyCode::Char('s'));
+        assert_eq!(visible_keys(&browser), ["a", "c"]);
+        assert_eq!(browser.selected().unwrap().key, "c");
+
+        browser.handle_key(KeyCode::Char('t'));
+        assert_eq!(visible_keys(&browser), ["a"]);
+        assert_eq!(browser.selected().unwrap().key, "a");
+
+        browser.handle_key(KeyCode::Char('t'));
+        assert!(visible_keys(&browser).is_empty());
+        assert!(browser.selected().is_none());
+
+        browser.handle_key(KeyCode::Char('c'));
+        assert_eq!(visible_keys(&browser), ["a", "b", "c"]);
+        assert_eq!(browser.selected().unwrap().key, "a");
+        assert_eq!(browser.handle_key(KeyCode::Char('q')), Action::Quit);
+    }
+
+    #[test]
+    fn test_move_stops_at_either_end() {
+        let issues = vec![issue("a", "MAJOR", "BUG"), issue("b", "MAJOR", "BUG")];
+        let mut browser = Browser::new(&issues, "my-proj");
+        browser.handle_key(KeyCode::Up);
+        assert_eq!(browser.list.selected(), Some(0));
+        browser.handle_key(KeyCode::PageDown);
+        assert_eq!(browser.list.selected(), Some(1));
+    }
+}
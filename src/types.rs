@@ -1,7 +1,7 @@
 //! SonarQube API response types
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 /// SonarQube issue from the API
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -26,6 +26,38 @@ pub struct SonarIssue {
     pub effort: Option<String>,
     #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(rename = "updateDate", default, skip_serializing_if = "Option::is_none")]
+    pub update_date: Option<String>,
+    /// Set once the issue is closed
+    #[serde(rename = "closeDate", default, skip_serializing_if = "Option::is_none")]
+    pub close_date: Option<String>,
+    /// Default severity of the issue's rule; filled in by `issues --show-rule-severity`
+    #[serde(rename = "ruleSeverity", default, skip_serializing_if = "Option::is_none")]
+    pub rule_severity: Option<String>,
+    /// Human-readable name of the issue's rule; filled in by `issues --with-rule-names`
+    #[serde(rename = "ruleName", default, skip_serializing_if = "Option::is_none")]
+    pub rule_name: Option<String>,
+    /// Comment thread, only returned with `additionalFields=comments`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<IssueComment>,
+    /// Source lines around `line`; filled in by `issues --context`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snippet: Vec<SourceLine>,
+}
+
+/// A comment on an issue
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IssueComment {
+    #[serde(default)]
+    pub key: String,
+    #[serde(default)]
+    pub login: Option<String>,
+    #[serde(default)]
+    pub markdown: Option<String>,
+    #[serde(rename = "htmlText", default)]
+    pub html_text: Option<String>,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: Option<String>,
 }
 
 /// Text range for an issue
@@ -46,6 +78,23 @@ pub struct TextRange {
 pub struct IssuesResponse {
     pub total: usize,
     pub issues: Vec<SonarIssue>,
+    /// Present only when facets were requested
+    #[serde(default)]
+    pub facets: Vec<Facet>,
+}
+
+/// Counts of search results grouped by one property (e.g. `severities`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct Facet {
+    pub property: String,
+    pub values: Vec<FacetValue>,
+}
+
+/// One bucket of a facet
+#[derive(Debug, Clone, Deserialize)]
+pub struct FacetValue {
+    pub val: String,
+    pub count: usize,
 }
 
 /// Quality gate status
@@ -81,6 +130,25 @@ pub struct QualityGateCondition {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MeasuresResponse {
     pub component: MeasuresComponent,
+    /// Metric metadata (`additionalFields=metrics`); display-only, kept out of JSON output
+    #[serde(default, skip_serializing)]
+    pub metrics: Vec<MetricDefinition>,
+}
+
+/// Metric metadata returned alongside measures
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MetricDefinition {
+    pub key: String,
+    /// Value type: INT, FLOAT, PERCENT, SIZE (NCLOC-like counts), RATING, WORK_DUR, ...
+    #[serde(rename = "type")]
+    pub metric_type: String,
+}
+
+/// Response from the metrics search API
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetricsSearchResponse {
+    pub metrics: Vec<MetricDefinition>,
+    pub total: Option<usize>,
 }
 
 /// Component with measures
@@ -101,6 +169,82 @@ pub struct Measure {
     pub period: Option<MeasurePeriod>,
 }
 
+/// What a metric's value measures, for the `kind` of a measure in JSON output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricKind {
+    Percent,
+    /// 1.0 (A) to 5.0 (E)
+    Rating,
+    Count,
+    /// Minutes of remediation effort
+    Duration,
+}
+
+/// Kind of the metrics the CLI knows about; `new_` variants share their base metric's kind
+pub const METRIC_META: &[(&str, MetricKind)] = &[
+    ("coverage", MetricKind::Percent),
+    ("line_coverage", MetricKind::Percent),
+    ("branch_coverage", MetricKind::Percent),
+    ("duplicated_lines_density", MetricKind::Percent),
+    ("comment_lines_density", MetricKind::Percent),
+    ("sqale_debt_ratio", MetricKind::Percent),
+    ("test_success_density", MetricKind::Percent),
+    ("security_hotspots_reviewed", MetricKind::Percent),
+    ("reliability_rating", MetricKind::Rating),
+    ("security_rating", MetricKind::Rating),
+    ("sqale_rating", MetricKind::Rating),
+    ("maintainability_rating", MetricKind::Rating),
+    ("security_review_rating", MetricKind::Rating),
+    ("ncloc", MetricKind::Count),
+    ("lines", MetricKind::Count),
+    ("files", MetricKind::Count),
+    ("functions", MetricKind::Count),
+    ("classes", MetricKind::Count),
+    ("statements", MetricKind::Count),
+    ("complexity", MetricKind::Count),
+    ("cognitive_complexity", MetricKind::Count),
+    ("bugs", MetricKind::Count),
+    ("vulnerabilities", MetricKind::Count),
+    ("code_smells", MetricKind::Count),
+    ("violations", MetricKind::Count),
+    ("security_hotspots", MetricKind::Count),
+    ("duplicated_lines", MetricKind::Count),
+    ("duplicated_blocks", MetricKind::Count),
+    ("lines_to_cover", MetricKind::Count),
+    ("uncovered_lines", MetricKind::Count),
+    ("conditions_to_cover", MetricKind::Count),
+    ("uncovered_conditions", MetricKind::Count),
+    ("tests", MetricKind::Count),
+    ("test_failures", MetricKind::Count),
+    ("test_errors", MetricKind::Count),
+    ("sqale_index", MetricKind::Duration),
+    ("technical_debt", MetricKind::Duration),
+    ("reliability_remediation_effort", MetricKind::Duration),
+    ("security_remediation_effort", MetricKind::Duration),
+];
+
+/// Kind of `metric` from [`METRIC_META`]
+pub fn metric_kind(metric: &str) -> Option<MetricKind> {
+    let base = metric.strip_prefix("new_").unwrap_or(metric);
+    METRIC_META
+        .iter()
+        .find(|(key, _)| *key == metric || *key == base)
+        .map(|(_, kind)| *kind)
+}
+
+/// Letter for a rating value: "1.0" is A through "5.0" is E
+pub fn rating_label(value: &str) -> Option<&'static str> {
+    match value.trim().parse::<f64>().ok()?.round() as i64 {
+        1 => Some("A"),
+        2 => Some("B"),
+        3 => Some("C"),
+        4 => Some("D"),
+        5 => Some("E"),
+        _ => None,
+    }
+}
+
 /// Measure period (for new code)
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MeasurePeriod {
@@ -113,6 +257,12 @@ pub struct AnalysisResponse {
     pub task: AnalysisTask,
 }
 
+/// Response from the compute engine activity API (most recent task first)
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActivityResponse {
+    pub tasks: Vec<AnalysisTask>,
+}
+
 /// Analysis task details
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AnalysisTask {
@@ -135,6 +285,28 @@ pub mod task_status {
     pub const SUCCESS: &str = "SUCCESS";
     pub const FAILED: &str = "FAILED";
     pub const CANCELED: &str = "CANCELED";
+    pub const PENDING: &str = "PENDING";
+    pub const IN_PROGRESS: &str = "IN_PROGRESS";
+
+    /// Statuses after which a task no longer changes
+    pub const TERMINAL: &[&str] = &[SUCCESS, FAILED, CANCELED];
+
+    /// Every status a compute engine task can have
+    pub const ALL: &[&str] = &[PENDING, IN_PROGRESS, SUCCESS, FAILED, CANCELED];
+}
+
+/// Issue status values
+pub mod issue_status {
+    pub const CLOSED: &str = "CLOSED";
+
+    /// Statuses searched when no `--status` is given
+    pub const DEFAULT: &str = "OPEN,CONFIRMED,REOPENED";
+
+    /// Statuses of issues that are no longer open
+    pub const INACTIVE: &str = "RESOLVED,CLOSED";
+
+    /// Every issue status, open or not
+    pub const ALL: &str = "OPEN,CONFIRMED,REOPENED,RESOLVED,CLOSED";
 }
 
 /// Issue severity values
@@ -174,7 +346,8 @@ pub struct Paging {
     pub total: usize,
 }
 
-/// Component in tree response with measures
+/// Component in a tree response (`/api/measures/component_tree` with measures,
+/// `/api/components/tree` without)
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TreeComponent {
     pub key: String,
@@ -184,7 +357,7 @@ pub struct TreeComponent {
     pub path: Option<String>,
     #[serde(default)]
     pub qualifier: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub measures: Vec<Measure>,
 }
 
@@ -194,7 +367,7 @@ pub struct DuplicationsResponse {
     #[serde(default)]
     pub duplications: Vec<Duplication>,
     #[serde(default)]
-    pub files: HashMap<String, DuplicationFile>,
+    pub files: BTreeMap<String, DuplicationFile>,
 }
 
 /// A duplication group (set of duplicated blocks)
@@ -246,6 +419,52 @@ pub struct SecurityHotspot {
     #[serde(rename = "textRange")]
     #[serde(default)]
     pub text_range: Option<TextRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+}
+
+/// One hotspot from `/api/hotspots/show`, with its rule's review guidance.
+/// Fields other than those shown by `hotspots show` are dropped.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HotspotDetails {
+    pub key: String,
+    pub component: HotspotComponent,
+    pub rule: HotspotRule,
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+}
+
+/// File a hotspot is in
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HotspotComponent {
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// Rule of a hotspot; the descriptions are HTML
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotspotRule {
+    pub key: String,
+    pub name: String,
+    #[serde(default)]
+    pub security_category: String,
+    #[serde(default)]
+    pub vulnerability_probability: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vulnerability_description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix_recommendations: Option<String>,
 }
 
 /// Response from the components/search API (projects listing)
@@ -269,6 +488,23 @@ pub struct ProjectInfo {
     pub last_analysis_date: Option<String>,
 }
 
+/// Response from the components/show API
+#[derive(Debug, Clone, Deserialize)]
+pub struct ComponentShowResponse {
+    pub component: ComponentDetails,
+}
+
+/// A single component from the components/show API
+#[derive(Debug, Clone, Deserialize)]
+pub struct ComponentDetails {
+    #[serde(default)]
+    pub qualifier: Option<String>,
+    /// Date of the last completed analysis; absent for a never-analyzed project
+    #[serde(rename = "analysisDate", alias = "lastAnalysisDate")]
+    #[serde(default)]
+    pub analysis_date: Option<String>,
+}
+
 /// Response from the measures/search_history API
 #[derive(Debug, Clone, Deserialize)]
 pub struct MeasuresHistoryResponse {
@@ -283,6 +519,22 @@ pub struct MeasureHistory {
     pub history: Vec<HistoryValue>,
 }
 
+/// Response from `/api/project_analyses/search` (most recent analysis first)
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectAnalysesResponse {
+    #[serde(default)]
+    pub paging: Option<Paging>,
+    #[serde(default)]
+    pub analyses: Vec<ProjectAnalysis>,
+}
+
+/// A past analysis of a project
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectAnalysis {
+    pub key: String,
+    pub date: String,
+}
+
 /// A single historical data point
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct HistoryValue {
@@ -315,19 +567,333 @@ pub struct RuleInfo {
     #[serde(rename = "langName")]
     #[serde(default)]
     pub lang_name: Option<String>,
+    /// Rule that supersedes this one, for deprecated rules
+    #[serde(rename = "replacedBy")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaced_by: Option<String>,
+    /// Keys the rule was known by before, when the server returns them
+    #[serde(rename = "deprecatedKeys")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated_keys: Option<DeprecatedKeys>,
+}
+
+/// A rule's former keys (`deprecatedKeys` in `/api/rules/search`)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DeprecatedKeys {
+    #[serde(rename = "deprecatedKey", default)]
+    pub keys: Vec<String>,
+}
+
+/// Response from `/api/rules/show`
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuleShowResponse {
+    pub rule: RuleDetails,
+}
+
+/// Full rule definition from `/api/rules/show`. Fields other than those shown
+/// by `rule` are kept as-is so `rule --json` prints the server's payload.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RuleDetails {
+    pub key: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub rule_type: Option<String>,
+    #[serde(rename = "langName", default, skip_serializing_if = "Option::is_none")]
+    pub lang_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Built-in tags, as opposed to the instance's custom `tags`
+    #[serde(rename = "sysTags", default, skip_serializing_if = "Vec::is_empty")]
+    pub sys_tags: Vec<String>,
+    /// Whole description; older servers only
+    #[serde(rename = "htmlDesc", default, skip_serializing_if = "Option::is_none")]
+    pub html_desc: Option<String>,
+    /// Description split into sections (why, how to fix, ...); SonarQube 9.6+
+    #[serde(rename = "descriptionSections", default, skip_serializing_if = "Vec::is_empty")]
+    pub description_sections: Vec<RuleDescriptionSection>,
+    /// Fixed remediation effort per issue, e.g. "5min"
+    #[serde(rename = "remFnBaseEffort", default, skip_serializing_if = "Option::is_none")]
+    pub rem_fn_base_effort: Option<String>,
+    /// Effort added per unit of the issue's gap, e.g. "1min"
+    #[serde(rename = "remFnGapMultiplier", default, skip_serializing_if = "Option::is_none")]
+    pub rem_fn_gap_multiplier: Option<String>,
+    /// What the gap counts, e.g. "per complexity point over the threshold"
+    #[serde(rename = "gapDescription", default, skip_serializing_if = "Option::is_none")]
+    pub gap_description: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// One section of a rule description, keyed `introduction`, `root_cause`,
+/// `assess_the_problem`, `how_to_fix` or `resources`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RuleDescriptionSection {
+    pub key: String,
+    #[serde(default)]
+    pub content: String,
+}
+
+/// Response from the qualityprofiles/search API
+#[derive(Debug, Clone, Deserialize)]
+pub struct QualityProfilesResponse {
+    pub profiles: Vec<QualityProfile>,
+}
+
+/// A quality profile (one per language per project)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QualityProfile {
+    pub key: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub language: String,
+    #[serde(rename = "languageName", default)]
+    pub language_name: Option<String>,
+    #[serde(rename = "activeRuleCount", default)]
+    pub active_rule_count: usize,
+    /// Default profile for its language
+    #[serde(rename = "isDefault", default)]
+    pub is_default: bool,
+    #[serde(rename = "isBuiltIn", default)]
+    pub is_built_in: bool,
+}
+
+/// Name and key of one side of a profile comparison
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProfileRef {
+    pub key: String,
+    #[serde(default)]
+    pub name: String,
+}
+
+/// A rule active in only one of two compared profiles
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ComparedRule {
+    pub key: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub severity: Option<String>,
+}
+
+/// How a rule is activated in one profile
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RuleActivation {
+    #[serde(default)]
+    pub severity: Option<String>,
+    #[serde(default)]
+    pub params: std::collections::BTreeMap<String, String>,
+}
+
+/// A rule active in both profiles with a different severity or parameters
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ModifiedRule {
+    pub key: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub left: RuleActivation,
+    #[serde(default)]
+    pub right: RuleActivation,
+}
+
+/// Response from the qualityprofiles/compare API (rules active in both are omitted)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProfileComparison {
+    pub left: ProfileRef,
+    pub right: ProfileRef,
+    #[serde(rename = "inLeft", default)]
+    pub in_left: Vec<ComparedRule>,
+    #[serde(rename = "inRight", default)]
+    pub in_right: Vec<ComparedRule>,
+    #[serde(default)]
+    pub modified: Vec<ModifiedRule>,
 }
 
 /// A line of source code (constructed from API responses)
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SourceLine {
     pub line: usize,
     pub code: String,
 }
 
+/// A source line with SCM blame info from /api/sources/lines
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BlameLine {
+    pub line: usize,
+    #[serde(default)]
+    pub code: String,
+    #[serde(rename = "scmAuthor", default)]
+    pub scm_author: Option<String>,
+    #[serde(rename = "scmDate", default)]
+    pub scm_date: Option<String>,
+    #[serde(rename = "scmRevision", default)]
+    pub scm_revision: Option<String>,
+}
+
+/// Response from /api/sources/lines
+#[derive(Debug, Clone, Deserialize)]
+pub struct SourceLinesResponse {
+    #[serde(default)]
+    pub sources: Vec<BlameLine>,
+}
+
+/// New code definition from /api/new_code_periods/show
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NewCodePeriod {
+    #[serde(rename = "projectKey", default)]
+    pub project_key: Option<String>,
+    #[serde(rename = "branchKey", default)]
+    pub branch_key: Option<String>,
+    /// PREVIOUS_VERSION, NUMBER_OF_DAYS, REFERENCE_BRANCH or SPECIFIC_ANALYSIS
+    #[serde(rename = "type")]
+    pub period_type: String,
+    #[serde(default)]
+    pub value: Option<String>,
+    /// Whether the definition comes from the project or instance default
+    #[serde(default)]
+    pub inherited: bool,
+}
+
+/// Server fingerprint: version plus, when the token may read `/api/system/info`,
+/// server id, edition and database
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SystemInfo {
+    pub version: Option<String>,
+    #[serde(rename = "serverId")]
+    pub server_id: Option<String>,
+    pub edition: Option<String>,
+    /// Database product and version, e.g. "PostgreSQL 15.4"
+    pub database: Option<String>,
+}
+
+/// Response from `/api/system/health`, with each cause reduced to its message
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SystemHealth {
+    /// GREEN, YELLOW or RED
+    pub health: String,
+    #[serde(default, deserialize_with = "cause_messages")]
+    pub causes: Vec<String>,
+}
+
+/// Flatten `[{"message": "..."}]` into the messages
+fn cause_messages<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    struct Cause {
+        message: String,
+    }
+    Ok(Vec::<Cause>::deserialize(deserializer)?.into_iter().map(|c| c.message).collect())
+}
+
+/// Response from `/api/authentication/validate`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenValidation {
+    pub valid: bool,
+}
+
+/// Response from `/api/users/current`. Fields other than login and name are
+/// kept as-is so `whoami --json` can print the object the server returned.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CurrentUser {
+    #[serde(rename = "isLoggedIn", default)]
+    pub is_logged_in: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub login: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Response from `/api/project_branches/list`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectBranchesResponse {
+    #[serde(default)]
+    pub branches: Vec<ProjectBranch>,
+}
+
+/// A branch of a project
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProjectBranch {
+    pub name: String,
+    #[serde(rename = "isMain", default)]
+    pub is_main: bool,
+    /// `LONG`, `SHORT` or `BRANCH` (newer servers), absent on older ones
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub branch_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<BranchStatus>,
+    #[serde(rename = "analysisDate", default, skip_serializing_if = "Option::is_none")]
+    pub analysis_date: Option<String>,
+}
+
+/// Quality gate status of a branch, absent until the branch is analyzed
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BranchStatus {
+    #[serde(rename = "qualityGateStatus", default, skip_serializing_if = "Option::is_none")]
+    pub quality_gate_status: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_metric_kind_and_rating_label() {
+        assert_eq!(metric_kind("coverage"), Some(MetricKind::Percent));
+        assert_eq!(metric_kind("new_coverage"), Some(MetricKind::Percent));
+        assert_eq!(metric_kind("sqale_index"), Some(MetricKind::Duration));
+        assert_eq!(metric_kind("alert_status"), None);
+
+        assert_eq!(rating_label("1.0"), Some("A"));
+        assert_eq!(rating_label("5"), Some("E"));
+        assert_eq!(rating_label("0.0"), None);
+        assert_eq!(rating_label("OK"), None);
+    }
+
+    #[test]
+    fn test_deserialize_project_analyses() {
+        let json = r#"{
+            "paging": {"pageIndex": 1, "pageSize": 100, "total": 2},
+            "analyses": [
+                {"key": "AU-new", "date": "2026-03-02T09:30:00+0000", "events": []},
+                {"key": "AU-old", "date": "2026-03-01T10:00:00+0000", "events": []}
+            ]
+        }"#;
+
+        let response: ProjectAnalysesResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.paging.map(|p| p.total), Some(2));
+        assert_eq!(response.analyses[1].key, "AU-old");
+        assert_eq!(response.analyses[1].date, "2026-03-01T10:00:00+0000");
+    }
+
+    #[test]
+    fn test_deserialize_project_branches() {
+        let json = r#"{
+            "branches": [
+                {
+                    "name": "main",
+                    "isMain": true,
+                    "type": "BRANCH",
+                    "status": {"qualityGateStatus": "OK"},
+                    "analysisDate": "2026-03-01T10:00:00+0000"
+                },
+                {"name": "feature/x", "isMain": false, "type": "SHORT"}
+            ]
+        }"#;
+
+        let response: ProjectBranchesResponse = serde_json::from_str(json).unwrap();
+        let main = &response.branches[0];
+        assert!(main.is_main);
+        assert_eq!(main.branch_type.as_deref(), Some("BRANCH"));
+        let status = main.status.as_ref().and_then(|s| s.quality_gate_status.as_deref());
+        assert_eq!(status, Some("OK"));
+        assert!(response.branches[1].status.is_none());
+        assert!(response.branches[1].analysis_date.is_none());
+    }
+
     #[test]
     fn test_deserialize_issue() {
         let json = r#"{
@@ -347,6 +913,38 @@ mod tests {
         assert_eq!(issue.key, "AYtest123");
         assert_eq!(issue.severity, "INFO");
         assert_eq!(issue.line, Some(42));
+        assert!(issue.comments.is_empty());
+    }
+
+    #[test]
+    fn test_deserialize_issue_with_comments() {
+        let json = r#"{
+            "key": "AYtest123",
+            "rule": "rust:S1135",
+            "severity": "INFO",
+            "component": "project:src/main.rs",
+            "project": "project",
+            "message": "Complete the task associated to this TODO comment.",
+            "type": "CODE_SMELL",
+            "status": "CONFIRMED",
+            "comments": [
+                {
+                    "key": "c1",
+                    "login": "alice",
+                    "htmlText": "Tracked in <strong>JIRA-1</strong>",
+                    "markdown": "Tracked in *JIRA-1*",
+                    "updatable": true,
+                    "createdAt": "2026-01-02T10:00:00+0000"
+                }
+            ]
+        }"#;
+
+        let issue: SonarIssue = serde_json::from_str(json).unwrap();
+        assert_eq!(issue.comments.len(), 1);
+        let comment = &issue.comments[0];
+        assert_eq!(comment.login.as_deref(), Some("alice"));
+        assert_eq!(comment.markdown.as_deref(), Some("Tracked in *JIRA-1*"));
+        assert_eq!(comment.created_at.as_deref(), Some("2026-01-02T10:00:00+0000"));
     }
 
     #[test]
@@ -371,6 +969,105 @@ mod tests {
         assert_eq!(response.project_status.conditions.len(), 1);
     }
 
+    #[test]
+    fn test_deserialize_quality_profiles() {
+        let json = r#"{
+            "profiles": [
+                {
+                    "key": "AU-Tpxb--iU5OvuD2FLy",
+                    "name": "Sonar way",
+                    "language": "java",
+                    "languageName": "Java",
+                    "isInherited": false,
+                    "isDefault": true,
+                    "activeRuleCount": 512,
+                    "activeDeprecatedRuleCount": 3,
+                    "isBuiltIn": true
+                },
+                {
+                    "key": "AX-custom",
+                    "name": "Strict",
+                    "language": "py",
+                    "languageName": "Python",
+                    "activeRuleCount": 290
+                }
+            ]
+        }"#;
+
+        let response: QualityProfilesResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.profiles.len(), 2);
+        let sonar_way = &response.profiles[0];
+        assert_eq!(sonar_way.name, "Sonar way");
+        assert_eq!(sonar_way.language_name.as_deref(), Some("Java"));
+        assert_eq!(sonar_way.active_rule_count, 512);
+        assert!(sonar_way.is_default && sonar_way.is_built_in);
+        assert!(!response.profiles[1].is_default);
+
+        // `rules --project` only needs the key
+        let minimal: QualityProfilesResponse =
+            serde_json::from_str(r#"{"profiles": [{"key": "k"}]}"#).unwrap();
+        assert_eq!(minimal.profiles[0].active_rule_count, 0);
+    }
+
+    #[test]
+    fn test_deserialize_profile_comparison() {
+        let json = r#"{
+            "left": {"key": "A", "name": "Sonar way"},
+            "right": {"key": "B", "name": "Strict"},
+            "inLeft": [{"key": "java:S100", "name": "Method names", "severity": "MINOR"}],
+            "inRight": [],
+            "modified": [{
+                "key": "java:S107",
+                "name": "Too many parameters",
+                "left": {"severity": "MAJOR", "params": {"max": "7"}},
+                "right": {"severity": "CRITICAL", "params": {"max": "5"}}
+            }],
+            "same": [{"key": "java:S1"}]
+        }"#;
+
+        let cmp: ProfileComparison = serde_json::from_str(json).unwrap();
+        assert_eq!(cmp.left.name, "Sonar way");
+        assert_eq!(cmp.in_left[0].key, "java:S100");
+        assert!(cmp.in_right.is_empty());
+        assert_eq!(cmp.modified[0].right.params["max"], "5");
+    }
+
+    #[test]
+    fn test_deserialize_rule_show() {
+        let json = r#"{
+            "rule": {
+                "key": "rust:S3776",
+                "repo": "rust",
+                "name": "Cognitive Complexity of functions should not be too high",
+                "severity": "CRITICAL",
+                "type": "CODE_SMELL",
+                "langName": "Rust",
+                "tags": [],
+                "sysTags": ["brain-overload"],
+                "descriptionSections": [
+                    {"key": "root_cause", "content": "<p>Hard to read.</p>"}
+                ],
+                "remFnType": "LINEAR_OFFSET",
+                "remFnBaseEffort": "5min",
+                "remFnGapMultiplier": "1min",
+                "gapDescription": "per complexity point over the threshold"
+            },
+            "actives": []
+        }"#;
+
+        let rule = serde_json::from_str::<RuleShowResponse>(json).unwrap().rule;
+        assert_eq!(rule.key, "rust:S3776");
+        assert_eq!(rule.rule_type.as_deref(), Some("CODE_SMELL"));
+        assert_eq!(rule.sys_tags, vec!["brain-overload"]);
+        assert_eq!(rule.html_desc, None);
+        assert_eq!(rule.description_sections[0].key, "root_cause");
+        assert_eq!(rule.rem_fn_base_effort.as_deref(), Some("5min"));
+        // Unmodelled fields survive for `rule --json`
+        let raw = serde_json::to_value(&rule).unwrap();
+        assert_eq!(raw["remFnType"], "LINEAR_OFFSET");
+        assert_eq!(raw["repo"], "rust");
+    }
+
     #[test]
     fn test_severity_ordinal() {
         assert_eq!(severity::ordinal("INFO"), 0);
@@ -3,14 +3,22 @@ use assert_cmd::Command;
 use predicates::prelude::*;
 
 /// Build a CLI command with SonarQube env vars cleared so tests are hermetic.
+/// The config file and config directory (response cache) point into the temp dir
+/// so runs never touch the user's config.
 #[allow(deprecated)]
 fn cli() -> Command {
     let mut cmd = Command::cargo_bin("sonar-cli").unwrap();
-    cmd.env_remove("SONAR_HOST_URL")
+    let config_home = std::env::temp_dir().join("sonar-cli-test-cli-config");
+    cmd.env("SONAR_CLI_CONFIG", config_home.join("config.toml"))
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env_remove("SONAR_HOST_URL")
         .env_remove("SONAR_URL")
         .env_remove("SONAR_TOKEN")
         .env_remove("SONAR_PROJECT_KEY")
         .env_remove("SONAR_BRANCH")
+        .env_remove("SONAR_PULL_REQUEST")
+        // Fail fast against the unreachable test server instead of backing off
+        .env("SONAR_RETRIES", "0")
         .current_dir(std::env::temp_dir());
     cmd
 }
@@ -45,6 +53,11 @@ fn test_auth_help() {
     assert_help_contains("auth", &["Manage stored credentials"]);
 }
 
+#[test]
+fn test_cache_help() {
+    assert_help_contains("cache", &["response cache", "--cache-ttl", "clear"]);
+}
+
 #[test]
 fn test_auth_login_help() {
     cli()
@@ -114,57 +127,248 @@ fn test_invalid_subcommand() {
 
 #[test]
 fn test_health_help() {
-    assert_help_contains("health", &["Check SonarQube server health"]);
+    assert_help_contains("health", &["Check SonarQube server health", "--full", "--strict", "/api/system/health"]);
+}
+
+#[test]
+fn test_version_help() {
+    assert_help_contains("version", &["server version, edition and database", "admin rights"]);
+}
+
+#[test]
+fn test_whoami_help() {
+    assert_help_contains("whoami", &["whether the token is valid", "code 4"]);
 }
 
 #[test]
 fn test_quality_gate_help() {
-    assert_help_contains("quality-gate", &["quality gate status", "--fail-on-error"]);
+    assert_help_contains("quality-gate", &["quality gate status", "--fail-on-error", "--only"]);
 }
 
 #[test]
 fn test_issues_help() {
-    assert_help_contains("issues", &["--severity", "--status", "--rule", "--language", "--new-code"]);
+    assert_help_contains(
+        "issues",
+        &[
+            "--severity",
+            "--status",
+            "--rule",
+            "--language",
+            "--new-code",
+            "--file",
+            "--qualifiers",
+            "--include-closed",
+            "--all-statuses",
+            "--show-rule-severity",
+            "--with-rule-names",
+            "--comments",
+            "--no-sort",
+            "--top",
+            "--skip",
+            "--changed-since-analysis",
+            "--format",
+            "ndjson",
+            "--open",
+            "--show-full-component",
+            "--group-by",
+            "--show-effort",
+            "--path",
+            "--exclude-path",
+            "--page",
+            "--page-size",
+            "--since-last-green",
+            "--watch",
+            "--interval",
+            "--watch-mode",
+            "--fail-on-issues",
+            "--max-issues",
+            "--fail-on-severity",
+        ],
+    );
 }
 
 #[test]
 fn test_measures_help() {
-    assert_help_contains("measures", &["--metrics"]);
+    assert_help_contains("measures", &["--metrics", "--compare-branch", "--component", "--new-code", "--human", "--trend", "--projects", "--format", "--all"]);
 }
 
 #[test]
 fn test_coverage_help() {
-    assert_help_contains("coverage", &["--min-coverage", "--sort"]);
+    assert_help_contains("coverage", &["--min-coverage", "--sort", "--top", "--skip", "--format"]);
+}
+
+#[test]
+fn test_new_code_help() {
+    assert_help_contains("new-code", &["new code definition"]);
+}
+
+#[test]
+fn test_new_code_missing_project() {
+    assert_missing_project(&["new-code"]);
+}
+
+#[test]
+fn test_diff_help() {
+    assert_help_contains("diff", &["--from", "--to", "--metrics"]);
+}
+
+#[test]
+fn test_diff_missing_project() {
+    assert_missing_project(&["diff", "--from", "2025-01-01"]);
+}
+
+#[test]
+fn test_branches_help() {
+    assert_help_contains("branches", &["quality gate status"]);
+}
+
+#[test]
+fn test_branches_missing_project() {
+    assert_missing_project(&["branches"]);
+}
+
+#[test]
+fn test_tree_help() {
+    assert_help_contains("tree", &["--qualifier", "--search", "component keys"]);
+}
+
+#[test]
+fn test_tree_missing_project() {
+    assert_missing_project(&["tree"]);
 }
 
 #[test]
 fn test_duplications_help() {
-    assert_help_contains("duplications", &["--details"]);
+    assert_help_contains("duplications", &["--details", "--top", "--skip"]);
 }
 
 #[test]
 fn test_hotspots_help() {
-    assert_help_contains("hotspots", &["--status", "--new-code"]);
+    assert_help_contains("hotspots", &["--status", "--new-code", "--assigned-to", "assign", "show", "resolve"]);
+}
+
+#[test]
+fn test_issue_help() {
+    assert_help_contains("issue", &["assign", "comment", "transition"]);
+}
+
+#[test]
+fn test_report_help() {
+    assert_help_contains("report", &["--output-dir", "--resume"]);
+}
+
+#[test]
+fn test_named_profile_login_and_selection() {
+    let config_file = std::env::temp_dir().join(format!("sonar-cli-test-profiles-{}.toml", std::process::id()));
+    let _ = std::fs::remove_file(&config_file);
+    let run = |args: &[&str]| {
+        let mut cmd = cli();
+        cmd.env("SONAR_CLI_CONFIG", &config_file).args(args);
+        cmd
+    };
+
+    run(&["auth", "login", "--profile", "prod", "--url", "http://localhost:1", "--token", "squ_prod_token"])
+        .assert()
+        .success();
+    let config = std::fs::read_to_string(&config_file).unwrap();
+    assert!(config.contains("[profiles.prod]"), "{config}");
+
+    run(&["--profile", "prod", "--json", "--bare-json", "auth", "status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"configured\""));
+    // No `default` profile was stored
+    run(&["--json", "--bare-json", "auth", "status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("not_configured"));
+    run(&["--profile", "staging", "--project", "my-proj", "quality-gate"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown profile 'staging' (configured: prod)"));
+
+    let _ = std::fs::remove_file(&config_file);
 }
 
 #[test]
 fn test_projects_help() {
-    assert_help_contains("projects", &["--search", "--qualifier"]);
+    assert_help_contains("projects", &["--search", "--qualifier", "tsv", "--recent", "--page", "--page-size"]);
+}
+
+#[test]
+fn test_recent_project_recorded_and_resolved() {
+    let config_file = std::env::temp_dir().join(format!("sonar-cli-test-recent-{}.toml", std::process::id()));
+    std::fs::write(&config_file, "recent_projects = [\"my-proj\"]\n").unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = cli();
+        cmd.env("SONAR_CLI_CONFIG", &config_file).args(args);
+        cmd
+    };
+
+    // Neither a failed command nor a dry run records its project
+    run(&["--url", "http://localhost:1", "--timeout", "1", "--project", "unreachable", "quality-gate"])
+        .assert()
+        .failure();
+    run(&["--url", "http://localhost:1", "--project", "dry", "--dry-run", "quality-gate"])
+        .assert()
+        .success();
+    run(&["projects", "--recent", "--json", "--bare-json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""key": "my-proj""#))
+        .stdout(predicate::str::contains(r#""key": "unreachable""#).not())
+        .stdout(predicate::str::contains(r#""key": "dry""#).not());
+
+    // @1 resolves to the most recent project
+    run(&["--url", "http://localhost:1", "--project", "@1", "--dry-run", "quality-gate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("projectKey=my-proj"));
+
+    run(&["--url", "http://localhost:1", "--project", "@2", "quality-gate"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No recent project @2"));
+
+    let _ = std::fs::remove_file(&config_file);
 }
 
 #[test]
 fn test_history_help() {
-    assert_help_contains("history", &["--metrics", "--from", "--to"]);
+    assert_help_contains(
+        "history",
+        &["--metrics", "--from", "--to", "--table", "--gate-history"],
+    );
+}
+
+#[test]
+fn test_profiles_help() {
+    assert_help_contains("profiles", &["--language", "--compare"]);
 }
 
 #[test]
 fn test_rules_help() {
-    assert_help_contains("rules", &["--language", "--severity", "--rule-type"]);
+    assert_help_contains("rules", &["--language", "--severity", "--rule-type", "--top", "--skip", "--deprecated", "--quality-profile", "--page-size"]);
+}
+
+#[test]
+fn test_rule_help() {
+    assert_help_contains("rule", &["description and remediation effort", "rust:S3776"]);
 }
 
 #[test]
 fn test_source_help() {
-    assert_help_contains("source", &["--from", "--to", "<COMPONENT>"]);
+    assert_help_contains("source", &["--from", "--to", "--blame", "<COMPONENT>"]);
+}
+
+#[test]
+fn test_activity_help() {
+    assert_help_contains("activity", &["--status", "--limit"]);
+}
+
+#[test]
+fn test_activity_missing_project() {
+    assert_missing_project(&["activity"]);
 }
 
 #[test]
@@ -174,7 +378,7 @@ fn test_wait_help() {
 
 #[test]
 fn test_scan_help() {
-    assert_help_contains("scan", &["--clippy-report", "--coverage-report", "--wait", "--wait-timeout", "--poll-interval", "--no-scm", "--skip-unchanged", "--exclusions", "--sources", "--inclusions", "--scanner", "--solution", "--opencover-report", "--lcov-report", "--run-id", "--skip-tests"]);
+    assert_help_contains("scan", &["--clippy-report", "--coverage-report", "--auto-coverage", "--wait", "--summary-only", "--gate-wait", "--wait-timeout", "--poll-interval", "--no-scm", "--skip-unchanged", "--exclusions", "--sources", "--inclusions", "--scanner", "--solution", "--opencover-report", "--lcov-report", "--run-id", "--skip-tests"]);
 }
 
 // ── Missing --project validation (exits before any network call) ────
@@ -225,6 +429,201 @@ fn test_history_missing_required_metrics_arg() {
         .stderr(predicate::str::contains("--metrics"));
 }
 
+#[test]
+fn test_history_empty_metrics_rejected_locally() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "test-proj", "history", "--metrics", " , "])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("at least one metric key"));
+}
+
+#[test]
+fn test_history_gate_history_conflicts_with_metrics() {
+    cli()
+        .args(["--project", "test-proj", "history", "--gate-history", "--metrics", "coverage"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_measures_trend_conflicts_with_new_code() {
+    cli()
+        .args(["--project", "test-proj", "measures", "--trend", "--new-code"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_measures_projects_empty_stdin() {
+    cli()
+        .args(["--url", "http://localhost:1", "measures", "--projects", "-"])
+        .write_stdin("\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No project keys on stdin"));
+}
+
+#[test]
+fn test_json_envelope_wraps_payload() {
+    cli()
+        .args(["--url", "http://localhost:1", "--json", "--compact-json", "health"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(r#""command":"health""#))
+        .stdout(predicate::str::contains(r#""server":"http://localhost:1""#))
+        .stdout(predicate::str::contains(r#""data":{"#));
+}
+
+#[test]
+fn test_bare_json_omits_envelope() {
+    cli()
+        .args(["--url", "http://localhost:1", "--json", "--bare-json", "health"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("UNREACHABLE"))
+        .stdout(predicate::str::contains(r#""command""#).not());
+}
+
+#[test]
+fn test_output_writes_to_file_instead_of_stdout() {
+    let dir = std::env::temp_dir().join(format!("sonar-cli-test-output-flag-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = dir.join("nested").join("health.json");
+    cli()
+        .args(["--url", "http://localhost:1", "--json", "--bare-json", "health", "--output"])
+        .arg(&path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::is_empty());
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(written.contains("UNREACHABLE"), "{written}");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_print_url_shows_issue_search_url() {
+    cli()
+        .args([
+            "--url", "http://localhost:1", "--project", "my-proj", "--branch", "dev",
+            "--retries", "0", "--print-url", "issues",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "GET http://localhost:1/api/issues/search?componentKeys=my-proj",
+        ))
+        .stderr(predicate::str::contains("branch=dev"));
+}
+
+#[test]
+fn test_branch_and_pull_request_are_exclusive() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "--pull-request", "42", "issues"])
+        .env("SONAR_BRANCH", "main")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--branch and --pull-request cannot be used together"));
+}
+
+#[test]
+fn test_color_rejects_unknown_mode() {
+    cli()
+        .args(["--url", "http://localhost:1", "--color", "sometimes", "health"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Valid values: auto, always, never"));
+}
+
+#[test]
+fn test_color_never_reaches_json_output() {
+    cli()
+        .args(["--url", "http://localhost:1", "--color", "always", "--json", "health"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("UNREACHABLE"))
+        .stdout(predicate::str::contains("\x1b").not());
+}
+
+#[test]
+fn test_invalid_proxy_fails_fast() {
+    cli()
+        .args(["--url", "http://localhost:1", "--proxy", "not a url", "health"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid proxy URL 'not a url'"));
+}
+
+#[test]
+fn test_insecure_prints_warning() {
+    cli()
+        .args(["--url", "http://localhost:1", "--insecure", "health"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--insecure disables TLS certificate verification"));
+}
+
+#[test]
+fn test_rules_deprecated_conflicts_with_status() {
+    cli()
+        .args(["--url", "http://localhost:1", "rules", "--deprecated", "--status", "READY"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_quiet_hides_progress_but_not_errors() {
+    // No sonar-scanner on PATH: the scan fails right after its progress line
+    let scan = |quiet: bool| {
+        let mut cmd = cli();
+        cmd.env("PATH", "").args(["--url", "http://localhost:1", "--project", "p"]);
+        if quiet {
+            cmd.arg("-q");
+        }
+        cmd.arg("scan");
+        cmd
+    };
+    scan(false)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Running sonar-scanner for project 'p'"));
+    scan(true)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Running sonar-scanner").not())
+        .stderr(predicate::str::contains("Failed to start sonar-scanner"));
+}
+
+#[test]
+fn test_scan_gate_wait_conflicts_with_wait() {
+    cli()
+        .args(["--project", "test-proj", "scan", "--gate-wait", "--wait"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_scan_coverage_base_requires_coverage_report() {
+    cli()
+        .args(["--project", "test-proj", "scan", "--coverage-base", "."])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--coverage-report"));
+}
+
+#[test]
+fn test_scan_summary_only_requires_wait() {
+    cli()
+        .args(["--project", "test-proj", "scan", "--summary-only"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--wait"));
+}
+
 #[test]
 fn test_source_missing_required_component_arg() {
     cli()
@@ -290,6 +689,84 @@ fn test_verbose_long_flag_accepted() {
     assert_missing_project(&["--verbose", "issues"]);
 }
 
+#[test]
+fn test_header_flag_accepted() {
+    // Exercises --header parsing in build_config(); repeatable
+    assert_missing_project(&["--header", "X-Gateway-Key: abc", "--header", "X-Trace: 1", "issues"]);
+}
+
+#[test]
+fn test_header_flag_invalid_format() {
+    cli()
+        .args(["--url", "http://localhost:1", "--header", "no-colon", "health"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Expected format: 'Name: Value'"));
+}
+
+#[test]
+fn test_header_flag_rejects_authorization() {
+    cli()
+        .args(["--url", "http://localhost:1", "--header", "Authorization: Bearer x", "health"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--allow-auth-header"));
+}
+
+#[test]
+fn test_retry_idempotent_only_requires_bool() {
+    cli()
+        .args(["--retry-idempotent-only", "maybe", "health"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("possible values: true, false"));
+}
+
+#[test]
+fn test_concurrency_must_be_in_range() {
+    cli()
+        .args(["--concurrency", "0", "health"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("0 is not in 1..=64"));
+}
+
+#[test]
+fn test_health_strict_requires_full() {
+    cli()
+        .args(["--url", "http://localhost:1", "health", "--strict"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--full"));
+}
+
+#[test]
+fn test_connect_timeout_requires_number() {
+    cli()
+        .args(["--connect-timeout", "soon", "health"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value 'soon' for '--connect-timeout <SECS>'"));
+}
+
+#[test]
+fn test_ignore_file_missing_path_fails() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "--ignore-file", "does-not-exist.ignore", "coverage"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Ignore file not found"));
+}
+
+#[test]
+fn test_no_ignore_file_conflicts_with_ignore_file() {
+    cli()
+        .args(["--project", "p", "--ignore-file", "x", "--no-ignore-file", "issues"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
 // ── require_project() error path for all project-requiring commands ──
 
 #[test]
@@ -319,6 +796,17 @@ fn test_issues_with_all_filters_missing_project() {
     ]);
 }
 
+#[test]
+fn test_measures_component_bypasses_project_check() {
+    // Nothing listens on port 1, so the request fails fast after the project check
+    cli()
+        .args(["--url", "http://localhost:1", "measures", "--component", "VW_KEY"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Project key is required").not())
+        .stderr(predicate::str::contains("Failed to get measures"));
+}
+
 #[test]
 fn test_measures_with_metrics_missing_project() {
     // Exercises Measures command arm with --metrics flag
@@ -349,6 +837,83 @@ fn test_hotspots_with_status_missing_project() {
     assert_missing_project(&["hotspots", "--status", "REVIEWED"]);
 }
 
+#[test]
+fn test_report_missing_project() {
+    assert_missing_project(&["report", "--output-dir", "out"]);
+}
+
+#[test]
+fn test_report_requires_output_dir() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "report"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--output-dir"));
+}
+
+#[test]
+fn test_hotspots_assign_requires_to() {
+    cli()
+        .args(["--url", "http://localhost:1", "hotspots", "assign", "--hotspot", "AX1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--to"));
+}
+
+#[test]
+fn test_issue_transition_rejects_unknown_transition() {
+    cli()
+        .args(["--url", "http://localhost:1", "--token", "t", "issue", "transition", "AX1", "--do", "close"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown transition 'close'"));
+}
+
+#[test]
+fn test_issue_assign_requires_to() {
+    cli()
+        .args(["--url", "http://localhost:1", "issue", "assign", "AX1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--to"));
+}
+
+#[test]
+fn test_exit_codes_distinguish_error_kinds() {
+    // Missing token: auth (4), with a hint
+    cli()
+        .args(["--url", "http://localhost:1", "issue", "assign", "AX1", "--to", "alice"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("requires a token"));
+    // Unreachable server: network (5)
+    cli()
+        .args(["--url", "http://localhost:1", "health"])
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains("Failed to reach SonarQube at http://localhost:1"));
+    // Bad flag value: general failure (1)
+    cli()
+        .args(["--url", "http://localhost:1", "--token", "t", "issue", "transition", "AX1", "--do", "close"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_hotspots_resolve_requires_token() {
+    cli()
+        .args(["--url", "http://localhost:1", "hotspots", "resolve", "AX1", "--resolution", "SAFE"])
+        .assert()
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains("Reviewing a hotspot requires a token"));
+}
+
+#[test]
+fn test_hotspots_assigned_to_missing_project() {
+    assert_missing_project(&["hotspots", "--assigned-to", "me"]);
+}
+
 #[test]
 fn test_issues_with_new_code_missing_project() {
     // Exercises Issues command arm with --new-code flag
@@ -591,3 +1156,229 @@ fn test_scan_inclusions_flag_accepted() {
         "--inclusions", "src/main.rs,src/lib.rs",
     ]);
 }
+
+// ── Output format ────────────────────────────────────────────────────
+
+#[test]
+fn test_rules_invalid_format() {
+    cli()
+        .args(["--url", "http://localhost:1", "rules", "--format", "yaml"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown output format"));
+}
+
+#[test]
+fn test_projects_format_in_help() {
+    assert_help_contains("projects", &["--format", "ndjson", "csv"]);
+}
+
+#[test]
+fn test_rules_csv_format_rejected() {
+    cli()
+        .args(["--url", "http://localhost:1", "rules", "--format", "csv"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("CSV output is not supported for rules"));
+}
+
+#[test]
+fn test_issues_all_statuses_conflicts_with_status() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "issues", "--all-statuses", "--status", "OPEN"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_issues_since_last_green_conflicts_with_created_after() {
+    cli()
+        .args([
+            "--url", "http://localhost:1", "--project", "p", "issues", "--since-last-green",
+            "--created-after", "2025-01-01",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_issues_watch_rejects_json() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "--json", "issues", "--watch"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--watch only supports text output"));
+}
+
+#[test]
+fn test_issues_watch_rejects_ndjson() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "issues", "--watch", "--format", "ndjson"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--watch only supports text output"));
+}
+
+#[test]
+fn test_issues_fail_on_severity_rejects_unknown_level() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "issues", "--fail-on-severity", "HUGE"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown severity 'HUGE'. Valid values: INFO, MINOR, MAJOR, CRITICAL, BLOCKER"));
+}
+
+#[test]
+fn test_issues_group_by_rejects_unknown_key() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "issues", "--group-by", "author"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown group 'author'. Valid values: rule, file, severity, type"));
+}
+
+#[test]
+fn test_missing_token_file_is_an_error() {
+    cli()
+        .args(["--url", "http://localhost:1", "--token-file", "no-such-token-file", "--project", "p", "issues"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to read token file no-such-token-file"));
+}
+
+#[test]
+fn test_issues_dry_run_prints_request_url() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "issues", "--dry-run", "--severity", "CRITICAL"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("GET http://localhost:1/api/issues/search?"))
+        .stdout(predicate::str::contains("severities=CRITICAL"));
+}
+
+#[test]
+fn test_issue_thresholds_conflict_with_limit_and_page() {
+    for cut in [["--limit", "10"], ["--page", "2"]] {
+        cli()
+            .args(["--url", "http://localhost:1", "--project", "p", "issues", "--max-issues", "50"])
+            .args(cut)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+}
+
+#[test]
+fn test_dry_run_honors_output_file() {
+    let dir = std::env::temp_dir().join(format!("sonar-cli-test-dry-run-output-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = dir.join("requests.txt");
+    cli()
+        .args(["--url", "http://localhost:1", "--dry-run", "rules", "--language", "rust", "--output"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(written.contains("GET http://localhost:1/api/rules/search?"), "{written}");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_scan_rejects_dry_run() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "--dry-run", "scan"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--dry-run is not supported by scan"));
+}
+
+#[test]
+fn test_invalid_url_is_rejected_early() {
+    cli()
+        .args(["--url", "ftp://sonar.example.com", "--project", "p", "issues"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid URL 'ftp://sonar.example.com': scheme must be http or https, not 'ftp' (from --url)",
+        ));
+}
+
+#[test]
+fn test_measures_assert_rejects_missing_operator() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "measures", "--assert", "coverage80"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid assertion 'coverage80': no operator"));
+}
+
+#[test]
+fn test_issues_interactive_needs_a_terminal_or_the_tui_feature() {
+    // stdout is piped here, so the TUI build refuses too
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "issues", "--interactive"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("issues --interactive needs"));
+}
+
+#[test]
+fn test_issues_sort_rejects_unknown_key() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "issues", "--sort", "age"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown sort key 'age'. Valid values: severity, file, line, rule"));
+}
+
+#[test]
+fn test_issues_thresholds_conflict_with_watch() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "issues", "--watch", "--max-issues", "3"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_issues_interval_requires_watch() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "issues", "--interval", "5"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--watch"));
+}
+
+#[test]
+fn test_measures_tsv_rejected_with_trend() {
+    cli()
+        .args(["--url", "http://localhost:1", "--project", "p", "measures", "--trend", "--format", "tsv"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("TSV output is only supported for current measures"));
+}
+
+// ── Shell completions ────────────────────────────────────────────────
+
+#[test]
+fn test_completions_bash() {
+    cli()
+        .args(["completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("quality-gate"))
+        .stdout(predicate::str::contains("issues"));
+}
+
+#[test]
+fn test_completions_unknown_shell() {
+    cli()
+        .args(["completions", "tcsh"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown shell"));
+}